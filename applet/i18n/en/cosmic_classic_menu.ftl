@@ -1,6 +1,9 @@
 # categories
 all-applications=All applications
 recently-used=Recently used
+favorites=Favorites
+windows=Windows
+no-open-windows=No open windows
 audio=Audio
 video=Video
 development=Development
@@ -12,15 +15,173 @@ science=Science
 settings=Settings
 system=System
 utility=Utility
+other-category=Other
 
 # context menu
+menu-settings=Menu settings
+more-settings=More settings…
+applet-button-style=Button style
+icon-only=Icon only
+label-only=Label only
+icon-and-label=Icon and label
+auto=Auto
+menu-layout=Layout
+layout-classic=Classic
+layout-wide=Wide
+layout-grid=Grid
+app-menu-position=Categories side
+left=Left
+right=Right
+search-field-position=Search field
+top=Top
+bottom=Bottom
 system-monitor-label=System Monitor
 settings-label=System Settings
 disks-label=Disks
+tool-failed={ $name } couldn't be opened
 
 # main menu
 search-placeholder=Search apps
+send-mail-with=Send mail with { $app }
+open-in=Open in { $app }
+show-all-results=Show all { $count } results
+applet-tooltip={ $user }@{ $host } — { $count ->
+    [one] 1 application
+   *[other] { $count } applications
+}
+applet-tooltip-updates={ $summary }, { $count ->
+    [one] 1 update pending
+   *[other] { $count } updates pending
+}
+applet-tooltip-new-apps={ $summary }, { $count ->
+    [one] 1 new application
+   *[other] { $count } new applications
+}
+clipboard=Clipboard
+add-to-favorites=Add to favorites
+remove-from-favorites=Remove from favorites
+pinned=Pinned { $name }
+unpinned=Unpinned { $name }
+move-to-folder=Move to { $name }
+new-folder=Move to new folder…
+remove-from-folder=Remove from folder
+remove-from-recents=Remove from recently used
+most-used=Most used
+last-used=Last used
+launched-just-now=Launched just now
+launched-minutes-ago=Launched { $count ->
+    [one] 1 minute
+   *[other] { $count } minutes
+} ago
+launched-hours-ago=Launched { $count ->
+    [one] 1 hour
+   *[other] { $count } hours
+} ago
+launched-days-ago=Launched { $count ->
+    [one] 1 day
+   *[other] { $count } days
+} ago
+new-folder-for=New folder for { $name }
+folder-name=Folder name
+start-timer=Start { $duration } timer
+start-named-timer=Start { $duration } timer for { $name }
+copy-result=Copy { $result }
+run-command=Run { $command }
+command-failed={ $command } failed
+launch-failed={ $name } failed to launch: { $error }
+copy-command=Copy command
+search-web=Search the web for { $query }
+open-failed={ $target } couldn't be opened
+connect-to=Connect to { $host }
+open-settings-panel=Open Settings → { $panel }
+bookmarks=Bookmarks
+files=Files
+script-results=More results
+keep-menu-open=Keep the menu open after launching
+timer=Timer
+timer-finished=Time's up
+timer-finished-after=The { $duration } timer has finished
 no-apps=No applications found
+source-flatpak=Flatpak
+source-native=System package
+dgpu-unavailable-hint=Prefers a dedicated GPU, which isn't available
+dgpu-unavailable={ $name } prefers a dedicated GPU, which isn't available. It was launched on the default GPU.
+
+# errors
+error-config=Couldn't save the menu settings: { $error }
+error-state=Couldn't save the recently used apps: { $error }
+error-missing-exec={ $name } has no command to launch
+
+# application context menu
+update-now=Update now
+launch-with-dgpu=Launch using dedicated GPU
+run-as-administrator=Run as administrator
+details=Details
+show-desktop-file=Show desktop file
+properties=Properties
+admin-not-authorized=Not authorized to run { $name } as administrator
+admin-launch-failed=Failed to run { $name } as administrator
+no-actions=No actions available
+
+# application properties
+properties-of={ $name } properties
+properties-name=Name
+properties-command=Command
+properties-icon=Icon
+properties-categories=Categories
+properties-save-failed=Failed to save the properties
+save=Save
+cancel=Cancel
+
+# application details
+back=Back
+launch=Launch
+
+# launcher creator
+create-launcher=Create launcher…
+show-hidden-entries=Show hidden entries
+launcher-terminal=Run in terminal
+create=Create
+create-launcher-failed=Failed to create the launcher
+
+# quick toggles
+dark-mode=Dark mode
+dark-mode-failed=Couldn't switch the theme
+
+# power options
+power-logout=Log Out
+power-suspend=Sleep
+power-lock=Lock
+power-switch-user=Lock & Switch User
+power-reboot=Restart
+power-reboot-to-firmware=Restart to UEFI Settings
+restart-into=Restart into { $entry }
+restart-into-entry=Restart into…
+power-shutdown=Shut Down
+# Other words the power actions are found by in the search, separated by semicolons
+power-logout-keywords=logout;log off;sign out;exit
+power-suspend-keywords=suspend;sleep;standby
+power-lock-keywords=lock;lock screen
+power-switch-user-keywords=switch user;change user
+power-reboot-keywords=reboot;restart
+power-reboot-to-firmware-keywords=uefi;bios;firmware;setup
+power-shutdown-keywords=shutdown;power off;poweroff;turn off
+power-action-failed={ $action } failed
+power-action-not-authorized={ $action } wasn't authorized
+power-dialog-failed=The dialog to confirm { $action } couldn't be shown
+try-again=Try again
+suspend-unsupported=This system can't sleep, it isn't supported or is disabled
+shut-down-in=Shut down in…
+shutting-down-in=Shutting down in { $remaining }
+log-out-in=Log out in…
+logging-out-in=Logging out in { $remaining }
+timer-delay-custom=Custom…
+timer-delay-minutes=Minutes
+schedule=Schedule
+schedule-shutdown-failed=Couldn't schedule the shutdown
+
+# user sessions
+switch-to-user=Switch to { $user }
 
 # applet button
 menu-label=Menu
@@ -6,7 +6,12 @@ use cosmic::cctk::sctk::reexports::protocols::xdg::shell::client::xdg_positioner
     Anchor, Gravity,
 };
 use cosmic::cosmic_config::CosmicConfigEntry;
+use cosmic::iced::event::{self, wayland};
+use cosmic::iced::input_method;
+use cosmic::iced::keyboard::{self, key::Named, Key};
+use cosmic::iced::widget::scrollable::{self, RelativeOffset};
 use cosmic::iced::Subscription;
+use cosmic::iced::{mouse, touch};
 use cosmic::iced::{
     platform_specific::shell::commands::popup::{destroy_popup, get_popup},
     widget::{column, row},
@@ -14,21 +19,73 @@ use cosmic::iced::{
     Alignment,
 };
 use cosmic::iced_runtime::platform_specific::wayland::popup::SctkPositioner;
+use cosmic::widget::toaster::{Toast, ToastId, Toasts};
 use cosmic::{Application, Element};
+use cosmic_classic_menu_core::launch::SuperviseError;
+use cosmic_classic_menu_core::{favorites, ranking, recents};
+use freedesktop_desktop_entry::IconSource;
+use once_cell::sync::Lazy;
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
 use std::process;
+use std::time::{Duration, Instant, SystemTime};
 
 use crate::applet_button::AppletButton;
-use crate::applet_menu::AppletMenu;
-use crate::config::{AppletButtonStyle, CosmicClassicMenuConfig, RecentApplication};
+use crate::applet_menu::{app_entry_id, AppletMenu, APP_LIST_ID, SEARCH_FIELD_ID};
+use crate::config::{
+    unix_timestamp, AppletButtonStyle, CategoryNavigation, ContextMenuEntry,
+    CosmicClassicMenuConfig, HorizontalPosition, MenuLayout, MinimalPopup, NameDisplay,
+    RecentApplication, RecentsOrder, SystemPolicy, VerticalPosition,
+};
+use crate::error::Error;
 use crate::fl;
 use crate::logic::apps::{desktop_files, ApplicationCategory, Event, User};
-use crate::model::application_entry::ApplicationEntry;
+use crate::logic::appstream::AppDetails;
+use crate::logic::bookmarks::Bookmark;
+use crate::logic::elevation::ElevationError;
+use crate::logic::icons::{self, CachedIcon, IconData};
+use crate::logic::integrations::Integrations;
+use crate::logic::parental_controls::{self, ParentalControls};
+use crate::logic::recent_documents::RecentDocument;
+use crate::logic::script_providers::{self, ScriptResult};
+use crate::logic::search_providers::{self, Provider};
+use crate::logic::settings_panels::SettingsPanel;
+use crate::logic::usage;
+use crate::logic::windows::{OpenWindow, WindowActivator, WindowEvent, WindowHandle};
+use crate::model::application_entry::{ApplicationEntry, ApplicationSource};
+use crate::model::application_properties::{ApplicationProperties, PropertyField};
+use crate::model::new_launcher::NewLauncher;
+use crate::power_options::{BootEntry, PowerActionError, UserSession};
 
 pub const APP_ID: &str = "com.championpeak87.cosmic-classic-menu";
+/// Outputs shorter than this many logical pixels get the minimal popup. Low enough for the
+/// full menu to stay on 768 pixel high laptops and on 1080p screens scaled to 150%.
+const MINIMAL_POPUP_OUTPUT_HEIGHT: i32 = 600;
+/// How long an app has to be held in touch mode to open its actions.
+const LONG_PRESS_DURATION: Duration = Duration::from_millis(500);
+/// Longest pause between two clicks on an app that still launches it.
+const DOUBLE_CLICK_INTERVAL: Duration = Duration::from_millis(400);
+/// How many settings pages matching the search are listed.
+const MAX_SETTINGS_PANEL_RESULTS: usize = 3;
+/// How many recently used documents matching the search are listed.
+const MAX_RECENT_DOCUMENT_RESULTS: usize = 5;
+/// How many bookmarks matching the search are listed.
+const MAX_BOOKMARK_RESULTS: usize = 5;
+/// Delays offered for scheduling a shutdown or logout, in minutes, followed by a custom delay.
+pub const TIMER_DELAY_OPTIONS: [u32; 3] = [15, 30, 60];
+/// Longest delay a shutdown or logout can be scheduled in, anything longer is more likely
+/// a typo.
+pub const MAX_TIMER_DELAY: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+/// Options the menu is started with.
+#[derive(Clone, Debug, Default)]
+pub struct Flags {
+    /// Run the menu as a regular resizable window instead of a panel applet.
+    pub windowed: bool,
+}
 
 /// This is the struct that represents your application.
 /// It is used to define the data that will be used by your application.
-#[derive(Default)]
 pub struct CosmicClassicMenu {
     /// Application state which is managed by the COSMIC runtime.
     pub core: Core,
@@ -36,18 +93,146 @@ pub struct CosmicClassicMenu {
     popup: Option<Id>,
     /// The configuration that is used to store the application settings.
     pub config: CosmicClassicMenuConfig,
+    /// Settings enforced by the administrator, already applied to the configuration.
+    pub policy: SystemPolicy,
     /// The search field that is used to filter the applications.
     pub search_field: String,
     /// The list of available applications that are displayed in the menu.
     pub available_applications: Vec<ApplicationEntry>,
     /// The list of available categories that are displayed in the menu.
     pub available_categories: Vec<ApplicationCategory>,
+    /// Launch counts of the apps, stored apart from the configuration.
+    pub recent_applications: Vec<RecentApplication>,
     /// The popup type that is used to determine which popup to display.
     pub popup_type: PopupType,
     /// The selected category that is used to filter the applications.
     pub selected_category: Option<ApplicationCategory>,
+    /// Category shown before the search was started, shown again once it's cleared.
+    pub category_before_search: Option<ApplicationCategory>,
     /// Currently logged user
     pub current_user: Option<User>,
+    /// Desktop services found at startup, everything is assumed missing until probed.
+    pub integrations: Integrations,
+    /// Index of the application row highlighted by keyboard navigation.
+    pub selected_application: Option<usize>,
+    /// Id of the application whose context menu is open.
+    pub app_context_menu: Option<String>,
+    /// Ids of the installed Flatpak applications with pending updates.
+    pub pending_updates: HashSet<String>,
+    /// Ids of the apps installed when the menu was last opened, `None` until they're loaded.
+    pub known_apps: Option<HashSet<String>>,
+    /// Ids of the apps installed since the menu was last opened, badging the applet button.
+    pub new_apps: HashSet<String>,
+    /// Keep the menu open after launching an app or a tool, to launch several in a row.
+    pub pinned: bool,
+    /// Environment selecting the dedicated GPU, if the system has one.
+    pub dgpu_environment: Option<Vec<(String, String)>>,
+    /// Ids of the apps already warned about their preferred GPU missing, once per session.
+    pub dgpu_warned_apps: HashSet<String>,
+    /// Transient notifications shown inside the popup.
+    pub toasts: Toasts<Message>,
+    /// Properties of the application being edited, shown in place of the menu.
+    pub properties: Option<ApplicationProperties>,
+    /// Launcher being created, shown in place of the menu.
+    pub new_launcher: Option<NewLauncher>,
+    /// App whose details are shown in place of the menu, `None` details while loading or
+    /// when the app has no AppStream metadata.
+    pub details: Option<(ApplicationEntry, Option<AppDetails>)>,
+    /// App to put into a new folder of favorites, with the folder name being typed.
+    pub new_folder: Option<(ApplicationEntry, String)>,
+    /// Last failure the applet recovered from, shown in a banner until dismissed.
+    pub error: Option<String>,
+    /// Sessions of the other logged in users.
+    pub other_sessions: Vec<UserSession>,
+    /// Whether logind can suspend the system, assumed until it's asked.
+    pub can_suspend: bool,
+    /// Whether the firmware can be asked to open its setup on restart, not until it's asked.
+    pub can_reboot_to_firmware: bool,
+    /// Entries of the boot loader that can be restarted into, like another operating system.
+    pub boot_entries: Vec<BootEntry>,
+    /// Index of the boot entry picked to restart into.
+    pub selected_boot_entry: Option<usize>,
+    /// Whether a boot entry is set for the next restart while its confirmation dialog was
+    /// shown, to be reset unless the system restarted.
+    pub boot_entry_set: bool,
+    /// When logind is going to power off, if a shutdown is scheduled.
+    pub scheduled_shutdown: Option<SystemTime>,
+    /// When the session is going to be logged out, counted down by the applet itself as
+    /// logind can't schedule it.
+    pub scheduled_logout: Option<SystemTime>,
+    /// Minutes typed for the custom delay of a shutdown or logout, `None` unless the custom
+    /// delay was picked.
+    pub timer_delay_input: Option<(TimedAction, String)>,
+    /// Default web browser, email client, file manager and terminal.
+    pub default_apps: Vec<ApplicationEntry>,
+    /// When the `mimeapps.list` files were modified as the default apps were resolved,
+    /// `None` to resolve them again.
+    pub default_apps_modified: Option<Vec<Option<SystemTime>>>,
+    /// Folders of favorites with their apps.
+    pub favorite_folders: Vec<(String, Vec<ApplicationEntry>)>,
+    /// Name of the folder of favorites expanded in the list.
+    pub expanded_folder: Option<String>,
+    /// Power actions and system tools matching the search.
+    pub search_items: Vec<SearchItem>,
+    /// Runs of the search scripts by provider, aborted when the query changes before they
+    /// finish.
+    pub script_runs: HashMap<String, cosmic::iced::task::Handle>,
+    /// Whether an input method is composing text in the search field.
+    pub ime_composing: bool,
+    /// Logical heights of the outputs by name.
+    pub output_heights: HashMap<String, i32>,
+    /// Scale factors of the outputs by name, fractional ones included.
+    pub output_scales: HashMap<String, f32>,
+    /// Icons of the apps and windows read in the background, by `icons::key`.
+    pub icons: HashMap<String, CachedIcon>,
+    /// Size in physical pixels the cached icons were looked up at.
+    pub icons_size: u16,
+    /// Whether the search results past the configured limit are shown.
+    pub show_all_results: bool,
+    /// Pointer position over the fast scroller, as a fraction of its height.
+    pub fast_scroll_position: f32,
+    /// Letter of the application scrolled to while dragging the fast scroller.
+    pub fast_scroll_letter: Option<char>,
+    /// Whether a category has been opened in drill-down navigation.
+    pub category_opened: bool,
+    /// Page shown by the paginated grid.
+    pub grid_page: usize,
+    /// Scroll distance accumulated towards flipping the grid page, in lines.
+    pub grid_scroll: f32,
+    /// Relative scroll position of the flat list, picking the category of its sticky header.
+    pub app_list_scroll: f32,
+    /// Recently copied text, newest first, only kept while the history is enabled.
+    pub clipboard_history: Vec<String>,
+    /// Host aliases from the SSH config, offered in the search while it's enabled.
+    pub ssh_hosts: Vec<String>,
+    /// Browser bookmarks, offered in the search while it's enabled.
+    pub bookmarks: Vec<Bookmark>,
+    /// Pages of COSMIC Settings, offered in the search while it's enabled.
+    pub settings_panels: Vec<SettingsPanel>,
+    /// Recently used documents, offered in the search while it's enabled.
+    pub recent_documents: Vec<RecentDocument>,
+    /// Desktop id of the app held down in touch mode, until it's released or held long enough.
+    pub long_press: Option<String>,
+    /// Whether the last long press opened the app actions, so its release doesn't launch it.
+    pub long_press_opened: bool,
+    /// Desktop id of the favorite being dragged to a new position.
+    pub dragged_favorite: Option<String>,
+    /// Whether the dragged favorite was moved, so its release doesn't launch it.
+    pub favorites_reordered: bool,
+    /// Desktop id of the app clicked last and when, to launch it on a second click.
+    pub last_click: Option<(String, Instant)>,
+    /// Whether the menu runs in a regular window, where it's always shown.
+    pub windowed: bool,
+    /// Whether NoDisplay and Hidden entries are listed too, until the applet restarts.
+    pub show_hidden_entries: bool,
+    /// Whether the context menu shows the menu settings in place of its entries.
+    pub inline_settings: bool,
+    /// Windows listed in the windows category while it's enabled.
+    pub open_windows: Vec<OpenWindow>,
+    /// Focuses windows, once the compositor's windows are tracked.
+    pub window_activator: Option<WindowActivator>,
+    /// Window focused last from the menu, so clicking its app again moves on to the next one.
+    pub last_activated_window: Option<WindowHandle>,
 }
 
 /// This is the enum that contains all the possible variants that your application will need to transmit messages.
@@ -57,20 +242,288 @@ pub struct CosmicClassicMenu {
 pub enum Message {
     TogglePopup(PopupType),
     PopupClosed(Id),
+    /// Closes the popup from the keyboard, handing the focus back to the applet button.
+    DismissPopup,
+    /// Clears the search, or closes the popup when there's nothing to clear.
+    ClearSearchOrDismiss,
+    FocusSearch,
     SearchFieldInput(String),
+    ImeComposing(bool),
+    /// Logical height and scale factor of the named output.
+    UpdateOutput(String, i32, f32),
+    IconsLoaded(u16, Vec<(String, Option<IconData>)>),
+    IconFadeTick,
+    ShowAllResults,
+    JumpToLetter(char),
+    FastScrollMoved(f32),
+    FastScrollDragging(bool),
+    GridPageSelected(usize),
+    GridScrolled(f32),
+    AppListScrolled(f32),
+    UpdateUriSearchItem(String, Option<SearchItem>),
+    /// Paths starting with the one typed in the search.
+    UpdatePathSearchItems(String, Vec<PathBuf>),
+    /// Results of a search provider script for the query.
+    UpdateScriptResults(String, Vec<ScriptResult>),
+    OpenUri(String, ApplicationEntry),
+    CopyCharacter(&'static str),
+    StartTimer(Duration, String),
+    TimerFinished(Result<(), String>),
+    RunCommand(String),
+    CommandFinished(String, Result<(), String>),
+    LaunchFinished(String, String, Result<(), String>),
+    Open(String),
+    OpenFinished(String, Result<(), String>),
+    UpdateSshHosts(Vec<String>),
+    UpdateBookmarks(Vec<Bookmark>),
+    UpdateSettingsPanels(Vec<SettingsPanel>),
+    UpdateRecentDocuments(Vec<RecentDocument>),
+    /// Opens a page of COSMIC Settings.
+    OpenSettingsPanel(String),
+    WindowEvent(WindowEvent),
+    ActivateWindow(WindowHandle),
+    ConnectSsh(String),
+    ClipboardChanged(String),
+    CopyClipboardEntry(String),
     PowerOptionSelected(PowerAction),
+    ToggleDarkMode(bool),
+    DismissError,
+    ToggleShowHiddenEntries(bool),
+    UpdateOtherSessions(Vec<UserSession>),
+    UpdateCanSuspend(bool),
+    UpdateCanRebootToFirmware(bool),
+    UpdateBootEntries(Vec<BootEntry>),
+    SelectBootEntry(usize),
+    RestartIntoBootEntry(usize),
+    BootEntryRestartFinished(String, Result<(), PowerActionError>),
+    BootEntrySet(BootEntry, Result<(), PowerActionError>),
+    BootEntryDialogFinished(BootEntry, Result<(), SuperviseError>),
+    BootEntryReset(Result<(), PowerActionError>),
+    ActivateSession(String),
+    SelectTimerDelay(TimedAction, usize),
+    EditTimerDelay(String),
+    ScheduleTimer(TimedAction, Duration),
+    CancelTimer(TimedAction),
+    UpdateScheduledShutdown(Option<SystemTime>),
+    ScheduledShutdownFailed(String),
+    /// Logs out once the logout scheduled for the given time is still pending.
+    LogoutTimerElapsed(SystemTime),
+    /// Redraws the countdowns to the scheduled shutdown and logout.
+    TimerCountdownTick,
     ApplicationSelected(ApplicationEntry),
+    RecentsOrderChanged(RecentsOrder),
+    ShowInlineSettings(bool),
+    MenuSettingChanged(MenuSetting),
+    /// An app in the list was clicked while it takes a double click to launch one.
+    ApplicationClicked(usize, ApplicationEntry),
     CategorySelected(ApplicationCategory),
+    /// Opens the main menu on a category picked from the right-click menu.
+    OpenCategory(ApplicationCategory),
+    CloseCategory,
     LaunchTool(SystemTool),
     Zbus(Result<(), zbus::Error>),
     UpdateLoggedUser(Result<User, zbus::Error>),
+    UpdateIntegrations(Integrations),
     FileEvent(Event),
+    ParentalControlsChanged(ParentalControls),
+    UsageChanged,
     UpdateConfig(CosmicClassicMenuConfig),
     UpdateAvailableApplications(Vec<ApplicationEntry>),
     UpdateAvailableCategories(Vec<ApplicationCategory>),
+    MoveSelection(SelectionDirection),
+    LaunchSelectedApplication,
+    OpenAppContextMenu(String),
+    LongPressStarted(String),
+    LongPressElapsed(String),
+    /// The long press was released, over the app or not.
+    LongPressEnded,
+    FavoriteDragStarted(String),
+    FavoriteDragOver(String),
+    FavoriteDragEnded,
+    CloseAppContextMenu,
+    PerformApplicationAction(ApplicationEntry, ApplicationAction),
+    UpdatePendingUpdates(HashSet<String>),
+    UpdateInstalledApps(HashSet<String>),
+    TogglePinned,
+    FlatpakUpdated(String, Result<(), String>),
+    UpdateDedicatedGpu(Option<Vec<(String, String)>>),
+    ElevatedLaunchFinished(String, Result<(), ElevationError>),
+    ToolFinished(SystemTool, Result<(), String>),
+    SeedFavorites(Vec<String>),
+    EditProperty(PropertyField, String),
+    SaveProperties,
+    CloseProperties,
+    PropertiesSaved(Result<PathBuf, String>),
+    OpenLauncherCreator,
+    EditLauncher(NewLauncher),
+    CreateLauncher,
+    CloseLauncherCreator,
+    UpdateFavoriteFolders(Vec<(String, Vec<ApplicationEntry>)>),
+    UpdateDefaultApps(Vec<ApplicationEntry>),
+    ToggleFolder(String),
+    EditFolderName(String),
+    CreateFolder,
+    CloseFolderCreator,
+    UpdateAppDetails(String, Option<AppDetails>),
+    CloseDetails,
+    LauncherCreated(Result<PathBuf, String>),
+    PowerCommandFinished(PowerAction, Result<(), SuperviseError>),
+    PowerActionFinished(PowerAction, Result<(), PowerActionError>),
+    CloseToast(ToastId),
+    Shutdown,
 }
 
+/// Actions offered in the context menu of an application entry.
 #[derive(Clone, Debug)]
+pub enum ApplicationAction {
+    UpdateFlatpak,
+    LaunchWithDedicatedGpu,
+    RunAsAdministrator,
+    ShowDesktopFile,
+    Properties,
+    Details,
+    AddToFavorites,
+    RemoveFromFavorites,
+    MoveToFolder(String),
+    NewFolder,
+    RemoveFromFolder,
+    RemoveFromRecents,
+}
+
+impl ApplicationAction {
+    /// Returns the actions applicable to the given application.
+    pub fn available_for(applet: &CosmicClassicMenu, app: &ApplicationEntry) -> Vec<Self> {
+        let mut actions = Vec::new();
+        if applet.dgpu_environment.is_some() && !app.prefers_dgpu {
+            actions.push(ApplicationAction::LaunchWithDedicatedGpu);
+        }
+        // Sandboxed and terminal apps can't be elevated from the menu
+        if app.source == ApplicationSource::Native && !app.is_terminal {
+            actions.push(ApplicationAction::RunAsAdministrator);
+        }
+        actions.push(ApplicationAction::Details);
+        if app.path.is_some() {
+            actions.push(ApplicationAction::ShowDesktopFile);
+            actions.push(ApplicationAction::Properties);
+        }
+        if applet.pending_updates.contains(&app.id) {
+            actions.push(ApplicationAction::UpdateFlatpak);
+        }
+
+        // Favorites can be unpinned and grouped into folders
+        let current_folder = applet
+            .config
+            .favorite_folders
+            .iter()
+            .find(|folder| folder.apps.contains(&app.id));
+        if !applet.is_favorite(&app.id) {
+            actions.push(ApplicationAction::AddToFavorites);
+        } else {
+            actions.push(ApplicationAction::RemoveFromFavorites);
+            for folder in &applet.config.favorite_folders {
+                if current_folder.map(|current| &current.name) != Some(&folder.name) {
+                    actions.push(ApplicationAction::MoveToFolder(folder.name.clone()));
+                }
+            }
+            actions.push(ApplicationAction::NewFolder);
+        }
+        if current_folder.is_some() {
+            actions.push(ApplicationAction::RemoveFromFolder);
+        }
+        if applet.search_field.is_empty()
+            && applet.selected_category == Some(ApplicationCategory::RECENTLY_USED)
+        {
+            actions.push(ApplicationAction::RemoveFromRecents);
+        }
+        actions
+    }
+
+    pub fn get_display_name(&self) -> String {
+        match self {
+            ApplicationAction::UpdateFlatpak => fl!("update-now"),
+            ApplicationAction::LaunchWithDedicatedGpu => fl!("launch-with-dgpu"),
+            ApplicationAction::RunAsAdministrator => fl!("run-as-administrator"),
+            ApplicationAction::ShowDesktopFile => fl!("show-desktop-file"),
+            ApplicationAction::Properties => fl!("properties"),
+            ApplicationAction::Details => fl!("details"),
+            ApplicationAction::AddToFavorites => fl!("add-to-favorites"),
+            ApplicationAction::RemoveFromFavorites => fl!("remove-from-favorites"),
+            ApplicationAction::MoveToFolder(name) => fl!("move-to-folder", name = name.clone()),
+            ApplicationAction::NewFolder => fl!("new-folder"),
+            ApplicationAction::RemoveFromFolder => fl!("remove-from-folder"),
+            ApplicationAction::RemoveFromRecents => fl!("remove-from-recents"),
+        }
+    }
+
+    /// Returns whether the action can be performed on the app, the apps pinned by the
+    /// administrator can't be unpinned.
+    pub fn is_enabled_for(&self, applet: &CosmicClassicMenu, app: &ApplicationEntry) -> bool {
+        match self {
+            ApplicationAction::RemoveFromFavorites => !applet.policy.is_pinned(&app.id),
+            _ => true,
+        }
+    }
+
+    /// Returns whether the menu closes after performing the action. Actions only changing
+    /// the menu itself keep it open and refresh it in place.
+    pub fn closes_menu(&self) -> bool {
+        match self {
+            ApplicationAction::LaunchWithDedicatedGpu
+            | ApplicationAction::RunAsAdministrator
+            | ApplicationAction::ShowDesktopFile => true,
+            ApplicationAction::UpdateFlatpak
+            | ApplicationAction::Properties
+            | ApplicationAction::Details
+            | ApplicationAction::AddToFavorites
+            | ApplicationAction::RemoveFromFavorites
+            | ApplicationAction::MoveToFolder(_)
+            | ApplicationAction::NewFolder
+            | ApplicationAction::RemoveFromFolder
+            | ApplicationAction::RemoveFromRecents => false,
+        }
+    }
+}
+
+/// Settings changed from the context menu, applied right away.
+#[derive(Clone, Copy, Debug)]
+pub enum MenuSetting {
+    ButtonStyle(AppletButtonStyle),
+    Layout(MenuLayout),
+    AppMenuPosition(HorizontalPosition),
+    SearchFieldPosition(VerticalPosition),
+}
+
+impl MenuSetting {
+    /// Stores the setting, without writing the values enforced by the system policy.
+    fn persist(
+        self,
+        config: &mut CosmicClassicMenuConfig,
+        policy: &SystemPolicy,
+    ) -> crate::error::Result<()> {
+        let handler = CosmicClassicMenuConfig::try_config_handler()?;
+        match self {
+            MenuSetting::ButtonStyle(_) if policy.applet_button_style.is_some() => false,
+            MenuSetting::Layout(_) if policy.menu_layout.is_some() => false,
+            MenuSetting::ButtonStyle(style) => config.set_applet_button_style(&handler, style)?,
+            MenuSetting::Layout(layout) => config.set_menu_layout(&handler, layout)?,
+            MenuSetting::AppMenuPosition(position) => {
+                config.set_app_menu_position(&handler, position)?
+            }
+            MenuSetting::SearchFieldPosition(position) => {
+                config.set_search_field_position(&handler, position)?
+            }
+        };
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum SelectionDirection {
+    Previous,
+    Next,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub enum SystemTool {
     AppletSettings,
     SystemSettings,
@@ -78,74 +531,337 @@ pub enum SystemTool {
     DiskManagement,
 }
 
+/// The installed program of each external tool, looked up once as the views ask on every redraw.
+static TOOL_PROGRAMS: Lazy<HashMap<SystemTool, &'static str>> = Lazy::new(|| {
+    [
+        SystemTool::SystemSettings,
+        SystemTool::SystemMonitor,
+        SystemTool::DiskManagement,
+    ]
+    .into_iter()
+    .filter_map(|tool| tool.find_program().map(|program| (tool, program)))
+    .collect()
+});
+
 impl SystemTool {
-    fn perform(&self) {
+    /// Programs providing the tool, in order of preference.
+    fn candidates(&self) -> &'static [&'static str] {
         match self {
-            SystemTool::AppletSettings => {
-                let env_vars: Vec<(String, String)> = std::env::vars().collect();
-                let app_id = Some("com.championpeak87.cosmic-classic-menu.settings");
-                tokio::spawn(async move {
-                    cosmic::desktop::spawn_desktop_exec(
-                        "cosmic-classic-menu-settings",
-                        env_vars,
-                        app_id.as_deref(),
-                        false,
-                    )
-                    .await;
-                });
-                return;
-            }
-            _ => (),
+            SystemTool::AppletSettings => &[],
+            SystemTool::SystemSettings => &["cosmic-settings"],
+            SystemTool::SystemMonitor => &[
+                "gnome-system-monitor",
+                "plasma-systemmonitor",
+                "gnome-usage",
+            ],
+            SystemTool::DiskManagement => &["gnome-disks", "partitionmanager"],
         }
+    }
 
-        let is_flatpak = std::env::var("FLATPAK_ID").is_ok();
-        let main_exec = if is_flatpak {
-            "flatpak-spawn"
+    /// Returns the first of the candidates that's installed. The host can't be looked into
+    /// from a sandbox, so the preferred one is assumed there.
+    fn find_program(&self) -> Option<&'static str> {
+        let candidates = self.candidates();
+        if std::env::var("FLATPAK_ID").is_ok() {
+            return candidates.first().copied();
+        }
+        candidates
+            .iter()
+            .copied()
+            .find(|program| cosmic_classic_menu_core::launch::which(program))
+    }
+
+    /// Returns whether the tool can be launched, the applet settings are installed with it.
+    pub fn is_installed(&self) -> bool {
+        *self == SystemTool::AppletSettings || TOOL_PROGRAMS.contains_key(self)
+    }
+
+    /// Launches the tool, resolving with an error if it fails to start.
+    async fn perform(self) -> Result<(), String> {
+        if let SystemTool::AppletSettings = self {
+            let env_vars: Vec<(String, String)> = std::env::vars().collect();
+            let app_id = Some("com.championpeak87.cosmic-classic-menu.settings");
+            cosmic::desktop::spawn_desktop_exec(
+                "cosmic-classic-menu-settings",
+                env_vars,
+                app_id.as_deref(),
+                false,
+            )
+            .await;
+            return Ok(());
+        }
+
+        let Some(program) = TOOL_PROGRAMS.get(&self).copied() else {
+            return Err(format!("No program providing {:?} is installed", self));
+        };
+        if std::env::var("FLATPAK_ID").is_ok() {
+            cosmic_classic_menu_core::launch::supervise("flatpak-spawn", &["--host", program]).await
         } else {
-            match self {
-                SystemTool::SystemSettings => "cosmic-settings",
-                SystemTool::SystemMonitor => "gnome-system-monitor",
-                SystemTool::DiskManagement => "gnome-disks",
-                _ => "",
+            cosmic_classic_menu_core::launch::supervise(program, &[]).await
+        }
+    }
+
+    pub fn get_icon_name(&self) -> &'static str {
+        match self {
+            SystemTool::AppletSettings => "preferences-desktop-apps-symbolic",
+            SystemTool::SystemSettings => "preferences-system-symbolic",
+            SystemTool::SystemMonitor => "utilities-system-monitor-symbolic",
+            SystemTool::DiskManagement => "drive-harddisk-symbolic",
+        }
+    }
+
+    pub fn get_display_name(&self) -> String {
+        match self {
+            SystemTool::AppletSettings => fl!("settings"),
+            SystemTool::SystemSettings => fl!("settings-label"),
+            SystemTool::SystemMonitor => fl!("system-monitor-label"),
+            SystemTool::DiskManagement => fl!("disks-label"),
+        }
+    }
+}
+
+/// Non-application items found by searching their localized labels and keywords.
+#[derive(Clone, Debug)]
+pub enum SearchItem {
+    Power(PowerAction),
+    Tool(SystemTool),
+    /// Email or web address typed in the search, opened with its default handler.
+    OpenUri(String, ApplicationEntry),
+    /// Emoji or special character with its name, copied to the clipboard.
+    Character(&'static str, &'static str),
+    /// Timer typed in the search, e.g. `10m tea`, notifying once it's up.
+    Timer(Duration, String),
+    /// Result of the arithmetic after the `=` prefix, copied to the clipboard.
+    Calculation(String),
+    /// Shell command after the `>` prefix.
+    RunCommand(String),
+    /// Path starting with the `~` or `/` typed in the search.
+    OpenPath(PathBuf),
+    /// Query after the `?` prefix, with the URL searching for it.
+    WebSearch(String, String),
+    /// Host alias from the SSH config, connected to in a terminal.
+    SshHost(String),
+    /// Browser bookmark, opened in the default browser.
+    Bookmark(Bookmark),
+    /// Page of COSMIC Settings, opened directly.
+    SettingsPanel(SettingsPanel),
+    /// Recently used document, opened with its default app.
+    RecentDocument(RecentDocument),
+    /// Result of a search provider script, running its command.
+    Script(ScriptResult),
+}
+
+impl SearchItem {
+    /// Returns every searchable item, leaving out the power actions if they're hidden and the
+    /// tools that aren't installed.
+    fn registry(include_power_actions: bool) -> Vec<Self> {
+        let mut items: Vec<Self> = [
+            SystemTool::SystemSettings,
+            SystemTool::SystemMonitor,
+            SystemTool::DiskManagement,
+            SystemTool::AppletSettings,
+        ]
+        .into_iter()
+        .filter(SystemTool::is_installed)
+        .map(SearchItem::Tool)
+        .collect();
+        if include_power_actions {
+            items.extend([
+                SearchItem::Power(PowerAction::Lock),
+                SearchItem::Power(PowerAction::SwitchUser),
+                SearchItem::Power(PowerAction::Logout),
+                SearchItem::Power(PowerAction::Suspend),
+                SearchItem::Power(PowerAction::Reboot),
+                SearchItem::Power(PowerAction::RebootToFirmware),
+                SearchItem::Power(PowerAction::Shutdown),
+            ]);
+        }
+        items
+    }
+
+    /// Returns the items of the provider the query is routed to by its prefix.
+    fn from_provider(
+        provider: Provider,
+        query: &str,
+        config: &CosmicClassicMenuConfig,
+    ) -> Vec<Self> {
+        if query.is_empty() {
+            return Vec::new();
+        }
+        match provider {
+            Provider::Calculator => crate::logic::calculator::evaluate(query)
+                .map(|result| {
+                    SearchItem::Calculation(crate::logic::calculator::format_result(result))
+                })
+                .into_iter()
+                .collect(),
+            Provider::RunCommand => vec![SearchItem::RunCommand(query.to_string())],
+            // Read in the background, see `Message::UpdatePathSearchItems`
+            Provider::Files => Vec::new(),
+            Provider::WebSearch => vec![SearchItem::WebSearch(
+                query.to_string(),
+                search_providers::web_search_url(&config.web_search_url, query),
+            )],
+        }
+    }
+
+    /// Returns the items whose localized label matches the query, best match first, followed
+    /// by the items only matching by one of their keywords.
+    pub fn matching(query: &str, mode: SearchMode, include_power_actions: bool) -> Vec<Self> {
+        let registry = Self::registry(include_power_actions);
+        let mut items =
+            ranking::rank_with(mode, registry.clone(), query, SearchItem::get_display_name);
+        for item in registry {
+            let keyword_matches = item
+                .get_keywords()
+                .iter()
+                .any(|keyword| ranking::matched_indices_with(mode, keyword, query).is_some());
+            let listed = items
+                .iter()
+                .any(|listed| listed.get_display_name() == item.get_display_name());
+            if keyword_matches && !listed {
+                items.push(item);
             }
-        };
-        let args = if is_flatpak {
-            match self {
-                SystemTool::SystemSettings => vec!["--host", "cosmic-settings"],
-                SystemTool::SystemMonitor => vec!["--host", "gnome-system-monitor"],
-                SystemTool::DiskManagement => vec!["--host", "gnome-disks"],
-                _ => vec![],
+        }
+        items
+    }
+
+    /// Returns the localized terms the item is found by besides its label.
+    fn get_keywords(&self) -> Vec<String> {
+        match self {
+            SearchItem::Power(action) => action.get_keywords(),
+            _ => Vec::new(),
+        }
+    }
+
+    pub fn get_display_name(&self) -> String {
+        match self {
+            SearchItem::Power(action) => action.get_display_name(),
+            SearchItem::Tool(tool) => tool.get_display_name(),
+            SearchItem::OpenUri(uri, handler) if uri.starts_with("mailto:") => {
+                fl!("send-mail-with", app = handler.name.clone())
             }
-        } else {
-            vec![]
-        };
+            SearchItem::OpenUri(_, handler) => fl!("open-in", app = handler.name.clone()),
+            SearchItem::Character(glyph, name) => format!("{}  {}", glyph, name),
+            SearchItem::Timer(duration, label) if label.is_empty() => fl!(
+                "start-timer",
+                duration = crate::logic::timer::format_duration(*duration)
+            ),
+            SearchItem::Timer(duration, label) => fl!(
+                "start-named-timer",
+                duration = crate::logic::timer::format_duration(*duration),
+                name = label.clone()
+            ),
+            SearchItem::Calculation(result) => fl!("copy-result", result = result.clone()),
+            SearchItem::RunCommand(command) => fl!("run-command", command = command.clone()),
+            SearchItem::OpenPath(path) => path.display().to_string(),
+            SearchItem::WebSearch(query, _) => fl!("search-web", query = query.clone()),
+            SearchItem::SshHost(host) => fl!("connect-to", host = host.clone()),
+            SearchItem::Bookmark(bookmark) => bookmark.title.clone(),
+            SearchItem::SettingsPanel(panel) => {
+                fl!("open-settings-panel", panel = panel.name.clone())
+            }
+            SearchItem::RecentDocument(document) => document.name.clone(),
+            SearchItem::Script(result) => result.title.clone(),
+        }
+    }
 
-        if let Err(_) = process::Command::new(main_exec).args(args).spawn() {
-            eprintln!("Selected tool cannot be opened");
+    /// Returns the message performing the item.
+    pub fn message(&self) -> Message {
+        match self {
+            SearchItem::Power(action) => Message::PowerOptionSelected(action.clone()),
+            SearchItem::Tool(tool) => Message::LaunchTool(tool.clone()),
+            SearchItem::OpenUri(uri, handler) => Message::OpenUri(uri.clone(), handler.clone()),
+            SearchItem::Character(glyph, _) => Message::CopyCharacter(*glyph),
+            SearchItem::Timer(duration, label) => Message::StartTimer(*duration, label.clone()),
+            SearchItem::Calculation(result) => Message::CopyClipboardEntry(result.clone()),
+            SearchItem::RunCommand(command) => Message::RunCommand(command.clone()),
+            SearchItem::OpenPath(path) => Message::Open(path.display().to_string()),
+            SearchItem::WebSearch(_, url) => Message::Open(url.clone()),
+            SearchItem::SshHost(host) => Message::ConnectSsh(host.clone()),
+            SearchItem::Bookmark(bookmark) => Message::Open(bookmark.url.clone()),
+            SearchItem::SettingsPanel(panel) => Message::OpenSettingsPanel(panel.page.clone()),
+            SearchItem::RecentDocument(document) => {
+                Message::Open(document.path.display().to_string())
+            }
+            SearchItem::Script(result) => Message::RunCommand(result.command.clone()),
         }
     }
 }
 
+/// Power actions that can be scheduled from the footer of the menu.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TimedAction {
+    Shutdown,
+    Logout,
+}
+
 #[derive(Clone, Debug)]
 pub enum PowerAction {
     Shutdown,
     Logout,
     Lock,
+    SwitchUser,
     Reboot,
+    /// Restart into the firmware setup, only offered in the search.
+    RebootToFirmware,
     Suspend,
 }
 
 impl PowerAction {
+    pub fn get_display_name(&self) -> String {
+        match self {
+            PowerAction::Shutdown => fl!("power-shutdown"),
+            PowerAction::Logout => fl!("power-logout"),
+            PowerAction::Lock => fl!("power-lock"),
+            PowerAction::SwitchUser => fl!("power-switch-user"),
+            PowerAction::Reboot => fl!("power-reboot"),
+            PowerAction::RebootToFirmware => fl!("power-reboot-to-firmware"),
+            PowerAction::Suspend => fl!("power-suspend"),
+        }
+    }
+
+    /// Returns the other words the action is searched by, like "reboot" for restarting.
+    pub fn get_keywords(&self) -> Vec<String> {
+        let keywords = match self {
+            PowerAction::Shutdown => fl!("power-shutdown-keywords"),
+            PowerAction::Logout => fl!("power-logout-keywords"),
+            PowerAction::Lock => fl!("power-lock-keywords"),
+            PowerAction::SwitchUser => fl!("power-switch-user-keywords"),
+            PowerAction::Reboot => fl!("power-reboot-keywords"),
+            PowerAction::RebootToFirmware => fl!("power-reboot-to-firmware-keywords"),
+            PowerAction::Suspend => fl!("power-suspend-keywords"),
+        };
+        keywords
+            .split(';')
+            .map(str::trim)
+            .filter(|keyword| !keyword.is_empty())
+            .map(str::to_string)
+            .collect()
+    }
+
     fn perform(self) -> cosmic::iced::Task<cosmic::Action<Message>> {
-        let msg = |m| cosmic::Action::App(Message::Zbus(m));
+        let action = self.clone();
+        let msg = move |result: zbus::Result<()>| {
+            cosmic::Action::App(Message::PowerActionFinished(
+                action.clone(),
+                result.map_err(PowerActionError::from),
+            ))
+        };
         match self {
             PowerAction::Lock => cosmic::iced::Task::perform(crate::power_options::lock(), msg),
+            PowerAction::SwitchUser => {
+                cosmic::iced::Task::perform(crate::power_options::lock_and_switch_user(), msg)
+            }
             PowerAction::Logout => {
                 cosmic::iced::Task::perform(crate::power_options::log_out(), msg)
             }
             PowerAction::Reboot => {
                 cosmic::iced::Task::perform(crate::power_options::restart(), msg)
             }
+            PowerAction::RebootToFirmware => {
+                cosmic::iced::Task::perform(crate::power_options::restart_to_firmware_setup(), msg)
+            }
             PowerAction::Shutdown => {
                 cosmic::iced::Task::perform(crate::power_options::shutdown(), msg)
             }
@@ -178,7 +894,7 @@ impl Default for PopupType {
 /// - `APP_ID` is the unique identifier of your application.
 impl Application for CosmicClassicMenu {
     type Executor = cosmic::executor::multi::Executor;
-    type Flags = ();
+    type Flags = Flags;
     type Message = Message;
     const APP_ID: &'static str = APP_ID;
 
@@ -197,45 +913,147 @@ impl Application for CosmicClassicMenu {
     /// - `core` is used to passed on for you by libcosmic to use in the core of your own application.
     /// - `flags` is used to pass in any data that your application needs to use before it starts.
     /// - `Task` type is used to send messages to your application. `Task::none()` can be used to send no messages to your application.
-    fn init(core: Core, _flags: Self::Flags) -> (Self, Task<Self::Message>) {
+    fn init(core: Core, flags: Self::Flags) -> (Self, Task<Self::Message>) {
+        let policy = SystemPolicy::policy();
         let window = CosmicClassicMenu {
             core,
             popup: None,
             search_field: "".to_owned(),
             available_applications: vec![],
             available_categories: vec![],
+            recent_applications: usage::load(),
             popup_type: PopupType::MainMenu,
             selected_category: Some(ApplicationCategory::ALL),
-            config: CosmicClassicMenuConfig::config(),
+            category_before_search: None,
+            config: policy.enforce(CosmicClassicMenuConfig::user_config()),
+            policy,
             current_user: None,
+            integrations: Integrations::default(),
+            selected_application: None,
+            app_context_menu: None,
+            pending_updates: HashSet::new(),
+            known_apps: None,
+            new_apps: HashSet::new(),
+            pinned: false,
+            dgpu_environment: None,
+            dgpu_warned_apps: HashSet::new(),
+            toasts: Toasts::new(Message::CloseToast),
+            properties: None,
+            new_launcher: None,
+            details: None,
+            default_apps: Vec::new(),
+            default_apps_modified: None,
+            error: None,
+            other_sessions: Vec::new(),
+            can_suspend: true,
+            can_reboot_to_firmware: false,
+            boot_entries: Vec::new(),
+            selected_boot_entry: None,
+            boot_entry_set: false,
+            scheduled_shutdown: None,
+            scheduled_logout: None,
+            timer_delay_input: None,
+            new_folder: None,
+            favorite_folders: Vec::new(),
+            expanded_folder: None,
+            search_items: Vec::new(),
+            script_runs: HashMap::new(),
+            ime_composing: false,
+            output_heights: HashMap::new(),
+            output_scales: HashMap::new(),
+            icons: HashMap::new(),
+            icons_size: icons::MIN_LOOKUP_SIZE,
+            show_all_results: false,
+            fast_scroll_position: 0.0,
+            fast_scroll_letter: None,
+            category_opened: false,
+            grid_page: 0,
+            grid_scroll: 0.0,
+            app_list_scroll: 0.0,
+            clipboard_history: Vec::new(),
+            ssh_hosts: Vec::new(),
+            bookmarks: Vec::new(),
+            settings_panels: Vec::new(),
+            recent_documents: Vec::new(),
+            long_press: None,
+            long_press_opened: false,
+            dragged_favorite: None,
+            favorites_reordered: false,
+            last_click: None,
+            windowed: flags.windowed,
+            show_hidden_entries: false,
+            inline_settings: false,
+            open_windows: Vec::new(),
+            window_activator: None,
+            last_activated_window: None,
         };
 
-        // fetch current user asynchronously
-        let fetch_current_user_task =
-            Task::perform(crate::logic::apps::get_current_user(), |result| {
-                cosmic::Action::App(Message::UpdateLoggedUser(result))
+        // The user is fetched once AccountsService turns out to be available
+        let probe_integrations_task =
+            Task::perform(crate::logic::integrations::probe(), |integrations| {
+                cosmic::Action::App(Message::UpdateIntegrations(integrations))
             });
 
+        crate::logic::apps::Apps::set_load_comments(window.config.show_comments);
+        crate::logic::apps::Apps::set_load_generic_names(
+            window.config.name_display != NameDisplay::Name,
+        );
+        crate::logic::apps::Apps::set_hide_uninstalled(window.config.hide_uninstalled_apps);
+        crate::logic::apps::Apps::set_extra_application_dirs(
+            window.config.extra_application_dirs.clone(),
+        );
+        crate::logic::icon_themes::apply(&window.config.icon_theme);
         let fetch_all_apps_task = Task::perform(crate::logic::apps::Apps::load_apps(), |res| {
             cosmic::Action::App(Message::UpdateAvailableApplications(res))
         });
 
-        let fetch_available_categories_task =
-            Task::perform(crate::logic::apps::Apps::load_app_categories(), |res| {
-                cosmic::Action::App(Message::UpdateAvailableCategories(res))
+        let fetch_available_categories_task = Task::perform(
+            crate::logic::apps::Apps::load_app_categories(window.config.clone()),
+            |res| cosmic::Action::App(Message::UpdateAvailableCategories(res)),
+        );
+
+        let fetch_pending_updates_task =
+            Task::perform(crate::logic::flatpak::get_pending_updates(), |res| {
+                cosmic::Action::App(Message::UpdatePendingUpdates(res))
             });
 
-        (
-            window,
-            Task::batch(vec![
-                fetch_current_user_task,
-                fetch_all_apps_task,
-                fetch_available_categories_task,
-            ]),
-        )
+        let fetch_known_apps_task = CosmicClassicMenu::installed_apps_task();
+
+        let fetch_dual_gpu_task =
+            Task::perform(crate::logic::gpu::dedicated_gpu_environment(), |res| {
+                cosmic::Action::App(Message::UpdateDedicatedGpu(res))
+            });
+
+        let mut tasks = vec![
+            fetch_dual_gpu_task,
+            probe_integrations_task,
+            fetch_all_apps_task,
+            fetch_available_categories_task,
+            fetch_pending_updates_task,
+            fetch_known_apps_task,
+        ];
+
+        // Pre-pin commonly used apps on first run so the favorites aren't empty
+        if !window.config.favorites_seeded {
+            tasks.push(Task::perform(
+                crate::logic::favorites::seed_favorites(),
+                |res| cosmic::Action::App(Message::SeedFavorites(res)),
+            ));
+        }
+
+        // There's no popup to open in a window, so its data is loaded right away
+        if window.windowed {
+            tasks.extend(window.menu_data_tasks());
+        }
+
+        (window, Task::batch(tasks))
     }
 
     fn on_close_requested(&self, id: Id) -> Option<Message> {
+        // The candidate window of an input method may take the focus from the popup
+        if self.ime_composing {
+            return None;
+        }
         Some(Message::PopupClosed(id))
     }
 
@@ -246,6 +1064,10 @@ impl Application for CosmicClassicMenu {
     ///
     /// To get a better sense of which widgets are available, check out the `widget` module.
     fn view(&self) -> Element<'_, Message> {
+        if self.windowed {
+            return self.view_main_menu();
+        }
+
         let applet_button_style = &self.config.applet_button_style;
         let panel_type = &self.core.applet.panel_type;
         let size = &self.core.applet.size;
@@ -301,37 +1123,980 @@ impl Application for CosmicClassicMenu {
         match message {
             Message::TogglePopup(popup_type) => self.toggle_popup(popup_type),
             Message::PopupClosed(id) => self.close_popup(id),
+            Message::DismissPopup => match self.popup {
+                Some(id) => Task::batch(vec![
+                    self.close_popup(id),
+                    destroy_popup(id),
+                    AppletButton::focus(),
+                ]),
+                None => Task::none(),
+            },
+            Message::ClearSearchOrDismiss if self.search_field.is_empty() => {
+                self.update(Message::DismissPopup)
+            }
+            Message::ClearSearchOrDismiss => {
+                let category = self
+                    .category_before_search
+                    .take()
+                    .unwrap_or(ApplicationCategory::ALL);
+                self.search_items.clear();
+                self.show_all_results = false;
+                self.select_category(category)
+            }
+            Message::FocusSearch => cosmic::widget::text_input::focus(SEARCH_FIELD_ID.clone()),
             Message::SearchFieldInput(input) => self.update_search_field(&input),
+            Message::UpdateUriSearchItem(query, item) => {
+                // Drop results of queries that were typed over in the meantime
+                if query == self.search_field {
+                    self.search_items
+                        .retain(|item| !matches!(item, SearchItem::OpenUri(..)));
+                    if let Some(item) = item {
+                        self.search_items.insert(0, item);
+                    }
+                }
+                Task::none()
+            }
+            Message::UpdateScriptResults(query, results) => {
+                if query == self.search_field {
+                    // Replace the provider's results of the previous query
+                    if let Some(provider) = results.first().map(|result| result.provider.clone()) {
+                        self.search_items.retain(|item| {
+                            !matches!(item, SearchItem::Script(result) if result.provider == provider)
+                        });
+                    }
+                    self.search_items
+                        .extend(results.into_iter().map(SearchItem::Script));
+                }
+                Task::none()
+            }
+            Message::UpdatePathSearchItems(query, paths) => {
+                // Drop results of queries that were typed over in the meantime
+                if query == self.search_field {
+                    self.search_items = paths.into_iter().map(SearchItem::OpenPath).collect();
+                }
+                Task::none()
+            }
+            Message::OpenUri(uri, handler) => self.open_uri(uri, handler),
+            Message::CopyCharacter(glyph) => self.copy_to_clipboard(glyph.to_string()),
+            Message::StartTimer(duration, label) => {
+                let timer_task =
+                    Task::perform(crate::logic::timer::run(duration, label), |result| {
+                        cosmic::Action::App(Message::TimerFinished(
+                            result.map_err(|e| e.to_string()),
+                        ))
+                    });
+                match self.popup.take() {
+                    Some(p) => Task::batch(vec![destroy_popup(p), timer_task]),
+                    None => timer_task,
+                }
+            }
+            Message::TimerFinished(result) => {
+                if let Err(e) = result {
+                    eprintln!("Failed to notify about the finished timer: {}", e);
+                }
+                Task::none()
+            }
+            Message::RunCommand(command) => {
+                let run_task = Task::perform(
+                    search_providers::run_command(command.clone()),
+                    move |result| {
+                        cosmic::Action::App(Message::CommandFinished(command.clone(), result))
+                    },
+                );
+                match self.popup.take() {
+                    Some(p) => Task::batch(vec![destroy_popup(p), run_task]),
+                    None => run_task,
+                }
+            }
+            Message::CommandFinished(command, result) => match result {
+                Ok(()) => Task::none(),
+                Err(e) => {
+                    eprintln!("Command failed: {}", e);
+                    self.show_toast(fl!("command-failed", command = command))
+                }
+            },
+            Message::LaunchFinished(name, command, result) => match result {
+                Ok(()) => Task::none(),
+                Err(e) => {
+                    eprintln!("Failed to launch {}: {}", name, e);
+                    let toast = Toast::new(fl!("launch-failed", name = name, error = e))
+                        .action(fl!("copy-command"), move |_| {
+                            Message::CopyClipboardEntry(command.clone())
+                        });
+                    self.push_toast(toast)
+                }
+            },
+            Message::Open(target) => {
+                let open_task =
+                    Task::perform(search_providers::open(target.clone()), move |result| {
+                        cosmic::Action::App(Message::OpenFinished(target.clone(), result))
+                    });
+                match self.popup.take() {
+                    Some(p) => Task::batch(vec![destroy_popup(p), open_task]),
+                    None => open_task,
+                }
+            }
+            Message::UpdateSshHosts(hosts) => {
+                self.ssh_hosts = hosts;
+                Task::none()
+            }
+            Message::UpdateBookmarks(bookmarks) => {
+                self.bookmarks = bookmarks;
+                Task::none()
+            }
+            Message::UpdateSettingsPanels(panels) => {
+                self.settings_panels = panels;
+                Task::none()
+            }
+            Message::UpdateRecentDocuments(documents) => {
+                self.recent_documents = documents;
+                Task::none()
+            }
+            Message::OpenSettingsPanel(page) => {
+                let mut command = format!("cosmic-settings {}", page);
+                if std::env::var("FLATPAK_ID").is_ok() {
+                    command = format!("flatpak-spawn --host {}", command);
+                }
+                let env_vars: Vec<(String, String)> = std::env::vars().collect();
+                tokio::spawn(async move {
+                    cosmic::desktop::spawn_desktop_exec(
+                        command,
+                        env_vars,
+                        Some("com.system76.CosmicSettings"),
+                        false,
+                    )
+                    .await;
+                });
+
+                match self.popup.take() {
+                    Some(p) => destroy_popup(p),
+                    None => Task::none(),
+                }
+            }
+            Message::WindowEvent(WindowEvent::Ready(activator)) => {
+                self.window_activator = Some(activator);
+                Task::none()
+            }
+            Message::WindowEvent(WindowEvent::Changed(windows)) => {
+                self.open_windows = windows;
+                let sources = self
+                    .open_windows
+                    .iter()
+                    .map(|window| window.icon.clone())
+                    .collect();
+                self.load_icons(sources)
+            }
+            Message::ActivateWindow(handle) => {
+                if let Some(activator) = &self.window_activator {
+                    activator.activate(handle.clone());
+                    self.last_activated_window = Some(handle);
+                }
+
+                match self.popup.take() {
+                    Some(p) => destroy_popup(p),
+                    None => Task::none(),
+                }
+            }
+            Message::ConnectSsh(host) => {
+                let mut command = format!("ssh {}", host);
+                if std::env::var("FLATPAK_ID").is_ok() {
+                    command = format!("flatpak-spawn --host {}", command);
+                }
+                let env_vars: Vec<(String, String)> = std::env::vars().collect();
+                tokio::spawn(async move {
+                    cosmic::desktop::spawn_desktop_exec(command, env_vars, None, true).await;
+                });
+
+                match self.popup.take() {
+                    Some(p) => destroy_popup(p),
+                    None => Task::none(),
+                }
+            }
+            Message::OpenFinished(target, result) => match result {
+                Ok(()) => Task::none(),
+                Err(e) => {
+                    eprintln!("{}", e);
+                    self.show_toast(fl!("open-failed", target = target))
+                }
+            },
+            Message::ClipboardChanged(text) => {
+                if self.config.clipboard_history {
+                    self.clipboard_history.retain(|entry| *entry != text);
+                    self.clipboard_history.insert(0, text);
+                    self.clipboard_history
+                        .truncate(crate::logic::clipboard::HISTORY_LENGTH);
+                }
+                Task::none()
+            }
+            Message::CopyClipboardEntry(text) => self.copy_to_clipboard(text),
+            Message::JumpToLetter(letter) => {
+                match self
+                    .visible_applications()
+                    .iter()
+                    .position(|app| app.index_letter() == letter)
+                {
+                    Some(index) => self.select_application(index),
+                    None => Task::none(),
+                }
+            }
+            Message::FastScrollMoved(position) => {
+                self.fast_scroll_position = position.clamp(0.0, 1.0);
+                if self.fast_scroll_letter.is_some() {
+                    self.fast_scroll()
+                } else {
+                    Task::none()
+                }
+            }
+            Message::FastScrollDragging(true) => self.fast_scroll(),
+            Message::FastScrollDragging(false) => {
+                self.fast_scroll_letter = None;
+                Task::none()
+            }
+            Message::GridPageSelected(page) => {
+                self.grid_page = page;
+                self.grid_scroll = 0.0;
+                Task::none()
+            }
+            Message::AppListScrolled(offset) => {
+                self.app_list_scroll = offset;
+                Task::none()
+            }
+            Message::GridScrolled(delta) => {
+                self.grid_scroll += delta;
+                // Scrolling down or right flips to the next page
+                if self.grid_scroll <= -1.0 {
+                    self.grid_page = (self.grid_page + 1).min(self.grid_page_count() - 1);
+                    self.grid_scroll = 0.0;
+                } else if self.grid_scroll >= 1.0 {
+                    self.grid_page = self.grid_page.saturating_sub(1);
+                    self.grid_scroll = 0.0;
+                }
+                Task::none()
+            }
+            Message::ShowAllResults => {
+                self.show_all_results = true;
+                Task::none()
+            }
+            Message::UpdateOutput(name, height, scale) => {
+                self.output_heights.insert(name.clone(), height);
+                self.output_scales.insert(name, scale);
+                self.refresh_icons(false)
+            }
+            Message::IconsLoaded(size, loaded) => {
+                // Read at a size that changed since, the icons are being read again
+                if size != self.icons_size {
+                    return Task::none();
+                }
+                let now = Instant::now();
+                for (key, data) in loaded {
+                    self.icons.insert(key, CachedIcon::Ready(data, now));
+                }
+                Task::none()
+            }
+            // Only redraws the fading icons
+            Message::IconFadeTick => Task::none(),
+            Message::ImeComposing(composing) => {
+                let committed = self.ime_composing && !composing;
+                self.ime_composing = composing;
+
+                // Filter once the composed text is committed
+                if committed {
+                    let input = self.search_field.clone();
+                    self.update_search_field(&input)
+                } else {
+                    Task::none()
+                }
+            }
             Message::PowerOptionSelected(action) => self.perform_power_action(action),
-            Message::ApplicationSelected(app) => self.launch_application(app),
+            // Releasing a long press only closes it, the app actions are open by now
+            Message::ApplicationSelected(_) if self.long_press_opened => {
+                self.long_press_opened = false;
+                Task::none()
+            }
+            // Dropping a moved favorite doesn't launch it either
+            Message::ApplicationSelected(_) if self.favorites_reordered => {
+                self.dragged_favorite = None;
+                self.favorites_reordered = false;
+                Task::none()
+            }
+            Message::ApplicationSelected(app) => {
+                self.long_press = None;
+                self.dragged_favorite = None;
+                self.activate_or_launch(app)
+            }
+            Message::ApplicationClicked(index, app) => {
+                let now = Instant::now();
+                let double_click = self.last_click.take().is_some_and(|(app_id, clicked_at)| {
+                    app_id == app.id && now.duration_since(clicked_at) < DOUBLE_CLICK_INTERVAL
+                });
+                // Released long presses and drags are handled as before
+                if double_click || self.long_press_opened || self.favorites_reordered {
+                    return self.update(Message::ApplicationSelected(app));
+                }
+
+                self.long_press = None;
+                self.dragged_favorite = None;
+                self.last_click = Some((app.id.clone(), now));
+                self.select_application(index)
+            }
+            Message::RecentsOrderChanged(order) => {
+                if let Err(e) = self.set_recents_order(order) {
+                    self.report_error(e);
+                }
+                self.select_category(ApplicationCategory::RECENTLY_USED)
+            }
             Message::CategorySelected(category) => self.select_category(category),
+            Message::OpenCategory(category) => {
+                // Destroyed directly, so the close doesn't reset the category afterwards
+                let close_task = match self.popup.take() {
+                    Some(id) => destroy_popup(id),
+                    None => Task::none(),
+                };
+                let open_task = self.toggle_popup(PopupType::MainMenu);
+                Task::batch(vec![close_task, open_task, self.select_category(category)])
+            }
+            Message::CloseCategory => {
+                self.category_opened = false;
+                Task::none()
+            }
             Message::LaunchTool(tool) => self.launch_tool(tool),
             Message::Zbus(result) => self.handle_zbus_result(result),
             Message::UpdateLoggedUser(user) => {
                 self.current_user = user.ok();
                 Task::none()
             }
-            Message::FileEvent(event) => self.handle_event(event),
-            Message::UpdateConfig(config) => {
-                println!("Received updated config: {:?}", config);
-                self.config = config;
-
+            Message::UpdateIntegrations(integrations) => {
+                self.integrations = integrations;
+                let mut tasks = Vec::new();
+                if integrations.logind {
+                    // Systems without a swap or with suspending disabled can't suspend
+                    tasks.push(Task::perform(
+                        crate::power_options::can_suspend(),
+                        |result| {
+                            cosmic::Action::App(Message::UpdateCanSuspend(result.unwrap_or(true)))
+                        },
+                    ));
+                    // Only offered on EFI systems whose firmware supports it
+                    tasks.push(Task::perform(
+                        crate::power_options::can_reboot_to_firmware_setup(),
+                        |result| {
+                            cosmic::Action::App(Message::UpdateCanRebootToFirmware(
+                                result.unwrap_or(false),
+                            ))
+                        },
+                    ));
+                    // Only systemd-boot tells logind about its entries
+                    tasks.push(Task::perform(
+                        crate::power_options::boot_entries(),
+                        |result| {
+                            cosmic::Action::App(Message::UpdateBootEntries(
+                                result.unwrap_or_default(),
+                            ))
+                        },
+                    ));
+                }
+                if integrations.accounts {
+                    tasks.push(Task::perform(
+                        crate::logic::apps::get_current_user(),
+                        |result| cosmic::Action::App(Message::UpdateLoggedUser(result)),
+                    ));
+                }
+                Task::batch(tasks)
+            }
+            Message::UpdateCanSuspend(can_suspend) => {
+                self.can_suspend = can_suspend;
                 Task::none()
             }
-            Message::UpdateAvailableApplications(items) => {
-                self.available_applications = items;
-
+            Message::UpdateCanRebootToFirmware(can_reboot_to_firmware) => {
+                self.can_reboot_to_firmware = can_reboot_to_firmware;
                 Task::none()
             }
-            Message::UpdateAvailableCategories(items) => {
-                self.available_categories = items;
-
+            Message::UpdateBootEntries(boot_entries) => {
+                self.boot_entries = boot_entries;
+                self.selected_boot_entry = None;
                 Task::none()
             }
-        }
-    }
-
-    fn style(&self) -> Option<cosmic::iced_runtime::Appearance> {
+            Message::SelectBootEntry(index) => {
+                self.selected_boot_entry = Some(index);
+                Task::none()
+            }
+            Message::RestartIntoBootEntry(index) => {
+                let Some(entry) = self.boot_entries.get(index).cloned() else {
+                    return Task::none();
+                };
+                // Set before confirming, as cosmic-osd restarts on its own once confirmed
+                let restart_task = if self.integrations.cosmic_osd {
+                    Task::perform(
+                        crate::power_options::set_boot_loader_entry(entry.id.clone()),
+                        move |result| {
+                            cosmic::Action::App(Message::BootEntrySet(
+                                entry.clone(),
+                                result.map_err(PowerActionError::from),
+                            ))
+                        },
+                    )
+                } else {
+                    Self::restart_into_boot_entry(entry)
+                };
+                match self.popup.take() {
+                    Some(p) => Task::batch(vec![destroy_popup(p), restart_task]),
+                    None => restart_task,
+                }
+            }
+            Message::BootEntryRestartFinished(title, result) => match result {
+                Ok(()) => Task::none(),
+                Err(PowerActionError::NotAuthorized) => {
+                    eprintln!("Not authorized to restart into {}", title);
+                    self.show_toast(fl!(
+                        "power-action-not-authorized",
+                        action = fl!("restart-into", entry = title)
+                    ))
+                }
+                Err(PowerActionError::Failed(e)) => {
+                    eprintln!("Failed to restart into {}: {}", title, e);
+                    self.show_toast(fl!(
+                        "power-action-failed",
+                        action = fl!("restart-into", entry = title)
+                    ))
+                }
+            },
+            Message::FileEvent(event) => self.handle_event(event),
+            Message::ParentalControlsChanged(parental_controls) => {
+                crate::logic::apps::Apps::set_parental_controls(parental_controls);
+                self.refresh_apps()
+            }
+            Message::UsageChanged => {
+                // Written by this or another instance, the saved records are the current ones
+                self.recent_applications = usage::load();
+                if self.is_menu_shown()
+                    && self.search_field.is_empty()
+                    && self.selected_category == Some(ApplicationCategory::RECENTLY_USED)
+                {
+                    return self.refresh_apps();
+                }
+                Task::none()
+            }
+            Message::UpdateConfig(config) => {
+                println!("Received updated config: {:?}", config);
+                self.policy = SystemPolicy::policy();
+                let previous = std::mem::replace(&mut self.config, self.policy.enforce(config));
+                // Forget the copied text as soon as the history is turned off
+                if !self.config.clipboard_history {
+                    self.clipboard_history.clear();
+                }
+                if previous.icon_theme != self.config.icon_theme {
+                    crate::logic::icon_themes::apply(&self.config.icon_theme);
+                }
+                // Apps excluded later are forgotten too, not just no longer recorded
+                if previous.recents_exclusions != self.config.recents_exclusions {
+                    let exclusions = self.config.recents_exclusions.clone();
+                    match usage::update(|recent_applications| {
+                        recents::forget_excluded(recent_applications, &exclusions)
+                    }) {
+                        Ok(recent_applications) => self.recent_applications = recent_applications,
+                        Err(e) => self.report_error(Error::State(e)),
+                    }
+                }
+                // Apps of added or removed directories come and go
+                if previous.extra_application_dirs != self.config.extra_application_dirs {
+                    crate::logic::apps::Apps::set_extra_application_dirs(
+                        self.config.extra_application_dirs.clone(),
+                    );
+                    return self.handle_event(Event::Changed);
+                }
+                if previous.hide_uninstalled_apps != self.config.hide_uninstalled_apps {
+                    crate::logic::apps::Apps::set_hide_uninstalled(
+                        self.config.hide_uninstalled_apps,
+                    );
+                    return self.refresh_apps();
+                }
+                if previous.show_comments != self.config.show_comments {
+                    crate::logic::apps::Apps::set_load_comments(self.config.show_comments);
+                    return self.refresh_apps();
+                }
+                if previous.name_display != self.config.name_display {
+                    crate::logic::apps::Apps::set_load_generic_names(
+                        self.config.name_display != NameDisplay::Name,
+                    );
+                    return self.refresh_apps();
+                }
+                if previous.grid_layout != self.config.grid_layout {
+                    self.grid_page = 0;
+                }
+                if !self.tracks_windows() {
+                    self.open_windows.clear();
+                    self.window_activator = None;
+                    self.last_activated_window = None;
+                }
+                // The windows and empty categories come and go with their settings
+                if previous.show_windows != self.config.show_windows
+                    || previous.show_empty_categories != self.config.show_empty_categories
+                    || previous.use_applications_menu != self.config.use_applications_menu
+                    || previous.category_sort != self.config.category_sort
+                    || previous.category_order != self.config.category_order
+                    || previous.category_navigation != self.config.category_navigation
+                {
+                    return self.refresh_favorites();
+                }
+
+                // Favorites changed elsewhere, like by the instance on another panel, show up
+                // in the open menu right away
+                let favorites_changed = previous.merged_favorites()
+                    != self.config.merged_favorites()
+                    || previous.favorite_folders != self.config.favorite_folders;
+                if favorites_changed && self.is_menu_shown() && self.search_field.is_empty() {
+                    return self.refresh_favorites();
+                }
+
+                self.refresh_icons(previous.icon_theme != self.config.icon_theme)
+            }
+            Message::UpdateAvailableApplications(items) => {
+                self.available_applications = items;
+                self.selected_application = None;
+                self.grid_page = 0;
+                self.group_flat_list();
+
+                let sources = self
+                    .available_applications
+                    .iter()
+                    .map(|app| app.icon.clone())
+                    .collect();
+                self.load_icons(sources)
+            }
+            Message::UpdateAvailableCategories(items) => {
+                self.available_categories = items;
+                self.group_flat_list();
+
+                Task::none()
+            }
+            Message::MoveSelection(direction) => self.move_selection(direction),
+            // Enter also commits the composed text of an input method
+            Message::LaunchSelectedApplication if self.ime_composing => Task::none(),
+            Message::LaunchSelectedApplication => {
+                match self
+                    .selected_application
+                    .and_then(|index| self.visible_applications().get(index))
+                {
+                    Some(app) => self.activate_or_launch(app.clone()),
+                    // Prefixed queries only list the items of their provider
+                    None => match self.search_items.first() {
+                        Some(item) if search_providers::parse(&self.search_field).is_some() => {
+                            let message = item.message();
+                            self.update(message)
+                        }
+                        _ => Task::none(),
+                    },
+                }
+            }
+            Message::OpenAppContextMenu(app_id) => {
+                self.app_context_menu = Some(app_id);
+                Task::none()
+            }
+            Message::LongPressStarted(app_id) => {
+                self.long_press = Some(app_id.clone());
+                self.long_press_opened = false;
+                Task::perform(tokio::time::sleep(LONG_PRESS_DURATION), move |_| {
+                    cosmic::Action::App(Message::LongPressElapsed(app_id.clone()))
+                })
+            }
+            Message::FavoriteDragStarted(app_id) => {
+                self.dragged_favorite = Some(app_id.clone());
+                self.favorites_reordered = false;
+                // A button takes a single press handler, so the drag starts the long press too
+                if self.config.touch_mode {
+                    return self.update(Message::LongPressStarted(app_id));
+                }
+                Task::none()
+            }
+            Message::FavoriteDragOver(app_id) => self.move_dragged_favorite(&app_id),
+            Message::FavoriteDragEnded => {
+                self.dragged_favorite = None;
+                self.favorites_reordered = false;
+                Task::none()
+            }
+            Message::LongPressElapsed(app_id) => {
+                if self.long_press.as_ref() == Some(&app_id) {
+                    self.long_press = None;
+                    self.long_press_opened = true;
+                    self.app_context_menu = Some(app_id);
+                }
+                Task::none()
+            }
+            Message::LongPressEnded => {
+                self.long_press = None;
+                self.long_press_opened = false;
+                Task::none()
+            }
+            Message::CloseAppContextMenu => {
+                self.app_context_menu = None;
+                Task::none()
+            }
+            Message::PerformApplicationAction(app, action) => {
+                self.perform_application_action(app, action)
+            }
+            Message::UpdatePendingUpdates(pending_updates) => {
+                self.pending_updates = pending_updates;
+                Task::none()
+            }
+            Message::TogglePinned => {
+                self.pinned = !self.pinned;
+                Task::none()
+            }
+            Message::UpdateInstalledApps(installed) => {
+                match &self.known_apps {
+                    Some(known_apps) => {
+                        // Apps removed again before the menu was opened aren't new anymore
+                        self.new_apps.retain(|app_id| installed.contains(app_id));
+                        self.new_apps
+                            .extend(installed.difference(known_apps).cloned());
+                    }
+                    None => self.known_apps = Some(installed),
+                }
+                Task::none()
+            }
+            Message::FlatpakUpdated(app_id, result) => {
+                match result {
+                    Ok(()) => {
+                        self.pending_updates.remove(&app_id);
+                    }
+                    Err(e) => eprintln!("Failed to update '{}': {}", app_id, e),
+                }
+                Task::none()
+            }
+            Message::UpdateDedicatedGpu(dgpu_environment) => {
+                self.dgpu_environment = dgpu_environment;
+                Task::none()
+            }
+            Message::CloseToast(id) => {
+                self.toasts.remove(id);
+                Task::none()
+            }
+            Message::ElevatedLaunchFinished(name, result) => {
+                self.handle_elevated_launch_result(name, result)
+            }
+            Message::ToolFinished(tool, result) => match result {
+                Ok(()) => Task::none(),
+                Err(e) => {
+                    eprintln!("Selected tool cannot be opened: {}", e);
+                    self.show_toast(fl!("tool-failed", name = tool.get_display_name()))
+                }
+            },
+            Message::SeedFavorites(favorites) => {
+                println!("Seeding {} favorites", favorites.len());
+                let result = CosmicClassicMenuConfig::try_config_handler().and_then(|handler| {
+                    self.config
+                        .set_favorites(&handler, self.policy.user_favorites(&favorites))?;
+                    self.config.set_favorites_seeded(&handler, true)?;
+                    Ok(())
+                });
+                if let Err(e) = result {
+                    self.report_error(e);
+                }
+                self.policy.pin_apps(&mut self.config);
+
+                Task::perform(
+                    crate::logic::apps::Apps::load_app_categories(self.config.clone()),
+                    |res| cosmic::Action::App(Message::UpdateAvailableCategories(res)),
+                )
+            }
+            Message::EditProperty(field, value) => {
+                if let Some(properties) = self.properties.as_mut() {
+                    properties.set(field, value);
+                }
+                Task::none()
+            }
+            Message::SaveProperties => match self.properties.clone() {
+                Some(properties) => Task::perform(
+                    crate::logic::desktop_file::save_user_override(properties),
+                    |result| cosmic::Action::App(Message::PropertiesSaved(result)),
+                ),
+                None => Task::none(),
+            },
+            Message::CloseProperties => {
+                self.properties = None;
+                Task::none()
+            }
+            Message::PropertiesSaved(result) => match result {
+                // The file watcher picks up the override and refreshes the entry
+                Ok(_) => {
+                    self.properties = None;
+                    Task::none()
+                }
+                Err(e) => {
+                    eprintln!("Failed to save the desktop entry override: {}", e);
+                    self.show_toast(fl!("properties-save-failed"))
+                }
+            },
+            Message::OpenLauncherCreator => {
+                self.new_launcher = Some(NewLauncher::default());
+
+                // Swap the context menu for the main menu, which hosts the form
+                let mut tasks = vec![];
+                if let Some(p) = self.popup.take() {
+                    tasks.push(destroy_popup(p));
+                }
+                tasks.push(self.toggle_popup(PopupType::MainMenu));
+                Task::batch(tasks)
+            }
+            Message::EditLauncher(launcher) => {
+                self.new_launcher = Some(launcher);
+                Task::none()
+            }
+            Message::CreateLauncher => match self.new_launcher.clone() {
+                Some(launcher) => Task::perform(
+                    crate::logic::desktop_file::create_launcher(launcher),
+                    |result| cosmic::Action::App(Message::LauncherCreated(result)),
+                ),
+                None => Task::none(),
+            },
+            Message::CloseLauncherCreator => {
+                self.new_launcher = None;
+                Task::none()
+            }
+            Message::UpdateFavoriteFolders(folders) => {
+                self.favorite_folders = folders;
+                Task::none()
+            }
+            Message::DismissError => {
+                self.error = None;
+                Task::none()
+            }
+            Message::ShowInlineSettings(inline_settings) => {
+                self.inline_settings = inline_settings;
+                Task::none()
+            }
+            Message::MenuSettingChanged(setting) => {
+                if let Err(e) = setting.persist(&mut self.config, &self.policy) {
+                    self.report_error(e);
+                }
+                Task::none()
+            }
+            Message::ToggleShowHiddenEntries(show_hidden_entries) => {
+                self.show_hidden_entries = show_hidden_entries;
+                crate::logic::apps::Apps::set_show_hidden(show_hidden_entries);
+                // The hidden entries showing up aren't newly installed
+                self.known_apps = None;
+                Task::batch(vec![self.refresh_apps(), Self::installed_apps_task()])
+            }
+            Message::UpdateOtherSessions(sessions) => {
+                self.other_sessions = sessions;
+                Task::none()
+            }
+            Message::ActivateSession(id) => {
+                let activate_task =
+                    Task::perform(crate::power_options::activate_session(id), |res| {
+                        cosmic::Action::App(Message::Zbus(res))
+                    });
+                match self.popup.take() {
+                    Some(p) => Task::batch(vec![destroy_popup(p), activate_task]),
+                    None => activate_task,
+                }
+            }
+            Message::SelectTimerDelay(action, index) => match TIMER_DELAY_OPTIONS.get(index) {
+                Some(minutes) => {
+                    self.schedule_timer(action, Duration::from_secs(u64::from(*minutes) * 60))
+                }
+                // The last option lets the user type the delay
+                None => {
+                    self.timer_delay_input = Some((action, String::new()));
+                    Task::none()
+                }
+            },
+            Message::EditTimerDelay(minutes) => {
+                if let Some((_, input)) = self.timer_delay_input.as_mut() {
+                    *input = minutes;
+                }
+                Task::none()
+            }
+            Message::ScheduleTimer(action, delay) => self.schedule_timer(action, delay),
+            Message::CancelTimer(TimedAction::Shutdown) => {
+                Task::perform(crate::power_options::cancel_scheduled_shutdown(), |res| {
+                    cosmic::Action::App(match res {
+                        Ok(()) => Message::UpdateScheduledShutdown(None),
+                        Err(e) => Message::ScheduledShutdownFailed(e.to_string()),
+                    })
+                })
+            }
+            Message::CancelTimer(TimedAction::Logout) => {
+                self.scheduled_logout = None;
+                Task::none()
+            }
+            Message::LogoutTimerElapsed(at) => {
+                // Cancelled or scheduled again in the meantime
+                if self.scheduled_logout != Some(at) {
+                    return Task::none();
+                }
+                self.scheduled_logout = None;
+                // Nobody might be there to confirm, so it's not asked
+                match self.popup.take() {
+                    Some(p) => Task::batch(vec![destroy_popup(p), PowerAction::Logout.perform()]),
+                    None => PowerAction::Logout.perform(),
+                }
+            }
+            Message::UpdateScheduledShutdown(at) => {
+                self.scheduled_shutdown = at;
+                Task::none()
+            }
+            Message::ScheduledShutdownFailed(e) => {
+                eprintln!("Failed to schedule the shutdown: {}", e);
+                self.show_toast(fl!("schedule-shutdown-failed"))
+            }
+            Message::TimerCountdownTick => Task::none(),
+            Message::ToggleDarkMode(is_dark) => {
+                match crate::logic::theme_mode::set_dark_mode(is_dark) {
+                    Ok(()) => Task::none(),
+                    Err(e) => {
+                        eprintln!("Failed to switch the theme mode: {}", e);
+                        self.show_toast(fl!("dark-mode-failed"))
+                    }
+                }
+            }
+            Message::UpdateDefaultApps(apps) => {
+                self.default_apps = apps;
+                let sources = self
+                    .default_apps
+                    .iter()
+                    .map(|app| app.icon.clone())
+                    .collect();
+                self.load_icons(sources)
+            }
+            Message::ToggleFolder(name) => {
+                self.expanded_folder = if self.expanded_folder.as_ref() == Some(&name) {
+                    None
+                } else {
+                    Some(name)
+                };
+                Task::none()
+            }
+            Message::EditFolderName(name) => {
+                if let Some((_, folder_name)) = self.new_folder.as_mut() {
+                    *folder_name = name;
+                }
+                Task::none()
+            }
+            Message::CreateFolder => match self.new_folder.take() {
+                Some((app, name)) => self.move_to_folder(&app.id, Some(name.trim().to_string())),
+                None => Task::none(),
+            },
+            Message::CloseFolderCreator => {
+                self.new_folder = None;
+                Task::none()
+            }
+            Message::UpdateAppDetails(app_id, details) => {
+                if let Some((app, loaded)) = self.details.as_mut() {
+                    // Details of an app closed in the meantime aren't shown for another one
+                    if app.id == app_id {
+                        *loaded = details;
+                    }
+                }
+                Task::none()
+            }
+            Message::CloseDetails => {
+                self.details = None;
+                Task::none()
+            }
+            Message::LauncherCreated(result) => match result {
+                // The file watcher adds the new entry to the menu
+                Ok(_) => {
+                    self.new_launcher = None;
+                    Task::none()
+                }
+                Err(e) => {
+                    eprintln!("Failed to create the launcher: {}", e);
+                    self.show_toast(fl!("create-launcher-failed"))
+                }
+            },
+            Message::BootEntrySet(entry, result) => match result {
+                Ok(()) => {
+                    let (main_exec, args) = cosmic_osd_command("restart");
+                    Task::perform(
+                        async move {
+                            cosmic_classic_menu_core::launch::try_supervise(main_exec, &args).await
+                        },
+                        move |result| {
+                            cosmic::Action::App(Message::BootEntryDialogFinished(
+                                entry.clone(),
+                                result,
+                            ))
+                        },
+                    )
+                }
+                Err(e) => self.update(Message::BootEntryRestartFinished(entry.title, Err(e))),
+            },
+            Message::BootEntryDialogFinished(entry, result) => match result {
+                // The dialog may still be shown, so a cancelled one is only noticed on the
+                // next open of the popup, once logind isn't restarting
+                Ok(()) => {
+                    self.boot_entry_set = true;
+                    Task::none()
+                }
+                Err(SuperviseError::NotFound(e)) => {
+                    eprintln!("Failed to show the power dialog: {}", e);
+                    Self::restart_into_boot_entry(entry)
+                }
+                Err(SuperviseError::Failed(e)) => {
+                    eprintln!("Failed to show the power dialog: {}", e);
+                    Task::batch(vec![
+                        Self::reset_boot_entry(),
+                        self.show_toast(fl!(
+                            "power-dialog-failed",
+                            action = fl!("restart-into", entry = entry.title)
+                        )),
+                    ])
+                }
+            },
+            Message::BootEntryReset(result) => {
+                if let Err(e) = result {
+                    eprintln!("Failed to reset the restart into the boot entry: {:?}", e);
+                }
+                Task::none()
+            }
+            Message::PowerCommandFinished(action, result) => match result {
+                Ok(()) => Task::none(),
+                // Fall back to the session D-Bus interfaces without a confirmation dialog
+                // only when there's no dialog, a failing or cancelled one must not go ahead
+                Err(SuperviseError::NotFound(e)) => {
+                    eprintln!("Failed to show the power dialog: {}", e);
+                    action.perform()
+                }
+                Err(SuperviseError::Failed(e)) => {
+                    eprintln!("Failed to show the power dialog: {}", e);
+                    self.show_toast(fl!(
+                        "power-dialog-failed",
+                        action = action.get_display_name()
+                    ))
+                }
+            },
+            Message::PowerActionFinished(action, result) => match result {
+                Ok(()) => Task::none(),
+                // Asking again brings up the password prompt once an agent is running
+                Err(PowerActionError::NotAuthorized) => {
+                    eprintln!("Not authorized to perform {:?}", action);
+                    let toast = Toast::new(fl!(
+                        "power-action-not-authorized",
+                        action = action.get_display_name()
+                    ))
+                    .action(fl!("try-again"), move |_| {
+                        Message::PowerOptionSelected(action.clone())
+                    });
+                    self.push_toast(toast)
+                }
+                Err(PowerActionError::Failed(e)) => {
+                    eprintln!("Failed to perform {:?}: {}", action, e);
+                    self.show_toast(fl!(
+                        "power-action-failed",
+                        action = action.get_display_name()
+                    ))
+                }
+            },
+            Message::Shutdown => self.shutdown(),
+        }
+    }
+
+    fn style(&self) -> Option<cosmic::iced_runtime::Appearance> {
+        if self.windowed {
+            return None;
+        }
         Some(cosmic::applet::style())
     }
 
@@ -341,36 +2106,316 @@ impl Application for CosmicClassicMenu {
     /// emit messages to the application through a channel. They are started at the
     /// beginning of the application, and persist through its lifetime.
     fn subscription(&self) -> Subscription<Self::Message> {
-        Subscription::batch(vec![
-            desktop_files(Id::unique()).map(Message::FileEvent),
+        struct InstanceActivations;
+        struct TerminationRequests;
+        struct ClipboardChanges;
+        struct OpenWindows;
+        struct DesktopFiles;
+        struct UsageChanges;
+        struct ParentalControlsChanges;
+
+        let mut subscriptions = vec![
+            // The menu files and the directory files they name change the categories
+            desktop_files(
+                std::any::TypeId::of::<DesktopFiles>(),
+                crate::logic::apps::application_dirs_with(&self.config.extra_application_dirs)
+                    .into_iter()
+                    .chain(crate::logic::xdg_menu::menus_dirs())
+                    .collect(),
+            )
+            .map(Message::FileEvent),
+            // Other instances of the applet record their launches in the same file
+            usage::changes(std::any::TypeId::of::<UsageChanges>()).map(|_| Message::UsageChanged),
+            // The apps allowed by the parental controls changed
+            parental_controls::changes(std::any::TypeId::of::<ParentalControlsChanges>())
+                .map(Message::ParentalControlsChanged),
+            // Open the menu when a second instance forwards its activation
+            crate::instance::activations(std::any::TypeId::of::<InstanceActivations>())
+                .map(|_| Message::TogglePopup(PopupType::MainMenu)),
+            // Clean up when the panel terminates the applet
+            crate::logic::shutdown::termination_requests(
+                std::any::TypeId::of::<TerminationRequests>(),
+            )
+            .map(|_| Message::Shutdown),
             // Watch for application configuration changes.
             self.core
                 .watch_config::<CosmicClassicMenuConfig>(Self::APP_ID)
-                .map(|update| Message::UpdateConfig(update.config)),
-        ])
+                .map(|update| {
+                    for error in update.errors {
+                        eprintln!("Failed to load part of the updated config: {:?}", error);
+                    }
+                    Message::UpdateConfig(update.config)
+                }),
+        ];
+
+        // Only watch the clipboard once the user opted in to the history
+        if self.config.clipboard_history {
+            subscriptions.push(
+                crate::logic::clipboard::clipboard_changes(
+                    std::any::TypeId::of::<ClipboardChanges>(),
+                )
+                .map(Message::ClipboardChanged),
+            );
+        }
+
+        if self.tracks_windows() {
+            subscriptions.push(
+                crate::logic::windows::open_windows(std::any::TypeId::of::<OpenWindows>())
+                    .map(Message::WindowEvent),
+            );
+        }
+
+        // Redraw every frame while read icons fade in
+        if self.is_menu_shown() && self.icons.values().any(CachedIcon::is_fading) {
+            subscriptions.push(cosmic::iced::window::frames().map(|_| Message::IconFadeTick));
+        }
+
+        // Count down to the scheduled shutdown and logout while they're shown
+        if self.is_menu_shown()
+            && (self.scheduled_shutdown.is_some() || self.scheduled_logout.is_some())
+        {
+            subscriptions.push(
+                cosmic::iced::time::every(Duration::from_secs(1))
+                    .map(|_| Message::TimerCountdownTick),
+            );
+        }
+
+        // Track the output sizes to pick the minimal popup on small screens, and their scale
+        // to render the icons sharp
+        subscriptions.push(event::listen_with(|event, _status, _id| match event {
+            cosmic::iced::Event::PlatformSpecific(event::PlatformSpecific::Wayland(
+                wayland::Event::Output(
+                    wayland::OutputEvent::Created(Some(info))
+                    | wayland::OutputEvent::InfoUpdate(info),
+                    _,
+                ),
+            )) => {
+                let (width, height) = info.logical_size?;
+                // Fractional scales only show in the ratio of the current mode to the
+                // logical size, the integer factor is rounded up
+                let scale = info
+                    .modes
+                    .iter()
+                    .find(|mode| mode.current)
+                    .filter(|_| width > 0 && height > 0)
+                    .map(|mode| {
+                        mode.dimensions.0.max(mode.dimensions.1) as f32 / width.max(height) as f32
+                    })
+                    .unwrap_or(info.scale_factor as f32);
+                Some(Message::UpdateOutput(info.name?, height, scale))
+            }
+            _ => None,
+        }));
+
+        // End dragging a favorite when it's dropped outside of the apps
+        if self.dragged_favorite.is_some() {
+            subscriptions.push(event::listen_with(|event, status, _id| match event {
+                cosmic::iced::Event::Mouse(mouse::Event::ButtonReleased(mouse::Button::Left))
+                | cosmic::iced::Event::Touch(touch::Event::FingerLifted { .. })
+                    if status == event::Status::Ignored =>
+                {
+                    Some(Message::FavoriteDragEnded)
+                }
+                _ => None,
+            }));
+        }
+
+        // Forget a long press once it's released, the app only sees releases over it. The
+        // app's own message comes first when it's released over it.
+        if self.long_press.is_some() || self.long_press_opened {
+            subscriptions.push(event::listen_with(|event, _status, _id| match event {
+                cosmic::iced::Event::Mouse(mouse::Event::ButtonReleased(mouse::Button::Left))
+                | cosmic::iced::Event::Touch(touch::Event::FingerLifted { .. }) => {
+                    Some(Message::LongPressEnded)
+                }
+                _ => None,
+            }));
+        }
+
+        // Keyboard navigation of the application list while the main menu is open
+        if self.is_menu_shown()
+            && self.popup_type == PopupType::MainMenu
+            && self.properties.is_none()
+            && self.new_launcher.is_none()
+            && self.new_folder.is_none()
+            && self.details.is_none()
+        {
+            subscriptions.push(keyboard::on_key_press(|key, modifiers| match key {
+                Key::Named(Named::ArrowDown) => {
+                    Some(Message::MoveSelection(SelectionDirection::Next))
+                }
+                Key::Named(Named::ArrowUp) => {
+                    Some(Message::MoveSelection(SelectionDirection::Previous))
+                }
+                Key::Named(Named::Enter) => Some(Message::LaunchSelectedApplication),
+                Key::Named(Named::Escape) => Some(Message::ClearSearchOrDismiss),
+                Key::Character(character)
+                    if modifiers.command() && matches!(character.as_str(), "l" | "f") =>
+                {
+                    Some(Message::FocusSearch)
+                }
+                _ => None,
+            }));
+            subscriptions.push(event::listen_with(|event, _status, _id| match event {
+                cosmic::iced::Event::InputMethod(input_method::Event::Preedit(text, _)) => {
+                    Some(Message::ImeComposing(!text.is_empty()))
+                }
+                cosmic::iced::Event::InputMethod(
+                    input_method::Event::Commit(_) | input_method::Event::Closed,
+                ) => Some(Message::ImeComposing(false)),
+                _ => None,
+            }));
+        }
+
+        Subscription::batch(subscriptions)
     }
 }
 
 impl CosmicClassicMenu {
     pub fn handle_event(&mut self, event: Event) -> Task<Message> {
+        // Installed or updated apps may come with new images under the same icon names, so
+        // their icons are read again
         match event {
             Event::Changed => {
-                // Update set of available applications
-                Task::perform(crate::logic::apps::Apps::load_apps(), |res| {
-                    cosmic::Action::App(Message::UpdateAvailableApplications(res))
-                })
+                crate::logic::apps::Apps::invalidate_cache();
+                self.icons.clear();
+            }
+            Event::EntriesChanged(paths) => {
+                for source in crate::logic::apps::Apps::reload_entries(&paths) {
+                    self.icons.remove(&icons::key(&source));
+                }
             }
         }
+        // The default apps may have been installed or removed
+        self.default_apps_modified = None;
+
+        // The listed apps read theirs once refreshed, the rest aren't listed again
+        let sources = self
+            .default_apps
+            .iter()
+            .map(|app| app.icon.clone())
+            .chain(self.open_windows.iter().map(|window| window.icon.clone()))
+            .collect();
+        let icons_task = self.load_icons(sources);
+
+        Task::batch(vec![
+            self.refresh_apps(),
+            Self::installed_apps_task(),
+            icons_task,
+        ])
+    }
+
+    /// Loads the ids of all installed apps, to tell the new ones apart.
+    fn installed_apps_task() -> Task<Message> {
+        Task::perform(crate::logic::apps::Apps::load_apps(), |apps| {
+            cosmic::Action::App(Message::UpdateInstalledApps(
+                apps.into_iter().map(|app| app.id).collect(),
+            ))
+        })
+    }
+
+    /// Refreshes what the menu currently shows from the updated set of applications.
+    fn refresh_apps(&self) -> Task<Message> {
+        let apps_task = if self.search_field.is_empty() {
+            let category = self
+                .selected_category
+                .clone()
+                .unwrap_or(ApplicationCategory::ALL);
+            Task::perform(
+                crate::logic::apps::Apps::get_apps_of_category(
+                    category,
+                    self.recent_applications.clone(),
+                    self.config.clone(),
+                ),
+                |res| cosmic::Action::App(Message::UpdateAvailableApplications(res)),
+            )
+        } else {
+            Task::perform(
+                crate::logic::apps::Apps::load_filtered_apps(
+                    self.search_field.clone(),
+                    self.config.clone(),
+                ),
+                |res| cosmic::Action::App(Message::UpdateAvailableApplications(res)),
+            )
+        };
+        let categories_task = Task::perform(
+            crate::logic::apps::Apps::load_app_categories(self.config.clone()),
+            |res| cosmic::Action::App(Message::UpdateAvailableCategories(res)),
+        );
+
+        Task::batch(vec![apps_task, categories_task])
+    }
+
+    /// Whether launching leaves the menu open, as the user pinned it.
+    fn stays_open(&self) -> bool {
+        self.pinned && self.popup_type == PopupType::MainMenu
+    }
+
+    /// Whether the main menu is on screen, either in its popup or in a window.
+    fn is_menu_shown(&self) -> bool {
+        self.windowed || self.popup.is_some()
+    }
+
+    /// Loads the data shown in the main menu that may have changed since it was last shown.
+    ///
+    /// The applications aren't among them, they stay loaded and the file watcher keeps them
+    /// up to date, so the menu shows up without waiting for them.
+    fn menu_data_tasks(&mut self) -> Vec<Task<Message>> {
+        let mut tasks = vec![];
+        if self.integrations.logind {
+            tasks.push(Task::perform(
+                crate::power_options::other_sessions(),
+                |res| cosmic::action::app(Message::UpdateOtherSessions(res.unwrap_or_default())),
+            ));
+        }
+        // Shutdowns can also be scheduled with shutdown(8) while the menu is closed
+        tasks.push(Task::perform(
+            crate::power_options::scheduled_shutdown(),
+            |res| cosmic::action::app(Message::UpdateScheduledShutdown(res.unwrap_or(None))),
+        ));
+        if self.config.search_settings_panels {
+            tasks.push(Task::perform(
+                crate::logic::settings_panels::load(),
+                |panels| cosmic::action::app(Message::UpdateSettingsPanels(panels)),
+            ));
+        }
+        if self.config.search_recent_documents {
+            tasks.push(Task::perform(
+                crate::logic::recent_documents::load(),
+                |documents| cosmic::action::app(Message::UpdateRecentDocuments(documents)),
+            ));
+        }
+        if self.config.search_bookmarks {
+            tasks.push(Task::perform(
+                crate::logic::bookmarks::load(),
+                |bookmarks| cosmic::action::app(Message::UpdateBookmarks(bookmarks)),
+            ));
+        }
+        if self.config.search_ssh_hosts {
+            tasks.push(Task::perform(crate::logic::ssh_hosts::load(), |hosts| {
+                cosmic::action::app(Message::UpdateSshHosts(hosts))
+            }));
+        }
+        // Resolved again once the defaults changed in the meantime
+        let mimeapps_modified = crate::logic::mime_apps::mimeapps_modified();
+        if self.config.show_default_apps
+            && self.default_apps_modified.as_ref() != Some(&mimeapps_modified)
+        {
+            self.default_apps_modified = Some(mimeapps_modified);
+            tasks.push(Task::perform(
+                crate::logic::mime_apps::get_default_apps(),
+                |res| cosmic::action::app(Message::UpdateDefaultApps(res)),
+            ));
+        }
+        tasks
     }
 
     fn toggle_popup(&mut self, popup_type: PopupType) -> Task<Message> {
         let mut tasks = vec![];
         self.popup_type = popup_type;
+        self.inline_settings = false;
         if self.popup_type == PopupType::MainMenu {
-            tasks.push(Task::perform(
-                crate::logic::apps::Apps::load_apps(),
-                |res| cosmic::action::app(Message::UpdateAvailableApplications(res)),
-            ));
+            tasks.extend(self.menu_data_tasks());
         }
 
         if let Some(p) = self.popup.take() {
@@ -379,6 +2424,15 @@ impl CosmicClassicMenu {
         } else {
             let new_id = Id::unique();
             self.popup.replace(new_id);
+            // The restart into the boot entry was cancelled in the dialog of cosmic-osd
+            if std::mem::take(&mut self.boot_entry_set) {
+                tasks.push(Self::reset_boot_entry());
+            }
+            if self.popup_type == PopupType::MainMenu {
+                if let Some(known_apps) = &mut self.known_apps {
+                    known_apps.extend(self.new_apps.drain());
+                }
+            }
 
             let mut popup_settings = self.core.applet.get_popup_settings(
                 self.core.main_window_id().unwrap(),
@@ -388,109 +2442,661 @@ impl CosmicClassicMenu {
                 None,
             );
 
-            // Position the popup based on the panel anchor
-            let width = self.core.applet.suggested_window_size().0;
-            let height = self.core.applet.suggested_window_size().1;
+            // Open the popup away from the panel's edge, starting at the applet button's corner
+            // so it neither covers the panel nor opens off-screen
+            let (anchor, gravity) = match self.core.applet.anchor {
+                PanelAnchor::Left => (Anchor::TopRight, Gravity::BottomRight),
+                PanelAnchor::Right => (Anchor::TopLeft, Gravity::BottomLeft),
+                PanelAnchor::Top => (Anchor::BottomLeft, Gravity::BottomRight),
+                PanelAnchor::Bottom => (Anchor::TopLeft, Gravity::TopRight),
+            };
             popup_settings.positioner = SctkPositioner {
-                gravity: match self.core.applet.anchor {
-                    PanelAnchor::Left => Gravity::BottomLeft,
-                    PanelAnchor::Right => Gravity::BottomRight,
-                    PanelAnchor::Top => Gravity::TopRight,
-                    PanelAnchor::Bottom => Gravity::BottomRight,
-                },
-                anchor: match self.core.applet.anchor {
-                    PanelAnchor::Left => Anchor::TopRight,
-                    PanelAnchor::Right => Anchor::TopLeft,
-                    PanelAnchor::Top => Anchor::BottomLeft,
-                    PanelAnchor::Bottom => Anchor::TopLeft,
+                anchor,
+                gravity,
+                offset: (0, 0),
+                ..popup_settings.positioner
+            };
+
+            tasks.push(get_popup(popup_settings));
+            if self.popup_type == PopupType::MainMenu && self.config.focus_search_on_open {
+                tasks.push(cosmic::widget::text_input::focus(SEARCH_FIELD_ID.clone()));
+            }
+            Task::batch(tasks)
+        }
+    }
+
+    fn close_popup(&mut self, id: Id) -> Task<Message> {
+        let showed_all_apps = self.search_field.is_empty()
+            && self.selected_category == Some(ApplicationCategory::ALL);
+        self.search_field.clear();
+        self.selected_application = None;
+        self.app_context_menu = None;
+        self.properties = None;
+        self.new_launcher = None;
+        self.new_folder = None;
+        self.details = None;
+        self.timer_delay_input = None;
+        self.selected_boot_entry = None;
+        self.expanded_folder = None;
+        self.search_items.clear();
+        self.ime_composing = false;
+        self.show_all_results = false;
+        self.fast_scroll_letter = None;
+        self.category_opened = false;
+        self.grid_page = 0;
+        self.selected_category = Some(ApplicationCategory::ALL);
+        self.category_before_search = None;
+        self.long_press = None;
+        self.long_press_opened = false;
+
+        if self.popup.as_ref() == Some(&id) {
+            self.popup = None;
+        }
+
+        // The list is kept for the next open, only a filtered one is swapped for all apps
+        if showed_all_apps {
+            return Task::none();
+        }
+        Task::perform(crate::logic::apps::Apps::load_apps(), |res| {
+            cosmic::action::app(Message::UpdateAvailableApplications(res))
+        })
+    }
+
+    /// Returns the name of the output the panel is on.
+    fn panel_output() -> Option<String> {
+        std::env::var("COSMIC_PANEL_OUTPUT").ok()
+    }
+
+    /// Returns the logical height of the output the panel is on, if known.
+    pub fn output_height(&self) -> Option<i32> {
+        self.output_heights.get(&Self::panel_output()?).copied()
+    }
+
+    /// Returns the scale factor of the output the panel is on, `1.0` if unknown.
+    pub fn output_scale(&self) -> f32 {
+        Self::panel_output()
+            .and_then(|output| self.output_scales.get(&output).copied())
+            .filter(|scale| *scale > 0.0)
+            .unwrap_or(1.0)
+    }
+
+    /// Returns the size in physical pixels the icons are looked up at, large enough for the
+    /// biggest icons of the menu on the panel's output.
+    fn icon_lookup_size(&self) -> u16 {
+        let size = self
+            .config
+            .grid_layout
+            .icon_size
+            .max(icons::MIN_LOOKUP_SIZE);
+        (f32::from(size) * self.output_scale()).ceil() as u16
+    }
+
+    /// Starts reading the icons that aren't cached yet, in batches off the UI thread.
+    fn load_icons(&mut self, sources: Vec<IconSource>) -> Task<Message> {
+        let mut missing = Vec::new();
+        for source in sources {
+            let key = icons::key(&source);
+            if !self.icons.contains_key(&key) {
+                self.icons.insert(key, CachedIcon::Loading);
+                missing.push(source);
+            }
+        }
+
+        let size = self.icons_size;
+        Task::batch(missing.chunks(icons::BATCH_SIZE).map(|batch| {
+            Task::perform(icons::load(batch.to_vec(), size), move |loaded| {
+                cosmic::action::app(Message::IconsLoaded(size, loaded))
+            })
+        }))
+    }
+
+    /// Reads the icons again when the icon theme or the size they're looked up at changed.
+    fn refresh_icons(&mut self, icon_theme_changed: bool) -> Task<Message> {
+        let size = self.icon_lookup_size();
+        if !icon_theme_changed && size == self.icons_size {
+            return Task::none();
+        }
+
+        self.icons_size = size;
+        self.icons.clear();
+        let sources = self
+            .available_applications
+            .iter()
+            .chain(&self.default_apps)
+            .map(|app| app.icon.clone())
+            .chain(self.open_windows.iter().map(|window| window.icon.clone()))
+            .collect();
+        self.load_icons(sources)
+    }
+
+    /// Returns the layout bound to the panel's output, or the configured one.
+    pub fn menu_layout(&self) -> MenuLayout {
+        Self::panel_output()
+            .and_then(|output| self.config.output_layouts.get(&output).copied())
+            .unwrap_or(self.config.menu_layout)
+    }
+
+    /// Whether the popup should only show the search field and an icon grid.
+    pub fn is_minimal_popup(&self) -> bool {
+        match self.config.minimal_popup {
+            MinimalPopup::Always => true,
+            MinimalPopup::Never => false,
+            MinimalPopup::Automatic => self
+                .output_height()
+                .is_some_and(|height| height < MINIMAL_POPUP_OUTPUT_HEIGHT),
+        }
+    }
+
+    /// Returns the applications to show, capping search results at the configured limit.
+    pub fn visible_applications(&self) -> &[ApplicationEntry] {
+        let limit = self.config.search_results_limit as usize;
+        if self.search_field.is_empty() || self.show_all_results || limit == 0 {
+            &self.available_applications
+        } else {
+            &self.available_applications[..limit.min(self.available_applications.len())]
+        }
+    }
+
+    /// Number of search results left out by the limit.
+    pub fn hidden_results_count(&self) -> usize {
+        self.available_applications.len() - self.visible_applications().len()
+    }
+
+    /// Number of pages of the paginated grid.
+    pub fn grid_page_count(&self) -> usize {
+        match AppletMenu::grid_page_size(self) {
+            Some(page_size) => self.visible_applications().len().div_ceil(page_size).max(1),
+            None => 1,
+        }
+    }
+
+    fn update_search_field(&mut self, input: &str) -> Task<Message> {
+        // Pre-edit text isn't final, keep the results until it's committed
+        if self.ime_composing {
+            self.search_field = input.to_string();
+            return Task::none();
+        }
+
+        if self.search_field.is_empty() && !input.is_empty() {
+            self.category_before_search = self.selected_category.clone();
+        }
+        self.selected_category = None;
+        self.show_all_results = false;
+
+        // Prefixed queries go to their provider instead of searching the applications
+        if let Some((provider, query)) = search_providers::parse(input) {
+            self.search_field = input.to_string();
+            if provider == Provider::Files && !query.is_empty() {
+                // The previous paths stay until the new ones are read
+                self.search_items
+                    .retain(|item| matches!(item, SearchItem::OpenPath(_)));
+            } else {
+                self.search_items = SearchItem::from_provider(provider, query, &self.config);
+            }
+            let apps_task = Task::done(cosmic::action::app(Message::UpdateAvailableApplications(
+                Vec::new(),
+            )));
+            if provider != Provider::Files || query.is_empty() {
+                return apps_task;
+            }
+            let search_field = self.search_field.clone();
+            let paths_task = Task::perform(
+                search_providers::matching_paths(query.to_string()),
+                move |paths| {
+                    cosmic::action::app(Message::UpdatePathSearchItems(search_field.clone(), paths))
                 },
-                offset: (
-                    match self.core.applet.anchor {
-                        PanelAnchor::Left => width.get() as i32, // offset right
-                        _ => 0,
+            );
+            return Task::batch(vec![apps_task, paths_task]);
+        }
+
+        self.search_items = if input.is_empty() {
+            Vec::new()
+        } else {
+            let mode = self.config.search_mode;
+            let mut items = SearchItem::matching(input, mode, self.shows_power_options());
+            if !self.can_suspend {
+                items.retain(|item| !matches!(item, SearchItem::Power(PowerAction::Suspend)));
+            }
+            if !self.can_reboot_to_firmware {
+                items.retain(|item| {
+                    !matches!(item, SearchItem::Power(PowerAction::RebootToFirmware))
+                });
+            }
+            if let Some((duration, label)) = crate::logic::timer::parse_query(input) {
+                items.insert(0, SearchItem::Timer(duration, label));
+            }
+            if self.config.search_characters {
+                items.extend(
+                    crate::logic::characters::matching(input)
+                        .into_iter()
+                        .map(|(glyph, name)| SearchItem::Character(glyph, name)),
+                );
+            }
+            if self.config.search_ssh_hosts {
+                // Typing the command itself works too, like `ssh web`
+                let host_query = input.strip_prefix("ssh ").unwrap_or(input).trim();
+                items.extend(
+                    ranking::rank_with(mode, self.ssh_hosts.clone(), host_query, String::clone)
+                        .into_iter()
+                        .map(SearchItem::SshHost),
+                );
+            }
+            if self.config.search_settings_panels {
+                items.extend(
+                    self.matching_settings_panels(input)
+                        .into_iter()
+                        .map(SearchItem::SettingsPanel),
+                );
+            }
+            // Listed last, under their own headings
+            if self.config.search_recent_documents {
+                items.extend(
+                    ranking::rank_with(mode, self.recent_documents.clone(), input, |document| {
+                        document.name.clone()
+                    })
+                    .into_iter()
+                    .take(MAX_RECENT_DOCUMENT_RESULTS)
+                    .map(SearchItem::RecentDocument),
+                );
+            }
+            if self.config.search_bookmarks {
+                items.extend(
+                    ranking::rank_with(mode, self.bookmarks.clone(), input, |bookmark| {
+                        bookmark.title.clone()
+                    })
+                    .into_iter()
+                    .take(MAX_BOOKMARK_RESULTS)
+                    .map(SearchItem::Bookmark),
+                );
+            }
+            items
+        };
+
+        // Results of the previous query are dropped anyway, the scripts needn't finish them
+        for (_, run) in self.script_runs.drain() {
+            run.abort();
+        }
+
+        if input.is_empty() {
+            self.selected_category = Some(ApplicationCategory::ALL);
+            self.search_field = input.to_string();
+            Task::perform(crate::logic::apps::Apps::load_apps(), |res| {
+                cosmic::action::app(Message::UpdateAvailableApplications(res))
+            })
+        } else {
+            self.search_field = input.to_string();
+            let apps_task = Task::perform(
+                crate::logic::apps::Apps::load_filtered_apps(
+                    self.search_field.clone(),
+                    self.config.clone(),
+                ),
+                |res| cosmic::action::app(Message::UpdateAvailableApplications(res)),
+            );
+
+            let mut tasks = vec![apps_task];
+
+            // Offer to open email and web addresses with their default application
+            if let Some(uri) = crate::logic::mime_apps::uri_for_query(input) {
+                let query = self.search_field.clone();
+                tasks.push(Task::perform(
+                    async move {
+                        crate::logic::mime_apps::get_uri_handler(&uri)
+                            .await
+                            .map(|handler| SearchItem::OpenUri(uri, handler))
+                    },
+                    move |item| {
+                        cosmic::action::app(Message::UpdateUriSearchItem(query.clone(), item))
                     },
-                    match self.core.applet.anchor {
-                        PanelAnchor::Top => height.get() as i32, // offset down
-                        _ => 0,
+                ));
+            }
+
+            for provider in &self.config.search_scripts {
+                let query = self.search_field.clone();
+                let (run, handle) = Task::perform(
+                    script_providers::query(provider.clone(), query.clone()),
+                    move |results| {
+                        cosmic::action::app(Message::UpdateScriptResults(query.clone(), results))
                     },
-                ),
-                ..Default::default()
-            };
+                )
+                .abortable();
+                self.script_runs.insert(provider.clone(), handle);
+                tasks.push(run);
+            }
 
-            tasks.push(get_popup(popup_settings));
             Task::batch(tasks)
         }
     }
 
-    fn close_popup(&mut self, id: Id) -> Task<Message> {
-        self.search_field.clear();
-        self.selected_category = Some(ApplicationCategory::ALL);
-        self.available_applications = Vec::new();
+    /// Moves the dragged favorite to the position of the one it's dragged over,
+    /// saving the new order right away.
+    fn move_dragged_favorite(&mut self, target_id: &str) -> Task<Message> {
+        let Some(dragged_id) = self.dragged_favorite.as_deref() else {
+            return Task::none();
+        };
+        let dragged_index = self
+            .available_applications
+            .iter()
+            .position(|app| app.id == dragged_id);
+        let target_index = self
+            .available_applications
+            .iter()
+            .position(|app| app.id == target_id);
+        let (Some(dragged_index), Some(target_index)) = (dragged_index, target_index) else {
+            return Task::none();
+        };
+        // The apps pinned by the administrator keep their place at the top
+        if dragged_index == target_index
+            || self.policy.is_pinned(dragged_id)
+            || self.policy.is_pinned(target_id)
+        {
+            return Task::none();
+        }
 
-        if self.popup.as_ref() == Some(&id) {
-            self.popup = None;
+        // Only the user's own favorites are reordered, the listed ones also hold system-wide
+        // favorites and leave out the ones that aren't installed
+        let mut favorites = self.config.favorites.clone();
+        let dragged_favorite = favorites.iter().position(|id| id == dragged_id);
+        let target_favorite = favorites.iter().position(|id| id == target_id);
+        let (Some(dragged_favorite), Some(target_favorite)) = (dragged_favorite, target_favorite)
+        else {
+            return Task::none();
+        };
+        let dragged = favorites.remove(dragged_favorite);
+        favorites.insert(target_favorite, dragged);
+
+        let dragged = self.available_applications.remove(dragged_index);
+        self.available_applications.insert(target_index, dragged);
+        self.favorites_reordered = true;
+        // Dragged rather than held, the actions stay closed
+        self.long_press = None;
+
+        let result = CosmicClassicMenuConfig::try_config_handler().and_then(|handler| {
+            self.config
+                .set_favorites(&handler, self.policy.user_favorites(&favorites))
+                .map_err(Error::from)
+        });
+        if let Err(e) = result {
+            self.report_error(e);
         }
+        self.policy.pin_apps(&mut self.config);
 
         Task::none()
     }
 
-    fn update_search_field(&mut self, input: &str) -> Task<Message> {
-        self.selected_category = None;
+    /// Puts the text into the clipboard and closes the menu.
+    fn copy_to_clipboard(&mut self, text: String) -> Task<Message> {
+        let copy_task = cosmic::iced::clipboard::write(text);
+        match self.popup.take() {
+            Some(p) => Task::batch(vec![copy_task, destroy_popup(p)]),
+            None => copy_task,
+        }
+    }
 
-        if input.is_empty() {
-            self.selected_category = Some(ApplicationCategory::ALL);
-            self.search_field = input.to_string();
-            Task::perform(crate::logic::apps::Apps::load_apps(), |res| {
-                cosmic::action::app(Message::UpdateAvailableApplications(res))
-            })
-        } else {
-            self.search_field = input.to_string();
-            Task::perform(
-                crate::logic::apps::Apps::load_filtered_apps(self.search_field.clone()),
-                |res| cosmic::action::app(Message::UpdateAvailableApplications(res)),
-            )
+    /// Opens the URI with the handler, passing it in place of the field codes of its command.
+    fn open_uri(&mut self, uri: String, mut handler: ApplicationEntry) -> Task<Message> {
+        let exec = handler.exec.clone().unwrap_or_default();
+        handler.exec = Some(cosmic_classic_menu_core::launch::exec_with_urls(
+            &exec,
+            &[uri],
+        ));
+
+        self.launch_application(handler)
+    }
+
+    fn move_selection(&mut self, direction: SelectionDirection) -> Task<Message> {
+        let count = self.visible_applications().len();
+        if count == 0 {
+            return Task::none();
         }
+
+        let index = match (self.selected_application, direction) {
+            (None, SelectionDirection::Next) => 0,
+            (None, SelectionDirection::Previous) => count - 1,
+            (Some(index), SelectionDirection::Next) => (index + 1).min(count - 1),
+            (Some(index), SelectionDirection::Previous) => index.saturating_sub(1),
+        };
+
+        self.select_application(index)
     }
 
-    fn perform_power_action(&mut self, action: PowerAction) -> Task<Message> {
-        let is_flatpak = std::env::var("FLATPAK_ID").is_ok();
-        let main_exec = if is_flatpak {
-            "flatpak-spawn"
-        } else {
-            "cosmic-osd"
+    /// Scrolls the list proportionally to the pointer position over the fast scroller.
+    fn fast_scroll(&mut self) -> Task<Message> {
+        let applications = self.visible_applications();
+        let index = ((self.fast_scroll_position * applications.len() as f32) as usize)
+            .min(applications.len().saturating_sub(1));
+        self.fast_scroll_letter = Some(
+            applications
+                .get(index)
+                .map(ApplicationEntry::index_letter)
+                .unwrap_or('#'),
+        );
+
+        scrollable::snap_to(
+            APP_LIST_ID.clone(),
+            RelativeOffset {
+                x: 0.0,
+                y: self.fast_scroll_position,
+            },
+        )
+    }
+
+    /// Returns the settings pages whose name or keywords match the query, best name match
+    /// first.
+    fn matching_settings_panels(&self, query: &str) -> Vec<SettingsPanel> {
+        let mode = self.config.search_mode;
+        let mut panels = ranking::rank_with(mode, self.settings_panels.clone(), query, |panel| {
+            panel.name.clone()
+        });
+        for panel in &self.settings_panels {
+            let keyword_matches = panel
+                .keywords
+                .iter()
+                .any(|keyword| ranking::matched_indices_with(mode, keyword, query).is_some());
+            if keyword_matches && !panels.contains(panel) {
+                panels.push(panel.clone());
+            }
+        }
+        panels.truncate(MAX_SETTINGS_PANEL_RESULTS);
+        panels
+    }
+
+    /// Selects the application at `index` and scrolls the list to it.
+    fn select_application(&mut self, index: usize) -> Task<Message> {
+        let count = self.visible_applications().len();
+        self.selected_application = Some(index);
+        // Flip the paginated grid to the page of the selection
+        if let Some(page_size) = AppletMenu::grid_page_size(self) {
+            self.grid_page = index / page_size;
+        }
+
+        // The wide layout flows into columns, so scroll to the row within its column
+        let (row, rows) = match self.menu_layout() {
+            MenuLayout::Wide => {
+                let columns = self.config.wide_layout.columns as usize;
+                let rows = AppletMenu::rows_per_column(self, columns);
+                (index % rows, rows)
+            }
+            _ => (index, count),
         };
-        let mut args = if is_flatpak {
-            vec!["--host", "cosmic-osd"]
-        } else {
-            vec![]
+
+        // Focusing the row emits an accessibility focus event, which lets the
+        // magnifier viewport follow the keyboard selection.
+        Task::batch(vec![
+            cosmic::widget::button::focus(app_entry_id(index)),
+            scrollable::snap_to(
+                APP_LIST_ID.clone(),
+                RelativeOffset {
+                    x: 0.0,
+                    y: row as f32 / (rows - 1).max(1) as f32,
+                },
+            ),
+        ])
+    }
+
+    /// Returns whether all apps are listed at once under headers of their categories.
+    pub fn shows_flat_list(&self) -> bool {
+        self.config.category_navigation == CategoryNavigation::FlatList
+            && self.search_field.is_empty()
+            && self.selected_category == Some(ApplicationCategory::ALL)
+    }
+
+    /// Orders the apps of the flat list by their category, keeping them alphabetical within
+    /// it, so selecting them with the keyboard follows the list.
+    fn group_flat_list(&mut self) {
+        if !self.shows_flat_list() {
+            return;
+        }
+        let categories = &self.available_categories;
+        self.available_applications.sort_by_key(|app| {
+            categories
+                .iter()
+                .position(|category| category.includes(app))
+                // Apps without a category come last
+                .unwrap_or(usize::MAX)
+        });
+    }
+
+    /// Returns whether the power options are enabled and can be performed.
+    pub fn shows_power_options(&self) -> bool {
+        self.config.show_power_options && self.integrations.power_actions()
+    }
+
+    /// Schedules the shutdown with logind, or counts down to the logout, which logind can't
+    /// schedule, until the applet logs out itself.
+    fn schedule_timer(&mut self, action: TimedAction, delay: Duration) -> Task<Message> {
+        self.timer_delay_input = None;
+        let Some(at) = SystemTime::now()
+            .checked_add(delay)
+            .filter(|_| delay <= MAX_TIMER_DELAY)
+        else {
+            return Task::none();
         };
         match action {
-            PowerAction::Logout => {
-                args.push("log-out");
-            }
-            PowerAction::Reboot => {
-                args.push("restart");
+            TimedAction::Shutdown => {
+                Task::perform(crate::power_options::schedule_shutdown(at), move |res| {
+                    cosmic::Action::App(match res {
+                        Ok(()) => Message::UpdateScheduledShutdown(Some(at)),
+                        Err(e) => Message::ScheduledShutdownFailed(e.to_string()),
+                    })
+                })
             }
-            PowerAction::Shutdown => {
-                args.push("shutdown");
+            TimedAction::Logout => {
+                self.scheduled_logout = Some(at);
+                Task::perform(tokio::time::sleep(delay), move |_| {
+                    cosmic::Action::App(Message::LogoutTimerElapsed(at))
+                })
             }
+        }
+    }
+
+    fn perform_power_action(&mut self, action: PowerAction) -> Task<Message> {
+        // Without cosmic-osd there's no confirmation dialog, so the action is performed right away
+        if !self.integrations.cosmic_osd {
+            return match self.popup.take() {
+                Some(p) => Task::batch(vec![destroy_popup(p), action.perform()]),
+                None => action.perform(),
+            };
+        }
+
+        let dialog = match action {
+            PowerAction::Logout => "log-out",
+            PowerAction::Reboot => "restart",
+            PowerAction::RebootToFirmware => "enter-bios",
+            PowerAction::Shutdown => "shutdown",
             _ => return action.perform(),
         };
+        let (main_exec, args) = cosmic_osd_command(dialog);
+
+        let power_command_task = Task::perform(
+            async move { cosmic_classic_menu_core::launch::try_supervise(main_exec, &args).await },
+            move |result| {
+                cosmic::Action::App(Message::PowerCommandFinished(action.clone(), result))
+            },
+        );
 
-        if let Err(_) = process::Command::new(main_exec).args(args).spawn() {
-            return action.perform();
+        match self.popup.take() {
+            Some(p) => Task::batch(vec![destroy_popup(p), power_command_task]),
+            None => power_command_task,
         }
+    }
 
-        if let Some(p) = self.popup.take() {
-            return destroy_popup(p);
+    /// Makes the next restart boot into the default entry again, unless logind is already
+    /// restarting into the one set before.
+    fn reset_boot_entry() -> Task<Message> {
+        Task::perform(crate::power_options::reset_boot_loader_entry(), |result| {
+            cosmic::Action::App(Message::BootEntryReset(
+                result.map_err(PowerActionError::from),
+            ))
+        })
+    }
+
+    /// Restarts into the entry of the boot loader right away, without confirming.
+    fn restart_into_boot_entry(entry: BootEntry) -> Task<Message> {
+        Task::perform(
+            crate::power_options::restart_to_boot_loader_entry(entry.id),
+            move |result| {
+                cosmic::Action::App(Message::BootEntryRestartFinished(
+                    entry.title.clone(),
+                    result.map_err(PowerActionError::from),
+                ))
+            },
+        )
+    }
+
+    /// Whether the open windows are needed, for their category or to activate running apps.
+    fn tracks_windows(&self) -> bool {
+        self.config.show_windows || self.config.activate_running_apps
+    }
+
+    /// Focuses the app's next window when it's running and running apps are activated,
+    /// launching it otherwise.
+    fn activate_or_launch(&mut self, app: ApplicationEntry) -> Task<Message> {
+        let windows: Vec<&WindowHandle> = self
+            .open_windows
+            .iter()
+            .filter(|window| window.app_id.eq_ignore_ascii_case(&app.id))
+            .map(|window| &window.handle)
+            .collect();
+        if !self.config.activate_running_apps || windows.is_empty() {
+            return self.launch_application(app);
         }
 
-        Task::none()
+        // Each click moves on to the window after the one focused last
+        let next = self
+            .last_activated_window
+            .as_ref()
+            .and_then(|last| windows.iter().position(|handle| *handle == last))
+            .map_or(0, |index| (index + 1) % windows.len());
+        let handle = windows[next].clone();
+        self.update(Message::ActivateWindow(handle))
     }
 
     fn launch_application(&mut self, app: ApplicationEntry) -> Task<Message> {
-        let mut app_exec = app.exec.clone().unwrap();
-        let env_vars: Vec<(String, String)> = std::env::vars().collect();
+        let use_dgpu = app.prefers_dgpu;
+        self.launch_application_on(app, use_dgpu)
+    }
+
+    /// Launches the app, on the dedicated GPU if requested and available.
+    fn launch_application_on(&mut self, app: ApplicationEntry, use_dgpu: bool) -> Task<Message> {
+        let Some(mut app_exec) = app.exec.clone() else {
+            self.report_error(Error::MissingExec(app.name.clone()));
+            return Task::none();
+        };
+        let mut env_vars: Vec<(String, String)> = std::env::vars().collect();
+
+        if let Some(dgpu_environment) = self.dgpu_environment.as_ref().filter(|_| use_dgpu) {
+            env_vars.retain(|(key, _)| !dgpu_environment.iter().any(|(k, _)| k == key));
+            env_vars.extend(dgpu_environment.iter().cloned());
+        }
+
+        if let Some(launch_override) = self.config.launch_overrides.get(&app.id) {
+            app_exec = launch_override.apply_args(&app_exec);
+            env_vars.retain(|(key, _)| !launch_override.env.contains_key(key));
+            env_vars.extend(launch_override.env.clone());
+        }
+        if self.config.launch_in_scope {
+            app_exec = cosmic_classic_menu_core::launch::scoped_exec(&app_exec, &app.id);
+        }
         let app_id = Some(app.id.clone());
         let is_terminal = app.is_terminal;
 
@@ -500,57 +3106,343 @@ impl CosmicClassicMenu {
             app_exec = format!("flatpak-spawn --host {}", app_exec);
         }
 
-        tokio::spawn(async move {
-            cosmic::desktop::spawn_desktop_exec(app_exec, env_vars, app_id.as_deref(), is_terminal)
-                .await;
-        });
+        // Terminal apps report their failures in the terminal
+        let launch_task = if is_terminal {
+            // libcosmic only drops the field codes, the escaped percent signs are resolved here
+            let app_exec = cosmic_classic_menu_core::launch::join_exec(
+                &cosmic_classic_menu_core::launch::split_exec(&app_exec),
+            );
+            tokio::spawn(async move {
+                cosmic::desktop::spawn_desktop_exec(app_exec, env_vars, app_id.as_deref(), true)
+                    .await;
+            });
+            Task::none()
+        } else {
+            let name = app.name.clone();
+            let command = app_exec.clone();
+            Task::perform(
+                cosmic_classic_menu_core::launch::supervise_exec(app_exec, env_vars),
+                move |result| {
+                    cosmic::Action::App(Message::LaunchFinished(
+                        name.clone(),
+                        command.clone(),
+                        result,
+                    ))
+                },
+            )
+        };
+
+        // Keep the popup open so the user learns why the app may run on the default GPU,
+        // the first time only as it's the same on every launch
+        if self.is_missing_preferred_gpu(&app) && self.dgpu_warned_apps.insert(app.id.clone()) {
+            let toast = Toast::new(fl!("dgpu-unavailable", name = app.name.clone()));
+            if let Err(e) = self.update_recent_applications(&app) {
+                self.report_error(e);
+            }
+            return Task::batch(vec![
+                launch_task,
+                self.toasts.push(toast).map(cosmic::Action::App),
+            ]);
+        }
 
-        self.update_recent_applications(app);
+        // The app is running already, the banner shows the next time the menu opens
+        if let Err(e) = self.update_recent_applications(&app) {
+            self.report_error(e);
+        }
 
-        if let Some(p) = self.popup.take() {
-            return destroy_popup(p);
+        if self.stays_open() {
+            return launch_task;
+        }
+        match self.popup.take() {
+            Some(p) => Task::batch(vec![destroy_popup(p), launch_task]),
+            None => launch_task,
         }
-        Task::none()
     }
 
-    fn update_recent_applications(&mut self, app: ApplicationEntry) {
-        let current_recent_application = self
-            .config
-            .recent_applications
-            .iter_mut()
-            .find(|x| x.app_id == app.id);
-        if let Some(recent_app) = current_recent_application {
-            if recent_app.launch_count < u32::MAX {
-                recent_app.launch_count += 1;
+    /// Returns whether the app is pinned, directly or in a folder.
+    pub fn is_favorite(&self, app_id: &str) -> bool {
+        favorites::is_in_folder(&self.config.favorite_folders, app_id)
+            || self.config.merged_favorites().iter().any(|id| id == app_id)
+    }
+
+    /// Returns whether the app prefers a non-default GPU that isn't present.
+    pub fn is_missing_preferred_gpu(&self, app: &ApplicationEntry) -> bool {
+        app.prefers_dgpu && self.dgpu_environment.is_none()
+    }
+
+    fn perform_application_action(
+        &mut self,
+        app: ApplicationEntry,
+        action: ApplicationAction,
+    ) -> Task<Message> {
+        self.app_context_menu = None;
+
+        let closes_menu = action.closes_menu();
+        let action_task = match action {
+            ApplicationAction::UpdateFlatpak => {
+                let app_id = app.id.clone();
+                Task::perform(
+                    crate::logic::flatpak::update_application(app.id),
+                    move |result| {
+                        cosmic::Action::App(Message::FlatpakUpdated(app_id.clone(), result))
+                    },
+                )
             }
-        } else {
-            self.config.recent_applications.push(RecentApplication {
-                app_id: app.id.clone(),
-                launch_count: 1,
-            });
+            ApplicationAction::LaunchWithDedicatedGpu => self.launch_application_on(app, true),
+            ApplicationAction::RunAsAdministrator => {
+                let name = app.name.clone();
+                let exec = app.exec.clone().unwrap_or_default();
+                Task::perform(
+                    crate::logic::elevation::run_as_administrator(exec),
+                    move |result| {
+                        cosmic::Action::App(Message::ElevatedLaunchFinished(name.clone(), result))
+                    },
+                )
+            }
+            ApplicationAction::Properties => {
+                self.properties = ApplicationProperties::from_entry(&app);
+                Task::none()
+            }
+            ApplicationAction::Details => {
+                let app_id = app.id.clone();
+                self.details = Some((app, None));
+                Task::perform(
+                    crate::logic::appstream::load_details(app_id.clone()),
+                    move |res| cosmic::Action::App(Message::UpdateAppDetails(app_id.clone(), res)),
+                )
+            }
+            ApplicationAction::AddToFavorites => Task::batch(vec![
+                self.set_favorite(&app.id, true),
+                self.show_toast(fl!("pinned", name = app.name.clone())),
+            ]),
+            ApplicationAction::RemoveFromFavorites => Task::batch(vec![
+                self.set_favorite(&app.id, false),
+                self.show_toast(fl!("unpinned", name = app.name.clone())),
+            ]),
+            ApplicationAction::MoveToFolder(name) => self.move_to_folder(&app.id, Some(name)),
+            ApplicationAction::NewFolder => {
+                self.new_folder = Some((app, String::new()));
+                Task::none()
+            }
+            ApplicationAction::RemoveFromFolder => self.move_to_folder(&app.id, None),
+            ApplicationAction::RemoveFromRecents => {
+                if let Err(e) = self.forget_recent_application(&app.id) {
+                    self.report_error(e);
+                }
+                self.select_category(ApplicationCategory::RECENTLY_USED)
+            }
+            ApplicationAction::ShowDesktopFile => {
+                let Some(path) = app.path else {
+                    return Task::none();
+                };
+                Task::perform(
+                    async move { crate::logic::file_manager::show_item(&path).await },
+                    |result| cosmic::Action::App(Message::Zbus(result)),
+                )
+            }
+        };
+
+        // Get out of the way of whatever the action opened
+        if closes_menu {
+            if let Some(p) = self.popup.take() {
+                return Task::batch(vec![destroy_popup(p), action_task]);
+            }
+        }
+        action_task
+    }
+
+    fn handle_elevated_launch_result(
+        &mut self,
+        name: String,
+        result: Result<(), ElevationError>,
+    ) -> Task<Message> {
+        let message = match result {
+            Ok(()) | Err(ElevationError::Dismissed) => return Task::none(),
+            Err(ElevationError::NotAuthorized) => fl!("admin-not-authorized", name = name),
+            Err(ElevationError::Failed(e)) => {
+                eprintln!("Failed to run '{}' as administrator: {}", name, e);
+                fl!("admin-launch-failed", name = name)
+            }
+        };
+
+        self.show_toast(message)
+    }
+
+    fn update_recent_applications(&mut self, app: &ApplicationEntry) -> crate::error::Result<()> {
+        if recents::is_excluded(&app.id, &self.config.recents_exclusions) {
+            return Ok(());
         }
 
-        self.config
-            .write_entry(CosmicClassicMenuConfig::config_handler().as_ref().unwrap())
-            .expect("Failed to write recent applications config");
+        let half_life_days = self.config.launch_count_half_life_days;
+        self.recent_applications = usage::update(|recent_applications| {
+            recents::record_launch(
+                recent_applications,
+                &app.id,
+                unix_timestamp(),
+                half_life_days,
+            );
+            true
+        })
+        .map_err(Error::State)?;
+        Ok(())
+    }
+
+    fn set_recents_order(&mut self, order: RecentsOrder) -> crate::error::Result<()> {
+        let handler = CosmicClassicMenuConfig::try_config_handler()?;
+        self.config.set_recents_order(&handler, order)?;
+        Ok(())
+    }
+
+    /// Drops the app from the recently used ones, leaving its favorite and other state alone.
+    fn forget_recent_application(&mut self, app_id: &str) -> crate::error::Result<()> {
+        self.recent_applications =
+            usage::update(|recent_applications| recents::forget(recent_applications, app_id))
+                .map_err(Error::State)?;
+        Ok(())
+    }
+
+    /// Logs the failure and shows it in the banner of the popup.
+    fn report_error(&mut self, error: Error) {
+        eprintln!("cosmic-classic-menu ERROR: {:?}", error);
+        self.error = Some(error.to_string());
+    }
+
+    /// Releases the bus name before exiting, the usage data is saved on every change.
+    fn shutdown(&mut self) -> Task<Message> {
+        // Don't keep the panel waiting if the config or D-Bus calls hang
+        std::thread::spawn(|| {
+            std::thread::sleep(crate::logic::shutdown::SHUTDOWN_TIMEOUT);
+            eprintln!("Shutdown timed out, exiting");
+            process::exit(1);
+        });
+
+        crate::instance::release();
+
+        process::exit(0)
     }
 
     fn select_category(&mut self, category: ApplicationCategory) -> Task<Message> {
         self.search_field.clear();
         self.selected_category = Some(category.clone());
+        self.category_opened = true;
 
-        Task::perform(
-            crate::logic::apps::Apps::get_apps_of_category(category),
+        let folders_task = if category == ApplicationCategory::FAVORITES {
+            Task::perform(
+                crate::logic::apps::Apps::get_favorite_folders(
+                    self.config.favorite_folders.clone(),
+                ),
+                |res| cosmic::Action::App(Message::UpdateFavoriteFolders(res)),
+            )
+        } else {
+            Task::none()
+        };
+        let apps_task = Task::perform(
+            crate::logic::apps::Apps::get_apps_of_category(
+                category,
+                self.recent_applications.clone(),
+                self.config.clone(),
+            ),
             |res| cosmic::Action::App(Message::UpdateAvailableApplications(res)),
-        )
+        );
+
+        Task::batch(vec![apps_task, folders_task])
+    }
+
+    /// Moves the favorite into the named folder, creating it if needed, or out of its folder
+    /// when `folder` is `None`. Folders left empty are removed.
+    fn move_to_folder(&mut self, app_id: &str, folder: Option<String>) -> Task<Message> {
+        let mut folders = self.config.favorite_folders.clone();
+        favorites::move_to_folder(&mut folders, app_id, folder);
+
+        let result = CosmicClassicMenuConfig::try_config_handler().and_then(|handler| {
+            self.config
+                .set_favorite_folders(&handler, folders)
+                .map_err(Error::from)
+        });
+        if let Err(e) = result {
+            self.report_error(e);
+        }
+
+        self.refresh_favorites()
+    }
+
+    /// Pins or unpins the app, remembering unpinned system favorites so they stay unpinned.
+    fn set_favorite(&mut self, app_id: &str, favorite: bool) -> Task<Message> {
+        if self.policy.is_pinned(app_id) {
+            return Task::none();
+        }
+        let mut favorites = self.config.favorites.clone();
+        let mut removed_favorites = self.config.removed_favorites.clone();
+        favorites.retain(|id| id != app_id);
+        removed_favorites.retain(|id| id != app_id);
+        if favorite {
+            favorites.push(app_id.to_string());
+        } else {
+            removed_favorites.push(app_id.to_string());
+        }
+
+        let result = CosmicClassicMenuConfig::try_config_handler().and_then(|handler| {
+            self.config
+                .set_favorites(&handler, self.policy.user_favorites(&favorites))?;
+            self.config
+                .set_removed_favorites(&handler, removed_favorites)?;
+            Ok(())
+        });
+        if let Err(e) = result {
+            self.report_error(e);
+        }
+        self.policy.pin_apps(&mut self.config);
+
+        if favorite {
+            self.refresh_favorites()
+        } else {
+            // Unpinned apps don't stay in folders either
+            self.move_to_folder(app_id, None)
+        }
+    }
+
+    /// Reloads the categories, and the favorites if they're shown.
+    fn refresh_favorites(&mut self) -> Task<Message> {
+        let categories_task = Task::perform(
+            crate::logic::apps::Apps::load_app_categories(self.config.clone()),
+            |res| cosmic::Action::App(Message::UpdateAvailableCategories(res)),
+        );
+        match &self.selected_category {
+            Some(category) if *category == ApplicationCategory::FAVORITES => {
+                let category = category.clone();
+                Task::batch(vec![self.select_category(category), categories_task])
+            }
+            _ => categories_task,
+        }
     }
 
     fn launch_tool(&mut self, tool: SystemTool) -> Task<Message> {
-        tool.perform();
-        if let Some(p) = self.popup.take() {
-            return destroy_popup(p);
+        let tool_task = Task::perform(tool.clone().perform(), move |result| {
+            cosmic::Action::App(Message::ToolFinished(tool.clone(), result))
+        });
+
+        if self.stays_open() {
+            return tool_task;
+        }
+        match self.popup.take() {
+            Some(p) => Task::batch(vec![destroy_popup(p), tool_task]),
+            None => tool_task,
+        }
+    }
+
+    /// Shows a toast, reopening the menu since toasts are only visible inside it.
+    fn show_toast(&mut self, message: String) -> Task<Message> {
+        self.push_toast(Toast::new(message))
+    }
+
+    /// Like [`Self::show_toast`], for toasts with an action.
+    fn push_toast(&mut self, toast: Toast<Message>) -> Task<Message> {
+        let toast_task = self.toasts.push(toast).map(cosmic::Action::App);
+        if !self.is_menu_shown() {
+            Task::batch(vec![self.toggle_popup(PopupType::MainMenu), toast_task])
+        } else {
+            toast_task
         }
-        Task::none()
     }
 
     fn handle_zbus_result(&self, result: Result<(), zbus::Error>) -> Task<Message> {
@@ -562,37 +3454,231 @@ impl CosmicClassicMenu {
     }
 
     fn view_main_menu(&self) -> Element<'_, Message> {
-        // TODO: Implement grid view
         AppletMenu::view_main_menu_list(&self)
     }
 
     fn view_context_menu(&self) -> Element<'_, Message> {
-        let context_menu = column![
-            cosmic::applet::menu_button(
-                row![cosmic::widget::text::body(fl!("settings")),].align_y(Alignment::Center)
+        if self.inline_settings {
+            return self.view_inline_settings();
+        }
+
+        let mut entries: Vec<Element<'_, Message>> = self
+            .config
+            .context_menu_categories
+            .iter()
+            .filter_map(|id| {
+                self.available_categories
+                    .iter()
+                    .find(|category| category.id() == id)
+            })
+            .map(|category| {
+                Self::context_menu_button(
+                    category.get_display_name(),
+                    Message::OpenCategory(category.clone()),
+                )
+            })
+            .collect();
+        if !entries.is_empty() {
+            entries.push(
+                cosmic::applet::padded_control(cosmic::widget::divider::horizontal::default())
+                    .into(),
+            );
+        }
+
+        // Separators left next to each other or at the end by missing tools are dropped
+        let mut separated = true;
+        for entry in &self.config.context_menu_entries {
+            let element = match entry {
+                ContextMenuEntry::Separator if separated => continue,
+                ContextMenuEntry::Separator => {
+                    cosmic::applet::padded_control(cosmic::widget::divider::horizontal::default())
+                        .into()
+                }
+                ContextMenuEntry::MenuSettings => Self::context_menu_button(
+                    fl!("menu-settings"),
+                    Message::ShowInlineSettings(true),
+                ),
+                ContextMenuEntry::CreateLauncher => {
+                    Self::context_menu_button(fl!("create-launcher"), Message::OpenLauncherCreator)
+                }
+                ContextMenuEntry::ShowHiddenEntries => cosmic::applet::padded_control(
+                    row![
+                        cosmic::widget::text::body(fl!("show-hidden-entries"))
+                            .width(cosmic::iced::Length::Fill),
+                        cosmic::widget::toggler(self.show_hidden_entries)
+                            .on_toggle(Message::ToggleShowHiddenEntries),
+                    ]
+                    .align_y(Alignment::Center),
+                )
+                .into(),
+                ContextMenuEntry::AppletSettings
+                | ContextMenuEntry::SystemSettings
+                | ContextMenuEntry::SystemMonitor
+                | ContextMenuEntry::DiskManagement => {
+                    let tool = match entry {
+                        ContextMenuEntry::AppletSettings => SystemTool::AppletSettings,
+                        ContextMenuEntry::SystemSettings => SystemTool::SystemSettings,
+                        ContextMenuEntry::SystemMonitor => SystemTool::SystemMonitor,
+                        _ => SystemTool::DiskManagement,
+                    };
+                    if !tool.is_installed() {
+                        continue;
+                    }
+                    Self::context_menu_button(tool.get_display_name(), Message::LaunchTool(tool))
+                }
+            };
+            separated = *entry == ContextMenuEntry::Separator;
+            entries.push(element);
+        }
+        if separated {
+            entries.pop();
+        }
+
+        let context_menu = cosmic::widget::column::with_children(entries).padding([8, 0]);
+
+        self.core.applet.popup_container(context_menu).into()
+    }
+
+    fn context_menu_button<'a>(label: String, message: Message) -> Element<'a, Message> {
+        cosmic::applet::menu_button(
+            row![cosmic::widget::text::body(label),].align_y(Alignment::Center),
+        )
+        .class(cosmic::theme::Button::AppletMenu)
+        .on_press(message)
+        .into()
+    }
+
+    /// The most used settings of the menu, changed without opening the settings app.
+    fn view_inline_settings(&self) -> Element<'_, Message> {
+        const BUTTON_STYLES: [AppletButtonStyle; 4] = [
+            AppletButtonStyle::IconOnly,
+            AppletButtonStyle::LabelOnly,
+            AppletButtonStyle::IconAndLabel,
+            AppletButtonStyle::Auto,
+        ];
+        const LAYOUTS: [MenuLayout; 3] = [MenuLayout::Classic, MenuLayout::Wide, MenuLayout::Grid];
+
+        let setting_row = |label: String, control: Element<'static, Message>| {
+            cosmic::applet::padded_control(
+                row![
+                    cosmic::widget::text::body(label).width(cosmic::iced::Length::Fill),
+                    control,
+                ]
+                .spacing(8)
+                .align_y(Alignment::Center),
             )
-            .class(cosmic::theme::Button::AppletMenu)
-            .on_press(Message::LaunchTool(SystemTool::AppletSettings)),
-            cosmic::applet::padded_control(cosmic::widget::divider::horizontal::default()),
-            cosmic::applet::menu_button(
-                row![cosmic::widget::text::body(fl!("settings-label")),].align_y(Alignment::Center)
+        };
+
+        let button_styles = vec![
+            fl!("icon-only"),
+            fl!("label-only"),
+            fl!("icon-and-label"),
+            fl!("auto"),
+        ];
+        let button_style: Element<'static, Message> = match self.policy.applet_button_style {
+            Some(style) => Self::locked_control(button_styles[style as usize].clone()),
+            None => cosmic::widget::dropdown(
+                button_styles,
+                BUTTON_STYLES
+                    .iter()
+                    .position(|style| *style == self.config.applet_button_style),
+                |index| Message::MenuSettingChanged(MenuSetting::ButtonStyle(BUTTON_STYLES[index])),
             )
-            .class(cosmic::theme::Button::AppletMenu)
-            .on_press(Message::LaunchTool(SystemTool::SystemSettings)),
+            .into(),
+        };
+        let layouts = vec![
+            fl!("layout-classic"),
+            fl!("layout-wide"),
+            fl!("layout-grid"),
+        ];
+        let layout: Element<'static, Message> = match self.policy.menu_layout {
+            Some(layout) => Self::locked_control(layouts[layout as usize].clone()),
+            None => cosmic::widget::dropdown(
+                layouts,
+                LAYOUTS
+                    .iter()
+                    .position(|layout| *layout == self.config.menu_layout),
+                |index| Message::MenuSettingChanged(MenuSetting::Layout(LAYOUTS[index])),
+            )
+            .into(),
+        };
+        let app_menu_position = row![
+            cosmic::widget::Radio::new(
+                cosmic::widget::text::body(fl!("left")),
+                HorizontalPosition::Left,
+                Some(self.config.app_menu_position),
+                |position| Message::MenuSettingChanged(MenuSetting::AppMenuPosition(position)),
+            ),
+            cosmic::widget::Radio::new(
+                cosmic::widget::text::body(fl!("right")),
+                HorizontalPosition::Right,
+                Some(self.config.app_menu_position),
+                |position| Message::MenuSettingChanged(MenuSetting::AppMenuPosition(position)),
+            ),
+        ]
+        .spacing(8);
+        let search_field_position = row![
+            cosmic::widget::Radio::new(
+                cosmic::widget::text::body(fl!("top")),
+                VerticalPosition::Top,
+                Some(self.config.search_field_position),
+                |position| Message::MenuSettingChanged(MenuSetting::SearchFieldPosition(position)),
+            ),
+            cosmic::widget::Radio::new(
+                cosmic::widget::text::body(fl!("bottom")),
+                VerticalPosition::Bottom,
+                Some(self.config.search_field_position),
+                |position| Message::MenuSettingChanged(MenuSetting::SearchFieldPosition(position)),
+            ),
+        ]
+        .spacing(8);
+
+        let inline_settings = column![
             cosmic::applet::menu_button(
-                row![cosmic::widget::text::body(fl!("system-monitor-label")),]
-                    .align_y(Alignment::Center)
+                row![
+                    cosmic::widget::icon::from_name("go-previous-symbolic").size(16),
+                    cosmic::widget::text::body(fl!("back")),
+                ]
+                .spacing(8)
+                .align_y(Alignment::Center)
             )
             .class(cosmic::theme::Button::AppletMenu)
-            .on_press(Message::LaunchTool(SystemTool::SystemMonitor)),
+            .on_press(Message::ShowInlineSettings(false)),
+            cosmic::applet::padded_control(cosmic::widget::divider::horizontal::default()),
+            setting_row(fl!("applet-button-style"), button_style),
+            setting_row(fl!("menu-layout"), layout),
+            setting_row(fl!("app-menu-position"), app_menu_position.into()),
+            setting_row(fl!("search-field-position"), search_field_position.into()),
+            cosmic::applet::padded_control(cosmic::widget::divider::horizontal::default()),
             cosmic::applet::menu_button(
-                row![cosmic::widget::text::body(fl!("disks-label")),].align_y(Alignment::Center)
+                row![cosmic::widget::text::body(fl!("more-settings")),].align_y(Alignment::Center)
             )
             .class(cosmic::theme::Button::AppletMenu)
-            .on_press(Message::LaunchTool(SystemTool::DiskManagement)),
+            .on_press(Message::LaunchTool(SystemTool::AppletSettings)),
         ]
         .padding([8, 0]);
 
-        self.core.applet.popup_container(context_menu).into()
+        self.core.applet.popup_container(inline_settings).into()
+    }
+
+    /// Shows the value enforced by the administrator in place of the control changing it.
+    fn locked_control(value: String) -> Element<'static, Message> {
+        row![
+            cosmic::widget::icon::from_name("system-lock-screen-symbolic").size(16),
+            cosmic::widget::text::body(value),
+        ]
+        .spacing(8)
+        .align_y(Alignment::Center)
+        .into()
+    }
+}
+
+/// Returns the program and arguments showing the confirmation dialog of cosmic-osd, spawned
+/// on the host from a Flatpak.
+fn cosmic_osd_command(dialog: &'static str) -> (&'static str, Vec<&'static str>) {
+    if std::env::var("FLATPAK_ID").is_ok() {
+        ("flatpak-spawn", vec!["--host", "cosmic-osd", dialog])
+    } else {
+        ("cosmic-osd", vec![dialog])
     }
 }
@@ -1,15 +1,38 @@
-use std::path::PathBuf;
-
-use cosmic::iced::{widget::row, Alignment, Length};
-use cosmic::widget::mouse_area;
-use cosmic::Element;
+use cosmic::applet::cosmic_panel_config::PanelAnchor;
+use cosmic::iced::widget::stack;
+use cosmic::iced::{widget::row, Alignment, Background, Border, Length};
+use cosmic::widget::{container, mouse_area, tooltip};
+use cosmic::{Element, Task};
 
 use once_cell::sync::Lazy;
 
 use crate::applet::{CosmicClassicMenu, Message, PopupType};
+use crate::config::{AppletButtonIcon, CosmicClassicMenuConfig};
+use crate::fl;
+use crate::logic::apps::Apps;
 
 static AUTOSIZE_MAIN_ID: Lazy<cosmic::widget::Id> =
     Lazy::new(|| cosmic::widget::Id::new("autosize-main"));
+/// Icon name of the distribution's logo, read once from os-release.
+static DISTRO_LOGO: Lazy<Option<String>> = Lazy::new(|| {
+    ["/etc/os-release", "/usr/lib/os-release"]
+        .iter()
+        .find_map(|path| std::fs::read_to_string(path).ok())
+        .and_then(|os_release| {
+            os_release.lines().find_map(|line| {
+                let logo = line.strip_prefix("LOGO=")?.trim().trim_matches(['"', '\'']);
+                (!logo.is_empty()).then(|| logo.to_string())
+            })
+        })
+});
+/// Host name shown in the tooltip, read once.
+static HOSTNAME: Lazy<String> = Lazy::new(|| {
+    std::fs::read_to_string("/proc/sys/kernel/hostname")
+        .or_else(|_| std::fs::read_to_string("/etc/hostname"))
+        .map(|hostname| hostname.trim().to_string())
+        .unwrap_or_default()
+});
+static BUTTON_ID: Lazy<cosmic::widget::Id> = Lazy::new(|| cosmic::widget::Id::new("applet-button"));
 
 const BUTTON_DEFAULT_ICON: &[u8] =
     include_bytes!("../../res/icons/bundled/applet-button/default.svg");
@@ -18,6 +41,160 @@ const BUTTON_DEFAULT_ICON: &[u8] =
 pub struct AppletButton;
 
 impl AppletButton {
+    /// Returns the handle of the configured button icon, falling back to the bundled one.
+    ///
+    /// A `<name>-dark` or `<name>-light` file next to the icon is preferred while the matching
+    /// theme is active, and `-symbolic` icons are recolored to the theme's foreground.
+    fn icon_handle(applet: &CosmicClassicMenu) -> cosmic::widget::icon::Handle {
+        // The configured icon stands in for a logo missing from the icon theme
+        if applet.config.button_icon_source == AppletButtonIcon::DistroLogo {
+            if let Some(logo) = DISTRO_LOGO.as_deref() {
+                let named = cosmic::widget::icon::from_name(logo);
+                if named.clone().path().is_some() {
+                    return named.handle();
+                }
+            }
+        }
+
+        let button_icon = applet.config.button_icon_path();
+        if !button_icon.is_file() {
+            return cosmic::widget::icon::from_svg_bytes(BUTTON_DEFAULT_ICON);
+        }
+
+        let variant_suffix = if cosmic::theme::active().cosmic().is_dark {
+            "dark"
+        } else {
+            "light"
+        };
+        let variant = button_icon
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .map(|stem| {
+                let mut file_name = format!("{}-{}", stem, variant_suffix);
+                if let Some(extension) = button_icon.extension().and_then(|ext| ext.to_str()) {
+                    file_name = format!("{}.{}", file_name, extension);
+                }
+                button_icon.with_file_name(file_name)
+            })
+            .filter(|variant| variant.exists());
+
+        let icon_path = variant.unwrap_or(button_icon);
+        let symbolic = icon_path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .is_some_and(|stem| stem.ends_with("-symbolic"));
+        let mut icon_handle = cosmic::widget::icon::from_path(icon_path);
+        icon_handle.symbolic = symbolic;
+        icon_handle
+    }
+
+    /// Wraps the button in a tooltip telling who is logged in where and how many apps are
+    /// installed, since an icon alone gives no hint what the button is.
+    fn with_tooltip<'a>(
+        applet: &'a CosmicClassicMenu,
+        button: impl Into<Element<'a, Message>>,
+    ) -> Element<'a, Message> {
+        let user = applet
+            .current_user
+            .as_ref()
+            .map(|user| user.username.clone())
+            .or_else(|| std::env::var("USER").ok())
+            .unwrap_or_default();
+        let mut tooltip_text = fl!(
+            "applet-tooltip",
+            user = user,
+            host = HOSTNAME.as_str(),
+            count = Apps::listed_count()
+        );
+        if !applet.pending_updates.is_empty() {
+            tooltip_text = fl!(
+                "applet-tooltip-updates",
+                summary = tooltip_text,
+                count = applet.pending_updates.len()
+            );
+        }
+        if !applet.new_apps.is_empty() {
+            tooltip_text = fl!(
+                "applet-tooltip-new-apps",
+                summary = tooltip_text,
+                count = applet.new_apps.len()
+            );
+        }
+
+        // Shown away from the panel's edge
+        let position = match applet.core.applet.anchor {
+            PanelAnchor::Left => tooltip::Position::Right,
+            PanelAnchor::Right => tooltip::Position::Left,
+            PanelAnchor::Top => tooltip::Position::Bottom,
+            PanelAnchor::Bottom => tooltip::Position::Top,
+        };
+
+        tooltip(
+            AppletButton::with_badge(applet, button),
+            cosmic::widget::text(tooltip_text),
+            position,
+        )
+        .into()
+    }
+
+    /// Overlays a dot on the corner of the button while newly installed apps haven't been
+    /// seen in the menu yet.
+    fn with_badge<'a>(
+        applet: &CosmicClassicMenu,
+        button: impl Into<Element<'a, Message>>,
+    ) -> Element<'a, Message> {
+        const BADGE_SIZE: f32 = 8.0;
+
+        if applet.new_apps.is_empty() {
+            return button.into();
+        }
+
+        let badge = container(cosmic::widget::Space::new(BADGE_SIZE, BADGE_SIZE)).class(
+            cosmic::theme::Container::custom(|theme| container::Style {
+                background: Some(Background::Color(theme.cosmic().accent_color().into())),
+                border: Border {
+                    radius: (BADGE_SIZE / 2.0).into(),
+                    ..Default::default()
+                },
+                ..Default::default()
+            }),
+        );
+
+        // The button sizes the stack, the badge is aligned inside of it
+        stack![
+            button.into(),
+            container(badge)
+                .width(Length::Fill)
+                .height(Length::Fill)
+                .align_x(Alignment::End)
+                .align_y(Alignment::Start)
+                .padding(2)
+        ]
+        .into()
+    }
+
+    /// Moves the keyboard focus to the applet button, so Space or Enter reopens the menu.
+    pub fn focus() -> Task<Message> {
+        cosmic::widget::button::focus(BUTTON_ID.clone())
+    }
+
+    /// Returns the configured button label, cut with an ellipsis past the maximum length so
+    /// long labels can't blow up the panel layout.
+    fn label(applet: &CosmicClassicMenu) -> String {
+        let button_label = if applet.config.button_label.is_empty() {
+            CosmicClassicMenuConfig::default().button_label
+        } else {
+            applet.config.button_label.clone()
+        };
+
+        let max_length = applet.config.button_label_max_length as usize;
+        if max_length == 0 || button_label.chars().count() <= max_length {
+            return button_label;
+        }
+        let truncated: String = button_label.chars().take(max_length).collect();
+        format!("{}…", truncated.trim_end())
+    }
+
     /// Creates a view for the applet button with only an icon.
     ///
     /// This function generates a button that displays only the applet's icon.
@@ -30,22 +207,19 @@ impl AppletButton {
     /// # Returns
     /// An `Element<Message>` representing the icon-only applet button.
     pub fn view_icon_only(applet: &CosmicClassicMenu) -> Element<'_, Message> {
-        let button_icon: PathBuf = applet.config.button_icon.clone().into();
-        let icon_handle = if button_icon.exists() {
-            cosmic::widget::icon::from_path(button_icon)
-        } else {
-            cosmic::widget::icon::from_svg_bytes(BUTTON_DEFAULT_ICON)
-        };
+        let icon_handle = AppletButton::icon_handle(applet);
 
-        mouse_area(
+        let button = mouse_area(
             applet
                 .core
                 .applet
                 .icon_button_from_handle(icon_handle)
+                .id(BUTTON_ID.clone())
                 .on_press(Message::TogglePopup(PopupType::MainMenu)),
         )
-        .on_right_press(Message::TogglePopup(PopupType::ContextMenu))
-        .into()
+        .on_right_press(Message::TogglePopup(PopupType::ContextMenu));
+
+        AppletButton::with_tooltip(applet, button)
     }
 
     /// Creates a view for the applet button with only a label.
@@ -60,11 +234,7 @@ impl AppletButton {
     /// # Returns
     /// An `Element<Message>` representing the label-only applet button.
     pub fn view_label_only(applet: &CosmicClassicMenu) -> Element<'_, Message> {
-        let button_label = if applet.config.button_label.is_empty() {
-            CosmicClassicMenu::default().config.button_label.clone()
-        } else {
-            applet.config.button_label.clone()
-        };
+        let button_label = AppletButton::label(applet);
 
         let content = row!(
             applet.core.applet.text(button_label),
@@ -76,14 +246,17 @@ impl AppletButton {
         )
         .align_y(Alignment::Center);
 
+        let button = mouse_area(
+            cosmic::widget::button::custom(content)
+                .id(BUTTON_ID.clone())
+                .padding([0, applet.core.applet.suggested_padding(true)])
+                .class(cosmic::theme::Button::AppletIcon)
+                .on_press(Message::TogglePopup(PopupType::MainMenu)),
+        )
+        .on_right_press(Message::TogglePopup(PopupType::ContextMenu));
+
         cosmic::widget::autosize::autosize(
-            mouse_area(
-                cosmic::widget::button::custom(content)
-                    .padding([0, applet.core.applet.suggested_padding(true)])
-                    .class(cosmic::theme::Button::AppletIcon)
-                    .on_press(Message::TogglePopup(PopupType::MainMenu)),
-            )
-            .on_right_press(Message::TogglePopup(PopupType::ContextMenu)),
+            AppletButton::with_tooltip(applet, button),
             AUTOSIZE_MAIN_ID.clone(),
         )
         .into()
@@ -101,17 +274,8 @@ impl AppletButton {
     /// # Returns
     /// An `Element<Message>` representing the applet button with both an icon and a label.
     pub fn view_icon_and_label(applet: &CosmicClassicMenu) -> Element<'_, Message> {
-        let button_label = if applet.config.button_label.is_empty() {
-            CosmicClassicMenu::default().config.button_label.clone()
-        } else {
-            applet.config.button_label.clone()
-        };
-        let button_icon: PathBuf = applet.config.button_icon.clone().into();
-        let icon_handle = if button_icon.exists() {
-            cosmic::widget::icon::from_path(button_icon)
-        } else {
-            cosmic::widget::icon::from_svg_bytes(BUTTON_DEFAULT_ICON)
-        };
+        let button_label = AppletButton::label(applet);
+        let icon_handle = AppletButton::icon_handle(applet);
 
         let content = row!(
             icon_handle.icon(),
@@ -123,14 +287,17 @@ impl AppletButton {
         )
         .align_y(Alignment::Center);
 
+        let button = mouse_area(
+            cosmic::widget::button::custom(content)
+                .id(BUTTON_ID.clone())
+                .padding([0, applet.core.applet.suggested_padding(true)])
+                .class(cosmic::theme::Button::AppletIcon)
+                .on_press(Message::TogglePopup(PopupType::MainMenu)),
+        )
+        .on_right_press(Message::TogglePopup(PopupType::ContextMenu));
+
         cosmic::widget::autosize::autosize(
-            mouse_area(
-                cosmic::widget::button::custom(content)
-                    .padding([0, applet.core.applet.suggested_padding(true)])
-                    .class(cosmic::theme::Button::AppletIcon)
-                    .on_press(Message::TogglePopup(PopupType::MainMenu)),
-            )
-            .on_right_press(Message::TogglePopup(PopupType::ContextMenu)),
+            AppletButton::with_tooltip(applet, button),
             AUTOSIZE_MAIN_ID.clone(),
         )
         .into()
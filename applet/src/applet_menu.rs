@@ -1,27 +1,73 @@
 use std::path::PathBuf;
+use std::time::Duration;
 
 use cosmic::cosmic_theme::Spacing;
 use cosmic::desktop::IconSourceExt;
+use cosmic::iced::mouse::ScrollDelta;
+use cosmic::iced::widget::{responsive, rich_text, span, stack, text::Span};
 use cosmic::iced::{
     widget::{column, row},
     Alignment, Length,
 };
 use cosmic::iced::{ContentFit, Font, Limits};
-use cosmic::widget::{container, ListColumn};
+use cosmic::widget::{container, icon, mouse_area, ListColumn};
 use cosmic::widget::{scrollable, text};
 use cosmic::{theme, Element};
 
-use crate::applet::{CosmicClassicMenu, Message, PowerAction};
-use crate::config::{HorizontalPosition, VerticalPosition};
+use cosmic_classic_menu_core::recents::Elapsed;
+use freedesktop_desktop_entry::IconSource;
+use once_cell::sync::Lazy;
+
+use crate::applet::{
+    ApplicationAction, CosmicClassicMenu, Message, PowerAction, SearchItem, TimedAction,
+    MAX_TIMER_DELAY, TIMER_DELAY_OPTIONS,
+};
+use crate::config::{
+    unix_timestamp, CategoryNavigation, HorizontalPosition, ListDensity, MenuLayout, RecentsOrder,
+    VerticalPosition,
+};
 use crate::fl;
+use crate::logic::apps::ApplicationCategory;
+use crate::logic::appstream::AppDetails;
+use crate::logic::icons::{self, CachedIcon, IconData};
+use crate::model::application_entry::ApplicationEntry;
+use crate::model::application_properties::{ApplicationProperties, PropertyField};
+use crate::model::new_launcher::NewLauncher;
+
+/// Widget id of the scrollable holding the application list.
+pub static APP_LIST_ID: Lazy<cosmic::widget::Id> =
+    Lazy::new(|| cosmic::widget::Id::new("app-list"));
+
+/// Widget id of the search field.
+pub static SEARCH_FIELD_ID: Lazy<cosmic::widget::Id> =
+    Lazy::new(|| cosmic::widget::Id::new("search-field"));
+
+/// Returns the widget id of the application row at `index`.
+pub fn app_entry_id(index: usize) -> cosmic::widget::Id {
+    cosmic::widget::Id::new(format!("app-entry-{index}"))
+}
 
 pub struct AppletMenu;
 
 impl AppletMenu {
-    const POPUP_MAX_WIDTH: f32 = 625.0;
     const POPUP_MIN_WIDTH: f32 = 625.0;
     const POPUP_MAX_HEIGHT: f32 = 700.0;
     const POPUP_MIN_HEIGHT: f32 = 700.0;
+    const TOOLTIP_MAX_WIDTH: f32 = 300.0;
+    /// Text size of the app names at the comfortable density, the theme's body size.
+    const APP_NAME_SIZE: f32 = 14.0;
+    /// Narrowest categories pane that fits the power buttons with their labels.
+    const POWER_LABELS_MIN_WIDTH: f32 = 500.0;
+    const MINIMAL_POPUP_WIDTH: f32 = 360.0;
+    const MINIMAL_POPUP_HEIGHT: f32 = 420.0;
+    const MINIMAL_GRID_COLUMNS: usize = 4;
+    const GRID_MIN_AUTO_COLUMNS: usize = 2;
+    const GRID_MAX_AUTO_COLUMNS: usize = 8;
+    /// Height of the grid area on a page, below the header and above the page indicators.
+    const GRID_PAGE_HEIGHT: f32 = 480.0;
+    const TOUCH_SCROLLBAR_WIDTH: f32 = 12.0;
+    /// Pixels of touchpad scrolling counted as one line of a mouse wheel.
+    const GRID_SCROLL_LINE_HEIGHT: f32 = 60.0;
 
     const SYSTEM_LOCKSCREEN_SYMBOLIC_ICON: &[u8] = include_bytes!(
         "../../res/icons/bundled/system-lock-screen-symbolic.svg"
@@ -43,11 +89,19 @@ impl AppletMenu {
     );
 
     pub fn view_main_menu_list(applet: &CosmicClassicMenu) -> Element<'_, Message> {
+        if applet.is_minimal_popup() {
+            return AppletMenu::view_minimal_menu(applet);
+        }
+
         let Spacing {
             space_xxs, space_s, ..
-        } = theme::active().cosmic().spacing;
+        } = AppletMenu::spacing(applet);
 
-        let current_user = AppletMenu::create_logged_user_widget(&applet);
+        let current_user = column![
+            AppletMenu::create_logged_user_widget(&applet),
+            AppletMenu::create_other_sessions(&applet),
+        ];
+        let default_apps = AppletMenu::create_default_apps(&applet);
         let search_field = AppletMenu::create_search_field(&applet);
         let app_list = AppletMenu::create_app_list(&applet);
         let categories_pane = AppletMenu::create_categories_pane(&applet);
@@ -58,7 +112,29 @@ impl AppletMenu {
                 .width(Length::Shrink)
                 .padding(5);
 
+        // Search results span the whole popup, the categories don't apply to them
         let dual_pane = match applet.config.app_menu_position {
+            _ if !applet.search_field.is_empty() => row![app_list].padding([space_xxs, 0]),
+            // The power options stay below the list without the categories pane
+            _ if applet.config.category_navigation == CategoryNavigation::FlatList => {
+                let flat_list = column![
+                    AppletMenu::create_special_categories(applet),
+                    app_list,
+                    cosmic::widget::column::with_children(AppletMenu::create_system_controls(
+                        applet
+                    ))
+                ];
+                row![flat_list].padding([space_xxs, 0])
+            }
+            _ if applet.config.category_navigation == CategoryNavigation::DrillDown => {
+                if applet.category_opened {
+                    let opened_category =
+                        column![AppletMenu::create_category_header(applet), app_list];
+                    row![opened_category].padding([space_xxs, 0])
+                } else {
+                    row![categories_pane].padding([space_xxs, 0])
+                }
+            }
             HorizontalPosition::Left => {
                 row![app_list, vertical_spacer, categories_pane].padding([space_xxs, 0])
             }
@@ -67,53 +143,510 @@ impl AppletMenu {
             }
         };
         let menu_layout = match applet.config.search_field_position {
-            VerticalPosition::Top => {
-                column![current_user, search_field, dual_pane].padding([space_xxs, space_s])
-            }
+            VerticalPosition::Top => column![current_user, default_apps, search_field, dual_pane]
+                .padding([space_xxs, space_s]),
             VerticalPosition::Bottom => {
-                column![current_user, dual_pane, search_field].padding([space_xxs, space_s])
+                column![current_user, default_apps, dual_pane, search_field]
+                    .padding([space_xxs, space_s])
             }
         };
 
+        let content = AppletMenu::with_error_banner(
+            applet,
+            AppletMenu::create_form_view(applet).unwrap_or_else(|| menu_layout.into()),
+        );
+
         applet
             .core
             .applet
-            .popup_container(menu_layout)
+            .popup_container(cosmic::widget::toaster(&applet.toasts, content))
             .limits(
                 Limits::NONE
                     .max_height(AppletMenu::POPUP_MAX_HEIGHT)
                     .min_height(AppletMenu::POPUP_MIN_HEIGHT)
-                    .max_width(AppletMenu::POPUP_MAX_WIDTH)
-                    .min_width(AppletMenu::POPUP_MIN_WIDTH),
+                    .max_width(AppletMenu::popup_width(applet))
+                    .min_width(AppletMenu::popup_width(applet)),
+            )
+            .into()
+    }
+
+    /// Compact popup with only the search field and an icon grid, for small screens.
+    fn view_minimal_menu(applet: &CosmicClassicMenu) -> Element<'_, Message> {
+        let Spacing {
+            space_xxs,
+            space_s,
+            space_xl,
+            ..
+        } = AppletMenu::spacing(applet);
+
+        let search_field = AppletMenu::create_search_field(&applet);
+        let app_grid = AppletMenu::wrap_app_list(
+            applet,
+            AppletMenu::create_app_grid(
+                applet,
+                applet.visible_applications(),
+                0,
+                AppletMenu::MINIMAL_GRID_COLUMNS,
+                space_xl,
+            ),
+        );
+
+        let content = AppletMenu::create_form_view(applet).unwrap_or_else(|| {
+            let menu_layout = match applet.config.search_field_position {
+                VerticalPosition::Top => column![search_field, app_grid],
+                VerticalPosition::Bottom => column![app_grid, search_field],
+            };
+            menu_layout
+                .spacing(space_xxs)
+                .padding([space_xxs, space_s])
+                .into()
+        });
+        let content = AppletMenu::with_error_banner(applet, content);
+
+        applet
+            .core
+            .applet
+            .popup_container(cosmic::widget::toaster(&applet.toasts, content))
+            .limits(
+                Limits::NONE
+                    .max_height(AppletMenu::MINIMAL_POPUP_HEIGHT)
+                    .min_height(AppletMenu::MINIMAL_POPUP_HEIGHT)
+                    .max_width(AppletMenu::MINIMAL_POPUP_WIDTH)
+                    .min_width(AppletMenu::MINIMAL_POPUP_WIDTH),
             )
             .into()
     }
 
-    fn create_power_menu(_applet: &CosmicClassicMenu) -> Element<'_, Message> {
+    /// Puts the last error above the content, until it's dismissed.
+    fn with_error_banner<'a>(
+        applet: &'a CosmicClassicMenu,
+        content: Element<'a, Message>,
+    ) -> Element<'a, Message> {
+        let Spacing {
+            space_xxs, space_s, ..
+        } = AppletMenu::spacing(applet);
+
+        match &applet.error {
+            Some(error) => column![
+                container(cosmic::widget::warning(error).on_close(Message::DismissError))
+                    .padding([space_xxs, space_s]),
+                content,
+            ]
+            .into(),
+            None => content,
+        }
+    }
+
+    /// Returns the width of the popup for the configured layout.
+    fn popup_width(applet: &CosmicClassicMenu) -> f32 {
+        let width_factor = match applet.menu_layout() {
+            MenuLayout::Classic | MenuLayout::Grid => 1.0,
+            // Each extra column of the flowed app list widens the popup by half.
+            MenuLayout::Wide => 1.0 + 0.5 * applet.config.wide_layout.columns.max(1) as f32,
+        };
+
+        AppletMenu::POPUP_MIN_WIDTH * width_factor
+    }
+
+    /// Returns the width of the categories pane holding the power options, which takes the
+    /// whole popup unless it shares it with the app list in a 5:3 split.
+    fn categories_pane_width(applet: &CosmicClassicMenu) -> f32 {
+        match applet.config.category_navigation {
+            CategoryNavigation::SidePane => AppletMenu::popup_width(applet) * 3.0 / 8.0,
+            _ => AppletMenu::popup_width(applet),
+        }
+    }
+
+    /// Returns the form temporarily taking over the whole menu, if one is open.
+    fn create_form_view(applet: &CosmicClassicMenu) -> Option<Element<'_, Message>> {
+        if let Some(properties) = &applet.properties {
+            return Some(AppletMenu::create_properties_view(applet, properties));
+        }
+        if let Some(launcher) = &applet.new_launcher {
+            return Some(AppletMenu::create_launcher_view(applet, launcher));
+        }
+        if let Some((app, details)) = &applet.details {
+            return Some(AppletMenu::create_details_view(
+                applet,
+                app,
+                details.as_ref(),
+            ));
+        }
+        if let Some((app, name)) = &applet.new_folder {
+            return Some(AppletMenu::create_folder_view(applet, app, name));
+        }
+        None
+    }
+
+    fn create_folder_view<'a>(
+        applet: &CosmicClassicMenu,
+        app: &'a ApplicationEntry,
+        name: &'a str,
+    ) -> Element<'a, Message> {
+        let Spacing {
+            space_xs, space_s, ..
+        } = AppletMenu::spacing(applet);
+
+        let create_button = cosmic::widget::button::suggested(fl!("create"))
+            .on_press_maybe((!name.trim().is_empty()).then_some(Message::CreateFolder));
+        let buttons = row![
+            cosmic::widget::horizontal_space(),
+            cosmic::widget::button::standard(fl!("cancel")).on_press(Message::CloseFolderCreator),
+            create_button,
+        ]
+        .spacing(space_xs);
+
+        column![
+            text::title4(fl!("new-folder-for", name = app.name.clone())),
+            AppletMenu::create_form_field(
+                applet,
+                fl!("folder-name"),
+                name,
+                Message::EditFolderName
+            ),
+            buttons,
+        ]
+        .spacing(space_xs)
+        .padding([space_s, space_s])
+        .into()
+    }
+
+    /// Description and screenshot of the app from its AppStream metadata.
+    fn create_details_view<'a>(
+        applet: &'a CosmicClassicMenu,
+        app: &'a ApplicationEntry,
+        details: Option<&'a AppDetails>,
+    ) -> Element<'a, Message> {
+        let Spacing {
+            space_xxs,
+            space_xs,
+            space_s,
+            space_xl,
+            ..
+        } = AppletMenu::spacing(applet);
+
+        let header = row![
+            AppletMenu::app_icon(applet, &app.icon, space_xl),
+            column![
+                text::title4(&app.name),
+                text::caption(app.generic_name.as_deref().unwrap_or_default()),
+            ],
+        ]
+        .spacing(space_xs)
+        .align_y(Alignment::Center);
+
+        let mut body = cosmic::widget::column::with_capacity(4).spacing(space_xs);
+        match details {
+            Some(details) => {
+                if let Some(screenshot) = &details.screenshot {
+                    body = body.push(
+                        cosmic::widget::image(screenshot.clone())
+                            .width(Length::Fill)
+                            .content_fit(ContentFit::Contain)
+                            .border_radius([space_xxs as f32; 4]),
+                    );
+                }
+                for paragraph in &details.description {
+                    body = body.push(text::body(paragraph));
+                }
+            }
+            // Apps without AppStream metadata still have their comment
+            None => body = body.push(text::body(app.comment.as_deref().unwrap_or_default())),
+        }
+
+        let pin_action = if applet.is_favorite(&app.id) {
+            ApplicationAction::RemoveFromFavorites
+        } else {
+            ApplicationAction::AddToFavorites
+        };
+        let buttons = row![
+            cosmic::widget::button::standard(fl!("back")).on_press(Message::CloseDetails),
+            cosmic::widget::horizontal_space(),
+            cosmic::widget::button::standard(pin_action.get_display_name()).on_press_maybe(
+                pin_action
+                    .is_enabled_for(applet, app)
+                    .then(|| Message::PerformApplicationAction(app.clone(), pin_action)),
+            ),
+            cosmic::widget::button::suggested(fl!("launch"))
+                .on_press(Message::ApplicationSelected(app.clone())),
+        ]
+        .spacing(space_xs);
+
+        column![
+            header,
+            cosmic::widget::scrollable(body).height(Length::Fill),
+            buttons,
+        ]
+        .spacing(space_xs)
+        .padding([space_s, space_s])
+        .into()
+    }
+
+    fn create_properties_view<'a>(
+        applet: &CosmicClassicMenu,
+        properties: &'a ApplicationProperties,
+    ) -> Element<'a, Message> {
+        let Spacing {
+            space_xs, space_s, ..
+        } = AppletMenu::spacing(applet);
+
+        let mut properties_column = cosmic::widget::column::with_capacity(6)
+            .spacing(space_xs)
+            .padding([space_s, space_s])
+            .push(text::title4(fl!(
+                "properties-of",
+                name = properties.app_name.clone()
+            )));
+        for field in PropertyField::ALL {
+            properties_column = properties_column.push(AppletMenu::create_form_field(
+                applet,
+                field.get_display_name(),
+                properties.get(field),
+                move |value| Message::EditProperty(field, value),
+            ));
+        }
+
+        let save_button = cosmic::widget::button::suggested(fl!("save"))
+            .on_press_maybe(properties.is_changed().then_some(Message::SaveProperties));
+        let buttons = row![
+            cosmic::widget::horizontal_space(),
+            cosmic::widget::button::standard(fl!("cancel")).on_press(Message::CloseProperties),
+            save_button,
+        ]
+        .spacing(space_xs);
+
+        properties_column.push(buttons).into()
+    }
+
+    fn create_launcher_view<'a>(
+        applet: &CosmicClassicMenu,
+        launcher: &'a NewLauncher,
+    ) -> Element<'a, Message> {
+        let Spacing {
+            space_xs, space_s, ..
+        } = AppletMenu::spacing(applet);
+
+        let name_field = AppletMenu::create_form_field(
+            applet,
+            fl!("properties-name"),
+            &launcher.name,
+            move |name| {
+                Message::EditLauncher(NewLauncher {
+                    name,
+                    ..launcher.clone()
+                })
+            },
+        );
+        let exec_field = AppletMenu::create_form_field(
+            applet,
+            fl!("properties-command"),
+            &launcher.exec,
+            move |exec| {
+                Message::EditLauncher(NewLauncher {
+                    exec,
+                    ..launcher.clone()
+                })
+            },
+        );
+        let icon_field = AppletMenu::create_form_field(
+            applet,
+            fl!("properties-icon"),
+            &launcher.icon,
+            move |icon| {
+                Message::EditLauncher(NewLauncher {
+                    icon,
+                    ..launcher.clone()
+                })
+            },
+        );
+        let terminal_toggle = cosmic::widget::checkbox(fl!("launcher-terminal"), launcher.terminal)
+            .on_toggle(move |terminal| {
+                Message::EditLauncher(NewLauncher {
+                    terminal,
+                    ..launcher.clone()
+                })
+            });
+
+        let create_button = cosmic::widget::button::suggested(fl!("create"))
+            .on_press_maybe(launcher.is_valid().then_some(Message::CreateLauncher));
+        let buttons = row![
+            cosmic::widget::horizontal_space(),
+            cosmic::widget::button::standard(fl!("cancel")).on_press(Message::CloseLauncherCreator),
+            create_button,
+        ]
+        .spacing(space_xs);
+
+        column![
+            text::title4(fl!("create-launcher")),
+            name_field,
+            exec_field,
+            icon_field,
+            terminal_toggle,
+            buttons,
+        ]
+        .spacing(space_xs)
+        .padding([space_s, space_s])
+        .into()
+    }
+
+    fn create_form_field<'a>(
+        applet: &CosmicClassicMenu,
+        label: String,
+        value: &'a str,
+        on_input: impl Fn(String) -> Message + 'a,
+    ) -> Element<'a, Message> {
+        let Spacing { space_xxs, .. } = AppletMenu::spacing(applet);
+
+        column![
+            text::caption(label),
+            cosmic::widget::text_input("", value)
+                .on_input(on_input)
+                .width(Length::Fill),
+        ]
+        .spacing(space_xxs)
+        .into()
+    }
+
+    /// Spacing tokens of the theme, scaled to the configured density.
+    fn spacing(applet: &CosmicClassicMenu) -> Spacing {
+        applet
+            .config
+            .density
+            .spacing(theme::active().cosmic().spacing)
+    }
+
+    /// Looks the named icon up at its size in physical pixels, so raster icons of the theme
+    /// aren't upscaled on HiDPI and fractionally scaled outputs.
+    fn named_icon(applet: &CosmicClassicMenu, name: &str, size: u16) -> icon::Named {
+        let physical_size = (f32::from(size) * applet.output_scale()).ceil() as u16;
+        icon::from_name(name).size(physical_size)
+    }
+
+    /// Returns the icon of an app or window drawn at `size` logical pixels. Icons read in the
+    /// background show a placeholder until they're ready and then fade in, the others are
+    /// looked up right away.
+    fn app_icon<'a>(
+        applet: &CosmicClassicMenu,
+        source: &IconSource,
+        size: u16,
+    ) -> Element<'a, Message> {
+        let Some(cached) = applet.icons.get(&icons::key(source)) else {
+            return AppletMenu::lookup_app_icon(applet, source, size).into();
+        };
+
+        let length = Length::Fixed(size.into());
+        let opacity = cached.opacity();
+        let icon: Element<'a, Message> = match cached {
+            CachedIcon::Ready(Some(IconData::Svg(handle)), _) => {
+                cosmic::widget::svg(handle.clone())
+                    .width(length)
+                    .height(length)
+                    .content_fit(ContentFit::ScaleDown)
+                    .opacity(opacity)
+                    .into()
+            }
+            CachedIcon::Ready(Some(IconData::Raster(handle)), _) => {
+                cosmic::widget::image(handle.clone())
+                    .width(length)
+                    .height(length)
+                    .content_fit(ContentFit::ScaleDown)
+                    .opacity(opacity)
+                    .into()
+            }
+            // Still loading, or there's no icon at all
+            _ => return AppletMenu::icon_placeholder(size, 1.0),
+        };
+        if opacity < 1.0 {
+            stack![AppletMenu::icon_placeholder(size, 1.0 - opacity), icon].into()
+        } else {
+            icon
+        }
+    }
+
+    /// Neutral square shown in place of an icon that isn't read yet.
+    fn icon_placeholder<'a>(size: u16, opacity: f32) -> Element<'a, Message> {
+        container(cosmic::widget::Space::new(size, size))
+            .class(cosmic::theme::Container::custom(move |theme| {
+                let mut color: cosmic::iced::Color = theme.cosmic().palette.neutral_4.into();
+                color.a *= opacity;
+                cosmic::widget::container::Style {
+                    background: Some(cosmic::iced::Background::Color(color)),
+                    border: cosmic::iced::Border {
+                        radius: theme.cosmic().corner_radii.radius_s.into(),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                }
+            }))
+            .into()
+    }
+
+    /// Looks the icon of an app or window up while drawing it at `size` logical pixels, sharp
+    /// at the scale of the output.
+    fn lookup_app_icon(applet: &CosmicClassicMenu, source: &IconSource, size: u16) -> icon::Icon {
+        let icon = match source {
+            IconSource::Name(name) => AppletMenu::named_icon(applet, name, size)
+                .fallback(Some(icon::IconFallback::Names(vec![
+                    "application-default".into(),
+                    "application-x-executable".into(),
+                ])))
+                .icon(),
+            // Images are scalable or come in a single size
+            IconSource::Path(_) => source.as_cosmic_icon(),
+        };
+        icon.width(Length::Fixed(size.into()))
+            .height(Length::Fixed(size.into()))
+            .content_fit(ContentFit::ScaleDown)
+    }
+
+    fn create_power_menu(applet: &CosmicClassicMenu) -> Element<'_, Message> {
+        let Spacing { space_xxs, .. } = AppletMenu::spacing(applet);
+
+        // Fall back to icons only when the labels wouldn't fit
+        let show_labels = applet.config.show_power_labels
+            && AppletMenu::categories_pane_width(applet) >= AppletMenu::POWER_LABELS_MIN_WIDTH;
+
         container(
             row![
-                cosmic::widget::button::icon(cosmic::widget::icon::from_svg_bytes(
+                AppletMenu::create_power_button(
+                    applet,
                     AppletMenu::SYSTEM_LOGOUT_SYMBOLIC_ICON,
-                ).symbolic(true))
-                .on_press(Message::PowerOptionSelected(PowerAction::Logout)),
-                cosmic::widget::button::icon(cosmic::widget::icon::from_svg_bytes(
+                    PowerAction::Logout,
+                    show_labels,
+                ),
+                AppletMenu::create_power_button(
+                    applet,
                     AppletMenu::SYSTEM_SUSPEND_SYMBOLIC_ICON,
-                ).symbolic(true))
-                .on_press(Message::PowerOptionSelected(PowerAction::Suspend)),
-                cosmic::widget::button::icon(cosmic::widget::icon::from_svg_bytes(
+                    PowerAction::Suspend,
+                    show_labels,
+                ),
+                AppletMenu::create_power_button(
+                    applet,
                     AppletMenu::SYSTEM_LOCKSCREEN_SYMBOLIC_ICON,
-                ).symbolic(true))
-                .on_press(Message::PowerOptionSelected(PowerAction::Lock)),
-                cosmic::widget::button::icon(cosmic::widget::icon::from_svg_bytes(
+                    PowerAction::Lock,
+                    show_labels,
+                ),
+                AppletMenu::create_power_button(
+                    applet,
+                    AppletMenu::USER_IDLE_SYMBOLIC,
+                    PowerAction::SwitchUser,
+                    show_labels,
+                ),
+                AppletMenu::create_power_button(
+                    applet,
                     AppletMenu::SYSTEM_REBOOT_SYMBOLIC_ICON,
-                ).symbolic(true))
-                .on_press(Message::PowerOptionSelected(PowerAction::Reboot)),
-                cosmic::widget::button::icon(cosmic::widget::icon::from_svg_bytes(
+                    PowerAction::Reboot,
+                    show_labels,
+                ),
+                AppletMenu::create_power_button(
+                    applet,
                     AppletMenu::SYSTEM_SHUTDOWN_SYMBOLIC_ICON,
-                ).symbolic(true))
-                .on_press(Message::PowerOptionSelected(PowerAction::Shutdown)),
+                    PowerAction::Shutdown,
+                    show_labels,
+                ),
             ]
-            .align_y(Alignment::Center),
+            .spacing(space_xxs)
+            .align_y(Alignment::Top),
         )
         .width(Length::Fill)
         .padding([20, 0])
@@ -121,102 +654,1493 @@ impl AppletMenu {
         .into()
     }
 
-    fn create_search_field(applet: &CosmicClassicMenu) -> Element<'_, Message> {
+    /// Switches for desktop settings which are otherwise only a few clicks away in Settings.
+    fn create_quick_toggles(applet: &CosmicClassicMenu) -> Element<'_, Message> {
         let Spacing {
-            space_xxs, space_s, ..
-        } = theme::active().cosmic().spacing;
+            space_xxs, space_m, ..
+        } = AppletMenu::spacing(applet);
 
-        cosmic::widget::search_input(fl!("search-placeholder"), &applet.search_field)
-            .on_input(Message::SearchFieldInput)
-            .always_active()
+        row![
+            container(cosmic::widget::icon::from_name("weather-clear-night-symbolic").size(16))
+                .padding([0, space_m]),
+            text(fl!("dark-mode")).width(Length::Fill),
+            cosmic::widget::toggler(applet.core.system_theme_mode().is_dark)
+                .on_toggle(Message::ToggleDarkMode),
+        ]
+        .align_y(Alignment::Center)
+        .padding([space_xxs, space_xxs, space_xxs, 0])
+        .into()
+    }
+
+    /// Lets restarting into another entry of the boot loader, like another operating system.
+    fn create_boot_entries(applet: &CosmicClassicMenu) -> Element<'_, Message> {
+        let Spacing {
+            space_xxs, space_m, ..
+        } = AppletMenu::spacing(applet);
+
+        let titles: Vec<String> = applet
+            .boot_entries
+            .iter()
+            .map(|entry| entry.title.clone())
+            .collect();
+        let restart = applet
+            .selected_boot_entry
+            .map(Message::RestartIntoBootEntry);
+        row![
+            container(cosmic::widget::icon::from_name("system-reboot-symbolic").size(16))
+                .padding([0, space_m]),
+            text(fl!("restart-into-entry")).width(Length::Fill),
+            cosmic::widget::dropdown(titles, applet.selected_boot_entry, Message::SelectBootEntry),
+            cosmic::widget::button::text(fl!("power-reboot")).on_press_maybe(restart),
+        ]
+        .spacing(space_xxs)
+        .align_y(Alignment::Center)
+        .padding([space_xxs, space_xxs, space_xxs, 0])
+        .into()
+    }
+
+    /// Countdown to the scheduled shutdown or logout with a cancel button, or the delays to
+    /// schedule one.
+    fn create_timer(applet: &CosmicClassicMenu, action: TimedAction) -> Element<'_, Message> {
+        let Spacing {
+            space_xxs, space_m, ..
+        } = AppletMenu::spacing(applet);
+
+        let scheduled = match action {
+            TimedAction::Shutdown => applet.scheduled_shutdown,
+            TimedAction::Logout => applet.scheduled_logout,
+        };
+        let delay_input = applet
+            .timer_delay_input
+            .as_ref()
+            .filter(|(input_action, _)| *input_action == action);
+        let icon = container(cosmic::widget::icon::from_name("alarm-symbolic").size(16))
+            .padding([0, space_m]);
+        let content = if let Some(at) = scheduled {
+            let remaining = at
+                .duration_since(std::time::SystemTime::now())
+                .unwrap_or_default();
+            let remaining =
+                crate::logic::timer::format_duration(Duration::from_secs(remaining.as_secs()));
+            let countdown = match action {
+                TimedAction::Shutdown => fl!("shutting-down-in", remaining = remaining),
+                TimedAction::Logout => fl!("logging-out-in", remaining = remaining),
+            };
+            row![
+                icon,
+                text(countdown).width(Length::Fill),
+                cosmic::widget::button::text(fl!("cancel")).on_press(Message::CancelTimer(action)),
+            ]
+        } else if let Some((_, minutes)) = delay_input {
+            let delay = minutes
+                .trim()
+                .parse::<u64>()
+                .ok()
+                .filter(|minutes| *minutes > 0)
+                .and_then(|minutes| minutes.checked_mul(60))
+                .map(Duration::from_secs)
+                .filter(|delay| *delay <= MAX_TIMER_DELAY);
+            row![
+                icon,
+                cosmic::widget::text_input(fl!("timer-delay-minutes"), minutes)
+                    .on_input(Message::EditTimerDelay)
+                    .width(Length::Fill),
+                cosmic::widget::button::text(fl!("schedule"))
+                    .on_press_maybe(delay.map(|delay| Message::ScheduleTimer(action, delay))),
+            ]
+        } else {
+            let mut delays: Vec<String> = TIMER_DELAY_OPTIONS
+                .iter()
+                .map(|minutes| {
+                    crate::logic::timer::format_duration(Duration::from_secs(
+                        u64::from(*minutes) * 60,
+                    ))
+                })
+                .collect();
+            delays.push(fl!("timer-delay-custom"));
+            let label = match action {
+                TimedAction::Shutdown => fl!("shut-down-in"),
+                TimedAction::Logout => fl!("log-out-in"),
+            };
+            row![
+                icon,
+                text(label).width(Length::Fill),
+                cosmic::widget::dropdown(delays, None, move |index| {
+                    Message::SelectTimerDelay(action, index)
+                }),
+            ]
+        };
+
+        content
+            .spacing(space_xxs)
+            .align_y(Alignment::Center)
+            .padding([space_xxs, space_xxs, space_xxs, 0])
+            .into()
+    }
+
+    fn create_power_button(
+        applet: &CosmicClassicMenu,
+        icon: &'static [u8],
+        action: PowerAction,
+        show_label: bool,
+    ) -> Element<'static, Message> {
+        let Spacing {
+            space_xxs, space_l, ..
+        } = AppletMenu::spacing(applet);
+
+        let icon = cosmic::widget::icon::from_svg_bytes(icon)
+            .symbolic(true)
+            .icon()
+            .size(space_l);
+        let mut content = column![icon].align_x(Alignment::Center).spacing(space_xxs);
+        if show_label {
+            content = content.push(text::caption(action.get_display_name()));
+        }
+
+        let supported = !matches!(action, PowerAction::Suspend) || applet.can_suspend;
+        let button = cosmic::widget::button::custom(content)
+            .class(cosmic::theme::Button::Icon)
+            .padding(space_xxs)
+            .on_press_maybe(supported.then_some(Message::PowerOptionSelected(action)));
+        if supported {
+            return button.into();
+        }
+
+        // Disabled rather than hidden, so the row keeps its layout
+        cosmic::widget::tooltip(
+            button,
+            text(fl!("suspend-unsupported")),
+            cosmic::widget::tooltip::Position::Top,
+        )
+        .into()
+    }
+
+    /// One-click buttons for the default web browser, email client, file manager and terminal.
+    fn create_default_apps(applet: &CosmicClassicMenu) -> Element<'_, Message> {
+        let Spacing {
+            space_xxs, space_l, ..
+        } = AppletMenu::spacing(applet);
+
+        if !applet.config.show_default_apps || applet.default_apps.is_empty() {
+            return row![].into();
+        }
+
+        let buttons = applet.default_apps.iter().map(|app| {
+            let button =
+                cosmic::widget::button::custom(AppletMenu::app_icon(applet, &app.icon, space_l))
+                    .class(cosmic::theme::Button::Icon)
+                    .padding(space_xxs)
+                    .on_press(Message::ApplicationSelected(app.clone()));
+
+            cosmic::widget::tooltip(
+                button,
+                text(app.display_name(applet.config.name_display).into_owned()),
+                cosmic::widget::tooltip::Position::Bottom,
+            )
+            .into()
+        });
+
+        container(cosmic::iced::widget::Row::with_children(buttons).spacing(space_xxs))
             .width(Length::Fill)
-            .padding([space_xxs, space_s])
+            .padding([space_xxs, 0])
+            .align_x(Alignment::Center)
             .into()
     }
 
-    fn create_app_list(applet: &CosmicClassicMenu) -> Element<'_, Message> {
+    fn create_search_field(applet: &CosmicClassicMenu) -> Element<'_, Message> {
         let Spacing {
-            space_l, space_xl, ..
-        } = theme::active().cosmic().spacing;
-
-        let app_list: ListColumn<Message> = applet.available_applications.iter().fold(
-            cosmic::widget::list_column().padding([0., 0.]),
-            |list, app| {
-                let button = cosmic::widget::button::custom(
-                    container(row![
-                        app.icon
-                            .as_cosmic_icon()
-                            .width(Length::Fixed(space_l.into()))
-                            .height(Length::Fixed(space_l.into()))
-                            .content_fit(ContentFit::ScaleDown),
-                        cosmic::widget::Space::new(5, Length::Fill),
-                        column![
-                            text(&app.name),
-                            text(app.comment.as_deref().unwrap_or_default()).size(8.0),
-                        ]
-                        .padding([0, 0]),
-                    ])
-                    .align_y(Alignment::Center),
-                )
-                .on_press(Message::ApplicationSelected(app.clone()))
-                .class(cosmic::theme::Button::MenuItem)
+            space_xxs, space_s, ..
+        } = AppletMenu::spacing(applet);
+
+        let search_input =
+            cosmic::widget::search_input(fl!("search-placeholder"), &applet.search_field)
+                .id(SEARCH_FIELD_ID.clone())
+                .on_input(Message::SearchFieldInput)
                 .width(Length::Fill)
-                .height(space_xl);
+                .padding([space_xxs, space_s]);
 
-                list.add(button)
-            },
+        // Without the focus, typing doesn't get in the way of navigating the list
+        let search_input: Element<Message> = if applet.config.focus_search_on_open {
+            search_input.always_active().into()
+        } else {
+            search_input.into()
+        };
+
+        let pin_button = cosmic::widget::tooltip(
+            cosmic::widget::button::icon(icon::from_name("view-pin-symbolic"))
+                .selected(applet.pinned)
+                .on_press(Message::TogglePinned),
+            text(fl!("keep-menu-open")),
+            cosmic::widget::tooltip::Position::Bottom,
         );
 
-        scrollable(app_list)
-            .height(Length::Fill)
-            .width(Length::FillPortion(5))
+        row![search_input, pin_button]
+            .spacing(space_xxs)
+            .align_y(Alignment::Center)
             .into()
     }
 
-    fn create_categories_pane(applet: &CosmicClassicMenu) -> Element<'_, Message> {
-        let Spacing { space_m, .. } = cosmic::theme::active().cosmic().spacing;
+    fn create_app_list(applet: &CosmicClassicMenu) -> Element<'_, Message> {
+        let config = &applet.config;
+        let content = match applet.menu_layout() {
+            MenuLayout::Classic => {
+                AppletMenu::create_app_columns(applet, 1, config.list_layout.density)
+            }
+            MenuLayout::Wide => AppletMenu::create_app_columns(
+                applet,
+                config.wide_layout.columns.max(1) as usize,
+                config.wide_layout.density,
+            ),
+            MenuLayout::Grid if AppletMenu::grid_page_size(applet).is_some() => {
+                return AppletMenu::create_paged_grid(applet);
+            }
+            MenuLayout::Grid => AppletMenu::create_app_grid(
+                applet,
+                applet.visible_applications(),
+                0,
+                AppletMenu::grid_columns(applet),
+                config.grid_layout.icon_size,
+            ),
+        };
 
-        let mut categories_pane: Vec<Element<Message>> = applet.available_categories
-            .iter()
-            .map(|category| {
-                cosmic::widget::button::custom(
-                    row![
-                        container(cosmic::widget::icon::from_svg_bytes(category.icon_svg_bytes)
-                            .symbolic(true)
-                            .icon())
-                            .padding([0, space_m]),
-                        text(category.get_display_name()),
-                    ]
-                    .align_y(Alignment::Center),
-                )
-                .on_press(Message::CategorySelected(category.clone()))
-                .class(if applet.selected_category == Some(category.clone()) {
-                    cosmic::theme::Button::Suggested
-                } else {
-                    cosmic::theme::Button::AppletMenu
-                })
-                .width(Length::Fill)
-                .into()
-            })
-            .collect();
+        let app_list = AppletMenu::wrap_app_list(applet, content);
+        if !applet.search_field.is_empty() {
+            return app_list;
+        }
 
-        let horizontal_divider =
-            cosmic::applet::padded_control(cosmic::widget::divider::horizontal::default())
-                .align_x(Alignment::Center)
-                .align_y(Alignment::Center)
-                .padding(5)
-                .into();
-        if !categories_pane.is_empty() {
-            categories_pane.insert(2, horizontal_divider);
+        // Show the letter scrolled to while dragging the fast scroller
+        let app_list: Element<Message> = match applet.fast_scroll_letter {
+            Some(letter) => stack![app_list, AppletMenu::create_letter_bubble(letter)].into(),
+            None => app_list,
+        };
+
+        // The header of the category scrolled to stays on top of the single column
+        let app_list: Element<Message> =
+            if applet.shows_flat_list() && applet.menu_layout() == MenuLayout::Classic {
+                stack![app_list, AppletMenu::create_sticky_header(applet)].into()
+            } else {
+                app_list
+            };
+
+        // Jumping by letter only makes sense for the full alphabetical list
+        let alphabetical =
+            applet.selected_category == Some(ApplicationCategory::ALL) && !applet.shows_flat_list();
+        let mut list_row = row![app_list].width(Length::FillPortion(5));
+        if alphabetical {
+            list_row = list_row.push(AppletMenu::create_letter_index(applet));
         }
 
-        // add power menu to the bottom of the categories pane
-        categories_pane.push(cosmic::widget::Space::new(Length::Fill, Length::Fill).into());
-        categories_pane.push(AppletMenu::create_power_menu(&applet));
+        list_row.push(AppletMenu::create_fast_scroller()).into()
+    }
 
-        cosmic::widget::column::with_children(categories_pane)
+    /// Zone on the right edge of the list which scrolls proportionally while dragged.
+    fn create_fast_scroller() -> Element<'static, Message> {
+        let Spacing { space_s, .. } = theme::active().cosmic().spacing;
+
+        let scroller = responsive(move |size| {
+            let track = container(cosmic::widget::divider::vertical::light())
+                .center_x(space_s)
+                .height(Length::Fill);
+
+            cosmic::iced::widget::mouse_area(track)
+                .on_press(Message::FastScrollDragging(true))
+                .on_release(Message::FastScrollDragging(false))
+                .on_exit(Message::FastScrollDragging(false))
+                .on_move(move |point| Message::FastScrollMoved(point.y / size.height.max(1.0)))
+                .into()
+        });
+
+        container(scroller)
+            .width(space_s)
             .height(Length::Fill)
-            .width(Length::FillPortion(3))
+            .into()
+    }
+
+    /// Header of the category at the top of the scrolled flat list.
+    fn create_sticky_header(applet: &CosmicClassicMenu) -> Element<'_, Message> {
+        // The category of every row of the list, each section starting with its header row
+        let mut rows: Vec<Option<ApplicationCategory>> = Vec::new();
+        for app in applet.visible_applications() {
+            let group = ApplicationCategory::primary_of(&applet.available_categories, app);
+            if rows.last() != Some(&group) {
+                rows.push(group.clone());
+            }
+            rows.push(group);
+        }
+        // Rows are about equally high, so the scroll position tells the row at the top
+        let top_index = ((rows.len() as f32 * applet.app_list_scroll) as usize)
+            .min(rows.len().saturating_sub(1));
+        let Some(category) = rows.get(top_index).cloned() else {
+            return cosmic::widget::Space::new(0, 0).into();
+        };
+
+        container(AppletMenu::create_group_header(applet, category))
+            .width(Length::Fill)
+            .class(cosmic::theme::Container::custom(|theme| {
+                cosmic::widget::container::Style {
+                    background: Some(cosmic::iced::Background::Color(
+                        theme.cosmic().background.base.into(),
+                    )),
+                    ..Default::default()
+                }
+            }))
+            .into()
+    }
+
+    fn create_letter_bubble(letter: char) -> Element<'static, Message> {
+        let Spacing { space_xxl, .. } = theme::active().cosmic().spacing;
+
+        container(
+            container(text::title2(letter.to_string()))
+                .center(space_xxl)
+                .class(cosmic::theme::Container::Primary),
+        )
+        .center(Length::Fill)
+        .into()
+    }
+
+    /// Slim strip of the initial letters of the listed applications.
+    fn create_letter_index(applet: &CosmicClassicMenu) -> Element<'_, Message> {
+        let Spacing { space_xxxs, .. } = AppletMenu::spacing(applet);
+
+        let mut letters: Vec<char> = applet
+            .visible_applications()
+            .iter()
+            .map(ApplicationEntry::index_letter)
+            .collect();
+        letters.sort_unstable();
+        letters.dedup();
+
+        let letter_buttons: Vec<Element<Message>> = letters
+            .into_iter()
+            .map(|letter| {
+                cosmic::widget::button::custom(text::caption(letter.to_string()))
+                    .on_press(Message::JumpToLetter(letter))
+                    .class(cosmic::theme::Button::Text)
+                    .padding([0, space_xxxs])
+                    .into()
+            })
+            .collect();
+
+        let letter_column =
+            cosmic::widget::column::with_children(letter_buttons).align_x(Alignment::Center);
+
+        scrollable(letter_column).height(Length::Fill).into()
+    }
+
+    /// Puts the search items above `content` in the scrollable application list.
+    fn wrap_app_list<'a>(
+        applet: &'a CosmicClassicMenu,
+        content: Element<'a, Message>,
+    ) -> Element<'a, Message> {
+        // Matching power actions and tools come before the apps
+        let content: Element<Message> = if applet.search_items.is_empty() {
+            content
+        } else {
+            column![AppletMenu::create_search_items(applet), content].into()
+        };
+
+        let content: Element<Message> = if applet.search_field.is_empty()
+            && applet.selected_category == Some(ApplicationCategory::WINDOWS)
+        {
+            column![AppletMenu::create_window_list(applet), content].into()
+        } else {
+            content
+        };
+
+        let content: Element<Message> = if applet.search_field.is_empty()
+            && applet.selected_category == Some(ApplicationCategory::RECENTLY_USED)
+        {
+            column![AppletMenu::create_recents_order_toggle(applet), content].into()
+        } else {
+            content
+        };
+
+        // Folders of favorites come before the favorites outside of them
+        let content: Element<Message> = if applet.search_field.is_empty()
+            && applet.selected_category == Some(ApplicationCategory::FAVORITES)
+            && !applet.favorite_folders.is_empty()
+        {
+            column![AppletMenu::create_favorite_folders(applet), content].into()
+        } else {
+            content
+        };
+
+        let content: Element<Message> =
+            if applet.search_field.is_empty() && !applet.clipboard_history.is_empty() {
+                column![AppletMenu::create_clipboard_section(applet), content].into()
+            } else {
+                content
+            };
+
+        let content: Element<Message> = if applet.hidden_results_count() == 0 {
+            content
+        } else {
+            column![content, AppletMenu::create_show_all_row(applet)].into()
+        };
+
+        // A wider scrollbar is easier to grab with a finger
+        let scrollbar = if applet.config.touch_mode {
+            scrollable::Scrollbar::new()
+                .width(AppletMenu::TOUCH_SCROLLBAR_WIDTH)
+                .scroller_width(AppletMenu::TOUCH_SCROLLBAR_WIDTH)
+        } else {
+            scrollable::Scrollbar::new()
+        };
+
+        let app_list = scrollable(content)
+            .id(APP_LIST_ID.clone())
+            .direction(scrollable::Direction::Vertical(scrollbar))
+            .height(Length::Fill)
+            .width(Length::FillPortion(5));
+        // Only followed for the sticky header of the flat list
+        if applet.shows_flat_list() {
+            app_list
+                .on_scroll(|viewport| Message::AppListScrolled(viewport.relative_offset().y))
+                .into()
+        } else {
+            app_list.into()
+        }
+    }
+
+    /// Folders of favorites, listing their apps below the expanded one.
+    fn create_favorite_folders(applet: &CosmicClassicMenu) -> Element<'_, Message> {
+        let Spacing {
+            space_xs, space_xl, ..
+        } = AppletMenu::spacing(applet);
+
+        // Apps in folders aren't part of the keyboard navigation, so their rows get
+        // indices past the end of the list
+        let mut index = applet.available_applications.len();
+        let mut list = cosmic::widget::list_column().padding([0., 0.]);
+        for (name, apps) in &applet.favorite_folders {
+            let expanded = applet.expanded_folder.as_ref() == Some(name);
+            let folder_row = row![
+                cosmic::widget::icon::from_name("folder-symbolic").size(16),
+                text(name).width(Length::Fill),
+                text::caption(apps.len().to_string()),
+                cosmic::widget::icon::from_name(if expanded {
+                    "go-up-symbolic"
+                } else {
+                    "go-down-symbolic"
+                })
+                .size(16),
+            ]
+            .spacing(space_xs)
+            .align_y(Alignment::Center);
+            list = list.add(
+                cosmic::widget::button::custom(folder_row)
+                    .on_press(Message::ToggleFolder(name.clone()))
+                    .class(cosmic::theme::Button::MenuItem)
+                    .width(Length::Fill)
+                    .height(space_xl),
+            );
+
+            if expanded {
+                for app in apps {
+                    list = list.add(AppletMenu::create_app_entry(
+                        applet,
+                        index,
+                        app,
+                        applet.config.list_layout.density,
+                    ));
+                    index += 1;
+                }
+            }
+        }
+
+        list.into()
+    }
+
+    /// Recently copied snippets, copied back to the clipboard when clicked.
+    fn create_clipboard_section(applet: &CosmicClassicMenu) -> Element<'_, Message> {
+        let Spacing {
+            space_xxs,
+            space_xs,
+            space_xl,
+            ..
+        } = AppletMenu::spacing(applet);
+
+        let header = container(text::caption_heading(fl!("clipboard")))
+            .padding([space_xs, space_xxs, space_xxs, space_xxs]);
+
+        applet
+            .clipboard_history
+            .iter()
+            .fold(
+                cosmic::widget::list_column().padding([0., 0.]).add(header),
+                |list, entry| {
+                    // Only the first line is shown, long snippets are cut at the end
+                    let preview = entry.lines().find(|line| !line.trim().is_empty());
+                    let entry_row = row![
+                        cosmic::widget::icon::from_name("edit-paste-symbolic").size(16),
+                        text(preview.unwrap_or_default().trim().to_string())
+                            .wrapping(cosmic::iced::widget::text::Wrapping::None)
+                            .width(Length::Fill),
+                    ]
+                    .spacing(space_xs)
+                    .align_y(Alignment::Center);
+
+                    list.add(
+                        cosmic::widget::button::custom(entry_row)
+                            .on_press(Message::CopyClipboardEntry(entry.clone()))
+                            .class(cosmic::theme::Button::MenuItem)
+                            .width(Length::Fill)
+                            .height(space_xl),
+                    )
+                },
+            )
+            .into()
+    }
+
+    /// Lists the open windows, focusing the one that's clicked.
+    fn create_window_list(applet: &CosmicClassicMenu) -> Element<'_, Message> {
+        let Spacing {
+            space_xxs,
+            space_xs,
+            space_l,
+            space_xl,
+            ..
+        } = AppletMenu::spacing(applet);
+
+        if applet.open_windows.is_empty() {
+            return container(text::caption(fl!("no-open-windows")))
+                .padding([space_xs, space_xxs])
+                .into();
+        }
+
+        applet
+            .open_windows
+            .iter()
+            .fold(
+                cosmic::widget::list_column().padding([0., 0.]),
+                |list, window| {
+                    // Untitled windows still need something to tell them apart
+                    let title = if window.title.is_empty() {
+                        &window.app_id
+                    } else {
+                        &window.title
+                    };
+                    let window_row = row![
+                        AppletMenu::app_icon(applet, &window.icon, space_l),
+                        text(title.clone())
+                            .wrapping(cosmic::iced::widget::text::Wrapping::None)
+                            .width(Length::Fill),
+                    ]
+                    .spacing(space_xs)
+                    .align_y(Alignment::Center);
+
+                    list.add(
+                        cosmic::widget::button::custom(window_row)
+                            .on_press(Message::ActivateWindow(window.handle.clone()))
+                            .class(cosmic::theme::Button::MenuItem)
+                            .width(Length::Fill)
+                            .height(space_xl),
+                    )
+                },
+            )
+            .into()
+    }
+
+    fn create_search_items(applet: &CosmicClassicMenu) -> Element<'_, Message> {
+        let Spacing {
+            space_l, space_xl, ..
+        } = AppletMenu::spacing(applet);
+
+        applet
+            .search_items
+            .iter()
+            .enumerate()
+            .fold(
+                cosmic::widget::list_column().padding([0., 0.]),
+                |list, (index, item)| {
+                    let icon = match item {
+                        SearchItem::Power(action) => {
+                            cosmic::widget::icon::from_svg_bytes(AppletMenu::get_power_icon(action))
+                                .symbolic(true)
+                                .icon()
+                        }
+                        SearchItem::Tool(tool) => {
+                            cosmic::widget::icon::from_name(tool.get_icon_name()).icon()
+                        }
+                        SearchItem::OpenUri(_, handler) => {
+                            AppletMenu::lookup_app_icon(applet, &handler.icon, space_l)
+                        }
+                        SearchItem::Character(..) => {
+                            cosmic::widget::icon::from_name("edit-copy-symbolic").icon()
+                        }
+                        SearchItem::Timer(..) => {
+                            cosmic::widget::icon::from_name("alarm-symbolic").icon()
+                        }
+                        SearchItem::Calculation(..) => {
+                            cosmic::widget::icon::from_name("accessories-calculator-symbolic")
+                                .icon()
+                        }
+                        SearchItem::RunCommand(..) => {
+                            cosmic::widget::icon::from_name("utilities-terminal-symbolic").icon()
+                        }
+                        SearchItem::OpenPath(path) if path.is_dir() => {
+                            cosmic::widget::icon::from_name("folder-symbolic").icon()
+                        }
+                        SearchItem::OpenPath(..) => {
+                            cosmic::widget::icon::from_name("text-x-generic-symbolic").icon()
+                        }
+                        SearchItem::WebSearch(..) => {
+                            cosmic::widget::icon::from_name("web-browser-symbolic").icon()
+                        }
+                        SearchItem::SshHost(..) => {
+                            cosmic::widget::icon::from_name("network-server-symbolic").icon()
+                        }
+                        SearchItem::Bookmark(..) => {
+                            cosmic::widget::icon::from_name("starred-symbolic").icon()
+                        }
+                        SearchItem::SettingsPanel(panel) => {
+                            cosmic::widget::icon::from_name(panel.icon.as_str()).icon()
+                        }
+                        SearchItem::RecentDocument(document) if document.path.is_dir() => {
+                            cosmic::widget::icon::from_name("folder-symbolic").icon()
+                        }
+                        SearchItem::RecentDocument(..) => {
+                            cosmic::widget::icon::from_name("text-x-generic-symbolic").icon()
+                        }
+                        SearchItem::Script(result) => match result.icon.as_deref() {
+                            Some(path) if path.starts_with('/') => {
+                                cosmic::widget::icon::from_path(path.into()).icon()
+                            }
+                            Some(name) => cosmic::widget::icon::from_name(name).icon(),
+                            None => cosmic::widget::icon::from_name("system-run-symbolic").icon(),
+                        },
+                    };
+                    // Documents, bookmarks and script results come last, under their own
+                    // headings
+                    let previous = index
+                        .checked_sub(1)
+                        .and_then(|previous| applet.search_items.get(previous));
+                    let list = match (item, previous) {
+                        (SearchItem::RecentDocument(..), Some(SearchItem::RecentDocument(..)))
+                        | (SearchItem::Bookmark(..), Some(SearchItem::Bookmark(..)))
+                        | (SearchItem::Script(..), Some(SearchItem::Script(..))) => list,
+                        (SearchItem::RecentDocument(..), _) => {
+                            list.add(AppletMenu::create_section_header(applet, fl!("files")))
+                        }
+                        (SearchItem::Bookmark(..), _) => {
+                            list.add(AppletMenu::create_section_header(applet, fl!("bookmarks")))
+                        }
+                        (SearchItem::Script(..), _) => list.add(AppletMenu::create_section_header(
+                            applet,
+                            fl!("script-results"),
+                        )),
+                        _ => list,
+                    };
+                    let item_row = row![
+                        icon.size(space_l),
+                        cosmic::widget::Space::new(5, Length::Fill),
+                        text(item.get_display_name()).width(Length::Fill),
+                    ]
+                    .align_y(Alignment::Center);
+
+                    list.add(
+                        cosmic::widget::button::custom(container(item_row))
+                            .on_press(item.message())
+                            .class(cosmic::theme::Button::MenuItem)
+                            .width(Length::Fill)
+                            .height(space_xl),
+                    )
+                },
+            )
+            .into()
+    }
+
+    fn create_show_all_row(applet: &CosmicClassicMenu) -> Element<'_, Message> {
+        let Spacing { space_xl, .. } = AppletMenu::spacing(applet);
+
+        cosmic::widget::button::custom(
+            container(text(fl!(
+                "show-all-results",
+                count = applet.available_applications.len()
+            )))
+            .center_x(Length::Fill),
+        )
+        .on_press(Message::ShowAllResults)
+        .class(cosmic::theme::Button::MenuItem)
+        .width(Length::Fill)
+        .height(space_xl)
+        .into()
+    }
+
+    fn get_power_icon(action: &PowerAction) -> &'static [u8] {
+        match action {
+            PowerAction::Logout => AppletMenu::SYSTEM_LOGOUT_SYMBOLIC_ICON,
+            PowerAction::Suspend => AppletMenu::SYSTEM_SUSPEND_SYMBOLIC_ICON,
+            PowerAction::Lock => AppletMenu::SYSTEM_LOCKSCREEN_SYMBOLIC_ICON,
+            PowerAction::SwitchUser => AppletMenu::USER_IDLE_SYMBOLIC,
+            PowerAction::Reboot | PowerAction::RebootToFirmware => {
+                AppletMenu::SYSTEM_REBOOT_SYMBOLIC_ICON
+            }
+            PowerAction::Shutdown => AppletMenu::SYSTEM_SHUTDOWN_SYMBOLIC_ICON,
+        }
+    }
+
+    fn create_app_columns(
+        applet: &CosmicClassicMenu,
+        column_count: usize,
+        density: ListDensity,
+    ) -> Element<'_, Message> {
+        let Spacing { space_xxs, .. } = AppletMenu::spacing(applet);
+
+        let show_group_headers = applet.shows_flat_list()
+            || (applet.config.group_search_results && !applet.search_field.is_empty());
+
+        let rows_per_column = AppletMenu::rows_per_column(applet, column_count);
+
+        let app_columns: Vec<Element<Message>> = applet
+            .visible_applications()
+            .chunks(rows_per_column)
+            .enumerate()
+            .map(|(column_index, chunk)| {
+                let mut app_list: ListColumn<Message> =
+                    cosmic::widget::list_column().padding([0., 0.]);
+                let mut current_group = None;
+                for (row_index, app) in chunk.iter().enumerate() {
+                    // Start a new section whenever the primary category changes
+                    if show_group_headers {
+                        let group = ApplicationCategory::primary_of(&applet.available_categories, app);
+                        if current_group.as_ref() != Some(&group) {
+                            app_list = app_list.add(AppletMenu::create_group_header(applet, group.clone()));
+                            current_group = Some(group);
+                        }
+                    }
+                    app_list = app_list.add(AppletMenu::create_app_entry(
+                        applet,
+                        column_index * rows_per_column + row_index,
+                        app,
+                        density,
+                    ));
+                }
+
+                container(app_list).width(Length::FillPortion(1)).into()
+            })
+            .collect();
+
+        cosmic::widget::row::with_children(app_columns)
+            .spacing(space_xxs)
+            .into()
+    }
+
+    /// Returns the number of rows of a column of the app list. Entries flow top-to-bottom,
+    /// so the first column holds the first part of the list.
+    pub fn rows_per_column(applet: &CosmicClassicMenu, column_count: usize) -> usize {
+        applet
+            .visible_applications()
+            .len()
+            .div_ceil(column_count.max(1))
+            .max(1)
+    }
+
+    /// Returns the configured grid columns, or as many as fit the list when automatic.
+    fn grid_columns(applet: &CosmicClassicMenu) -> usize {
+        let grid_layout = &applet.config.grid_layout;
+        if grid_layout.columns > 0 {
+            return grid_layout.columns as usize;
+        }
+
+        // The list shares the popup with the categories pane in a 5:3 split
+        let shares_popup = applet.search_field.is_empty()
+            && applet.config.category_navigation == CategoryNavigation::SidePane;
+        let list_width = if shares_popup {
+            AppletMenu::popup_width(applet) * 5.0 / 8.0
+        } else {
+            AppletMenu::popup_width(applet)
+        };
+        // Cells are about twice as wide as their icon to leave room for the names
+        let cell_width = f32::from(grid_layout.icon_size.max(16)) * 2.0;
+
+        ((list_width / cell_width) as usize).clamp(
+            AppletMenu::GRID_MIN_AUTO_COLUMNS,
+            AppletMenu::GRID_MAX_AUTO_COLUMNS,
+        )
+    }
+
+    /// Returns the number of apps on a page of the grid, if the grid is paginated.
+    pub fn grid_page_size(applet: &CosmicClassicMenu) -> Option<usize> {
+        let grid_layout = &applet.config.grid_layout;
+        if !grid_layout.paginated
+            || applet.menu_layout() != MenuLayout::Grid
+            || applet.is_minimal_popup()
+        {
+            return None;
+        }
+
+        // Each cell holds the icon above a line or two of the app name
+        let cell_height = f32::from(grid_layout.icon_size) + 48.0;
+        let rows = ((AppletMenu::GRID_PAGE_HEIGHT / cell_height) as usize).max(1);
+        Some(rows * AppletMenu::grid_columns(applet))
+    }
+
+    /// Grid showing a page of apps at a time, flipped with the indicators or by scrolling.
+    fn create_paged_grid(applet: &CosmicClassicMenu) -> Element<'_, Message> {
+        let Spacing { space_xxs, .. } = AppletMenu::spacing(applet);
+
+        let applications = applet.visible_applications();
+        let page_size = AppletMenu::grid_page_size(applet).unwrap_or(applications.len().max(1));
+        let page_count = applet.grid_page_count();
+        let page = applet.grid_page.min(page_count - 1);
+        let first_index = page * page_size;
+        let page_applications =
+            &applications[first_index..(first_index + page_size).min(applications.len())];
+
+        let page_grid = AppletMenu::create_app_grid(
+            applet,
+            page_applications,
+            first_index,
+            AppletMenu::grid_columns(applet),
+            applet.config.grid_layout.icon_size,
+        );
+        let page_grid = cosmic::iced::widget::mouse_area(
+            container(page_grid)
+                .width(Length::Fill)
+                .height(Length::Fill),
+        )
+        .on_scroll(|delta| {
+            let lines = match delta {
+                ScrollDelta::Lines { x, y } => x + y,
+                ScrollDelta::Pixels { x, y } => (x + y) / AppletMenu::GRID_SCROLL_LINE_HEIGHT,
+            };
+            Message::GridScrolled(lines)
+        });
+
+        let mut content = cosmic::widget::column::with_capacity(4).spacing(space_xxs);
+        if !applet.search_items.is_empty() {
+            content = content.push(AppletMenu::create_search_items(applet));
+        }
+        content = content.push(page_grid);
+        if page_count > 1 {
+            content = content.push(AppletMenu::create_page_indicators(applet, page, page_count));
+        }
+        if applet.hidden_results_count() > 0 {
+            content = content.push(AppletMenu::create_show_all_row(applet));
+        }
+
+        container(content)
+            .height(Length::Fill)
+            .width(Length::FillPortion(5))
+            .into()
+    }
+
+    /// Row of dots for the pages of the grid, the current one highlighted.
+    fn create_page_indicators(
+        applet: &CosmicClassicMenu,
+        page: usize,
+        page_count: usize,
+    ) -> Element<'static, Message> {
+        let Spacing {
+            space_xxs, space_s, ..
+        } = AppletMenu::spacing(applet);
+
+        let dots: Vec<Element<Message>> = (0..page_count)
+            .map(|index| {
+                let icon_name = if index == page {
+                    "media-record-symbolic"
+                } else {
+                    "radio-symbolic"
+                };
+                cosmic::widget::button::icon(
+                    cosmic::widget::icon::from_name(icon_name).size(space_s),
+                )
+                .on_press(Message::GridPageSelected(index))
+                .padding(space_xxs)
+                .into()
+            })
+            .collect();
+
+        container(cosmic::widget::row::with_children(dots))
+            .center_x(Length::Fill)
+            .into()
+    }
+
+    fn create_app_grid<'a>(
+        applet: &'a CosmicClassicMenu,
+        applications: &'a [ApplicationEntry],
+        first_index: usize,
+        column_count: usize,
+        icon_size: u16,
+    ) -> Element<'a, Message> {
+        let Spacing { space_xxs, .. } = AppletMenu::spacing(applet);
+
+        let grid_rows: Vec<Element<Message>> = applications
+            .chunks(column_count)
+            .enumerate()
+            .map(|(row_index, chunk)| {
+                let mut cells: Vec<Element<Message>> = chunk
+                    .iter()
+                    .enumerate()
+                    .map(|(column_index, app)| {
+                        AppletMenu::create_app_grid_cell(
+                            applet,
+                            first_index + row_index * column_count + column_index,
+                            app,
+                            icon_size,
+                        )
+                    })
+                    .collect();
+                // Pad the last row so its cells keep the same width as the others
+                cells.resize_with(column_count, || {
+                    cosmic::widget::Space::new(Length::Fill, Length::Shrink).into()
+                });
+
+                cosmic::widget::row::with_children(cells)
+                    .spacing(space_xxs)
+                    .into()
+            })
+            .collect();
+
+        cosmic::widget::column::with_children(grid_rows)
+            .spacing(space_xxs)
+            .into()
+    }
+
+    fn create_group_header(
+        applet: &CosmicClassicMenu,
+        category: Option<ApplicationCategory>,
+    ) -> Element<'static, Message> {
+        let label = match category {
+            Some(category) => category.get_display_name(),
+            None => fl!("other-category"),
+        };
+        AppletMenu::create_section_header(applet, label)
+    }
+
+    fn create_section_header(
+        applet: &CosmicClassicMenu,
+        label: String,
+    ) -> Element<'static, Message> {
+        let Spacing {
+            space_xxs,
+            space_xs,
+            ..
+        } = AppletMenu::spacing(applet);
+
+        container(text::caption_heading(label))
+            .padding([space_xs, space_xxs, space_xxs, space_xxs])
+            .into()
+    }
+
+    fn create_app_entry<'a>(
+        applet: &'a CosmicClassicMenu,
+        index: usize,
+        app: &'a ApplicationEntry,
+        density: ListDensity,
+    ) -> Element<'a, Message> {
+        let Spacing {
+            space_xs,
+            space_m,
+            space_l,
+            space_xl,
+            space_xxl,
+            ..
+        } = AppletMenu::spacing(applet);
+
+        let (icon_size, row_height) = match density {
+            _ if applet.config.touch_mode => (space_xl, space_xxl),
+            ListDensity::Comfortable => (space_l, space_xl),
+            ListDensity::Compact => (space_m, space_l),
+        };
+
+        let mut labels = column![AppletMenu::create_app_name(applet, app)]
+            .padding([0, 0])
+            .width(Length::Fill);
+        // Without the second line, the rows only need to fit their icons
+        let row_height = if applet.config.show_comments {
+            labels = labels.push(
+                text(AppletMenu::app_secondary_text(applet, app))
+                    .size(applet.config.density.text_size(8.0)),
+            );
+            row_height
+        } else {
+            icon_size + space_xs
+        };
+
+        let mut entry_row = row![
+            AppletMenu::app_icon(applet, &app.icon, icon_size),
+            cosmic::widget::Space::new(5, Length::Fill),
+            labels,
+        ]
+        .align_y(Alignment::Center);
+        if applet.pending_updates.contains(&app.id) {
+            entry_row = entry_row.push(AppletMenu::create_update_indicator());
+        }
+        let mut entry_content = container(entry_row);
+        if app.is_hidden {
+            entry_content = entry_content.class(AppletMenu::hidden_entry_style());
+        }
+
+        let entry_button = cosmic::widget::button::custom(entry_content)
+            .id(app_entry_id(index))
+            .on_press(AppletMenu::app_press_message(applet, index, app))
+            .class(cosmic::theme::Button::MenuItem)
+            .selected(applet.selected_application == Some(index))
+            .width(Length::Fill)
+            .height(row_height);
+        let entry_button = AppletMenu::with_long_press(applet, app, entry_button);
+        let entry_button = AppletMenu::with_favorite_drag(applet, app, entry_button);
+
+        AppletMenu::with_context_menu(
+            applet,
+            app,
+            AppletMenu::with_details_tooltip(applet, app, entry_button),
+        )
+    }
+
+    /// Returns the line under the app's name, when it was last launched in the Recently Used
+    /// view and its comment elsewhere.
+    fn app_secondary_text(applet: &CosmicClassicMenu, app: &ApplicationEntry) -> String {
+        let is_recents_view = applet.search_field.is_empty()
+            && applet.selected_category == Some(ApplicationCategory::RECENTLY_USED);
+        let last_launched = applet
+            .recent_applications
+            .iter()
+            .find(|recent_app| recent_app.app_id == app.id)
+            .and_then(|recent_app| Elapsed::since(recent_app.last_launched, unix_timestamp()));
+        match last_launched {
+            Some(elapsed) if is_recents_view => match elapsed {
+                Elapsed::JustNow => fl!("launched-just-now"),
+                Elapsed::Minutes(count) => fl!("launched-minutes-ago", count = count),
+                Elapsed::Hours(count) => fl!("launched-hours-ago", count = count),
+                Elapsed::Days(count) => fl!("launched-days-ago", count = count),
+            },
+            _ => app.comment.clone().unwrap_or_default(),
+        }
+    }
+
+    /// Switches the Recently Used apps between the most used and the last used first.
+    fn create_recents_order_toggle(applet: &CosmicClassicMenu) -> Element<'_, Message> {
+        let Spacing {
+            space_xxs,
+            space_xs,
+            ..
+        } = AppletMenu::spacing(applet);
+
+        let order_button = |label: String, order: RecentsOrder| {
+            cosmic::widget::button::custom(text::caption(label))
+                .class(cosmic::theme::Button::MenuItem)
+                .selected(applet.config.recents_order == order)
+                .padding([space_xxs, space_xs])
+                .on_press(Message::RecentsOrderChanged(order))
+        };
+
+        container(
+            row![
+                order_button(fl!("most-used"), RecentsOrder::MostUsed),
+                order_button(fl!("last-used"), RecentsOrder::LastUsed),
+            ]
+            .spacing(space_xxs),
+        )
+        .width(Length::Fill)
+        .padding([space_xxs, 0])
+        .align_x(Alignment::End)
+        .into()
+    }
+
+    /// Returns the name of the app with the characters matching the search in bold.
+    fn create_app_name<'a>(
+        applet: &CosmicClassicMenu,
+        app: &'a ApplicationEntry,
+    ) -> Element<'a, Message> {
+        let name = app.display_name(applet.config.name_display);
+        let name_size = applet.config.density.text_size(AppletMenu::APP_NAME_SIZE);
+        let query = &applet.search_field;
+        let matched_indices = if query.is_empty() {
+            None
+        } else {
+            cosmic_classic_menu_core::ranking::matched_indices_with(
+                applet.config.search_mode,
+                &name,
+                query,
+            )
+        };
+        let Some(matched_indices) = matched_indices else {
+            return text(name).size(name_size).into();
+        };
+
+        // Split the name into runs of matched and unmatched characters
+        let mut spans: Vec<Span<'a>> = Vec::new();
+        let mut run_start = 0;
+        let mut run_matched = false;
+        for (char_index, (byte_index, _)) in name.char_indices().enumerate() {
+            let matched = matched_indices.contains(&char_index);
+            if matched != run_matched && byte_index > run_start {
+                spans.push(AppletMenu::create_name_span(
+                    &name[run_start..byte_index],
+                    run_matched,
+                ));
+                run_start = byte_index;
+            }
+            run_matched = matched;
+        }
+        spans.push(AppletMenu::create_name_span(
+            &name[run_start..],
+            run_matched,
+        ));
+
+        rich_text(spans).size(name_size).into()
+    }
+
+    fn create_name_span(fragment: &str, matched: bool) -> Span<'static> {
+        let name_span = span(fragment.to_string());
+        if matched {
+            name_span.font(Font {
+                weight: cosmic::iced::font::Weight::Bold,
+                ..Default::default()
+            })
+        } else {
+            name_span
+        }
+    }
+
+    fn create_app_grid_cell<'a>(
+        applet: &'a CosmicClassicMenu,
+        index: usize,
+        app: &'a ApplicationEntry,
+        icon_size: u16,
+    ) -> Element<'a, Message> {
+        let Spacing {
+            space_xxs,
+            space_xs,
+            ..
+        } = AppletMenu::spacing(applet);
+
+        let mut cell_icon = row![AppletMenu::app_icon(applet, &app.icon, icon_size)];
+        if applet.pending_updates.contains(&app.id) {
+            cell_icon = cell_icon.push(AppletMenu::create_update_indicator());
+        }
+
+        let cell_button = cosmic::widget::button::custom(
+            column![cell_icon, AppletMenu::create_app_name(applet, app)]
+                .spacing(space_xxs)
+                .align_x(Alignment::Center)
+                .width(Length::Fill),
+        )
+        .id(app_entry_id(index))
+        .on_press(AppletMenu::app_press_message(applet, index, app))
+        .class(cosmic::theme::Button::MenuItem)
+        .selected(applet.selected_application == Some(index))
+        .padding(if applet.config.touch_mode {
+            space_xs
+        } else {
+            space_xxs
+        })
+        .width(Length::Fill);
+        let cell_button = AppletMenu::with_long_press(applet, app, cell_button);
+        let cell_button = AppletMenu::with_favorite_drag(applet, app, cell_button);
+
+        AppletMenu::with_context_menu(
+            applet,
+            app,
+            AppletMenu::with_details_tooltip(applet, app, cell_button),
+        )
+    }
+
+    /// Subdued text of the entries that are only listed on request.
+    fn hidden_entry_style() -> cosmic::theme::Container<'static> {
+        cosmic::theme::Container::custom(|theme| cosmic::widget::container::Style {
+            text_color: Some(theme.cosmic().palette.neutral_6.into()),
+            ..Default::default()
+        })
+    }
+
+    /// Small dot marking applications with a pending Flatpak update.
+    fn create_update_indicator() -> Element<'static, Message> {
+        cosmic::widget::icon::from_name("software-update-available-symbolic")
+            .size(12)
+            .icon()
+            .into()
+    }
+
+    /// Opens the app actions when the app is held down in touch mode.
+    fn with_long_press<'a>(
+        applet: &CosmicClassicMenu,
+        app: &ApplicationEntry,
+        button: cosmic::widget::Button<'a, Message>,
+    ) -> cosmic::widget::Button<'a, Message> {
+        if applet.config.touch_mode {
+            button.on_press_down(Message::LongPressStarted(app.id.clone()))
+        } else {
+            button
+        }
+    }
+
+    /// Launches the app, or only selects it while it takes a double click to launch one.
+    fn app_press_message(
+        applet: &CosmicClassicMenu,
+        index: usize,
+        app: &ApplicationEntry,
+    ) -> Message {
+        if applet.config.double_click_launch {
+            Message::ApplicationClicked(index, app.clone())
+        } else {
+            Message::ApplicationSelected(app.clone())
+        }
+    }
+
+    /// Lets the favorites be dragged over each other to reorder them. Holding one still opens
+    /// its actions in touch mode, as starting the drag starts the long press too.
+    fn with_favorite_drag<'a>(
+        applet: &CosmicClassicMenu,
+        app: &ApplicationEntry,
+        button: cosmic::widget::Button<'a, Message>,
+    ) -> Element<'a, Message> {
+        let is_favorites_view = applet.search_field.is_empty()
+            && applet.selected_category == Some(ApplicationCategory::FAVORITES);
+        if !is_favorites_view {
+            return button.into();
+        }
+
+        cosmic::iced::widget::mouse_area(
+            button.on_press_down(Message::FavoriteDragStarted(app.id.clone())),
+        )
+        .on_enter(Message::FavoriteDragOver(app.id.clone()))
+        .into()
+    }
+
+    /// Opens the application's context menu on right click.
+    fn with_context_menu<'a>(
+        applet: &'a CosmicClassicMenu,
+        app: &'a ApplicationEntry,
+        content: Element<'a, Message>,
+    ) -> Element<'a, Message> {
+        let content =
+            mouse_area(content).on_right_press(Message::OpenAppContextMenu(app.id.clone()));
+
+        if applet.app_context_menu.as_ref() != Some(&app.id) {
+            return content.into();
+        }
+
+        let actions = ApplicationAction::available_for(applet, app);
+        let menu_items: Vec<Element<Message>> = if actions.is_empty() {
+            vec![cosmic::applet::padded_control(text::body(fl!("no-actions"))).into()]
+        } else {
+            actions
+                .into_iter()
+                .map(|action| {
+                    let enabled = action.is_enabled_for(applet, app);
+                    cosmic::applet::menu_button(text::body(action.get_display_name()))
+                        .class(cosmic::theme::Button::AppletMenu)
+                        .on_press_maybe(
+                            enabled.then(|| Message::PerformApplicationAction(app.clone(), action)),
+                        )
+                        .into()
+                })
+                .collect()
+        };
+
+        cosmic::widget::popover(content)
+            .popup(
+                container(cosmic::widget::column::with_children(menu_items))
+                    .padding([8, 0])
+                    .width(Length::Fixed(AppletMenu::TOOLTIP_MAX_WIDTH))
+                    .class(cosmic::theme::Container::Dropdown),
+            )
+            .on_close(Message::CloseAppContextMenu)
+            .into()
+    }
+
+    /// Wraps an application widget in a tooltip showing its full name, comment and source,
+    /// since rows and grid cells truncate them.
+    fn with_details_tooltip<'a>(
+        applet: &'a CosmicClassicMenu,
+        app: &'a ApplicationEntry,
+        content: impl Into<Element<'a, Message>>,
+    ) -> Element<'a, Message> {
+        let Spacing { space_xxs, .. } = AppletMenu::spacing(applet);
+
+        let mut details = column![text::heading(&app.name)].spacing(space_xxs);
+        if let Some(comment) = app.comment.as_deref().filter(|comment| !comment.is_empty()) {
+            details = details.push(text::body(comment));
+        }
+        details = details.push(text::caption(app.source.get_display_name()));
+        if applet.is_missing_preferred_gpu(app) {
+            details = details.push(text::caption(fl!("dgpu-unavailable-hint")));
+        }
+
+        cosmic::widget::tooltip(
+            content,
+            container(details).max_width(AppletMenu::TOOLTIP_MAX_WIDTH),
+            cosmic::widget::tooltip::Position::Bottom,
+        )
+        .into()
+    }
+
+    /// Header of an opened category in drill-down navigation, leading back to the categories.
+    fn create_category_header(applet: &CosmicClassicMenu) -> Element<'_, Message> {
+        let Spacing { space_xxs, .. } = AppletMenu::spacing(applet);
+
+        let category_name = applet
+            .selected_category
+            .as_ref()
+            .map(ApplicationCategory::get_display_name)
+            .unwrap_or_default();
+
+        cosmic::widget::button::custom(
+            row![
+                cosmic::widget::icon::from_name("go-previous-symbolic").size(16),
+                text::heading(category_name),
+            ]
+            .spacing(space_xxs)
+            .align_y(Alignment::Center),
+        )
+        .on_press(Message::CloseCategory)
+        .class(cosmic::theme::Button::Text)
+        .padding(space_xxs)
+        .into()
+    }
+
+    fn create_categories_pane(applet: &CosmicClassicMenu) -> Element<'_, Message> {
+        let Spacing { space_m, .. } = AppletMenu::spacing(applet);
+
+        let mut categories_pane: Vec<Element<Message>> = applet.available_categories
+            .iter()
+            .map(|category| {
+                let icon = AppletMenu::category_icon(applet, category);
+                cosmic::widget::button::custom(
+                    row![
+                        container(icon).padding([0, space_m]),
+                        text(category.get_display_name()),
+                    ]
+                    .align_y(Alignment::Center),
+                )
+                .on_press(Message::CategorySelected(category.clone()))
+                .class(if applet.selected_category == Some(category.clone()) {
+                    cosmic::theme::Button::Suggested
+                } else {
+                    cosmic::theme::Button::AppletMenu
+                })
+                .width(Length::Fill)
+                .into()
+            })
+            .collect();
+
+        let horizontal_divider =
+            cosmic::applet::padded_control(cosmic::widget::divider::horizontal::default())
+                .align_x(Alignment::Center)
+                .align_y(Alignment::Center)
+                .padding(5)
+                .into();
+        if !categories_pane.is_empty() {
+            categories_pane.insert(2, horizontal_divider);
+        }
+
+        // add power menu to the bottom of the categories pane
+        categories_pane.push(cosmic::widget::Space::new(Length::Fill, Length::Fill).into());
+        categories_pane.extend(AppletMenu::create_system_controls(applet));
+
+        cosmic::widget::column::with_children(categories_pane)
+            .height(Length::Fill)
+            .width(Length::FillPortion(3))
+            .into()
+    }
+
+    /// Icon of the category, the one named in the settings or its built-in one.
+    fn category_icon(applet: &CosmicClassicMenu, category: &ApplicationCategory) -> icon::Icon {
+        match category.get_icon_name(&applet.config) {
+            // Sized like the built-in icons, themes may only ship larger images
+            Some(icon_name) => AppletMenu::named_icon(applet, icon_name, 16)
+                .icon()
+                .width(Length::Fixed(16.0))
+                .height(Length::Fixed(16.0)),
+            None => cosmic::widget::icon::from_svg_bytes(category.icon_svg_bytes)
+                .symbolic(true)
+                .icon(),
+        }
+    }
+
+    /// Buttons of the categories that aren't sections of the flat list, all apps included to
+    /// return to it.
+    fn create_special_categories(applet: &CosmicClassicMenu) -> Element<'_, Message> {
+        let Spacing {
+            space_xxs,
+            space_xs,
+            ..
+        } = AppletMenu::spacing(applet);
+
+        let buttons = [
+            ApplicationCategory::ALL,
+            ApplicationCategory::FAVORITES,
+            ApplicationCategory::RECENTLY_USED,
+            ApplicationCategory::WINDOWS,
+        ]
+        .into_iter()
+        .filter(|category| applet.available_categories.contains(category))
+        .map(|category| {
+            cosmic::widget::button::custom(
+                row![
+                    AppletMenu::category_icon(applet, &category),
+                    text(category.get_display_name()),
+                ]
+                .spacing(space_xs)
+                .align_y(Alignment::Center),
+            )
+            .on_press(Message::CategorySelected(category.clone()))
+            .class(if applet.selected_category.as_ref() == Some(&category) {
+                cosmic::theme::Button::Suggested
+            } else {
+                cosmic::theme::Button::AppletMenu
+            })
+            .into()
+        })
+        .collect::<Vec<Element<Message>>>();
+
+        cosmic::widget::row::with_children(buttons)
+            .spacing(space_xxs)
+            .padding([0, 0, space_xxs, 0])
+            .into()
+    }
+
+    /// Quick toggles and power options, at the bottom of the categories pane.
+    fn create_system_controls(applet: &CosmicClassicMenu) -> Vec<Element<'_, Message>> {
+        let mut controls = Vec::new();
+        if applet.config.show_quick_toggles {
+            controls.push(AppletMenu::create_quick_toggles(applet));
+        }
+        if applet.shows_power_options() {
+            if !applet.boot_entries.is_empty() {
+                controls.push(AppletMenu::create_boot_entries(applet));
+            }
+            controls.push(AppletMenu::create_timer(applet, TimedAction::Shutdown));
+            controls.push(AppletMenu::create_timer(applet, TimedAction::Logout));
+            controls.push(AppletMenu::create_power_menu(applet));
+        }
+        controls
+    }
+
+    /// Other logged in users, whose sessions can be switched to without logging out.
+    fn create_other_sessions(applet: &CosmicClassicMenu) -> Element<'_, Message> {
+        let Spacing {
+            space_xs, space_xl, ..
+        } = AppletMenu::spacing(applet);
+
+        applet
+            .other_sessions
+            .iter()
+            .fold(
+                cosmic::widget::list_column().padding([0., 0.]),
+                |list, session| {
+                    let session_row = row![
+                        cosmic::widget::icon::from_name("system-users-symbolic").size(16),
+                        text(fl!("switch-to-user", user = session.user.clone()))
+                            .width(Length::Fill),
+                    ]
+                    .spacing(space_xs)
+                    .align_y(Alignment::Center);
+
+                    list.add(
+                        cosmic::widget::button::custom(session_row)
+                            .on_press(Message::ActivateSession(session.id.clone()))
+                            .class(cosmic::theme::Button::MenuItem)
+                            .width(Length::Fill)
+                            .height(space_xl),
+                    )
+                },
+            )
             .into()
     }
 
@@ -2,10 +2,21 @@
 
 use crate::fl;
 use cosmic::{
-    cosmic_config::{self, cosmic_config_derive::CosmicConfigEntry, Config, CosmicConfigEntry},
+    cosmic_config::{
+        self, cosmic_config_derive::CosmicConfigEntry, Config, ConfigGet, CosmicConfigEntry,
+    },
+    cosmic_theme::Spacing,
     Application,
 };
+use cosmic_classic_menu_core::launch;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+pub use cosmic_classic_menu_core::categories::CategoryOrder;
+pub use cosmic_classic_menu_core::favorites::FavoriteFolder;
+pub use cosmic_classic_menu_core::ranking::SearchMode;
+pub use cosmic_classic_menu_core::recents::{unix_timestamp, RecentApplication, RecentsOrder};
 
 #[derive(Debug, Clone, CosmicConfigEntry, Eq, PartialEq)]
 #[version = 1]
@@ -13,11 +24,110 @@ use serde::{Deserialize, Serialize};
 pub struct CosmicClassicMenuConfig {
     pub app_menu_position: HorizontalPosition,
     pub search_field_position: VerticalPosition,
+    /// Focus the search field when the menu opens, so typing searches right away.
+    pub focus_search_on_open: bool,
     pub applet_button_style: AppletButtonStyle,
     pub user_widget: UserWidgetStyle,
     pub button_label: String,
+    /// Longest label (in characters) shown on the applet button, `0` doesn't limit it.
+    pub button_label_max_length: u16,
     pub button_icon: String,
-    pub recent_applications: Vec<RecentApplication>,
+    pub button_icon_source: AppletButtonIcon,
+    pub launch_count_half_life_days: u32,
+    /// How the Recently Used apps are ordered.
+    pub recents_order: RecentsOrder,
+    /// App ids never recorded as recently used, `*` and `?` matching like in file names.
+    pub recents_exclusions: Vec<String>,
+    pub menu_layout: MenuLayout,
+    pub list_layout: ListLayoutSettings,
+    pub wide_layout: WideLayoutSettings,
+    pub grid_layout: GridLayoutSettings,
+    /// Layouts used instead of `menu_layout` on specific outputs, by output name.
+    pub output_layouts: BTreeMap<String, MenuLayout>,
+    /// When to use the compact popup with only the search field and an icon grid.
+    pub minimal_popup: MinimalPopup,
+    /// Whether the categories are shown next to the apps or replace them when opened.
+    pub category_navigation: CategoryNavigation,
+    /// Whether the apps are listed by their name, their generic name or both.
+    pub name_display: NameDisplay,
+    /// Show the comment of the apps under their names, single-line rows without it.
+    pub show_comments: bool,
+    /// Leave out apps whose `TryExec` program, or absolute `Exec` program, isn't installed.
+    pub hide_uninstalled_apps: bool,
+    /// Spacing, row heights and text sizes of the whole popup.
+    pub density: MenuDensity,
+    /// Icon theme of the menu, the system one when empty.
+    pub icon_theme: String,
+    /// Show the default web browser, email client, file manager and terminal at the top.
+    pub show_default_apps: bool,
+    /// Show switches for desktop settings, like dark mode, above the power options.
+    pub show_quick_toggles: bool,
+    pub show_power_options: bool,
+    /// Show text labels under the power icons where the popup is wide enough.
+    pub show_power_labels: bool,
+    /// Group search results under headers of their primary category.
+    pub group_search_results: bool,
+    /// How app names and the other search results are matched against the query.
+    pub search_mode: SearchMode,
+    /// Number of search results shown before the "Show all" row, `0` shows them all.
+    pub search_results_limit: u32,
+    /// Offer emoji and special characters matching the search, copied on activation.
+    pub search_characters: bool,
+    /// Offer connecting to the hosts of `~/.ssh/config` matching the search.
+    pub search_ssh_hosts: bool,
+    /// Offer the Firefox and Chromium bookmarks matching the search.
+    pub search_bookmarks: bool,
+    /// Offer the COSMIC Settings pages matching the search.
+    pub search_settings_panels: bool,
+    /// Offer the recently used documents matching the search.
+    pub search_recent_documents: bool,
+    /// Executables queried with every search, `~/` expanding to the home directory. They
+    /// receive the query on stdin and print a JSON array of results with a `title`, an
+    /// optional `icon` and the `command` run when it's chosen.
+    pub search_scripts: Vec<String>,
+    /// URL the `?` search prefix opens, with `%s` replaced by the query.
+    pub web_search_url: String,
+    /// Keep the last few copied snippets in memory and list them in the menu.
+    pub clipboard_history: bool,
+    /// List the open windows in their own category, focusing the one that's clicked.
+    pub show_windows: bool,
+    /// Focus the windows of a running app in turn instead of launching it again.
+    pub activate_running_apps: bool,
+    /// Larger hit targets and long-press for the app actions, for touch screens.
+    pub touch_mode: bool,
+    /// Launch apps with a double click, a single click only selects them.
+    pub double_click_launch: bool,
+    /// How the categories are ordered.
+    pub category_sort: CategoryOrder,
+    /// Manual order of the categories by id, sorted by their localized names when empty.
+    pub category_order: Vec<String>,
+    /// List the categories no installed app belongs to as well.
+    pub show_empty_categories: bool,
+    /// Take the categories from the distro's applications menu when it configures one,
+    /// instead of the built-in ones.
+    pub use_applications_menu: bool,
+    /// Icon names shown instead of the default ones, by category id.
+    pub category_icons: BTreeMap<String, String>,
+    /// Categories listed in the applet's right-click menu by id, opening the menu on them.
+    pub context_menu_categories: Vec<String>,
+    /// Entries of the applet's right-click menu, in order. Tools that aren't installed are
+    /// left out.
+    pub context_menu_entries: Vec<ContextMenuEntry>,
+    /// Directories scanned for desktop files after the XDG data directories, `~/` expanding
+    /// to the home directory.
+    pub extra_application_dirs: Vec<String>,
+    /// Extra environment variables and arguments applied when launching an app, by app id.
+    pub launch_overrides: BTreeMap<String, LaunchOverride>,
+    /// Launch every app in a transient systemd scope of its own.
+    pub launch_in_scope: bool,
+    /// Desktop ids of the pinned applications, in display order.
+    pub favorites: Vec<String>,
+    /// Desktop ids of the system-wide favorites the user unpinned.
+    pub removed_favorites: Vec<String>,
+    /// Named groups of favorites, listed in the favorites instead of their apps.
+    pub favorite_folders: Vec<FavoriteFolder>,
+    /// Whether the favorites were already seeded with commonly used apps on first run.
+    pub favorites_seeded: bool,
 }
 
 impl Default for CosmicClassicMenuConfig {
@@ -25,27 +135,196 @@ impl Default for CosmicClassicMenuConfig {
         CosmicClassicMenuConfig {
             app_menu_position: HorizontalPosition::default(),
             search_field_position: VerticalPosition::default(),
+            focus_search_on_open: true,
             applet_button_style: AppletButtonStyle::default(),
             user_widget: UserWidgetStyle::default(),
             button_label: fl!("menu-label").to_owned(),
+            button_label_max_length: 20,
             button_icon: format!("/usr/share/cosmic/{}/applet-buttons/default.svg", crate::applet::CosmicClassicMenu::APP_ID).to_owned(),
-            recent_applications: vec![],
+            button_icon_source: AppletButtonIcon::default(),
+            launch_count_half_life_days: 30,
+            recents_order: RecentsOrder::default(),
+            recents_exclusions: vec![],
+            menu_layout: MenuLayout::default(),
+            list_layout: ListLayoutSettings::default(),
+            wide_layout: WideLayoutSettings::default(),
+            grid_layout: GridLayoutSettings::default(),
+            output_layouts: BTreeMap::new(),
+            minimal_popup: MinimalPopup::default(),
+            category_navigation: CategoryNavigation::default(),
+            name_display: NameDisplay::default(),
+            show_comments: true,
+            hide_uninstalled_apps: true,
+            density: MenuDensity::default(),
+            icon_theme: String::new(),
+            show_default_apps: false,
+            show_quick_toggles: false,
+            show_power_options: true,
+            show_power_labels: false,
+            group_search_results: false,
+            search_mode: SearchMode::default(),
+            search_results_limit: 20,
+            search_characters: false,
+            search_ssh_hosts: true,
+            search_bookmarks: false,
+            search_settings_panels: true,
+            search_recent_documents: true,
+            search_scripts: vec![],
+            web_search_url: "https://duckduckgo.com/?q=%s".to_string(),
+            clipboard_history: false,
+            show_windows: false,
+            activate_running_apps: false,
+            touch_mode: false,
+            double_click_launch: false,
+            category_sort: CategoryOrder::default(),
+            category_order: vec![],
+            show_empty_categories: false,
+            use_applications_menu: true,
+            category_icons: BTreeMap::new(),
+            context_menu_categories: vec![],
+            context_menu_entries: ContextMenuEntry::DEFAULT.to_vec(),
+            extra_application_dirs: vec![],
+            launch_overrides: BTreeMap::new(),
+            launch_in_scope: false,
+            favorites: vec![],
+            removed_favorites: vec![],
+            favorite_folders: vec![],
+            favorites_seeded: false,
         }
     }
 }
 
 impl CosmicClassicMenuConfig {
     pub fn config_handler() -> Option<Config> {
-        Config::new(crate::applet::CosmicClassicMenu::APP_ID, 1).ok()
+        Self::try_config_handler().ok()
+    }
+
+    /// Returns the config handler, or why the config can't be opened.
+    pub fn try_config_handler() -> crate::error::Result<Config> {
+        Ok(Config::new(crate::applet::CosmicClassicMenu::APP_ID, 1)?)
     }
 
+    /// Returns the effective configuration, which is resolved in this order of precedence:
+    ///
+    /// 1. values enforced by the administrator's [`SystemPolicy`],
+    /// 2. values set by the user,
+    /// 3. system-wide defaults shipped in the cosmic-config system scope,
+    /// 4. the built-in defaults.
+    ///
+    /// The favorites are the exception, see [`Self::merged_favorites`].
     pub fn config() -> CosmicClassicMenuConfig {
+        SystemPolicy::policy().enforce(Self::user_config())
+    }
+
+    /// Returns the configuration set by the user, without the system policy applied.
+    pub fn user_config() -> CosmicClassicMenuConfig {
         match Self::config_handler() {
             Some(config_handler) => CosmicClassicMenuConfig::get_entry(&config_handler)
                 .unwrap_or_else(|(_errs, config)| config),
             None => CosmicClassicMenuConfig::default(),
         }
     }
+
+    /// Returns the path of the button icon image, with a leading `~` standing for the home
+    /// directory.
+    pub fn button_icon_path(&self) -> PathBuf {
+        match (
+            self.button_icon.strip_prefix("~/"),
+            std::env::var_os("HOME"),
+        ) {
+            (Some(relative), Some(home)) => PathBuf::from(home).join(relative),
+            _ => PathBuf::from(&self.button_icon),
+        }
+    }
+
+    /// Returns the pinned applications: the user's favorites followed by the system-wide
+    /// ones they don't have yet, so vendor entries added later still show up.
+    pub fn merged_favorites(&self) -> Vec<String> {
+        let system_favorites = Config::system(crate::applet::CosmicClassicMenu::APP_ID, 1)
+            .ok()
+            .and_then(|config| config.get::<Vec<String>>("favorites").ok())
+            .unwrap_or_default();
+
+        cosmic_classic_menu_core::favorites::merge(
+            &self.favorites,
+            &system_favorites,
+            &self.removed_favorites,
+        )
+    }
+}
+
+/// Settings enforced by the administrator, read from the cosmic-config system scope
+/// (e.g. `/usr/share/cosmic/com.championpeak87.cosmic-classic-menu.policy/v1/`).
+///
+/// Enforced values take precedence over the user configuration, unset ones leave it untouched.
+#[derive(Debug, Clone, CosmicConfigEntry, Default, Eq, PartialEq)]
+#[version = 1]
+#[id = "cosmic-classic-menu-policy"]
+pub struct SystemPolicy {
+    pub hide_power_options: bool,
+    pub menu_layout: Option<MenuLayout>,
+    pub applet_button_style: Option<AppletButtonStyle>,
+    pub user_widget: Option<UserWidgetStyle>,
+    /// Desktop ids of the apps always pinned first among the favorites.
+    pub pinned_apps: Vec<String>,
+}
+
+impl SystemPolicy {
+    pub const POLICY_ID: &'static str = "com.championpeak87.cosmic-classic-menu.policy";
+
+    pub fn policy() -> SystemPolicy {
+        match Config::system(Self::POLICY_ID, 1) {
+            Ok(config_handler) => {
+                SystemPolicy::get_entry(&config_handler).unwrap_or_else(|(_errs, policy)| policy)
+            }
+            Err(_) => SystemPolicy::default(),
+        }
+    }
+
+    /// Applies the enforced settings on top of the user configuration.
+    pub fn enforce(&self, mut config: CosmicClassicMenuConfig) -> CosmicClassicMenuConfig {
+        if self.hide_power_options {
+            config.show_power_options = false;
+        }
+        // The layouts of the outputs would override the enforced one
+        if let Some(menu_layout) = self.menu_layout {
+            config.menu_layout = menu_layout;
+            config.output_layouts.clear();
+        }
+        if let Some(applet_button_style) = self.applet_button_style {
+            config.applet_button_style = applet_button_style;
+        }
+        if let Some(user_widget) = self.user_widget {
+            config.user_widget = user_widget;
+        }
+        self.pin_apps(&mut config);
+        config
+    }
+
+    /// Puts the enforced apps first among the favorites, where they can't be unpinned.
+    pub fn pin_apps(&self, config: &mut CosmicClassicMenuConfig) {
+        if self.pinned_apps.is_empty() {
+            return;
+        }
+        let mut favorites = self.pinned_apps.clone();
+        favorites.extend(self.user_favorites(&config.favorites));
+        config.favorites = favorites;
+        config.removed_favorites.retain(|id| !self.is_pinned(id));
+    }
+
+    /// Returns whether the app is pinned by the administrator.
+    pub fn is_pinned(&self, app_id: &str) -> bool {
+        self.pinned_apps.iter().any(|id| id == app_id)
+    }
+
+    /// Returns the favorites to store for the user, without the enforced ones.
+    pub fn user_favorites(&self, favorites: &[String]) -> Vec<String> {
+        favorites
+            .iter()
+            .filter(|id| !self.is_pinned(id))
+            .cloned()
+            .collect()
+    }
 }
 
 #[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
@@ -102,8 +381,250 @@ impl Default for VerticalPosition {
     }
 }
 
-#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
-pub struct RecentApplication {
-    pub app_id: String,
-    pub launch_count: u32,
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+pub enum MenuLayout {
+    /// Single column application list next to the categories pane.
+    Classic,
+    /// Double-width popup with the application list split into columns.
+    Wide,
+    /// Application icons arranged in a grid next to the categories pane.
+    Grid,
+}
+
+impl Default for MenuLayout {
+    fn default() -> Self {
+        MenuLayout::Classic
+    }
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+pub enum AppletButtonIcon {
+    /// The image at `button_icon`.
+    Custom,
+    /// The logo of the distribution, named by `LOGO=` in os-release.
+    DistroLogo,
+}
+
+impl Default for AppletButtonIcon {
+    fn default() -> Self {
+        AppletButtonIcon::Custom
+    }
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+pub enum MinimalPopup {
+    /// Use the minimal popup on outputs too short for the full menu.
+    Automatic,
+    Always,
+    Never,
+}
+
+impl Default for MinimalPopup {
+    fn default() -> Self {
+        MinimalPopup::Automatic
+    }
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+pub enum NameDisplay {
+    /// The name of the app, e.g. "Firefox".
+    Name,
+    /// The generic name of the app, e.g. "Web Browser", or its name if it has none.
+    GenericName,
+    /// Both, e.g. "Web Browser — Firefox".
+    GenericNameAndName,
+}
+
+impl Default for NameDisplay {
+    fn default() -> Self {
+        NameDisplay::Name
+    }
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+pub enum CategoryNavigation {
+    /// Categories are listed in a pane next to the apps.
+    SidePane,
+    /// Categories are listed on their own, opening one replaces them with its apps.
+    DrillDown,
+    /// All apps are listed at once, under a header for each category.
+    FlatList,
+}
+
+impl Default for CategoryNavigation {
+    fn default() -> Self {
+        CategoryNavigation::SidePane
+    }
+}
+
+/// Icon size and row height of the app list, picked from the spacing tokens that
+/// [`MenuDensity`] scales, so both apply on top of each other.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+pub enum ListDensity {
+    Comfortable,
+    Compact,
+}
+
+impl Default for ListDensity {
+    fn default() -> Self {
+        ListDensity::Comfortable
+    }
+}
+
+/// Scales the spacing tokens of the theme, and with them everything sized by them, including
+/// the rows sized by the [`ListDensity`] of the layout.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub enum MenuDensity {
+    /// The theme's own spacing.
+    #[default]
+    Comfortable,
+    Cozy,
+    Compact,
+}
+
+impl MenuDensity {
+    /// Returns the theme's spacing scaled to the density.
+    pub fn spacing(self, spacing: Spacing) -> Spacing {
+        let factor = match self {
+            MenuDensity::Comfortable => 1.0,
+            MenuDensity::Cozy => 0.75,
+            MenuDensity::Compact => 0.5,
+        };
+        let scale = |space: u16| (f32::from(space) * factor).round() as u16;
+        Spacing {
+            space_none: spacing.space_none,
+            space_xxxs: scale(spacing.space_xxxs),
+            space_xxs: scale(spacing.space_xxs),
+            space_xs: scale(spacing.space_xs),
+            space_s: scale(spacing.space_s),
+            space_m: scale(spacing.space_m),
+            space_l: scale(spacing.space_l),
+            space_xl: scale(spacing.space_xl),
+            space_xxl: scale(spacing.space_xxl),
+            space_xxxl: scale(spacing.space_xxxl),
+        }
+    }
+
+    /// Returns the text size scaled to the density, less than the spacing to stay legible.
+    pub fn text_size(self, size: f32) -> f32 {
+        match self {
+            MenuDensity::Comfortable => size,
+            MenuDensity::Cozy => size * 0.93,
+            MenuDensity::Compact => size * 0.86,
+        }
+    }
+}
+
+/// Entry of the applet's right-click menu.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+pub enum ContextMenuEntry {
+    /// The most used settings, changed in place.
+    MenuSettings,
+    AppletSettings,
+    CreateLauncher,
+    ShowHiddenEntries,
+    SystemSettings,
+    SystemMonitor,
+    DiskManagement,
+    Separator,
+}
+
+impl ContextMenuEntry {
+    /// Every entry, in the order they're offered to be added.
+    pub const ALL: [ContextMenuEntry; 8] = [
+        ContextMenuEntry::MenuSettings,
+        ContextMenuEntry::AppletSettings,
+        ContextMenuEntry::CreateLauncher,
+        ContextMenuEntry::ShowHiddenEntries,
+        ContextMenuEntry::SystemSettings,
+        ContextMenuEntry::SystemMonitor,
+        ContextMenuEntry::DiskManagement,
+        ContextMenuEntry::Separator,
+    ];
+
+    pub const DEFAULT: [ContextMenuEntry; 8] = [
+        ContextMenuEntry::MenuSettings,
+        ContextMenuEntry::AppletSettings,
+        ContextMenuEntry::CreateLauncher,
+        ContextMenuEntry::ShowHiddenEntries,
+        ContextMenuEntry::Separator,
+        ContextMenuEntry::SystemSettings,
+        ContextMenuEntry::SystemMonitor,
+        ContextMenuEntry::DiskManagement,
+    ];
+}
+
+// Each layout keeps its own settings, so switching layouts doesn't reset their tuning.
+
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct ListLayoutSettings {
+    pub density: ListDensity,
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+pub struct WideLayoutSettings {
+    pub columns: u8,
+    pub density: ListDensity,
+}
+
+impl Default for WideLayoutSettings {
+    fn default() -> Self {
+        WideLayoutSettings {
+            columns: 2,
+            density: ListDensity::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GridLayoutSettings {
+    /// Number of columns, `0` fits as many as the popup width allows.
+    pub columns: u8,
+    /// Size of the app icons in logical pixels.
+    pub icon_size: u16,
+    /// Split the apps into pages flipped horizontally instead of one long scroll.
+    pub paginated: bool,
+}
+
+impl Default for GridLayoutSettings {
+    fn default() -> Self {
+        GridLayoutSettings {
+            columns: 4,
+            icon_size: 32,
+            paginated: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct LaunchOverride {
+    /// Environment variables set for the app, e.g. `MOZ_ENABLE_WAYLAND=1`.
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+    /// Arguments added to the app's command line, e.g. `--ozone-platform=wayland`.
+    #[serde(default)]
+    pub args: Vec<String>,
+}
+
+impl LaunchOverride {
+    /// Inserts the extra arguments into an `Exec` command line, ahead of its field codes
+    /// and the `@@` markers of Flatpak's forwarded files, so they aren't mistaken for the
+    /// files or URLs the app is opened with.
+    pub fn apply_args(&self, exec: &str) -> String {
+        if self.args.is_empty() {
+            return exec.to_string();
+        }
+
+        let mut args = launch::exec_args(exec);
+        let insert_at = args
+            .iter()
+            .position(|arg| launch::has_field_code(arg) || arg.starts_with("@@"))
+            .unwrap_or(args.len());
+        args.splice(
+            insert_at..insert_at,
+            self.args.iter().map(|arg| launch::escape_field_codes(arg)),
+        );
+        launch::join_exec(&args)
+    }
 }
@@ -0,0 +1,13 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use zbus::proxy;
+
+/// Seat of the display manager, at the path in `XDG_SEAT_PATH`.
+#[proxy(
+    interface = "org.freedesktop.DisplayManager.Seat",
+    default_service = "org.freedesktop.DisplayManager"
+)]
+pub trait DisplayManagerSeat {
+    /// Shows the greeter, keeping the current session running in the background.
+    fn switch_to_greeter(&self) -> zbus::Result<()>;
+}
@@ -0,0 +1,40 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use std::fmt;
+
+use cosmic::cosmic_config;
+
+use crate::fl;
+
+/// Failures the applet recovers from, shown to the user in a banner inside the popup.
+#[derive(Debug)]
+pub enum Error {
+    /// The configuration couldn't be opened or written.
+    Config(cosmic_config::Error),
+    /// The usage data couldn't be written to the state directory.
+    State(String),
+    /// The desktop entry of the named app has no command line to launch.
+    MissingExec(String),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Config(e) => write!(f, "{}", fl!("error-config", error = e.to_string())),
+            Error::State(e) => write!(f, "{}", fl!("error-state", error = e.clone())),
+            Error::MissingExec(name) => {
+                write!(f, "{}", fl!("error-missing-exec", name = name.clone()))
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<cosmic_config::Error> for Error {
+    fn from(e: cosmic_config::Error) -> Self {
+        Error::Config(e)
+    }
+}
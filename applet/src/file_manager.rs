@@ -0,0 +1,13 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use zbus::proxy;
+
+#[proxy(
+    interface = "org.freedesktop.FileManager1",
+    default_service = "org.freedesktop.FileManager1",
+    default_path = "/org/freedesktop/FileManager1"
+)]
+pub trait FileManager1 {
+    /// Opens the parent folders of the given URIs with the items selected.
+    fn show_items(&self, uris: &[&str], startup_id: &str) -> zbus::Result<()>;
+}
@@ -11,11 +11,60 @@ use rust_embed::RustEmbed;
 
 /// Applies the requested language(s) to requested translations from the `fl!()` macro.
 pub fn init(requested_languages: &[LanguageIdentifier]) {
-    if let Err(why) = localizer().select(&requested_languages) {
+    if let Err(why) = localizer().select(&with_language_fallbacks(requested_languages)) {
         eprintln!("error while loading fluent localizations: {why}");
     }
 }
 
+/// Inserts the language-only variant after each regional language (e.g. `de-AT` → `de`),
+/// the loader's fallback language (`en`) being the last resort.
+fn with_language_fallbacks(requested_languages: &[LanguageIdentifier]) -> Vec<LanguageIdentifier> {
+    let mut languages: Vec<LanguageIdentifier> = Vec::with_capacity(requested_languages.len() * 2);
+    for language in requested_languages {
+        let language_only = LanguageIdentifier::from_parts(language.language, None, None, &[]);
+        for candidate in [language.clone(), language_only] {
+            if !languages.contains(&candidate) {
+                languages.push(candidate);
+            }
+        }
+    }
+    languages
+}
+
+/// Returns the locales used for desktop entry lookups in priority order, each regional
+/// locale followed by its language (e.g. `de_AT` → `de`).
+///
+/// Desktop entries fall back to their unlocalized (English) keys when none of them match.
+pub fn locale_fallback_chain() -> Vec<String> {
+    // LANGUAGE is a colon separated priority list which takes precedence over the locale
+    let mut candidates: Vec<String> = std::env::var("LANGUAGE")
+        .map(|languages| languages.split(':').map(str::to_string).collect())
+        .unwrap_or_default();
+    if let Some(locale) = ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .iter()
+        .find_map(|var| std::env::var(var).ok().filter(|value| !value.is_empty()))
+    {
+        candidates.push(locale);
+    }
+
+    let mut chain: Vec<String> = Vec::new();
+    for candidate in &candidates {
+        // Strip the codeset and modifier, e.g. `de_AT.UTF-8@euro`
+        let locale = candidate.split(['.', '@']).next().unwrap_or_default();
+        if locale.is_empty() || locale == "C" || locale == "POSIX" {
+            continue;
+        }
+
+        let language = locale.split('_').next().unwrap_or(locale);
+        for entry in [locale, language] {
+            if !chain.iter().any(|known| known == entry) {
+                chain.push(entry.to_string());
+            }
+        }
+    }
+    chain
+}
+
 // Get the `Localizer` to be used for localizing this library.
 #[must_use]
 pub fn localizer() -> Box<dyn Localizer> {
@@ -0,0 +1,142 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Single-instance enforcement through a well-known session bus name per panel and output.
+
+use std::hash::Hash;
+use std::sync::Mutex;
+
+use cosmic::iced::{stream, Subscription};
+use cosmic::iced_futures::futures::{self, SinkExt};
+use once_cell::sync::OnceCell;
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
+use zbus::fdo::{RequestNameFlags, RequestNameReply};
+use zbus::{interface, proxy};
+
+const BUS_NAME_PREFIX: &str = "com.championpeak87.CosmicClassicMenu";
+const OBJECT_PATH: &str = "/com/championpeak87/CosmicClassicMenu";
+
+/// Connection owning the bus name, kept alive for the lifetime of the process.
+static CONNECTION: OnceCell<zbus::blocking::Connection> = OnceCell::new();
+
+/// Activations forwarded by other instances, consumed by [`activations`].
+static ACTIVATIONS: Mutex<Option<UnboundedReceiver<()>>> = Mutex::new(None);
+
+struct MenuInstance {
+    activations: UnboundedSender<()>,
+}
+
+#[interface(name = "com.championpeak87.CosmicClassicMenu")]
+impl MenuInstance {
+    fn activate(&self) {
+        _ = self.activations.send(());
+    }
+}
+
+#[proxy(
+    interface = "com.championpeak87.CosmicClassicMenu",
+    default_path = "/com/championpeak87/CosmicClassicMenu"
+)]
+trait RunningInstance {
+    fn activate(&self) -> zbus::Result<()>;
+}
+
+/// Returns the bus name of this panel instance, e.g.
+/// `com.championpeak87.CosmicClassicMenu.Panel.DP_1`. The panel runs an instance per output,
+/// so the output is part of it.
+fn bus_name() -> String {
+    ["COSMIC_PANEL_NAME", "COSMIC_PANEL_OUTPUT"]
+        .into_iter()
+        .filter_map(|variable| bus_name_element(&std::env::var(variable).unwrap_or_default()))
+        .fold(BUS_NAME_PREFIX.to_string(), |bus_name, element| {
+            format!("{}.{}", bus_name, element)
+        })
+}
+
+/// Returns the value as an element of a bus name, which may only hold ASCII letters, digits
+/// and underscores and can't start with a digit.
+fn bus_name_element(value: &str) -> Option<String> {
+    let element: String = value
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+
+    match element.chars().next() {
+        None => None,
+        Some(c) if c.is_ascii_digit() => Some(format!("_{}", element)),
+        Some(_) => Some(element),
+    }
+}
+
+/// Claims the bus name of this panel instance.
+///
+/// Returns `false` after forwarding the activation when another instance already owns it.
+/// Without a session bus the applet runs unrestricted.
+pub fn claim() -> bool {
+    let bus_name = bus_name();
+    let (tx, rx) = unbounded_channel();
+
+    let result = (|| -> zbus::Result<RequestNameReply> {
+        let connection = zbus::blocking::Connection::session()?;
+        connection
+            .object_server()
+            .at(OBJECT_PATH, MenuInstance { activations: tx })?;
+        let reply = connection
+            .request_name_with_flags(bus_name.as_str(), RequestNameFlags::DoNotQueue.into())?;
+        if reply == RequestNameReply::PrimaryOwner {
+            _ = CONNECTION.set(connection);
+        }
+        Ok(reply)
+    })();
+
+    match result {
+        Ok(RequestNameReply::PrimaryOwner) => {
+            *ACTIVATIONS.lock().unwrap() = Some(rx);
+            true
+        }
+        Ok(_) => {
+            println!("{} is already running, forwarding activation", bus_name);
+            if let Err(e) = forward_activation(&bus_name) {
+                eprintln!("Failed to activate the running instance: {}", e);
+            }
+            false
+        }
+        Err(e) => {
+            eprintln!("Failed to claim {}: {}", bus_name, e);
+            true
+        }
+    }
+}
+
+/// Releases the bus name so a new instance can claim it right away.
+pub fn release() {
+    if let Some(connection) = CONNECTION.get() {
+        if let Err(e) = connection.release_name(bus_name()) {
+            eprintln!("Failed to release the bus name: {}", e);
+        }
+    }
+}
+
+fn forward_activation(bus_name: &str) -> zbus::Result<()> {
+    let connection = zbus::blocking::Connection::session()?;
+    let instance = RunningInstanceProxyBlocking::builder(&connection)
+        .destination(bus_name)?
+        .build()?;
+    instance.activate()
+}
+
+/// Emits an event whenever another instance forwards its activation.
+pub fn activations<I: 'static + Hash + Copy + Send + Sync>(id: I) -> Subscription<()> {
+    Subscription::run_with_id(
+        id,
+        stream::channel(4, move |mut output| async move {
+            let receiver = ACTIVATIONS.lock().unwrap().take();
+            if let Some(mut receiver) = receiver {
+                while receiver.recv().await.is_some() {
+                    _ = output.send(()).await;
+                }
+            }
+
+            futures::future::pending().await
+        }),
+    )
+}
@@ -1,10 +1,17 @@
 pub mod applet;
 pub mod i18n;
+pub mod instance;
 pub mod config;
+pub mod error;
 pub mod logic;
 pub mod power_options;
 pub mod cosmic_session;
+pub mod display_manager;
+pub mod file_manager;
+pub mod malcontent;
+pub mod notifications;
 pub mod session_manager;
+pub mod switcheroo_control;
 pub mod applet_button;
 pub mod applet_menu;
 pub mod model;
\ No newline at end of file
@@ -0,0 +1,209 @@
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+use freedesktop_desktop_entry::IconSource;
+use serde::{Deserialize, Serialize};
+
+use crate::model::application_entry::{ApplicationEntry, ApplicationSource};
+
+/// Version of the index format, indexes of other versions are written again.
+const INDEX_VERSION: u32 = 1;
+
+/// Parsed desktop entries saved in the cache directory, so the applet doesn't have to
+/// parse every desktop file again after a restart.
+#[derive(Serialize, Deserialize)]
+struct AppIndex {
+    /// Format of the index, `0` for indexes written before it was versioned.
+    #[serde(default)]
+    version: u32,
+    /// Locales the names were resolved for, the index is stale in any other locale.
+    locales: Vec<String>,
+    /// Whether the comments were read, the index is stale once they're shown again.
+    #[serde(default)]
+    comments: bool,
+    /// Whether the generic names were read, the index is stale once they're shown again.
+    #[serde(default)]
+    generic_names: bool,
+    /// Modification times of the application directories and the desktop files in them.
+    files: Vec<(PathBuf, Option<SystemTime>)>,
+    apps: Vec<IndexedApp>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct IndexedApp {
+    name: String,
+    generic_name: Option<String>,
+    id: String,
+    icon_name: Option<String>,
+    icon_path: Option<PathBuf>,
+    comment: Option<String>,
+    exec: Option<String>,
+    category: Vec<String>,
+    is_terminal: bool,
+    is_flatpak: bool,
+    prefers_dgpu: bool,
+    path: Option<PathBuf>,
+    is_hidden: bool,
+    try_exec: Option<String>,
+}
+
+impl From<&ApplicationEntry> for IndexedApp {
+    fn from(app: &ApplicationEntry) -> Self {
+        let (icon_name, icon_path) = match &app.icon {
+            IconSource::Name(name) => (Some(name.clone()), None),
+            IconSource::Path(path) => (None, Some(path.clone())),
+        };
+        IndexedApp {
+            name: app.name.clone(),
+            generic_name: app.generic_name.clone(),
+            id: app.id.clone(),
+            icon_name,
+            icon_path,
+            comment: app.comment.clone(),
+            exec: app.exec.clone(),
+            category: app.category.clone(),
+            is_terminal: app.is_terminal,
+            is_flatpak: app.source == ApplicationSource::Flatpak,
+            prefers_dgpu: app.prefers_dgpu,
+            path: app.path.clone(),
+            is_hidden: app.is_hidden,
+            try_exec: app.try_exec.clone(),
+        }
+    }
+}
+
+impl From<IndexedApp> for ApplicationEntry {
+    fn from(app: IndexedApp) -> Self {
+        let icon = match (app.icon_name, app.icon_path) {
+            (_, Some(path)) => IconSource::Path(path),
+            (name, None) => IconSource::Name(name.unwrap_or_default()),
+        };
+        ApplicationEntry {
+            name: app.name,
+            generic_name: app.generic_name,
+            id: app.id,
+            icon,
+            comment: app.comment,
+            exec: app.exec,
+            category: app.category,
+            is_terminal: app.is_terminal,
+            source: if app.is_flatpak {
+                ApplicationSource::Flatpak
+            } else {
+                ApplicationSource::Native
+            },
+            prefers_dgpu: app.prefers_dgpu,
+            path: app.path,
+            is_hidden: app.is_hidden,
+            try_exec: app.try_exec,
+            // Programs come and go without their desktop files, they're looked up again
+            is_installed: true,
+        }
+    }
+}
+
+/// Returns the applet's directory in `$XDG_CACHE_HOME`.
+pub fn cache_dir() -> Option<PathBuf> {
+    let cache_home = std::env::var_os("XDG_CACHE_HOME")
+        .map(PathBuf::from)
+        .filter(|path| path.is_absolute())
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".cache")))?;
+    Some(cache_home.join(crate::applet::APP_ID))
+}
+
+fn index_path() -> Option<PathBuf> {
+    cache_dir().map(|dir| dir.join("apps.ron"))
+}
+
+/// Returns the indexed applications, or `None` when there's no index or a desktop file
+/// changed since it was written.
+pub fn load(
+    locales: &[String],
+    comments: bool,
+    generic_names: bool,
+) -> Option<Vec<ApplicationEntry>> {
+    let contents = std::fs::read_to_string(index_path()?).ok()?;
+    let index: AppIndex = match ron::from_str(&contents) {
+        Ok(index) => index,
+        Err(e) => {
+            eprintln!("Failed to read the application index: {}", e);
+            return None;
+        }
+    };
+
+    if index.version != INDEX_VERSION
+        || index.locales != locales
+        || index.comments != comments
+        || index.generic_names != generic_names
+        || index.files != snapshot()
+    {
+        return None;
+    }
+    Some(index.apps.into_iter().map(Into::into).collect())
+}
+
+/// Writes the applications to the index, replacing the previous one.
+pub fn save<'a>(
+    locales: &[String],
+    comments: bool,
+    generic_names: bool,
+    apps: impl Iterator<Item = &'a ApplicationEntry>,
+) {
+    let Some(path) = index_path() else {
+        return;
+    };
+    let index = AppIndex {
+        version: INDEX_VERSION,
+        locales: locales.to_vec(),
+        comments,
+        generic_names,
+        files: snapshot(),
+        apps: apps.map(Into::into).collect(),
+    };
+
+    // Written next to the index first, so a crash never leaves half of it behind
+    let temporary_path = path.with_extension("ron.tmp");
+    let result = ron::to_string(&index)
+        .map_err(|e| e.to_string())
+        .and_then(|contents| {
+            std::fs::create_dir_all(path.parent().unwrap_or(&path))
+                .and_then(|_| std::fs::write(&temporary_path, contents))
+                .and_then(|_| std::fs::rename(&temporary_path, &path))
+                .map_err(|e| e.to_string())
+        });
+    if let Err(e) = result {
+        eprintln!("Failed to write the application index: {}", e);
+    }
+}
+
+/// Returns the modification times of the application directories and the desktop files
+/// in them, sorted by path.
+fn snapshot() -> Vec<(PathBuf, Option<SystemTime>)> {
+    let mut files = vec![];
+    let mut dirs = super::apps::application_dirs();
+    while let Some(dir) = dirs.pop() {
+        let modified = std::fs::metadata(&dir)
+            .and_then(|metadata| metadata.modified())
+            .ok();
+        files.push((dir.clone(), modified));
+
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                dirs.push(path);
+            } else if path.extension().is_some_and(|ext| ext == "desktop") {
+                let modified = entry
+                    .metadata()
+                    .and_then(|metadata| metadata.modified())
+                    .ok();
+                files.push((path, modified));
+            }
+        }
+    }
+
+    files.sort();
+    files
+}
@@ -1,136 +1,516 @@
 use crate::{
-    config::{CosmicClassicMenuConfig, RecentApplication},
+    config::{unix_timestamp, CosmicClassicMenuConfig, FavoriteFolder, RecentApplication},
     fl,
+    logic::{
+        app_index,
+        parental_controls::ParentalControls,
+        xdg_dirs,
+        xdg_menu::{self, MenuCategory, Rule},
+    },
     model::application_entry::ApplicationEntry,
 };
-use std::{collections::HashMap, fmt::Display, string::String};
+use std::{
+    borrow::Cow,
+    collections::{HashMap, HashSet},
+    fmt::Display,
+    path::{Path, PathBuf},
+    string::String,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        RwLock,
+    },
+};
 
-use fuzzy_matcher::{skim::SkimMatcherV2, FuzzyMatcher};
+use cosmic_classic_menu_core::{categories, favorites, ranking, recents};
 use serde::{Deserialize, Serialize};
 
+use cosmic::desktop::DesktopEntryData;
 use cosmic::{
     iced::{stream, Subscription},
     iced_futures::futures::{self, SinkExt},
 };
+use freedesktop_desktop_entry::{DesktopEntry, IconSource};
 use notify::{Config, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use once_cell::sync::Lazy;
 use std::fmt::Debug;
 use std::hash::Hash;
 use tokio::sync::mpsc;
 
+/// Parsed desktop entries by application id, `None` until the first load.
+static APPS_CACHE: Lazy<RwLock<Option<HashMap<String, ApplicationEntry>>>> =
+    Lazy::new(|| RwLock::new(None));
+/// App filter of the parental controls, `None` until it's first fetched.
+static PARENTAL_CONTROLS: Lazy<RwLock<Option<ParentalControls>>> = Lazy::new(|| RwLock::new(None));
+/// Extra directories desktop files are loaded from, read from the config until the applet
+/// sets them.
+static EXTRA_APPLICATION_DIRS: Lazy<RwLock<Vec<String>>> =
+    Lazy::new(|| RwLock::new(CosmicClassicMenuConfig::config().extra_application_dirs));
+/// Whether NoDisplay and Hidden entries are listed too, for debugging desktop files.
+static SHOW_HIDDEN: AtomicBool = AtomicBool::new(false);
+/// Whether the comments of the entries are read, they're left out while they aren't shown.
+static LOAD_COMMENTS: AtomicBool = AtomicBool::new(true);
+/// Whether the generic names of the entries are read, only while the names show them.
+static LOAD_GENERIC_NAMES: AtomicBool = AtomicBool::new(false);
+/// Whether apps whose program isn't installed are left out.
+static HIDE_UNINSTALLED: AtomicBool = AtomicBool::new(true);
+
 pub struct Apps;
 
 impl Apps {
     pub async fn load_apps() -> Vec<ApplicationEntry> {
-        println!("Loading applications...");
-        let locale = std::env::var("LANG")
-            .ok()
-            .and_then(|l| l.split(".").next().map(str::to_string));
-        let mut all_entries: Vec<ApplicationEntry> =
-            cosmic::desktop::load_applications(locale.as_slice(), false, None)
-                .into_iter()
-                .map(Into::into)
-                .collect();
+        let parental_controls = Self::parental_controls().await;
+        let show_hidden = SHOW_HIDDEN.load(Ordering::Relaxed);
+        // The programs of the host aren't visible from within a Flatpak sandbox
+        let hide_uninstalled =
+            HIDE_UNINSTALLED.load(Ordering::Relaxed) && std::env::var("FLATPAK_ID").is_err();
+        // Reading the desktop files or the index blocks until they're parsed
+        let mut all_entries: Vec<ApplicationEntry> = tokio::task::spawn_blocking(Self::cached_apps)
+            .await
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|app| show_hidden || !app.is_hidden)
+            .filter(|app| !hide_uninstalled || app.is_installed)
+            .filter(|app| parental_controls.is_allowed(app))
+            .collect();
         all_entries.sort_by(|a, b| a.name.cmp(&b.name));
 
         all_entries
     }
 
-    pub async fn load_filtered_apps(filter: String) -> Vec<ApplicationEntry> {
-        let matcher: SkimMatcherV2 = SkimMatcherV2::default();
-        let mut search_result: Vec<(Option<i64>, ApplicationEntry)> = Self::load_apps()
-            .await
-            .into_iter()
-            .map(|app| (matcher.fuzzy_match(&app.name, &filter), app))
-            .filter(|app| app.0.is_some())
-            .collect();
+    /// Returns the app filter of the parental controls, fetched over D-Bus only on first use.
+    async fn parental_controls() -> ParentalControls {
+        let cached = PARENTAL_CONTROLS.read().unwrap().clone();
+        match cached {
+            Some(parental_controls) => parental_controls,
+            None => {
+                let parental_controls = ParentalControls::load().await;
+                Self::set_parental_controls(parental_controls.clone());
+                parental_controls
+            }
+        }
+    }
+
+    /// Filters the loaded apps with the changed app filter of the parental controls.
+    pub fn set_parental_controls(parental_controls: ParentalControls) {
+        *PARENTAL_CONTROLS.write().unwrap() = Some(parental_controls);
+    }
 
-        search_result.sort_by(|a, b| b.0.cmp(&a.0));
+    fn cached_apps() -> Vec<ApplicationEntry> {
+        if let Some(cache) = APPS_CACHE.read().unwrap().as_ref() {
+            return cache.values().cloned().collect();
+        }
+
+        let locales = crate::i18n::locale_fallback_chain();
+        let comments = Self::loads_comments();
+        let generic_names = Self::loads_generic_names();
+        let index = app_index::load(&locales, comments, generic_names);
+        let cache: HashMap<String, ApplicationEntry> = match index {
+            Some(apps) => apps
+                .into_iter()
+                .map(|mut app| {
+                    app.is_installed = app.resolve_installed();
+                    (app.id.clone(), app)
+                })
+                .collect(),
+            None => {
+                println!("Loading applications...");
+                // The first file of an id wins, even when it hides the application
+                let mut seen_ids = HashSet::new();
+                let cache: HashMap<String, ApplicationEntry> =
+                    freedesktop_desktop_entry::Iter::new(application_dirs().into_iter())
+                        .filter(|path| path.extension().is_some_and(|ext| ext == "desktop"))
+                        .filter(|path| {
+                            path.file_stem()
+                                .and_then(|stem| stem.to_str())
+                                .is_some_and(|id| seen_ids.insert(id.to_string()))
+                        })
+                        .filter_map(|path| Self::load_entry(&locales, &path))
+                        .map(|app| (app.id.clone(), app))
+                        .collect();
+                app_index::save(&locales, comments, generic_names, cache.values());
+                cache
+            }
+        };
+        let apps = cache.values().cloned().collect();
+        *APPS_CACHE.write().unwrap() = Some(cache);
 
-        search_result.into_iter().map(|(_, app)| app).collect()
+        apps
     }
 
-    pub async fn load_app_categories() -> Vec<ApplicationCategory> {
-        use std::collections::HashSet;
+    /// Returns the icon of the app with the desktop id, falling back to an icon named
+    /// like the id.
+    pub fn icon_of(app_id: &str) -> IconSource {
+        let cache = APPS_CACHE.read().unwrap();
+        cache
+            .as_ref()
+            .and_then(|apps| {
+                // Some apps report their id in a different case than their desktop file
+                apps.get(app_id).or_else(|| {
+                    apps.values()
+                        .find(|app| app.id.eq_ignore_ascii_case(app_id))
+                })
+            })
+            .map(|app| app.icon.clone())
+            .unwrap_or_else(|| IconSource::Name(app_id.to_string()))
+    }
 
-        println!("Loading app categories...");
-        let all_apps = Self::load_apps().await;
-        let mut used_categories = HashSet::new();
-        for app in &all_apps {
-            for cat in &app.category {
-                used_categories.insert(cat);
+    /// Returns how many apps are listed, `0` until they're first loaded.
+    pub fn listed_count() -> usize {
+        APPS_CACHE
+            .read()
+            .unwrap()
+            .as_ref()
+            .map_or(0, |apps| apps.values().filter(|app| !app.is_hidden).count())
+    }
+
+    /// Lists the NoDisplay and Hidden entries until it's turned off again.
+    pub fn set_show_hidden(show_hidden: bool) {
+        SHOW_HIDDEN.store(show_hidden, Ordering::Relaxed);
+    }
+
+    /// Reads the comments of the entries from now on, or stops reading them. The entries
+    /// parsed before are dropped when it changes.
+    pub fn set_load_comments(load_comments: bool) {
+        if LOAD_COMMENTS.swap(load_comments, Ordering::Relaxed) != load_comments {
+            Self::invalidate_cache();
+        }
+    }
+
+    /// Reads the generic names of the entries from now on, or stops reading them. The entries
+    /// parsed before are dropped when it changes.
+    pub fn set_load_generic_names(load_generic_names: bool) {
+        if LOAD_GENERIC_NAMES.swap(load_generic_names, Ordering::Relaxed) != load_generic_names {
+            Self::invalidate_cache();
+        }
+    }
+
+    /// Leaves out the apps whose program isn't installed, or lists them too. Looked up when
+    /// the entries are read, on start and when the application directories change.
+    pub fn set_hide_uninstalled(hide_uninstalled: bool) {
+        HIDE_UNINSTALLED.store(hide_uninstalled, Ordering::Relaxed);
+    }
+
+    /// Loads desktop files from these extra directories too, from the next full load on.
+    pub fn set_extra_application_dirs(extra_application_dirs: Vec<String>) {
+        *EXTRA_APPLICATION_DIRS.write().unwrap() = extra_application_dirs;
+    }
+
+    pub fn loads_comments() -> bool {
+        LOAD_COMMENTS.load(Ordering::Relaxed)
+    }
+
+    pub fn loads_generic_names() -> bool {
+        LOAD_GENERIC_NAMES.load(Ordering::Relaxed)
+    }
+
+    /// Drops all parsed entries and the app filter, so the next load re-reads every desktop
+    /// file and fetches the filter again.
+    pub fn invalidate_cache() {
+        *APPS_CACHE.write().unwrap() = None;
+        *PARENTAL_CONTROLS.write().unwrap() = None;
+        xdg_menu::invalidate_cache();
+    }
+
+    /// Re-parses only the given desktop files and patches the result into the cache.
+    ///
+    /// Each file is resolved against the data directories in order of precedence, so
+    /// removing a user override brings back the system entry it shadowed. Returns the icons
+    /// of the entries replaced and of the ones read, their images may have changed too.
+    pub fn reload_entries(paths: &[PathBuf]) -> Vec<IconSource> {
+        let mut cache = APPS_CACHE.write().unwrap();
+        let Some(cache) = cache.as_mut() else {
+            // Nothing to patch, the next load reads everything anyway
+            return Vec::new();
+        };
+
+        let mut changed_icons = Vec::new();
+        let locales = crate::i18n::locale_fallback_chain();
+        let data_dirs = application_dirs();
+        for path in paths {
+            println!("Reloading desktop entry: {}", path.display());
+            let relative_path = data_dirs
+                .iter()
+                .find_map(|dir| path.strip_prefix(dir).ok())
+                .map(Path::to_path_buf);
+            let candidates: Vec<PathBuf> = match relative_path {
+                Some(relative_path) => data_dirs
+                    .iter()
+                    .map(|dir| dir.join(&relative_path))
+                    .collect(),
+                None => vec![path.clone()],
+            };
+
+            cache.retain(|_, app| {
+                let is_changed = app
+                    .path
+                    .as_ref()
+                    .is_some_and(|app_path| candidates.contains(app_path));
+                if is_changed {
+                    changed_icons.push(app.icon.clone());
+                }
+                !is_changed
+            });
+            // The first existing file wins, even when it hides the application
+            if let Some(candidate) = candidates.iter().find(|candidate| candidate.exists()) {
+                if let Some(app) = Self::load_entry(&locales, candidate) {
+                    changed_icons.push(app.icon.clone());
+                    cache.insert(app.id.clone(), app);
+                }
             }
         }
+        app_index::save(
+            &locales,
+            Self::loads_comments(),
+            Self::loads_generic_names(),
+            cache.values(),
+        );
+        changed_icons
+    }
+
+    /// Parses a single desktop file, marking entries that shouldn't be displayed as hidden.
+    fn load_entry(locales: &[String], path: &Path) -> Option<ApplicationEntry> {
+        let entry = match DesktopEntry::from_path(path, Some(locales)) {
+            Ok(entry) => entry,
+            // Listed with the other problems in the diagnostics of the settings
+            Err(e) => {
+                eprintln!(
+                    "Skipping unreadable desktop entry {}: {}",
+                    path.display(),
+                    e
+                );
+                return None;
+            }
+        };
+        let is_hidden = entry.no_display() || entry.hidden();
+        let try_exec = entry.desktop_entry("TryExec").map(str::to_string);
+        let generic_name = Self::loads_generic_names()
+            .then(|| entry.generic_name(locales).map(Cow::into_owned))
+            .flatten();
+
+        let mut app: ApplicationEntry =
+            DesktopEntryData::from_desktop_entry(locales, path.to_path_buf(), entry).into();
+        // Apps repeating their name as the generic name don't have a meaningful one
+        app.generic_name = generic_name
+            .filter(|generic_name| !generic_name.is_empty() && *generic_name != app.name);
+        app.is_hidden = is_hidden;
+        app.try_exec = try_exec;
+        app.is_installed = app.resolve_installed();
+        Some(app)
+    }
 
-        // Všechny možné kategorie
-        const APPS_CATEGORIES: &[ApplicationCategory] = &[
-            ApplicationCategory::AUDIO,
-            ApplicationCategory::VIDEO,
-            ApplicationCategory::DEVELOPMENT,
-            ApplicationCategory::GAMES,
-            ApplicationCategory::GRAPHICS,
-            ApplicationCategory::NETWORK,
-            ApplicationCategory::OFFICE,
-            ApplicationCategory::SCIENCE,
-            ApplicationCategory::SETTINGS,
-            ApplicationCategory::SYSTEM,
-            ApplicationCategory::UTILITY,
-        ];
+    pub async fn load_filtered_apps(
+        filter: String,
+        config: CosmicClassicMenuConfig,
+    ) -> Vec<ApplicationEntry> {
+        // Matching the listed names keeps the highlighted characters in line with the ranking
+        let search_result = ranking::rank_with(
+            config.search_mode,
+            Self::load_apps().await,
+            &filter,
+            |app| app.display_name(config.name_display).into_owned(),
+        );
 
+        if config.group_search_results {
+            let app_categories = Self::app_categories(&config);
+            ranking::group_by(search_result, |app| {
+                ApplicationCategory::primary_of(&app_categories, app)
+            })
+        } else {
+            search_result
+        }
+    }
+
+    pub async fn load_app_categories(config: CosmicClassicMenuConfig) -> Vec<ApplicationCategory> {
+        println!("Loading app categories...");
+        let all_apps = Self::load_apps().await;
         // Vyberte pouze ty, které jsou použité
-        let mut categories = Vec::with_capacity(2 + APPS_CATEGORIES.len());
+        let mut app_categories: Vec<ApplicationCategory> = Self::app_categories(&config)
+            .into_iter()
+            .filter(|category| {
+                config.show_empty_categories || all_apps.iter().any(|app| category.includes(app))
+            })
+            .collect();
+        Self::sort_categories(&mut app_categories, &all_apps, &config);
+
+        // All applications, recently used, favorites and windows stay pinned to the top
+        let mut categories = Vec::with_capacity(4 + app_categories.len());
         categories.push(ApplicationCategory::ALL);
         categories.push(ApplicationCategory::RECENTLY_USED);
-        for cat in APPS_CATEGORIES {
-            if !cat.mime_name.is_empty() && used_categories.contains(&cat.mime_name.to_string()) {
-                categories.push(cat.clone());
-            }
+        if !config.merged_favorites().is_empty() || !config.favorite_folders.is_empty() {
+            categories.push(ApplicationCategory::FAVORITES);
         }
+        if config.show_windows {
+            categories.push(ApplicationCategory::WINDOWS);
+        }
+        categories.extend(app_categories);
         categories
     }
 
-    pub async fn get_recent_applications() -> Vec<ApplicationEntry> {
+    /// Returns the categories of the distro's applications menu, or the built-in ones when
+    /// it doesn't configure one or it's turned off.
+    fn app_categories(config: &CosmicClassicMenuConfig) -> Vec<ApplicationCategory> {
+        if !config.use_applications_menu {
+            return ApplicationCategory::APPS_CATEGORIES.to_vec();
+        }
+        let menu = xdg_menu::load(&crate::i18n::locale_fallback_chain());
+        if menu.is_empty() {
+            return ApplicationCategory::APPS_CATEGORIES.to_vec();
+        }
+        menu.into_iter().map(ApplicationCategory::from).collect()
+    }
+
+    /// Sorts categories the way the config orders them.
+    ///
+    /// Categories missing from the manual order follow the ordered ones alphabetically.
+    fn sort_categories(
+        categories: &mut [ApplicationCategory],
+        apps: &[ApplicationEntry],
+        config: &CosmicClassicMenuConfig,
+    ) {
+        categories::sort_by_order(
+            categories,
+            config.category_sort,
+            &config.category_order,
+            ApplicationCategory::id,
+            ApplicationCategory::get_display_name,
+            |category| apps.iter().filter(|app| category.includes(app)).count(),
+        );
+    }
+
+    /// Returns the installed apps of the usage records, ranked like the settings ask.
+    pub async fn get_recent_applications(
+        recent_applications: Vec<RecentApplication>,
+        config: &CosmicClassicMenuConfig,
+    ) -> Vec<ApplicationEntry> {
         println!("Loading recent applications...");
-        let recent_applications: &Vec<RecentApplication> =
-            &CosmicClassicMenuConfig::config().recent_applications;
         let all_applications_entries: HashMap<String, ApplicationEntry> = Self::load_apps()
             .await
             .into_iter()
             .map(|app| (app.id.clone(), app))
             .collect();
 
-        // recent_applications.sort_by(|a, b| b.launch_count.cmp(&a.launch_count));
-        recent_applications
+        recents::ranked_by(
+            &recent_applications,
+            config.recents_order,
+            unix_timestamp(),
+            config.launch_count_half_life_days,
+        )
+        .into_iter()
+        .filter_map(|app_id| all_applications_entries.get(app_id).cloned())
+        .collect()
+    }
+
+    /// Returns the favorites which aren't in a folder.
+    pub async fn get_favorite_applications(
+        config: &CosmicClassicMenuConfig,
+    ) -> Vec<ApplicationEntry> {
+        println!("Loading favorite applications...");
+        let mut all_applications_entries: HashMap<String, ApplicationEntry> = Self::load_apps()
+            .await
+            .into_iter()
+            .map(|app| (app.id.clone(), app))
+            .collect();
+
+        config
+            .merged_favorites()
             .iter()
-            .filter_map(|app| all_applications_entries.get(&app.app_id).cloned())
+            .filter(|app_id| !favorites::is_in_folder(&config.favorite_folders, app_id))
+            .filter_map(|app_id| all_applications_entries.remove(app_id))
+            .collect()
+    }
+
+    /// Returns the folders of favorites with their installed apps.
+    pub async fn get_favorite_folders(
+        folders: Vec<FavoriteFolder>,
+    ) -> Vec<(String, Vec<ApplicationEntry>)> {
+        let all_applications_entries: HashMap<String, ApplicationEntry> = Self::load_apps()
+            .await
+            .into_iter()
+            .map(|app| (app.id.clone(), app))
+            .collect();
+
+        folders
+            .into_iter()
+            .map(|folder| {
+                let apps = folder
+                    .apps
+                    .iter()
+                    .filter_map(|app_id| all_applications_entries.get(app_id).cloned())
+                    .collect();
+                (folder.name, apps)
+            })
             .collect()
     }
 
-    pub async fn get_apps_of_category(category: ApplicationCategory) -> Vec<ApplicationEntry> {
+    /// Returns the apps listed in the category, using the usage records for the recently used
+    /// ones so they match what the applet shows elsewhere.
+    pub async fn get_apps_of_category(
+        category: ApplicationCategory,
+        recent_applications: Vec<RecentApplication>,
+        config: CosmicClassicMenuConfig,
+    ) -> Vec<ApplicationEntry> {
         println!("Getting apps of category: {}", category.mime_name);
         if category == ApplicationCategory::ALL {
             Self::load_apps().await
         } else if category == ApplicationCategory::RECENTLY_USED {
-            Self::get_recent_applications().await
+            Self::get_recent_applications(recent_applications, &config).await
+        } else if category == ApplicationCategory::FAVORITES {
+            Self::get_favorite_applications(&config).await
+        } else if category == ApplicationCategory::WINDOWS {
+            // The open windows are listed instead
+            Vec::new()
         } else {
             Self::load_apps()
                 .await
                 .into_iter()
-                .filter(|app| app.category.contains(&category.mime_name.to_string()))
+                .filter(|app| category.includes(app))
                 .collect()
         }
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+/// Returns the directories desktop files are loaded from, most important first.
+///
+/// These are the `applications` directories of `XDG_DATA_HOME` and `XDG_DATA_DIRS`, followed
+/// by the extra directories from the config.
+pub fn application_dirs() -> Vec<PathBuf> {
+    application_dirs_with(&EXTRA_APPLICATION_DIRS.read().unwrap())
+}
+
+/// Returns the directories desktop files are loaded from with the given extra directories.
+pub fn application_dirs_with(extra_application_dirs: &[String]) -> Vec<PathBuf> {
+    let home = std::env::var_os("HOME").map(PathBuf::from);
+    let mut dirs: Vec<PathBuf> = xdg_dirs::data_dirs()
+        .into_iter()
+        .map(|dir| dir.join("applications"))
+        .collect();
+    for dir in extra_application_dirs {
+        let dir = match (dir.strip_prefix("~/"), &home) {
+            (Some(relative), Some(home)) => home.join(relative),
+            _ => PathBuf::from(dir),
+        };
+        if dir.is_absolute() && !dirs.contains(&dir) {
+            dirs.push(dir);
+        }
+    }
+    dirs
+}
+
+#[derive(Debug, Clone)]
 pub enum Event {
+    /// The data directories changed in a way that requires reading all desktop files again.
     Changed,
+    /// Only these desktop files were created, modified or removed.
+    EntriesChanged(Vec<PathBuf>),
 }
 
+/// Watches the directories for changed desktop files, starting over when they change.
 pub fn desktop_files<I: 'static + Hash + Copy + Send + Sync + Debug>(
     id: I,
+    dirs: Vec<PathBuf>,
 ) -> cosmic::iced::Subscription<Event> {
     Subscription::run_with_id(
-        id,
+        (id, dirs.clone()),
         stream::channel(50, move |mut output| async move {
             let handle = tokio::runtime::Handle::current();
             let (tx, mut rx) = mpsc::channel(4);
@@ -143,9 +523,22 @@ pub fn desktop_files<I: 'static + Hash + Copy + Send + Sync + Debug>(
                     if let Ok(event) = res {
                         match event.kind {
                             EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_) => {
+                                let is_desktop_file = |path: &PathBuf| {
+                                    path.extension().is_some_and(|ext| ext == "desktop")
+                                };
+                                // Single entries are cheap to patch, anything else triggers a
+                                // debounced full reload
+                                if !event.paths.is_empty()
+                                    && event.paths.iter().all(is_desktop_file)
+                                {
+                                    _ = handle
+                                        .block_on(tx.send(Event::EntriesChanged(event.paths)));
+                                    return;
+                                }
+
                                 let now = std::time::Instant::now();
                                 if now.duration_since(last_update).as_secs() > 3 {
-                                    _ = handle.block_on(tx.send(()));
+                                    _ = handle.block_on(tx.send(Event::Changed));
                                     last_update = now;
                                 }
                             }
@@ -158,12 +551,12 @@ pub fn desktop_files<I: 'static + Hash + Copy + Send + Sync + Debug>(
             );
 
             if let Ok(mut watcher) = watcher {
-                for path in freedesktop_desktop_entry::default_paths() {
-                    let _ = watcher.watch(path.as_ref(), RecursiveMode::Recursive);
+                for path in dirs {
+                    let _ = watcher.watch(&path, RecursiveMode::Recursive);
                 }
 
-                while rx.recv().await.is_some() {
-                    _ = output.send(Event::Changed).await;
+                while let Some(event) = rx.recv().await {
+                    _ = output.send(event).await;
                 }
             }
 
@@ -210,104 +603,206 @@ pub struct User {
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct ApplicationCategory {
-    pub display_name: &'static str,
+    pub display_name: Cow<'static, str>,
     pub icon_svg_bytes: &'static [u8],
-    pub mime_name: &'static str,
+    pub mime_name: Cow<'static, str>,
+    /// Themed icon from the menu's `.directory` file, shown instead of the bundled one.
+    pub icon_name: Option<String>,
+    /// Inclusion rules from the menu file, matching `mime_name` otherwise.
+    pub rule: Option<Rule>,
 }
 
 impl ApplicationCategory {
+    /// Všechny možné kategorie
+    pub const APPS_CATEGORIES: &[ApplicationCategory] = &[
+        ApplicationCategory::AUDIO,
+        ApplicationCategory::VIDEO,
+        ApplicationCategory::DEVELOPMENT,
+        ApplicationCategory::GAMES,
+        ApplicationCategory::GRAPHICS,
+        ApplicationCategory::NETWORK,
+        ApplicationCategory::OFFICE,
+        ApplicationCategory::SCIENCE,
+        ApplicationCategory::SETTINGS,
+        ApplicationCategory::SYSTEM,
+        ApplicationCategory::UTILITY,
+    ];
+
     pub const ALL: ApplicationCategory = ApplicationCategory {
-        display_name: "all-applications",
+        display_name: Cow::Borrowed("all-applications"),
         icon_svg_bytes: include_bytes!("../../../res/icons/bundled/open-menu-symbolic.svg"),
-        mime_name: "",
+        mime_name: Cow::Borrowed(""),
+        icon_name: None,
+        rule: None,
     };
     pub const RECENTLY_USED: ApplicationCategory = ApplicationCategory {
-        display_name: "recently-used",
+        display_name: Cow::Borrowed("recently-used"),
         icon_svg_bytes: include_bytes!(
             "../../../res/icons/bundled/document-open-recent-symbolic.svg"
         ),
-        mime_name: "",
+        mime_name: Cow::Borrowed(""),
+        icon_name: None,
+        rule: None,
+    };
+    pub const FAVORITES: ApplicationCategory = ApplicationCategory {
+        display_name: Cow::Borrowed("favorites"),
+        icon_svg_bytes: include_bytes!("../../../res/icons/bundled/starred-symbolic.svg"),
+        mime_name: Cow::Borrowed(""),
+        icon_name: None,
+        rule: None,
+    };
+    pub const WINDOWS: ApplicationCategory = ApplicationCategory {
+        display_name: Cow::Borrowed("windows"),
+        icon_svg_bytes: include_bytes!("../../../res/icons/bundled/focus-windows-symbolic.svg"),
+        mime_name: Cow::Borrowed(""),
+        icon_name: None,
+        rule: None,
     };
     pub const AUDIO: ApplicationCategory = ApplicationCategory {
-        display_name: "audio",
+        display_name: Cow::Borrowed("audio"),
         icon_svg_bytes: include_bytes!(
             "../../../res/icons/bundled/applications-audio-symbolic.svg"
         ),
-        mime_name: "Audio",
+        mime_name: Cow::Borrowed("Audio"),
+        icon_name: None,
+        rule: None,
     };
     pub const VIDEO: ApplicationCategory = ApplicationCategory {
-        display_name: "video",
+        display_name: Cow::Borrowed("video"),
         icon_svg_bytes: include_bytes!(
             "../../../res/icons/bundled/applications-video-symbolic.svg"
         ),
-        mime_name: "Video",
+        mime_name: Cow::Borrowed("Video"),
+        icon_name: None,
+        rule: None,
     };
     pub const DEVELOPMENT: ApplicationCategory = ApplicationCategory {
-        display_name: "development",
+        display_name: Cow::Borrowed("development"),
         icon_svg_bytes: include_bytes!(
             "../../../res/icons/bundled/applications-engineering-symbolic.svg"
         ),
-        mime_name: "Development",
+        mime_name: Cow::Borrowed("Development"),
+        icon_name: None,
+        rule: None,
     };
     pub const GAMES: ApplicationCategory = ApplicationCategory {
-        display_name: "games",
+        display_name: Cow::Borrowed("games"),
         icon_svg_bytes: include_bytes!(
             "../../../res/icons/bundled/applications-games-symbolic.svg"
         ),
-        mime_name: "Game",
+        mime_name: Cow::Borrowed("Game"),
+        icon_name: None,
+        rule: None,
     };
     pub const GRAPHICS: ApplicationCategory = ApplicationCategory {
-        display_name: "graphics",
+        display_name: Cow::Borrowed("graphics"),
         icon_svg_bytes: include_bytes!(
             "../../../res/icons/bundled/applications-graphics-symbolic.svg"
         ),
-        mime_name: "Graphics",
+        mime_name: Cow::Borrowed("Graphics"),
+        icon_name: None,
+        rule: None,
     };
     pub const NETWORK: ApplicationCategory = ApplicationCategory {
-        display_name: "network",
+        display_name: Cow::Borrowed("network"),
         icon_svg_bytes: include_bytes!("../../../res/icons/bundled/network-workgroup-symbolic.svg"),
-        mime_name: "Network",
+        mime_name: Cow::Borrowed("Network"),
+        icon_name: None,
+        rule: None,
     };
     pub const OFFICE: ApplicationCategory = ApplicationCategory {
-        display_name: "office",
+        display_name: Cow::Borrowed("office"),
         icon_svg_bytes: include_bytes!(
             "../../../res/icons/bundled/applications-office-symbolic.svg"
         ),
-        mime_name: "Office",
+        mime_name: Cow::Borrowed("Office"),
+        icon_name: None,
+        rule: None,
     };
     pub const SCIENCE: ApplicationCategory = ApplicationCategory {
-        display_name: "science",
+        display_name: Cow::Borrowed("science"),
         icon_svg_bytes: include_bytes!(
             "../../../res/icons/bundled/applications-science-symbolic.svg"
         ),
-        mime_name: "Science",
+        mime_name: Cow::Borrowed("Science"),
+        icon_name: None,
+        rule: None,
     };
     pub const SETTINGS: ApplicationCategory = ApplicationCategory {
-        display_name: "settings",
+        display_name: Cow::Borrowed("settings"),
         icon_svg_bytes: include_bytes!(
             "../../../res/icons/bundled/preferences-system-symbolic.svg"
         ),
-        mime_name: "Settings",
+        mime_name: Cow::Borrowed("Settings"),
+        icon_name: None,
+        rule: None,
     };
     pub const SYSTEM: ApplicationCategory = ApplicationCategory {
-        display_name: "system",
+        display_name: Cow::Borrowed("system"),
         icon_svg_bytes: include_bytes!(
             "../../../res/icons/bundled/applications-system-symbolic.svg"
         ),
-        mime_name: "System",
+        mime_name: Cow::Borrowed("System"),
+        icon_name: None,
+        rule: None,
     };
     pub const UTILITY: ApplicationCategory = ApplicationCategory {
-        display_name: "utility",
+        display_name: Cow::Borrowed("utility"),
         icon_svg_bytes: include_bytes!(
             "../../../res/icons/bundled/applications-utilities-symbolic.svg"
         ),
-        mime_name: "Utility",
+        mime_name: Cow::Borrowed("Utility"),
+        icon_name: None,
+        rule: None,
     };
 
+    /// Returns the first of the categories that includes the app.
+    pub fn primary_of(
+        categories: &[ApplicationCategory],
+        app: &ApplicationEntry,
+    ) -> Option<ApplicationCategory> {
+        categories
+            .iter()
+            .find(|category| category.includes(app))
+            .cloned()
+    }
+
+    /// Returns the id of the category in the config, the name of its submenu for the
+    /// categories of the applications menu.
+    pub fn id(&self) -> &str {
+        match self.rule {
+            Some(_) => &self.mime_name,
+            None => &self.display_name,
+        }
+    }
+
+    /// Returns the icon name configured for the category, or else the one of its submenu.
+    ///
+    /// The bundled icon is used when neither is set.
+    pub fn get_icon_name<'a>(&'a self, config: &'a CosmicClassicMenuConfig) -> Option<&'a str> {
+        config
+            .category_icons
+            .get(self.id())
+            .or(self.icon_name.as_ref())
+            .map(String::as_str)
+    }
+
+    /// Returns whether the app is listed in this category.
+    pub fn includes(&self, app: &ApplicationEntry) -> bool {
+        match &self.rule {
+            Some(rule) => rule.matches(app),
+            None => {
+                !self.mime_name.is_empty() && categories::contains(&app.category, &self.mime_name)
+            }
+        }
+    }
+
     pub fn get_display_name(&self) -> String {
-        match self.display_name {
+        match self.display_name.as_ref() {
             "all-applications" => fl!("all-applications"),
             "recently-used" => fl!("recently-used"),
+            "favorites" => fl!("favorites"),
+            "windows" => fl!("windows"),
             "audio" => fl!("audio"),
             "video" => fl!("video"),
             "development" => fl!("development"),
@@ -324,6 +819,18 @@ impl ApplicationCategory {
     }
 }
 
+impl From<MenuCategory> for ApplicationCategory {
+    fn from(category: MenuCategory) -> Self {
+        ApplicationCategory {
+            display_name: Cow::Owned(category.name),
+            icon_svg_bytes: ApplicationCategory::ALL.icon_svg_bytes,
+            mime_name: Cow::Owned(category.id),
+            icon_name: category.icon,
+            rule: Some(category.rule),
+        }
+    }
+}
+
 impl Display for ApplicationCategory {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}", self.mime_name)
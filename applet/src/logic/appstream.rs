@@ -0,0 +1,183 @@
+use std::path::{Path, PathBuf};
+
+use tokio::process::Command;
+
+/// Details of an app from its AppStream metadata.
+#[derive(Clone, Debug, Default)]
+pub struct AppDetails {
+    /// Paragraphs of the description, list items prefixed with a bullet.
+    pub description: Vec<String>,
+    /// Locally cached thumbnail of the default screenshot.
+    pub screenshot: Option<PathBuf>,
+}
+
+/// Loads the details of the app from the metainfo file it installed, if any.
+///
+/// The screenshot is downloaded with `curl` into the cache directory once, and skipped
+/// when that fails or `curl` isn't installed.
+pub async fn load_details(app_id: String) -> Option<AppDetails> {
+    let contents = metainfo_paths(&app_id)
+        .iter()
+        .find_map(|path| std::fs::read_to_string(path).ok())?;
+    let (description, screenshot_url) = parse_metainfo(&contents)?;
+
+    let screenshot = match screenshot_url {
+        Some(url) => cached_screenshot(&app_id, &url).await,
+        None => None,
+    };
+
+    Some(AppDetails {
+        description,
+        screenshot,
+    })
+}
+
+/// Returns the paths the metainfo file of the app may be installed at.
+fn metainfo_paths(app_id: &str) -> Vec<PathBuf> {
+    let file_names = [
+        format!("metainfo/{}.metainfo.xml", app_id),
+        format!("metainfo/{}.appdata.xml", app_id),
+        format!("appdata/{}.appdata.xml", app_id),
+    ];
+
+    // Flatpak only exports the desktop files, the metadata stays inside of the app
+    let mut data_dirs: Vec<PathBuf> = freedesktop_desktop_entry::default_paths()
+        .filter_map(|applications_dir| applications_dir.parent().map(Path::to_path_buf))
+        .collect();
+    let flatpak_file_dirs = [
+        std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/share/flatpak")),
+        Some(PathBuf::from("/var/lib/flatpak")),
+    ];
+    for flatpak_dir in flatpak_file_dirs.into_iter().flatten() {
+        data_dirs.push(flatpak_dir.join(format!("app/{}/current/active/files/share", app_id)));
+    }
+
+    data_dirs
+        .iter()
+        .flat_map(|dir| file_names.iter().map(move |file_name| dir.join(file_name)))
+        .collect()
+}
+
+/// Returns the untranslated description paragraphs and the URL of the default screenshot's
+/// thumbnail, or of its source image when it has no thumbnails.
+fn parse_metainfo(contents: &str) -> Option<(Vec<String>, Option<String>)> {
+    let document = roxmltree::Document::parse(contents).ok()?;
+    let component = document.root_element();
+    let is_untranslated = |node: &roxmltree::Node| {
+        node.attribute(("http://www.w3.org/XML/1998/namespace", "lang"))
+            .is_none()
+    };
+    let text_of = |node: roxmltree::Node| -> String {
+        node.descendants()
+            .filter(|descendant| descendant.is_text())
+            .filter_map(|descendant| descendant.text())
+            .collect::<String>()
+            .split_whitespace()
+            .collect::<Vec<&str>>()
+            .join(" ")
+    };
+
+    let description = component
+        .children()
+        .find(|node| node.has_tag_name("description") && is_untranslated(node))
+        .map(|description| {
+            description
+                .descendants()
+                .filter(|node| node.has_tag_name("p") || node.has_tag_name("li"))
+                .filter(is_untranslated)
+                .map(|node| match node.tag_name().name() {
+                    "li" => format!("• {}", text_of(node)),
+                    _ => text_of(node),
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let screenshots: Vec<roxmltree::Node> = component
+        .descendants()
+        .filter(|node| node.has_tag_name("screenshot"))
+        .collect();
+    let screenshot = screenshots
+        .iter()
+        .find(|screenshot| screenshot.attribute("type") == Some("default"))
+        .or(screenshots.first());
+    let screenshot_url = screenshot.and_then(|screenshot| {
+        let images: Vec<roxmltree::Node> = screenshot
+            .children()
+            .filter(|node| node.has_tag_name("image"))
+            .collect();
+        images
+            .iter()
+            .find(|image| image.attribute("type") == Some("thumbnail"))
+            .or(images.first())
+            .and_then(|image| image.text())
+            .map(|url| url.trim().to_string())
+    });
+
+    Some((description, screenshot_url))
+}
+
+/// Extensions of the image formats screenshots are cached in.
+const SCREENSHOT_EXTENSIONS: [&str; 4] = ["png", "jpg", "webp", "gif"];
+
+/// Returns the screenshot from the cache, downloading it the first time.
+///
+/// It's downloaded next to its final path and only moved there once it's complete, named
+/// after the format of its contents since URLs don't always end with an extension.
+async fn cached_screenshot(app_id: &str, url: &str) -> Option<PathBuf> {
+    let cache_dir = super::app_index::cache_dir()?.join("screenshots");
+    if let Some(path) = SCREENSHOT_EXTENSIONS
+        .iter()
+        .map(|extension| cache_dir.join(format!("{}.{}", app_id, extension)))
+        .find(|path| path.is_file())
+    {
+        return Some(path);
+    }
+
+    tokio::fs::create_dir_all(&cache_dir).await.ok()?;
+    let download_path = cache_dir.join(format!("{}.{}.part", app_id, std::process::id()));
+    let status = Command::new("curl")
+        .args([
+            "--silent",
+            "--fail",
+            "--location",
+            "--max-time",
+            "10",
+            "--output",
+        ])
+        .arg(&download_path)
+        .arg(url)
+        .status()
+        .await;
+    let downloaded = match status {
+        Ok(status) if status.success() => tokio::fs::read(&download_path).await.ok(),
+        _ => None,
+    };
+    let Some(extension) = downloaded.as_deref().and_then(image_extension) else {
+        eprintln!("Failed to download the screenshot of {}", app_id);
+        _ = tokio::fs::remove_file(&download_path).await;
+        return None;
+    };
+
+    let path = cache_dir.join(format!("{}.{}", app_id, extension));
+    match tokio::fs::rename(&download_path, &path).await {
+        Ok(()) => Some(path),
+        Err(e) => {
+            eprintln!("Failed to cache the screenshot of {}: {}", app_id, e);
+            _ = tokio::fs::remove_file(&download_path).await;
+            None
+        }
+    }
+}
+
+/// Returns the extension of the image format the contents start with, if it's one of
+/// [`SCREENSHOT_EXTENSIONS`].
+fn image_extension(contents: &[u8]) -> Option<&'static str> {
+    match contents {
+        [0x89, b'P', b'N', b'G', ..] => Some("png"),
+        [0xFF, 0xD8, 0xFF, ..] => Some("jpg"),
+        [b'R', b'I', b'F', b'F', _, _, _, _, b'W', b'E', b'B', b'P', ..] => Some("webp"),
+        [b'G', b'I', b'F', b'8', ..] => Some("gif"),
+        _ => None,
+    }
+}
@@ -0,0 +1,148 @@
+use std::path::{Path, PathBuf};
+
+use tokio::process::Command;
+
+/// Directories of the Chromium based browsers in the config directory.
+const CHROMIUM_DIRS: [&str; 5] = [
+    "chromium",
+    "google-chrome",
+    "BraveSoftware/Brave-Browser",
+    "microsoft-edge",
+    "vivaldi",
+];
+
+/// Directories holding the Firefox profiles, relative to the home directory.
+const FIREFOX_DIRS: [&str; 3] = [
+    ".mozilla/firefox",
+    ".var/app/org.mozilla.firefox/.mozilla/firefox",
+    "snap/firefox/common/.mozilla/firefox",
+];
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Bookmark {
+    pub title: String,
+    pub url: String,
+}
+
+/// Loads the bookmarks of every Firefox and Chromium profile, without duplicate URLs.
+///
+/// Firefox keeps them in a database that's read with the `sqlite3` command, its bookmarks
+/// are skipped when it isn't installed.
+pub async fn load() -> Vec<Bookmark> {
+    let Some(home) = std::env::var_os("HOME").map(PathBuf::from) else {
+        return Vec::new();
+    };
+
+    let mut bookmarks = Vec::new();
+    for profile in firefox_profiles(&home) {
+        bookmarks.extend(firefox_bookmarks(&profile).await);
+    }
+    for bookmarks_file in chromium_bookmark_files(&home) {
+        bookmarks.extend(chromium_bookmarks(&bookmarks_file));
+    }
+
+    let mut seen_urls = std::collections::HashSet::new();
+    bookmarks.retain(|bookmark| seen_urls.insert(bookmark.url.clone()));
+    bookmarks
+}
+
+fn firefox_profiles(home: &Path) -> Vec<PathBuf> {
+    FIREFOX_DIRS
+        .iter()
+        .filter_map(|dir| std::fs::read_dir(home.join(dir)).ok())
+        .flat_map(|entries| entries.filter_map(|entry| entry.ok().map(|entry| entry.path())))
+        .filter(|profile| profile.join("places.sqlite").is_file())
+        .collect()
+}
+
+async fn firefox_bookmarks(profile: &Path) -> Vec<Bookmark> {
+    // Firefox locks the database while it runs, it's only read as it is
+    let database = format!(
+        "file:{}?immutable=1",
+        profile.join("places.sqlite").display()
+    );
+    let output = Command::new("sqlite3")
+        .args(["-readonly", "-separator", "\t", &database])
+        .arg(
+            "SELECT b.title, p.url FROM moz_bookmarks b JOIN moz_places p ON b.fk = p.id \
+             WHERE b.type = 1 AND b.title IS NOT NULL AND p.url LIKE 'http%'",
+        )
+        .output()
+        .await;
+
+    match output {
+        Ok(output) if output.status.success() => String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter_map(|line| line.split_once('\t'))
+            .map(|(title, url)| Bookmark {
+                title: title.to_string(),
+                url: url.to_string(),
+            })
+            .collect(),
+        Ok(output) => {
+            eprintln!(
+                "Failed to read the Firefox bookmarks of {}: {}",
+                profile.display(),
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+            Vec::new()
+        }
+        Err(_) => Vec::new(),
+    }
+}
+
+fn chromium_bookmark_files(home: &Path) -> Vec<PathBuf> {
+    let config_home = std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .filter(|path| path.is_absolute())
+        .unwrap_or_else(|| home.join(".config"));
+
+    // Profiles are `Default`, `Profile 1` and so on
+    CHROMIUM_DIRS
+        .iter()
+        .filter_map(|dir| std::fs::read_dir(config_home.join(dir)).ok())
+        .flat_map(|entries| entries.filter_map(|entry| entry.ok().map(|entry| entry.path())))
+        .map(|profile| profile.join("Bookmarks"))
+        .filter(|bookmarks_file| bookmarks_file.is_file())
+        .collect()
+}
+
+fn chromium_bookmarks(bookmarks_file: &Path) -> Vec<Bookmark> {
+    let Ok(contents) = std::fs::read_to_string(bookmarks_file) else {
+        return Vec::new();
+    };
+    let root: serde_json::Value = match serde_json::from_str(&contents) {
+        Ok(root) => root,
+        Err(e) => {
+            eprintln!("Failed to parse {}: {}", bookmarks_file.display(), e);
+            return Vec::new();
+        }
+    };
+
+    let mut bookmarks = Vec::new();
+    if let Some(roots) = root["roots"].as_object() {
+        for node in roots.values() {
+            collect_chromium_bookmarks(node, &mut bookmarks);
+        }
+    }
+    bookmarks
+}
+
+fn collect_chromium_bookmarks(node: &serde_json::Value, bookmarks: &mut Vec<Bookmark>) {
+    match node["type"].as_str() {
+        Some("url") => {
+            if let (Some(title), Some(url)) = (node["name"].as_str(), node["url"].as_str()) {
+                bookmarks.push(Bookmark {
+                    title: title.to_string(),
+                    url: url.to_string(),
+                });
+            }
+        }
+        Some("folder") => {
+            for child in node["children"].as_array().into_iter().flatten() {
+                collect_chromium_bookmarks(child, bookmarks);
+            }
+        }
+        _ => {}
+    }
+}
@@ -0,0 +1,168 @@
+/// Evaluates arithmetic like `2 * (3 + 4) ^ 2 % 5`, returning `None` for anything else.
+///
+/// Supports `+`, `-`, `*`, `/`, `%`, `^`, parentheses and decimal numbers with either
+/// a point or a comma.
+pub fn evaluate(expression: &str) -> Option<f64> {
+    let tokens = tokenize(expression)?;
+    let mut parser = Parser {
+        tokens,
+        position: 0,
+        depth: 0,
+    };
+    let result = parser.expression()?;
+
+    (parser.position == parser.tokens.len() && result.is_finite()).then_some(result)
+}
+
+/// Formats the result without a trailing fraction for whole numbers.
+pub fn format_result(result: f64) -> String {
+    if result.fract() == 0.0 && result.abs() < 1e15 {
+        format!("{}", result as i64)
+    } else {
+        let formatted = format!("{:.10}", result);
+        formatted
+            .trim_end_matches('0')
+            .trim_end_matches('.')
+            .to_string()
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Token {
+    Number(f64),
+    Operator(char),
+    Open,
+    Close,
+}
+
+fn tokenize(expression: &str) -> Option<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut chars = expression.chars().peekable();
+
+    while let Some(&c) = chars.peek() {
+        match c {
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            '0'..='9' | '.' | ',' => {
+                let mut number = String::new();
+                while let Some(&digit) = chars.peek() {
+                    match digit {
+                        '0'..='9' => number.push(digit),
+                        '.' | ',' => number.push('.'),
+                        _ => break,
+                    }
+                    chars.next();
+                }
+                tokens.push(Token::Number(number.parse().ok()?));
+            }
+            '+' | '-' | '*' | '/' | '%' | '^' => {
+                tokens.push(Token::Operator(c));
+                chars.next();
+            }
+            '×' => {
+                tokens.push(Token::Operator('*'));
+                chars.next();
+            }
+            '÷' => {
+                tokens.push(Token::Operator('/'));
+                chars.next();
+            }
+            '(' => {
+                tokens.push(Token::Open);
+                chars.next();
+            }
+            ')' => {
+                tokens.push(Token::Close);
+                chars.next();
+            }
+            _ => return None,
+        }
+    }
+    Some(tokens)
+}
+
+/// How deeply parentheses, signs and powers may nest, deeper ones would overflow the stack.
+const MAX_DEPTH: usize = 64;
+
+/// Recursive descent parser, with the usual precedence and `^` binding to the right.
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+    /// How many factors are being parsed inside each other.
+    depth: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<Token> {
+        self.tokens.get(self.position).copied()
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.peek();
+        self.position += 1;
+        token
+    }
+
+    fn expression(&mut self) -> Option<f64> {
+        let mut value = self.term()?;
+        while let Some(Token::Operator(operator @ ('+' | '-'))) = self.peek() {
+            self.next();
+            let rhs = self.term()?;
+            value = if operator == '+' {
+                value + rhs
+            } else {
+                value - rhs
+            };
+        }
+        Some(value)
+    }
+
+    fn term(&mut self) -> Option<f64> {
+        let mut value = self.factor()?;
+        while let Some(Token::Operator(operator @ ('*' | '/' | '%'))) = self.peek() {
+            self.next();
+            let rhs = self.factor()?;
+            value = match operator {
+                '*' => value * rhs,
+                '/' => value / rhs,
+                _ => value % rhs,
+            };
+        }
+        Some(value)
+    }
+
+    /// Every nesting goes through here, so it's where the depth is limited.
+    fn factor(&mut self) -> Option<f64> {
+        if self.depth >= MAX_DEPTH {
+            return None;
+        }
+        self.depth += 1;
+        let value = self.power();
+        self.depth -= 1;
+        value
+    }
+
+    fn power(&mut self) -> Option<f64> {
+        let base = self.unary()?;
+        if let Some(Token::Operator('^')) = self.peek() {
+            self.next();
+            return Some(base.powf(self.factor()?));
+        }
+        Some(base)
+    }
+
+    fn unary(&mut self) -> Option<f64> {
+        match self.next()? {
+            // Binds looser than `^`, so `-2^2` is `-4`
+            Token::Operator('-') => Some(-self.factor()?),
+            Token::Operator('+') => self.factor(),
+            Token::Number(number) => Some(number),
+            Token::Open => {
+                let value = self.expression()?;
+                (self.next()? == Token::Close).then_some(value)
+            }
+            _ => None,
+        }
+    }
+}
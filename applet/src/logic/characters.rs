@@ -0,0 +1,53 @@
+/// Most characters offered for a single search, emoji names overlap a lot.
+const MAX_RESULTS: usize = 5;
+
+/// Typographic characters without an emoji, by their Unicode name.
+const SPECIAL_CHARACTERS: &[(&str, &str)] = &[
+    ("—", "em dash"),
+    ("–", "en dash"),
+    ("…", "horizontal ellipsis"),
+    ("•", "bullet"),
+    ("°", "degree sign"),
+    ("±", "plus-minus sign"),
+    ("×", "multiplication sign"),
+    ("÷", "division sign"),
+    ("≠", "not equal to"),
+    ("≈", "almost equal to"),
+    ("€", "euro sign"),
+    ("£", "pound sign"),
+    ("¥", "yen sign"),
+    ("©", "copyright sign"),
+    ("®", "registered sign"),
+    ("§", "section sign"),
+    ("¶", "pilcrow sign"),
+    ("«", "left-pointing double angle quotation mark"),
+    ("»", "right-pointing double angle quotation mark"),
+    ("„", "double low-9 quotation mark"),
+    ("“", "left double quotation mark"),
+    ("”", "right double quotation mark"),
+    ("\u{a0}", "no-break space"),
+];
+
+/// Returns the emoji and special characters whose name or shortcode contains the query,
+/// as `(glyph, name)` pairs.
+pub fn matching(query: &str) -> Vec<(&'static str, &'static str)> {
+    let query = query.trim().to_lowercase();
+    if query.chars().count() < 2 {
+        return Vec::new();
+    }
+
+    let special_characters = SPECIAL_CHARACTERS
+        .iter()
+        .filter(|(_, name)| name.contains(&query))
+        .copied();
+    let emojis = emojis::iter()
+        .filter(|emoji| {
+            emoji.name().contains(&query)
+                || emoji
+                    .shortcodes()
+                    .any(|shortcode| shortcode.contains(&query))
+        })
+        .map(|emoji| (emoji.as_str(), emoji.name()));
+
+    special_characters.chain(emojis).take(MAX_RESULTS).collect()
+}
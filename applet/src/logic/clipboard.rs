@@ -0,0 +1,56 @@
+use std::hash::Hash;
+use std::process::Stdio;
+
+use cosmic::iced::{stream, Subscription};
+use cosmic::iced_futures::futures::{self, SinkExt};
+use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio::process::Command;
+
+/// Number of clipboard snippets kept in the history.
+pub const HISTORY_LENGTH: usize = 5;
+
+/// Emits the text copied to the clipboard by any application.
+///
+/// Relies on `wl-paste` from wl-clipboard, which reads the clipboard through the data-control
+/// protocol without needing keyboard focus. Nothing is emitted if it isn't installed.
+pub fn clipboard_changes<I: 'static + Hash + Copy + Send + Sync>(id: I) -> Subscription<String> {
+    Subscription::run_with_id(
+        id,
+        stream::channel(4, move |mut output| async move {
+            // `--watch` runs the command on every selection change, printing a line each time
+            let watcher = Command::new("wl-paste")
+                .args(["--watch", "echo"])
+                .stdout(Stdio::piped())
+                .kill_on_drop(true)
+                .spawn();
+
+            match watcher {
+                Ok(mut watcher) => {
+                    if let Some(stdout) = watcher.stdout.take() {
+                        let mut changes = BufReader::new(stdout).lines();
+                        while let Ok(Some(_)) = changes.next_line().await {
+                            if let Some(text) = read_text().await {
+                                _ = output.send(text).await;
+                            }
+                        }
+                    }
+                }
+                Err(e) => eprintln!("Failed to watch the clipboard: {}", e),
+            }
+
+            futures::future::pending().await
+        }),
+    )
+}
+
+/// Returns the text currently in the clipboard, if it holds any.
+async fn read_text() -> Option<String> {
+    let output = Command::new("wl-paste")
+        .args(["--no-newline", "--type", "text"])
+        .output()
+        .await
+        .ok()?;
+
+    let text = String::from_utf8(output.stdout).ok()?;
+    (output.status.success() && !text.trim().is_empty()).then_some(text)
+}
@@ -0,0 +1,115 @@
+use std::path::PathBuf;
+
+use crate::model::application_properties::{ApplicationProperties, PropertyField};
+use crate::model::new_launcher::NewLauncher;
+
+const MAIN_GROUP: &str = "[Desktop Entry]";
+
+/// Saves the edited properties as a user-level copy of the desktop file, which takes
+/// precedence over the original in `~/.local/share/applications`.
+pub async fn save_user_override(properties: ApplicationProperties) -> Result<PathBuf, String> {
+    let contents = tokio::fs::read_to_string(&properties.path)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    // Keep the path relative to the data directory, as it makes up the desktop file id
+    let relative_path = crate::logic::apps::application_dirs()
+        .into_iter()
+        .find_map(|dir| properties.path.strip_prefix(dir).ok().map(PathBuf::from))
+        .or_else(|| properties.path.file_name().map(PathBuf::from))
+        .ok_or_else(|| format!("Invalid desktop file path: {}", properties.path.display()))?;
+    let target = user_applications_dir()?.join(relative_path);
+
+    if let Some(parent) = target.parent() {
+        tokio::fs::create_dir_all(parent)
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+    tokio::fs::write(&target, rewrite_entry(&contents, &properties))
+        .await
+        .map_err(|e| e.to_string())?;
+
+    println!("Saved desktop entry override: {}", target.display());
+    Ok(target)
+}
+
+/// Writes the launcher as a new desktop file in `~/.local/share/applications`, without
+/// overwriting any existing one.
+pub async fn create_launcher(launcher: NewLauncher) -> Result<PathBuf, String> {
+    let dir = user_applications_dir()?;
+    tokio::fs::create_dir_all(&dir)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let stem = launcher.file_stem();
+    let mut target = dir.join(format!("{}.desktop", stem));
+    let mut suffix = 2;
+    while tokio::fs::try_exists(&target).await.unwrap_or(false) {
+        target = dir.join(format!("{}-{}.desktop", stem, suffix));
+        suffix += 1;
+    }
+
+    tokio::fs::write(&target, launcher.to_desktop_entry())
+        .await
+        .map_err(|e| e.to_string())?;
+
+    println!("Created launcher: {}", target.display());
+    Ok(target)
+}
+
+fn user_applications_dir() -> Result<PathBuf, String> {
+    let data_home = std::env::var_os("XDG_DATA_HOME")
+        .map(PathBuf::from)
+        .filter(|path| path.is_absolute())
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/share")))
+        .ok_or_else(|| "Unable to determine the user data directory".to_string())?;
+
+    Ok(data_home.join("applications"))
+}
+
+/// Replaces the edited keys of the main group, dropping their translations so the new
+/// values show up in every locale.
+fn rewrite_entry(contents: &str, properties: &ApplicationProperties) -> String {
+    let changes: Vec<(PropertyField, &str)> = properties.changes().collect();
+    let mut lines = Vec::new();
+    let mut in_main_group = false;
+
+    for line in contents.lines() {
+        let trimmed = line.trim();
+        if trimmed.starts_with('[') {
+            in_main_group = trimmed == MAIN_GROUP;
+            lines.push(line.to_string());
+            if in_main_group {
+                lines.extend(changes.iter().map(|(field, value)| {
+                    format!("{}={}", field.key(), format_value(*field, value))
+                }));
+            }
+            continue;
+        }
+
+        let key = trimmed
+            .split_once('=')
+            .map(|(key, _)| key.split('[').next().unwrap_or(key).trim());
+        let is_edited = key.is_some_and(|key| changes.iter().any(|(field, _)| field.key() == key));
+        if !(in_main_group && is_edited) {
+            lines.push(line.to_string());
+        }
+    }
+
+    let mut rewritten = lines.join("\n");
+    rewritten.push('\n');
+    rewritten
+}
+
+fn format_value(field: PropertyField, value: &str) -> String {
+    match field {
+        // Lists are separated and terminated by semicolons
+        PropertyField::Categories => value
+            .split(';')
+            .map(str::trim)
+            .filter(|category| !category.is_empty())
+            .map(|category| format!("{};", category))
+            .collect(),
+        _ => value.trim().to_string(),
+    }
+}
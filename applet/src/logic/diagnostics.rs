@@ -0,0 +1,138 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use cosmic_classic_menu_core::launch;
+use freedesktop_desktop_entry::DesktopEntry;
+
+use crate::logic::apps::application_dirs;
+
+/// Why a desktop file doesn't show up in the menu, or doesn't work when it does.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Problem {
+    /// The file couldn't be parsed, like for invalid UTF-8 or a broken group header.
+    Unreadable(String),
+    /// A file with the same desktop id in a more important directory is listed instead.
+    Shadowed(PathBuf),
+    /// `NoDisplay` or `Hidden` keep it out of the menu.
+    NotDisplayed,
+    /// The program of the `TryExec` line isn't installed, so it's left out of the menu.
+    NotInstalled(String),
+    /// There's no `Exec` line to launch it with.
+    MissingExec,
+    /// The program of the `Exec` line isn't installed.
+    ProgramNotFound(String),
+    /// The icon is neither in the icon theme nor a file.
+    MissingIcon(String),
+}
+
+impl Problem {
+    /// Returns whether the entry is left out of the menu because of it.
+    pub fn is_skipped(&self) -> bool {
+        matches!(
+            self,
+            Problem::Unreadable(_)
+                | Problem::Shadowed(_)
+                | Problem::NotDisplayed
+                | Problem::NotInstalled(_)
+        )
+    }
+}
+
+/// A problem of a desktop file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub path: PathBuf,
+    pub problem: Problem,
+}
+
+/// Checks every desktop file of the application directories, the entries left out of the
+/// menu first.
+pub async fn scan() -> Vec<Diagnostic> {
+    let mut diagnostics = tokio::task::spawn_blocking(scan_blocking)
+        .await
+        .unwrap_or_default();
+    diagnostics.sort_by_key(|diagnostic| !diagnostic.problem.is_skipped());
+    diagnostics
+}
+
+fn scan_blocking() -> Vec<Diagnostic> {
+    let locales = crate::i18n::locale_fallback_chain();
+    // The first file of an id wins, like when loading the apps
+    let mut first_paths: HashMap<String, PathBuf> = HashMap::new();
+    let mut diagnostics = Vec::new();
+    for path in freedesktop_desktop_entry::Iter::new(application_dirs().into_iter())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "desktop"))
+    {
+        let Some(id) = path.file_stem().and_then(|stem| stem.to_str()) else {
+            continue;
+        };
+        if let Some(first_path) = first_paths.get(id) {
+            diagnostics.push(Diagnostic {
+                path,
+                problem: Problem::Shadowed(first_path.clone()),
+            });
+            continue;
+        }
+        first_paths.insert(id.to_string(), path.clone());
+
+        diagnostics.extend(
+            check_entry(&locales, &path)
+                .into_iter()
+                .map(|problem| Diagnostic {
+                    path: path.clone(),
+                    problem,
+                }),
+        );
+    }
+    diagnostics
+}
+
+/// Returns the problems of a single desktop file.
+pub fn check_entry(locales: &[String], path: &Path) -> Vec<Problem> {
+    let entry = match DesktopEntry::from_path(path, Some(locales)) {
+        Ok(entry) => entry,
+        Err(e) => return vec![Problem::Unreadable(e.to_string())],
+    };
+    if entry.no_display() || entry.hidden() {
+        return vec![Problem::NotDisplayed];
+    }
+
+    if let Some(try_exec) = entry.desktop_entry("TryExec") {
+        if !launch::is_installed(try_exec) {
+            return vec![Problem::NotInstalled(try_exec.to_string())];
+        }
+    }
+
+    let mut problems = Vec::new();
+    // D-Bus activated apps can do without one
+    let dbus_activatable = entry
+        .desktop_entry("DBusActivatable")
+        .is_some_and(|value| value == "true");
+    match entry.exec().and_then(program_of) {
+        Some(program) if !launch::is_installed(&program) => {
+            problems.push(Problem::ProgramNotFound(program))
+        }
+        Some(_) => {}
+        None if !dbus_activatable => problems.push(Problem::MissingExec),
+        None => {}
+    }
+    if let Some(icon) = entry.icon().filter(|icon| !icon.is_empty()) {
+        let found = if Path::new(icon).is_absolute() {
+            Path::new(icon).exists()
+        } else {
+            cosmic::widget::icon::from_name(icon).path().is_some()
+        };
+        if !found {
+            problems.push(Problem::MissingIcon(icon.to_string()));
+        }
+    }
+    problems
+}
+
+/// Returns the program an `Exec` line runs, skipping `env` and its variables.
+fn program_of(exec: &str) -> Option<String> {
+    exec.split_whitespace()
+        .map(|word| word.trim_matches('"'))
+        .find(|word| *word != "env" && !word.contains('='))
+        .map(str::to_string)
+}
@@ -0,0 +1,79 @@
+use tokio::process::Command;
+
+use cosmic_classic_menu_core::launch::{spawn_detached, split_exec, which};
+
+/// Variables a graphical app needs to reach the user's session, which pkexec clears.
+const SESSION_ENV_VARS: [&str; 5] = [
+    "DISPLAY",
+    "WAYLAND_DISPLAY",
+    "XAUTHORITY",
+    "XDG_RUNTIME_DIR",
+    "XDG_SESSION_TYPE",
+];
+
+/// Exit status pkexec and run0 use when the authentication dialog was dismissed.
+const EXIT_DISMISSED: i32 = 126;
+/// Exit status pkexec and run0 use when the user isn't authorized.
+const EXIT_NOT_AUTHORIZED: i32 = 127;
+
+#[derive(Clone, Debug)]
+pub enum ElevationError {
+    /// The user closed the authentication dialog.
+    Dismissed,
+    /// polkit denied the authorization.
+    NotAuthorized,
+    /// The command couldn't be started at all.
+    Failed(String),
+}
+
+/// Runs the desktop entry's command line as root through pkexec, or run0 where pkexec
+/// isn't installed.
+///
+/// Resolves once the app has been running for [`STARTUP_TIMEOUT`], with an error only if
+/// it never got to run. Its own exit status later on isn't taken for the helper's.
+///
+/// [`STARTUP_TIMEOUT`]: cosmic_classic_menu_core::launch::STARTUP_TIMEOUT
+pub async fn run_as_administrator(exec: String) -> Result<(), ElevationError> {
+    let args = split_exec(&exec);
+    if args.is_empty() {
+        return Err(ElevationError::Failed(format!(
+            "Invalid command line: {}",
+            exec
+        )));
+    }
+
+    let helper = if which("pkexec") { "pkexec" } else { "run0" };
+
+    let is_flatpak = std::env::var("FLATPAK_ID").is_ok();
+    let mut command = if is_flatpak {
+        let mut command = Command::new("flatpak-spawn");
+        command.args(["--host", helper]);
+        command
+    } else {
+        Command::new(helper)
+    };
+    command.arg("env");
+    command.args(SESSION_ENV_VARS.iter().filter_map(|key| {
+        std::env::var(key)
+            .ok()
+            .map(|value| format!("{}={}", key, value))
+    }));
+    command.args(args);
+
+    let status = spawn_detached(command, helper)
+        .await
+        .map_err(|e| ElevationError::Failed(e.to_string()))?;
+
+    match status {
+        None => Ok(()),
+        Some(status) if status.success() => Ok(()),
+        Some(status) => match status.code() {
+            Some(EXIT_DISMISSED) => Err(ElevationError::Dismissed),
+            Some(EXIT_NOT_AUTHORIZED) => Err(ElevationError::NotAuthorized),
+            _ => Err(ElevationError::Failed(format!(
+                "'{}' exited with {}",
+                helper, status
+            ))),
+        },
+    }
+}
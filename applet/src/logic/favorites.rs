@@ -0,0 +1,68 @@
+use crate::logic::apps::Apps;
+
+/// Well-known desktop ids per role, in order of preference.
+const SEED_CANDIDATES: &[&[&str]] = &[
+    // Web browser
+    &[
+        "firefox",
+        "org.mozilla.firefox",
+        "chromium",
+        "org.chromium.Chromium",
+        "google-chrome",
+        "com.google.Chrome",
+        "brave-browser",
+        "com.brave.Browser",
+        "org.gnome.Epiphany",
+    ],
+    // File manager
+    &[
+        "com.system76.CosmicFiles",
+        "org.gnome.Nautilus",
+        "org.kde.dolphin",
+        "nemo",
+        "thunar",
+    ],
+    // Terminal
+    &[
+        "com.system76.CosmicTerm",
+        "org.gnome.Ptyxis",
+        "org.gnome.Console",
+        "org.gnome.Terminal",
+        "org.kde.konsole",
+    ],
+    // Settings
+    &[
+        "com.system76.CosmicSettings",
+        "org.gnome.Settings",
+        "systemsettings",
+    ],
+    // Software store
+    &[
+        "com.system76.CosmicStore",
+        "org.gnome.Software",
+        "io.elementary.appcenter",
+        "org.kde.discover",
+    ],
+];
+
+/// Returns the desktop ids of the commonly used apps that are installed, one per role,
+/// to pre-pin before the user curates the favorites.
+pub async fn seed_favorites() -> Vec<String> {
+    let installed: Vec<String> = Apps::load_apps()
+        .await
+        .into_iter()
+        .map(|app| app.id)
+        .collect();
+
+    SEED_CANDIDATES
+        .iter()
+        .filter_map(|candidates| {
+            candidates.iter().find_map(|candidate| {
+                installed
+                    .iter()
+                    .find(|id| id.eq_ignore_ascii_case(candidate))
+                    .cloned()
+            })
+        })
+        .collect()
+}
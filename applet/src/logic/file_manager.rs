@@ -0,0 +1,24 @@
+use std::path::Path;
+
+use crate::file_manager::FileManager1Proxy;
+
+/// Reveals the file in the user's file manager.
+pub async fn show_item(path: &Path) -> zbus::Result<()> {
+    let conn = zbus::Connection::session().await?;
+    let file_manager = FileManager1Proxy::new(&conn).await?;
+    file_manager.show_items(&[&file_uri(path)], "").await
+}
+
+/// Returns the `file://` URI of an absolute path, percent-encoding reserved bytes.
+fn file_uri(path: &Path) -> String {
+    let mut uri = String::from("file://");
+    for byte in path.as_os_str().as_encoded_bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'/' | b'-' | b'_' | b'.' | b'~' => {
+                uri.push(*byte as char)
+            }
+            _ => uri.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    uri
+}
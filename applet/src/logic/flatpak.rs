@@ -0,0 +1,51 @@
+use std::collections::HashSet;
+
+use tokio::process::Command;
+
+/// Returns the ids of installed Flatpak applications with pending updates.
+///
+/// Systems without Flatpak (or without network access) simply report no updates.
+pub async fn get_pending_updates() -> HashSet<String> {
+    println!("Checking for Flatpak updates...");
+    let output = flatpak_command(&["remote-ls", "--updates", "--app", "--columns=application"])
+        .output()
+        .await;
+
+    match output {
+        Ok(output) if output.status.success() => String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(str::to_string)
+            .collect(),
+        _ => HashSet::new(),
+    }
+}
+
+/// Updates a single Flatpak application without any interaction.
+pub async fn update_application(app_id: String) -> Result<(), String> {
+    println!("Updating Flatpak application: {}", app_id);
+    let output = flatpak_command(&["update", "--noninteractive", "-y", &app_id])
+        .output()
+        .await
+        .map_err(|e| e.to_string())?;
+
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(String::from_utf8_lossy(&output.stderr).trim().to_string())
+    }
+}
+
+fn flatpak_command(args: &[&str]) -> Command {
+    let is_flatpak = std::env::var("FLATPAK_ID").is_ok();
+    let mut command = if is_flatpak {
+        let mut command = Command::new("flatpak-spawn");
+        command.args(["--host", "flatpak"]);
+        command
+    } else {
+        Command::new("flatpak")
+    };
+    command.args(args);
+    command
+}
@@ -0,0 +1,41 @@
+use crate::switcheroo_control::SwitcherooControlProxy;
+
+/// Returns the environment that makes an app render on the dedicated GPU, or `None` when
+/// the system has a single GPU.
+///
+/// Systems without switcheroo-control are assumed to have a single GPU. When the
+/// dedicated GPU doesn't advertise an environment, `DRI_PRIME=1` is used.
+pub async fn dedicated_gpu_environment() -> Option<Vec<(String, String)>> {
+    async fn query() -> zbus::Result<Option<Vec<(String, String)>>> {
+        let conn = zbus::Connection::system().await?;
+        let switcheroo = SwitcherooControlProxy::new(&conn).await?;
+        if !switcheroo.has_dual_gpu().await? {
+            return Ok(None);
+        }
+
+        let environment = switcheroo
+            .gpus()
+            .await?
+            .into_iter()
+            .find(|gpu| {
+                !gpu.get("Default")
+                    .and_then(|value| value.try_clone().ok())
+                    .and_then(|value| bool::try_from(value).ok())
+                    .unwrap_or(false)
+            })
+            .and_then(|gpu| gpu.get("Environment")?.try_clone().ok())
+            .and_then(|value| Vec::<String>::try_from(value).ok())
+            .map(|environment| {
+                environment
+                    .chunks_exact(2)
+                    .map(|pair| (pair[0].clone(), pair[1].clone()))
+                    .collect::<Vec<_>>()
+            })
+            .filter(|environment| !environment.is_empty())
+            .unwrap_or_else(|| vec![("DRI_PRIME".to_string(), "1".to_string())]);
+
+        Ok(Some(environment))
+    }
+
+    query().await.unwrap_or(None)
+}
@@ -0,0 +1,37 @@
+use super::xdg_dirs;
+
+/// Returns the names of the installed icon themes, sorted.
+pub fn installed() -> Vec<String> {
+    let mut themes: Vec<String> = xdg_dirs::data_dirs()
+        .into_iter()
+        .map(|dir| dir.join("icons"))
+        .filter_map(|dir| std::fs::read_dir(dir).ok())
+        .flatten()
+        .flatten()
+        .filter(|entry| is_icon_theme(&entry.path().join("index.theme")))
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        // The fallback theme of every other theme, not one to pick
+        .filter(|name| name != "hicolor")
+        .collect();
+    themes.sort();
+    themes.dedup();
+    themes
+}
+
+/// Returns whether the index describes icons, cursor themes list no directories of them.
+fn is_icon_theme(index: &std::path::Path) -> bool {
+    std::fs::read_to_string(index).is_ok_and(|contents| {
+        contents
+            .lines()
+            .any(|line| line.starts_with("Directories="))
+    })
+}
+
+/// Makes the menu look its icons up in the theme, or in the system one when it's empty.
+pub fn apply(theme: &str) {
+    if theme.is_empty() {
+        cosmic::icon_theme::set_default(cosmic::config::icon_theme());
+    } else {
+        cosmic::icon_theme::set_default(theme.to_string());
+    }
+}
@@ -0,0 +1,90 @@
+use std::time::{Duration, Instant};
+
+use cosmic::widget::icon::IconFallback;
+use freedesktop_desktop_entry::IconSource;
+
+/// How many icons are read by a task, so the first ones show before all are read.
+pub const BATCH_SIZE: usize = 24;
+
+/// How long a read icon takes to fade in over its placeholder.
+pub const FADE_DURATION: Duration = Duration::from_millis(150);
+
+/// Smallest size the named icons are looked up at, in logical pixels, so every icon of the
+/// menu is downscaled from it instead of upscaled.
+pub const MIN_LOOKUP_SIZE: u16 = 64;
+
+/// Contents of an icon file, drawn without touching the file system again.
+#[derive(Clone, Debug)]
+pub enum IconData {
+    Svg(cosmic::widget::svg::Handle),
+    Raster(cosmic::widget::image::Handle),
+}
+
+/// Icon of the cache, missing ones are looked up synchronously while drawing.
+#[derive(Clone, Debug)]
+pub enum CachedIcon {
+    /// Read in the background, a placeholder is shown meanwhile.
+    Loading,
+    /// Read at the given time, `None` when neither it nor a fallback icon was found.
+    Ready(Option<IconData>, Instant),
+}
+
+impl CachedIcon {
+    /// Returns how visible the icon is while fading in, from 0 to 1.
+    pub fn opacity(&self) -> f32 {
+        match self {
+            CachedIcon::Loading => 0.0,
+            CachedIcon::Ready(_, at) => {
+                (at.elapsed().as_secs_f32() / FADE_DURATION.as_secs_f32()).min(1.0)
+            }
+        }
+    }
+
+    /// Returns whether the icon is still fading in.
+    pub fn is_fading(&self) -> bool {
+        matches!(self, CachedIcon::Ready(_, at) if at.elapsed() < FADE_DURATION)
+    }
+}
+
+/// Returns the key of the icon in the cache.
+pub fn key(source: &IconSource) -> String {
+    match source {
+        IconSource::Name(name) => name.clone(),
+        IconSource::Path(path) => path.display().to_string(),
+    }
+}
+
+/// Looks the icons up in the icon theme and reads their files on a blocking thread, a
+/// lookup walks the directories of every inherited theme.
+pub async fn load(sources: Vec<IconSource>, size: u16) -> Vec<(String, Option<IconData>)> {
+    tokio::task::spawn_blocking(move || {
+        sources
+            .iter()
+            .map(|source| (key(source), read(source, size)))
+            .collect()
+    })
+    .await
+    .unwrap_or_default()
+}
+
+fn read(source: &IconSource, size: u16) -> Option<IconData> {
+    let path = match source {
+        IconSource::Name(name) => cosmic::widget::icon::from_name(name.as_str())
+            .size(size)
+            .fallback(Some(IconFallback::Names(vec![
+                "application-default".into(),
+                "application-x-executable".into(),
+            ])))
+            .path()?,
+        IconSource::Path(path) => path.clone(),
+    };
+    let bytes = std::fs::read(&path).ok()?;
+    let is_svg = path
+        .extension()
+        .is_some_and(|extension| extension == "svg" || extension == "svgz");
+    Some(if is_svg {
+        IconData::Svg(cosmic::widget::svg::Handle::from_memory(bytes))
+    } else {
+        IconData::Raster(cosmic::widget::image::Handle::from_bytes(bytes))
+    })
+}
@@ -0,0 +1,65 @@
+use zbus::{fdo::DBusProxy, names::BusName, Connection};
+
+/// Desktop services the menu integrates with, probed at runtime so the applet degrades to a
+/// plain application menu on compositors without them.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Integrations {
+    /// AccountsService, providing the user widget.
+    pub accounts: bool,
+    /// logind, performing the power actions.
+    pub logind: bool,
+    /// cosmic-osd, confirming logging out, restarting and shutting down.
+    pub cosmic_osd: bool,
+    /// The desktop portal, providing the file chooser of the settings.
+    pub portals: bool,
+}
+
+impl Integrations {
+    /// Returns whether the power actions can be performed.
+    pub fn power_actions(&self) -> bool {
+        self.logind
+    }
+}
+
+/// Probes which of the integrations are available.
+pub async fn probe() -> Integrations {
+    let system = Connection::system().await.ok();
+    let session = Connection::session().await.ok();
+
+    let (accounts, logind, portals) = tokio::join!(
+        has_service(system.as_ref(), "org.freedesktop.Accounts"),
+        has_service(system.as_ref(), "org.freedesktop.login1"),
+        has_service(session.as_ref(), "org.freedesktop.portal.Desktop"),
+    );
+    // Inside of a sandbox cosmic-osd is spawned on the host, where it can't be looked up
+    let cosmic_osd = std::env::var("FLATPAK_ID").is_ok()
+        || cosmic_classic_menu_core::launch::which("cosmic-osd");
+
+    let integrations = Integrations {
+        accounts,
+        logind,
+        cosmic_osd,
+        portals,
+    };
+    println!("Detected integrations: {:?}", integrations);
+    integrations
+}
+
+/// Returns whether the service is running or can be activated on the bus.
+async fn has_service(connection: Option<&Connection>, name: &str) -> bool {
+    let Some(connection) = connection else {
+        return false;
+    };
+    let (Ok(dbus), Ok(bus_name)) = (DBusProxy::new(connection).await, BusName::try_from(name))
+    else {
+        return false;
+    };
+
+    if dbus.name_has_owner(bus_name).await.unwrap_or(false) {
+        return true;
+    }
+    dbus.list_activatable_names()
+        .await
+        .map(|names| names.iter().any(|activatable| activatable.as_str() == name))
+        .unwrap_or(false)
+}
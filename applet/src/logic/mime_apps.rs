@@ -0,0 +1,182 @@
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+use tokio::process::Command;
+
+use crate::logic::apps::Apps;
+use crate::model::application_entry::ApplicationEntry;
+
+/// Returns the URI a search query stands for, if it's an email address or a web address.
+pub fn uri_for_query(query: &str) -> Option<String> {
+    let query = query.trim();
+    if query.is_empty() || query.contains(char::is_whitespace) {
+        return None;
+    }
+
+    if query.starts_with("http://") || query.starts_with("https://") {
+        return Some(query.to_string());
+    }
+    if query.starts_with("www.") {
+        return Some(format!("https://{}", query));
+    }
+    if let Some(address) = query.strip_prefix("mailto:") {
+        return Some(format!("mailto:{}", address));
+    }
+
+    let (user, domain) = query.split_once('@')?;
+    let is_email = !user.is_empty()
+        && domain.contains('.')
+        && !domain.starts_with('.')
+        && !domain.ends_with('.');
+    is_email.then(|| format!("mailto:{}", query))
+}
+
+/// Returns the installed application handling the URI's scheme by default.
+pub async fn get_uri_handler(uri: &str) -> Option<ApplicationEntry> {
+    let (scheme, _) = uri.split_once(':')?;
+    let desktop_id = default_application(&format!("x-scheme-handler/{}", scheme))?;
+    let app_id = desktop_id.trim_end_matches(".desktop");
+
+    Apps::load_apps()
+        .await
+        .into_iter()
+        .find(|app| app.id == app_id)
+}
+
+/// Types whose default applications are offered at the top of the menu: the web browser,
+/// the email client, the file manager and the terminal, in this order.
+const DEFAULT_APP_TYPES: [&[&str]; 4] = [
+    &[
+        "x-scheme-handler/https",
+        "x-scheme-handler/http",
+        "text/html",
+    ],
+    &["x-scheme-handler/mailto"],
+    &["inode/directory"],
+    &["x-scheme-handler/terminal"],
+];
+
+/// Returns the default web browser, email client, file manager and terminal, skipping the
+/// ones that aren't set or no longer installed.
+pub async fn get_default_apps() -> Vec<ApplicationEntry> {
+    let apps = Apps::load_apps().await;
+    let mut default_apps = Vec::with_capacity(DEFAULT_APP_TYPES.len());
+    for mime_types in DEFAULT_APP_TYPES {
+        let mut desktop_id = mime_types
+            .iter()
+            .find_map(|mime_type| default_application(mime_type));
+        // Browsers can also be set through xdg-settings, which knows about more desktops
+        if desktop_id.is_none() && mime_types[0] == "x-scheme-handler/https" {
+            desktop_id = default_web_browser().await;
+        }
+
+        let Some(desktop_id) = desktop_id else {
+            continue;
+        };
+        let app_id = desktop_id.trim_end_matches(".desktop");
+        let is_listed = default_apps
+            .iter()
+            .any(|app: &ApplicationEntry| app.id == app_id);
+        if let Some(app) = apps.iter().find(|app| app.id == app_id && !is_listed) {
+            default_apps.push(app.clone());
+        }
+    }
+    default_apps
+}
+
+async fn default_web_browser() -> Option<String> {
+    let output = Command::new("xdg-settings")
+        .args(["get", "default-web-browser"])
+        .output()
+        .await
+        .ok()?;
+
+    let desktop_id = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (output.status.success() && !desktop_id.is_empty()).then_some(desktop_id)
+}
+
+/// Looks up the default application of the MIME type in the `mimeapps.list` files, in
+/// the order of precedence of the XDG specification.
+fn default_application(mime_type: &str) -> Option<String> {
+    mimeapps_lists().into_iter().find_map(|path| {
+        let contents = std::fs::read_to_string(path).ok()?;
+        let mut in_defaults = false;
+        for line in contents.lines().map(str::trim) {
+            if line.starts_with('[') {
+                in_defaults = line == "[Default Applications]";
+                continue;
+            }
+
+            if let Some((key, value)) = line.split_once('=') {
+                if in_defaults && key.trim() == mime_type {
+                    return value
+                        .split(';')
+                        .map(str::trim)
+                        .find(|id| !id.is_empty())
+                        .map(str::to_string);
+                }
+            }
+        }
+        None
+    })
+}
+
+/// Returns when the `mimeapps.list` files were last modified, which changes along with the
+/// default applications.
+pub fn mimeapps_modified() -> Vec<Option<SystemTime>> {
+    mimeapps_lists()
+        .iter()
+        .map(|path| {
+            std::fs::metadata(path)
+                .and_then(|metadata| metadata.modified())
+                .ok()
+        })
+        .collect()
+}
+
+fn mimeapps_lists() -> Vec<PathBuf> {
+    let home = std::env::var_os("HOME")
+        .map(PathBuf::from)
+        .unwrap_or_default();
+    let env_dir = |key: &str, fallback: PathBuf| {
+        std::env::var_os(key)
+            .map(PathBuf::from)
+            .filter(|path| path.is_absolute())
+            .unwrap_or(fallback)
+    };
+    let env_dirs = |key: &str, fallback: &str| -> Vec<PathBuf> {
+        std::env::var(key)
+            .ok()
+            .filter(|dirs| !dirs.is_empty())
+            .unwrap_or_else(|| fallback.to_string())
+            .split(':')
+            .map(PathBuf::from)
+            .collect()
+    };
+
+    let desktops: Vec<String> = std::env::var("XDG_CURRENT_DESKTOP")
+        .unwrap_or_default()
+        .split(':')
+        .filter(|desktop| !desktop.is_empty())
+        .map(str::to_lowercase)
+        .collect();
+    let lists_in = |dir: PathBuf| -> Vec<PathBuf> {
+        desktops
+            .iter()
+            .map(|desktop| dir.join(format!("{}-mimeapps.list", desktop)))
+            .chain(std::iter::once(dir.join("mimeapps.list")))
+            .collect()
+    };
+
+    let mut lists = lists_in(env_dir("XDG_CONFIG_HOME", home.join(".config")));
+    for dir in env_dirs("XDG_CONFIG_DIRS", "/etc/xdg") {
+        lists.extend(lists_in(dir));
+    }
+    lists.extend(lists_in(
+        env_dir("XDG_DATA_HOME", home.join(".local/share")).join("applications"),
+    ));
+    for dir in env_dirs("XDG_DATA_DIRS", "/usr/local/share:/usr/share") {
+        lists.extend(lists_in(dir.join("applications")));
+    }
+    lists
+}
@@ -1 +1,31 @@
-pub mod apps;
\ No newline at end of file
+pub mod app_index;
+pub mod apps;
+pub mod appstream;
+pub mod bookmarks;
+pub mod calculator;
+pub mod characters;
+pub mod clipboard;
+pub mod desktop_file;
+pub mod diagnostics;
+pub mod elevation;
+pub mod favorites;
+pub mod file_manager;
+pub mod flatpak;
+pub mod gpu;
+pub mod icon_themes;
+pub mod icons;
+pub mod integrations;
+pub mod mime_apps;
+pub mod parental_controls;
+pub mod recent_documents;
+pub mod script_providers;
+pub mod search_providers;
+pub mod settings_panels;
+pub mod shutdown;
+pub mod ssh_hosts;
+pub mod theme_mode;
+pub mod timer;
+pub mod usage;
+pub mod windows;
+pub mod xdg_dirs;
+pub mod xdg_menu;
\ No newline at end of file
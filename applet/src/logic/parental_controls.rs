@@ -0,0 +1,112 @@
+use std::{cell::OnceCell, hash::Hash, path::Path};
+
+use cosmic::iced::{stream, Subscription};
+use cosmic::iced_futures::futures::{self, SinkExt, StreamExt};
+use cosmic_classic_menu_core::launch::{find_program, split_exec};
+
+use crate::{malcontent::AppFilterProxy, model::application_entry::ApplicationEntry};
+
+/// Parental controls restrictions of the current user, as configured through malcontent.
+#[derive(Clone, Debug, Default)]
+pub struct ParentalControls {
+    is_allowlist: bool,
+    entries: Vec<String>,
+}
+
+impl ParentalControls {
+    /// Fetches the app filter of the current user, allowing everything when
+    /// malcontent isn't available.
+    pub async fn load() -> Self {
+        match Self::fetch().await {
+            Ok(parental_controls) => parental_controls,
+            Err(_) => Self::default(),
+        }
+    }
+
+    async fn fetch() -> zbus::Result<Self> {
+        let conn = zbus::Connection::system().await?;
+        let (is_allowlist, entries) = app_filter(&conn).await?.app_filter().await?;
+
+        Ok(ParentalControls {
+            is_allowlist,
+            entries,
+        })
+    }
+
+    /// Returns whether the application may be shown to the current user.
+    pub fn is_allowed(&self, app: &ApplicationEntry) -> bool {
+        // Only looked up in `PATH` once an entry of the filter names an executable
+        let executable = OnceCell::new();
+
+        let is_listed = self.entries.iter().any(|entry| {
+            if let Some(flatpak_ref) = entry.strip_prefix("app/") {
+                // Flatpak refs look like `app/org.example.App/x86_64/stable`
+                flatpak_ref.split('/').next() == Some(app.id.as_str())
+            } else if entry.starts_with('/') {
+                executable
+                    .get_or_init(|| {
+                        app.exec
+                            .as_deref()
+                            .and_then(exec_program)
+                            .and_then(|program| find_program(&program))
+                    })
+                    .as_deref()
+                    == Some(Path::new(entry))
+            } else {
+                // Content type entries only restrict the handlers of a content type
+                false
+            }
+        });
+
+        is_listed == self.is_allowlist
+    }
+}
+
+/// Emits the app filter of the current user again whenever malcontent changes it.
+pub fn changes<I: 'static + Hash + Copy + Send + Sync>(id: I) -> Subscription<ParentalControls> {
+    Subscription::run_with_id(
+        id,
+        stream::channel(1, move |mut output| async move {
+            let app_filter = match zbus::Connection::system().await {
+                Ok(conn) => app_filter(&conn).await,
+                Err(e) => Err(e),
+            };
+            match app_filter {
+                Ok(app_filter) => {
+                    let mut changes = app_filter.receive_app_filter_changed().await;
+                    while let Some(change) = changes.next().await {
+                        if let Ok((is_allowlist, entries)) = change.get().await {
+                            _ = output
+                                .send(ParentalControls {
+                                    is_allowlist,
+                                    entries,
+                                })
+                                .await;
+                        }
+                    }
+                }
+                Err(e) => eprintln!("Failed to watch the parental controls: {}", e),
+            }
+
+            futures::future::pending().await
+        }),
+    )
+}
+
+/// Returns the app filter on the AccountsService object of the current user.
+async fn app_filter(conn: &zbus::Connection) -> zbus::Result<AppFilterProxy<'static>> {
+    let uid = users::get_current_uid();
+    AppFilterProxy::builder(conn)
+        .path(format!("/org/freedesktop/Accounts/User{}", uid))?
+        .build()
+        .await
+}
+
+/// Returns the program an `Exec` line runs, looking past `env` and the variables it sets.
+fn exec_program(exec: &str) -> Option<String> {
+    let mut args = split_exec(exec).into_iter().peekable();
+    if args.next_if(|arg| arg == "env").is_some() {
+        while args.next_if(|arg| arg.contains('=')).is_some() {}
+    }
+    args.next()
+}
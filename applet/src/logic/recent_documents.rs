@@ -0,0 +1,88 @@
+use std::ffi::OsString;
+use std::os::unix::ffi::OsStringExt;
+use std::path::PathBuf;
+
+/// How many of the most recently used documents are searched.
+const MAX_DOCUMENTS: usize = 200;
+
+/// File listed in the recently used documents of the desktop.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RecentDocument {
+    /// File name, matched against the search.
+    pub name: String,
+    pub path: PathBuf,
+}
+
+/// Loads the local files of `recently-used.xbel` that still exist, most recently used first.
+pub async fn load() -> Vec<RecentDocument> {
+    let Some(path) = xbel_path() else {
+        return Vec::new();
+    };
+    let Ok(contents) = std::fs::read_to_string(&path) else {
+        return Vec::new();
+    };
+    let document = match roxmltree::Document::parse(&contents) {
+        Ok(document) => document,
+        Err(e) => {
+            eprintln!("Failed to parse {}: {}", path.display(), e);
+            return Vec::new();
+        }
+    };
+
+    // The timestamps are ISO 8601 in UTC, so they sort as text
+    let mut bookmarks: Vec<(&str, &str)> = document
+        .root_element()
+        .children()
+        .filter(|node| node.has_tag_name("bookmark"))
+        .filter_map(|node| {
+            let used = node
+                .attribute("visited")
+                .or(node.attribute("modified"))
+                .unwrap_or_default();
+            Some((used, node.attribute("href")?))
+        })
+        .collect();
+    bookmarks.sort_by(|a, b| b.0.cmp(a.0));
+
+    bookmarks
+        .into_iter()
+        .filter_map(|(_, href)| file_path(href))
+        .filter(|path| path.exists())
+        .take(MAX_DOCUMENTS)
+        .filter_map(|path| {
+            let name = path.file_name()?.to_string_lossy().into_owned();
+            Some(RecentDocument { name, path })
+        })
+        .collect()
+}
+
+fn xbel_path() -> Option<PathBuf> {
+    let data_home = std::env::var_os("XDG_DATA_HOME")
+        .map(PathBuf::from)
+        .filter(|path| path.is_absolute())
+        .or_else(|| {
+            std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/share"))
+        })?;
+    Some(data_home.join("recently-used.xbel"))
+}
+
+/// Returns the path of a `file://` URI, decoding its percent-encoded bytes.
+fn file_path(uri: &str) -> Option<PathBuf> {
+    let encoded = uri.strip_prefix("file://")?;
+    let mut bytes = Vec::with_capacity(encoded.len());
+    let mut rest = encoded.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        let decoded = match (byte, tail) {
+            (b'%', [high, low, ..]) => {
+                rest = &tail[2..];
+                u8::from_str_radix(std::str::from_utf8(&[*high, *low]).ok()?, 16).ok()?
+            }
+            _ => {
+                rest = tail;
+                byte
+            }
+        };
+        bytes.push(decoded);
+    }
+    Some(PathBuf::from(OsString::from_vec(bytes)))
+}
@@ -0,0 +1,94 @@
+//! Search providers implemented as executables from the config.
+//!
+//! A provider is run once per search with the query on its standard input, followed by a
+//! newline, and killed when the query changes before it's done. It prints a JSON array of
+//! results on its standard output, like
+//! `[{"title": "Open notes", "icon": "text-editor", "command": "gedit ~/notes.txt"}]`,
+//! where `icon` is optional and is an icon name or an absolute path. The command of the
+//! chosen result is run with the user's shell.
+
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::time::Duration;
+
+use serde::Deserialize;
+use tokio::io::AsyncWriteExt;
+
+/// How long a provider may take before its results are dropped.
+const TIMEOUT: Duration = Duration::from_secs(2);
+
+/// How many results of a provider are listed at most.
+const MAX_RESULTS: usize = 5;
+
+/// Result printed by a provider.
+#[derive(Clone, Debug, Deserialize)]
+pub struct ScriptResult {
+    pub title: String,
+    #[serde(default)]
+    pub icon: Option<String>,
+    pub command: String,
+    /// Provider that printed it, to replace its results of an older query.
+    #[serde(skip)]
+    pub provider: String,
+}
+
+/// Runs the provider with the query, returning no results when it fails, times out or
+/// prints something else than the results.
+pub async fn query(provider: String, query: String) -> Vec<ScriptResult> {
+    match run(&provider, &query).await {
+        Ok(results) => results
+            .into_iter()
+            .take(MAX_RESULTS)
+            .map(|result| ScriptResult {
+                provider: provider.clone(),
+                ..result
+            })
+            .collect(),
+        Err(e) => {
+            eprintln!("Search provider '{}' failed: {}", provider, e);
+            Vec::new()
+        }
+    }
+}
+
+async fn run(provider: &str, query: &str) -> Result<Vec<ScriptResult>, String> {
+    let path = expand_home(provider).ok_or("HOME isn't set")?;
+    // Providers are installed on the host, not in the sandbox
+    let mut command = if std::env::var("FLATPAK_ID").is_ok() {
+        let mut command = tokio::process::Command::new("flatpak-spawn");
+        command.arg("--host").arg(&path);
+        command
+    } else {
+        tokio::process::Command::new(&path)
+    };
+    let mut child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| e.to_string())?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(format!("{}\n", query).as_bytes())
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+    let output = tokio::time::timeout(TIMEOUT, child.wait_with_output())
+        .await
+        .map_err(|_| format!("no results after {} seconds", TIMEOUT.as_secs()))?
+        .map_err(|e| e.to_string())?;
+    if !output.status.success() {
+        return Err(format!("exited with {}", output.status));
+    }
+    serde_json::from_slice(&output.stdout).map_err(|e| e.to_string())
+}
+
+/// Replaces a leading `~/` with the home directory.
+fn expand_home(provider: &str) -> Option<PathBuf> {
+    match provider.strip_prefix("~/") {
+        Some(relative) => Some(PathBuf::from(std::env::var_os("HOME")?).join(relative)),
+        None => Some(PathBuf::from(provider)),
+    }
+}
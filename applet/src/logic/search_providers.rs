@@ -0,0 +1,120 @@
+use std::path::{Path, PathBuf};
+
+/// How many paths are offered for a partially typed path.
+const MAX_PATHS: usize = 8;
+
+/// Provider a query is routed to by its prefix, instead of searching the applications.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Provider {
+    /// `=2*3` evaluates arithmetic.
+    Calculator,
+    /// `>htop` runs a shell command.
+    RunCommand,
+    /// `~/Doc` or `/etc/` completes file paths.
+    Files,
+    /// `?rust` searches the web.
+    WebSearch,
+}
+
+/// Splits the query into its provider and the rest of it, paths keeping their `~` or `/`.
+pub fn parse(query: &str) -> Option<(Provider, &str)> {
+    let query = query.trim_start();
+    let provider = match query.chars().next()? {
+        '=' => Provider::Calculator,
+        '>' => Provider::RunCommand,
+        '?' => Provider::WebSearch,
+        '~' | '/' => return Some((Provider::Files, query)),
+        _ => return None,
+    };
+    Some((provider, query[1..].trim()))
+}
+
+/// Returns the paths starting with the typed one, directories first.
+///
+/// A path ending with `/` lists the directory, and hidden files are only offered once
+/// their name starts being typed. The directory is read on a blocking thread, it may be
+/// slow or on the network.
+pub async fn matching_paths(query: String) -> Vec<PathBuf> {
+    tokio::task::spawn_blocking(move || matching_paths_blocking(&query))
+        .await
+        .unwrap_or_default()
+}
+
+fn matching_paths_blocking(query: &str) -> Vec<PathBuf> {
+    let Some(path) = expand_home(query) else {
+        return Vec::new();
+    };
+    let (dir, prefix) = if query.ends_with('/') {
+        (path.as_path(), String::new())
+    } else {
+        let prefix = path
+            .file_name()
+            .map(|file_name| file_name.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        (path.parent().unwrap_or(Path::new("/")), prefix)
+    };
+
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            let file_name = path
+                .file_name()
+                .map(|file_name| file_name.to_string_lossy().to_lowercase())
+                .unwrap_or_default();
+            file_name.starts_with(&prefix)
+                && (prefix.starts_with('.') || !file_name.starts_with('.'))
+        })
+        .collect();
+    paths.sort_by_cached_key(|path| (!path.is_dir(), path.clone()));
+    paths.truncate(MAX_PATHS);
+    paths
+}
+
+/// Replaces a leading `~` with the home directory.
+fn expand_home(query: &str) -> Option<PathBuf> {
+    match query.strip_prefix('~') {
+        Some(rest) => {
+            let home = PathBuf::from(std::env::var_os("HOME")?);
+            Some(home.join(rest.trim_start_matches('/')))
+        }
+        None => Some(PathBuf::from(query)),
+    }
+}
+
+/// Returns the URL of the search, replacing `%s` in the configured URL with the
+/// percent-encoded query.
+pub fn web_search_url(url: &str, query: &str) -> String {
+    let mut encoded = String::new();
+    for byte in query.as_bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                encoded.push(*byte as char)
+            }
+            b' ' => encoded.push('+'),
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    url.replace("%s", &encoded)
+}
+
+/// Runs the command with the user's shell, on the host when running in a Flatpak.
+pub async fn run_command(command: String) -> Result<(), String> {
+    let shell = std::env::var("SHELL").unwrap_or_else(|_| "sh".to_string());
+    if std::env::var("FLATPAK_ID").is_ok() {
+        cosmic_classic_menu_core::launch::supervise(
+            "flatpak-spawn",
+            &["--host", &shell, "-c", &command],
+        )
+        .await
+    } else {
+        cosmic_classic_menu_core::launch::supervise(&shell, &["-c", &command]).await
+    }
+}
+
+/// Opens the path or URL with its default application.
+pub async fn open(target: String) -> Result<(), String> {
+    open::that_detached(&target).map_err(|e| format!("Failed to open '{}': {}", target, e))
+}
@@ -0,0 +1,70 @@
+use crate::logic::apps::application_dirs;
+
+/// Desktop entries of the COSMIC Settings pages start with this prefix.
+const PANEL_ID_PREFIX: &str = "com.system76.CosmicSettings.";
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SettingsPanel {
+    /// Localized name of the page, e.g. "Displays".
+    pub name: String,
+    /// Localized search terms of the page, e.g. "monitor" or "resolution".
+    pub keywords: Vec<String>,
+    /// Page argument of `cosmic-settings`, e.g. `display`.
+    pub page: String,
+    pub icon: String,
+}
+
+/// Loads the pages COSMIC Settings installs desktop entries for, in the order they're found.
+pub async fn load() -> Vec<SettingsPanel> {
+    let locales = crate::i18n::locale_fallback_chain();
+    let mut panels: Vec<SettingsPanel> = Vec::new();
+    for path in freedesktop_desktop_entry::Iter::new(application_dirs().into_iter()) {
+        let is_panel = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| name.starts_with(PANEL_ID_PREFIX));
+        if !is_panel {
+            continue;
+        }
+        let Ok(entry) =
+            freedesktop_desktop_entry::DesktopEntry::from_path(&path, Some(locales.as_slice()))
+        else {
+            continue;
+        };
+        // Only the pages opened with `cosmic-settings <page>` can be deep-linked
+        let Some(page) = entry.exec().and_then(page_of) else {
+            continue;
+        };
+        // Earlier data directories take precedence
+        if panels.iter().any(|panel| panel.page == page) {
+            continue;
+        }
+
+        panels.push(SettingsPanel {
+            name: entry.name(&locales).unwrap_or_default().into_owned(),
+            keywords: entry
+                .keywords(&locales)
+                .unwrap_or_default()
+                .into_iter()
+                .map(|keyword| keyword.into_owned())
+                .collect(),
+            page,
+            icon: entry
+                .icon()
+                .unwrap_or("preferences-system-symbolic")
+                .to_string(),
+        });
+    }
+    panels
+}
+
+/// Returns the page of a `cosmic-settings <page>` command line.
+fn page_of(exec: &str) -> Option<String> {
+    let mut args = exec.split_whitespace();
+    if args.next()?.rsplit('/').next()? != "cosmic-settings" {
+        return None;
+    }
+    args.next()
+        .filter(|page| !page.starts_with('%'))
+        .map(str::to_string)
+}
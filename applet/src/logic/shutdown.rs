@@ -0,0 +1,35 @@
+use std::hash::Hash;
+use std::time::Duration;
+
+use cosmic::iced::{stream, Subscription};
+use cosmic::iced_futures::futures::{self, SinkExt};
+use tokio::signal::unix::{signal, SignalKind};
+
+/// Time allowed for the cleanup before the process exits regardless.
+pub const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Emits an event once the panel asks the applet to terminate with `SIGTERM` or `SIGINT`.
+pub fn termination_requests<I: 'static + Hash + Copy + Send + Sync>(id: I) -> Subscription<()> {
+    Subscription::run_with_id(
+        id,
+        stream::channel(1, move |mut output| async move {
+            match (
+                signal(SignalKind::terminate()),
+                signal(SignalKind::interrupt()),
+            ) {
+                (Ok(mut terminate), Ok(mut interrupt)) => {
+                    tokio::select! {
+                        _ = terminate.recv() => {}
+                        _ = interrupt.recv() => {}
+                    }
+                    _ = output.send(()).await;
+                }
+                (Err(e), _) | (_, Err(e)) => {
+                    eprintln!("Failed to install termination signal handlers: {}", e);
+                }
+            }
+
+            futures::future::pending().await
+        }),
+    )
+}
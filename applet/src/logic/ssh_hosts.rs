@@ -0,0 +1,89 @@
+use std::path::{Path, PathBuf};
+
+/// How deep `Include` directives are followed, guarding against include loops.
+const MAX_INCLUDE_DEPTH: usize = 8;
+
+/// Returns the host aliases from `~/.ssh/config` and the files it includes, in their order.
+///
+/// Patterns like `*.example.com` or `!bastion` can't be connected to and are left out.
+pub async fn load() -> Vec<String> {
+    let Some(ssh_dir) = std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".ssh"))
+    else {
+        return Vec::new();
+    };
+
+    let mut hosts = Vec::new();
+    read_config(&ssh_dir.join("config"), &ssh_dir, 0, &mut hosts);
+    hosts
+}
+
+fn read_config(path: &Path, ssh_dir: &Path, depth: usize, hosts: &mut Vec<String>) {
+    if depth > MAX_INCLUDE_DEPTH {
+        return;
+    }
+    let Ok(contents) = std::fs::read_to_string(path) else {
+        return;
+    };
+
+    for line in contents.lines() {
+        let line = line.trim();
+        // Keywords are case-insensitive and may be separated from their value by `=`
+        let Some((keyword, value)) = line.split_once(|c: char| c.is_whitespace() || c == '=')
+        else {
+            continue;
+        };
+        let value = value.trim_start_matches(|c: char| c.is_whitespace() || c == '=');
+
+        if keyword.eq_ignore_ascii_case("Host") {
+            for host in value.split_whitespace() {
+                let is_pattern = host.contains(['*', '?', '!']);
+                if !is_pattern && !hosts.iter().any(|known| known == host) {
+                    hosts.push(host.to_string());
+                }
+            }
+        } else if keyword.eq_ignore_ascii_case("Include") {
+            for pattern in value.split_whitespace() {
+                for included in expand_include(pattern, ssh_dir) {
+                    read_config(&included, ssh_dir, depth + 1, hosts);
+                }
+            }
+        }
+    }
+}
+
+/// Resolves an `Include` relative to `~/.ssh`, expanding `~` and a `*` in the file name.
+fn expand_include(pattern: &str, ssh_dir: &Path) -> Vec<PathBuf> {
+    let path = match pattern.strip_prefix("~/") {
+        Some(relative) => ssh_dir.parent().unwrap_or(ssh_dir).join(relative),
+        None => ssh_dir.join(pattern),
+    };
+    let Some(file_pattern) = path.file_name().and_then(|name| name.to_str()) else {
+        return Vec::new();
+    };
+    let Some((prefix, suffix)) = file_pattern.split_once('*') else {
+        return vec![path];
+    };
+
+    let Some(dir) = path.parent() else {
+        return Vec::new();
+    };
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| {
+                    name.len() >= prefix.len() + suffix.len()
+                        && name.starts_with(prefix)
+                        && name.ends_with(suffix)
+                })
+        })
+        .filter(|path| path.is_file())
+        .collect();
+    // Matches are read in lexical order, like ssh does
+    paths.sort();
+    paths
+}
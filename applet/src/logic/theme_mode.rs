@@ -0,0 +1,10 @@
+use cosmic::cosmic_config::{self, CosmicConfigEntry};
+use cosmic::cosmic_theme::ThemeMode;
+
+/// Switches the desktop between the dark and the light theme, the same way COSMIC Settings
+/// does, so every running app follows.
+pub fn set_dark_mode(is_dark: bool) -> Result<(), cosmic_config::Error> {
+    let config = ThemeMode::config()?;
+    let mut theme_mode = ThemeMode::get_entry(&config).unwrap_or_else(|(_errs, mode)| mode);
+    theme_mode.set_is_dark(&config, is_dark)
+}
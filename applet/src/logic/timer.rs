@@ -0,0 +1,88 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use zbus::zvariant::Value;
+
+use crate::fl;
+use crate::notifications::NotificationsProxy;
+
+/// Longest timer that can be started, anything longer is more likely a typo.
+const MAX_DURATION: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Parses queries like `10m tea`, `1h30m` or `45s` into the timer duration and its label.
+pub fn parse_query(query: &str) -> Option<(Duration, String)> {
+    let query = query.trim();
+    let (duration, label) = query.split_once(char::is_whitespace).unwrap_or((query, ""));
+
+    let mut seconds = 0u64;
+    let mut amount = String::new();
+    for c in duration.chars() {
+        match c {
+            '0'..='9' => amount.push(c),
+            'h' | 'm' | 's' if !amount.is_empty() => {
+                let unit = match c {
+                    'h' => 60 * 60,
+                    'm' => 60,
+                    _ => 1,
+                };
+                seconds = seconds.checked_add(amount.parse::<u64>().ok()?.checked_mul(unit)?)?;
+                amount.clear();
+            }
+            _ => return None,
+        }
+    }
+    // Every amount needs its unit
+    if !amount.is_empty() || seconds == 0 {
+        return None;
+    }
+
+    let duration = Duration::from_secs(seconds);
+    (duration <= MAX_DURATION).then(|| (duration, label.trim().to_string()))
+}
+
+/// Returns the duration in its largest whole units, e.g. `1 h 30 min`.
+pub fn format_duration(duration: Duration) -> String {
+    let seconds = duration.as_secs();
+    let parts = [
+        (seconds / 3600, "h"),
+        (seconds % 3600 / 60, "min"),
+        (seconds % 60, "s"),
+    ];
+
+    parts
+        .iter()
+        .filter(|(amount, _)| *amount > 0)
+        .map(|(amount, unit)| format!("{} {}", amount, unit))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Waits for the duration, then notifies the user the timer is up.
+pub async fn run(duration: Duration, label: String) -> zbus::Result<()> {
+    tokio::time::sleep(duration).await;
+
+    let summary = if label.is_empty() {
+        fl!("timer-finished")
+    } else {
+        label
+    };
+    let body = fl!("timer-finished-after", duration = format_duration(duration));
+
+    let conn = zbus::Connection::session().await?;
+    let notifications = NotificationsProxy::new(&conn).await?;
+    let urgency = Value::U8(2);
+    let hints = HashMap::from([("urgency", &urgency)]);
+    notifications
+        .notify(
+            &fl!("timer"),
+            0,
+            "alarm-symbolic",
+            &summary,
+            &body,
+            &[],
+            hints,
+            -1,
+        )
+        .await
+        .map(|_| ())
+}
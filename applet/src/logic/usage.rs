@@ -0,0 +1,156 @@
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::path::PathBuf;
+
+use cosmic::cosmic_config::{ConfigGet, ConfigSet};
+use cosmic::{
+    iced::{stream, Subscription},
+    iced_futures::futures::{self, SinkExt},
+};
+use notify::{Config, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc;
+
+use crate::config::{CosmicClassicMenuConfig, RecentApplication};
+
+/// Launch counts of the apps, kept in the state directory so launching an app doesn't
+/// rewrite the settings.
+#[derive(Default, Serialize, Deserialize)]
+struct Usage {
+    recent_applications: Vec<RecentApplication>,
+}
+
+/// Returns the applet's directory in `$XDG_STATE_HOME`.
+pub fn state_dir() -> Option<PathBuf> {
+    let state_home = std::env::var_os("XDG_STATE_HOME")
+        .map(PathBuf::from)
+        .filter(|path| path.is_absolute())
+        .or_else(|| {
+            std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/state"))
+        })?;
+    Some(state_home.join(crate::applet::APP_ID))
+}
+
+fn usage_path() -> Option<PathBuf> {
+    state_dir().map(|dir| dir.join("usage.ron"))
+}
+
+/// Returns the recently used apps, moving them out of the settings the first time.
+pub fn load() -> Vec<RecentApplication> {
+    let Some(path) = usage_path() else {
+        return vec![];
+    };
+    match std::fs::read_to_string(&path) {
+        Ok(contents) => match ron::from_str::<Usage>(&contents) {
+            Ok(usage) => usage.recent_applications,
+            Err(e) => {
+                eprintln!("Failed to read the usage data: {}", e);
+                vec![]
+            }
+        },
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => migrate(),
+        Err(e) => {
+            eprintln!("Failed to read the usage data: {}", e);
+            vec![]
+        }
+    }
+}
+
+/// Writes the recently used apps, replacing the previous ones.
+pub fn save(recent_applications: &[RecentApplication]) -> Result<(), String> {
+    let path = usage_path().ok_or("The state directory is unknown")?;
+    let usage = Usage {
+        recent_applications: recent_applications.to_vec(),
+    };
+
+    // Written next to the file first, so a crash never leaves half of it behind. Named after
+    // the process so other instances writing at the same time don't share it
+    let temporary_path = path.with_extension(format!("ron.{}.tmp", std::process::id()));
+    let contents = ron::to_string(&usage).map_err(|e| e.to_string())?;
+    std::fs::create_dir_all(path.parent().unwrap_or(&path))
+        .and_then(|_| std::fs::write(&temporary_path, contents))
+        .and_then(|_| std::fs::rename(&temporary_path, &path))
+        .map_err(|e| e.to_string())
+}
+
+/// Applies the change to the recently used apps as saved, so the changes of other instances
+/// aren't overwritten, and saves them if it reports a change. Returns the updated apps.
+pub fn update(
+    change: impl FnOnce(&mut Vec<RecentApplication>) -> bool,
+) -> Result<Vec<RecentApplication>, String> {
+    let mut recent_applications = load();
+    if change(&mut recent_applications) {
+        save(&recent_applications)?;
+    }
+    Ok(recent_applications)
+}
+
+/// Notifies when the usage data is written, by this or another instance of the applet.
+pub fn changes<I: 'static + Hash + Copy + Send + Sync + Debug>(id: I) -> Subscription<()> {
+    Subscription::run_with_id(
+        id,
+        stream::channel(10, move |mut output| async move {
+            let (Some(dir), Some(path)) = (state_dir(), usage_path()) else {
+                return futures::future::pending().await;
+            };
+            // The directory is watched as the file is replaced on every write
+            _ = std::fs::create_dir_all(&dir);
+
+            let handle = tokio::runtime::Handle::current();
+            let (tx, mut rx) = mpsc::channel(4);
+            let watcher = RecommendedWatcher::new(
+                move |res: Result<notify::Event, notify::Error>| {
+                    if let Ok(event) = res {
+                        if matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_))
+                            && event.paths.contains(&path)
+                        {
+                            _ = handle.block_on(tx.send(()));
+                        }
+                    }
+                },
+                Config::default(),
+            );
+
+            if let Ok(mut watcher) = watcher {
+                if watcher.watch(&dir, RecursiveMode::NonRecursive).is_ok() {
+                    while rx.recv().await.is_some() {
+                        _ = output.send(()).await;
+                    }
+                }
+            }
+
+            futures::future::pending().await
+        }),
+    )
+}
+
+/// Moves the recently used apps stored by earlier versions in the settings to the state file.
+fn migrate() -> Vec<RecentApplication> {
+    let Some(handler) = CosmicClassicMenuConfig::config_handler() else {
+        return vec![];
+    };
+    let recent_applications = handler
+        .get::<Vec<RecentApplication>>("recent_applications")
+        .unwrap_or_default();
+    if recent_applications.is_empty() {
+        return recent_applications;
+    }
+
+    println!(
+        "Moving {} recent applications to the state directory",
+        recent_applications.len()
+    );
+    match save(&recent_applications) {
+        // Cleared only once saved, so a failed write doesn't lose them
+        Ok(()) => {
+            if let Err(e) = handler.set("recent_applications", Vec::<RecentApplication>::new()) {
+                eprintln!(
+                    "Failed to clear the recent applications in the settings: {}",
+                    e
+                );
+            }
+        }
+        Err(e) => eprintln!("Failed to write the usage data: {}", e),
+    }
+    recent_applications
+}
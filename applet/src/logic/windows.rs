@@ -0,0 +1,255 @@
+use std::fmt;
+use std::hash::Hash;
+
+use cosmic::cctk::{
+    self,
+    cosmic_protocols::toplevel_management::v1::client::zcosmic_toplevel_manager_v1,
+    sctk::{
+        self,
+        reexports::{
+            calloop::{self, channel},
+            calloop_wayland_source::WaylandSource,
+        },
+        registry::{ProvidesRegistryState, RegistryState},
+        seat::{Capability, SeatHandler, SeatState},
+    },
+    toplevel_info::{ToplevelInfoHandler, ToplevelInfoState},
+    toplevel_management::{ToplevelManagerHandler, ToplevelManagerState},
+    wayland_client::{
+        globals::registry_queue_init, protocol::wl_seat::WlSeat, Connection, QueueHandle, WEnum,
+    },
+    wayland_protocols::ext::foreign_toplevel_list::v1::client::ext_foreign_toplevel_handle_v1::ExtForeignToplevelHandleV1,
+};
+use cosmic::iced::{stream, Subscription};
+use cosmic::iced_futures::futures::{self, SinkExt, StreamExt};
+use freedesktop_desktop_entry::IconSource;
+
+use super::apps::Apps;
+
+/// Identifies a window for as long as it's open.
+pub type WindowHandle = ExtForeignToplevelHandleV1;
+
+#[derive(Clone, Debug)]
+pub struct OpenWindow {
+    pub handle: WindowHandle,
+    pub title: String,
+    pub app_id: String,
+    /// Icon of the app the window belongs to.
+    pub icon: IconSource,
+}
+
+#[derive(Clone, Debug)]
+pub enum WindowEvent {
+    /// The compositor is tracked, windows can be activated through the activator.
+    Ready(WindowActivator),
+    /// The open windows, in the order they were opened.
+    Changed(Vec<OpenWindow>),
+}
+
+/// Asks the compositor to focus windows.
+#[derive(Clone)]
+pub struct WindowActivator(channel::Sender<WindowHandle>);
+
+impl WindowActivator {
+    pub fn activate(&self, handle: WindowHandle) {
+        if let Err(e) = self.0.send(handle) {
+            eprintln!("Failed to activate the window: {}", e);
+        }
+    }
+}
+
+impl fmt::Debug for WindowActivator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WindowActivator").finish_non_exhaustive()
+    }
+}
+
+/// Emits the open windows whenever one opens, closes or changes its title.
+///
+/// Relies on COSMIC's toplevel protocols, nothing is emitted under other compositors.
+pub fn open_windows<I: 'static + Hash + Copy + Send + Sync>(id: I) -> Subscription<WindowEvent> {
+    Subscription::run_with_id(
+        id,
+        stream::channel(8, move |mut output| async move {
+            // The Wayland queue blocks, so it's dispatched on its own thread
+            let (sender, mut events) = futures::channel::mpsc::unbounded();
+            std::thread::spawn(move || {
+                if let Err(e) = run(sender) {
+                    eprintln!("Failed to track the open windows: {}", e);
+                }
+            });
+
+            while let Some(event) = events.next().await {
+                _ = output.send(event).await;
+            }
+
+            futures::future::pending().await
+        }),
+    )
+}
+
+fn run(sender: futures::channel::mpsc::UnboundedSender<WindowEvent>) -> Result<(), String> {
+    let conn = Connection::connect_to_env().map_err(|e| e.to_string())?;
+    let (globals, event_queue) = registry_queue_init(&conn).map_err(|e| e.to_string())?;
+    let qh = event_queue.handle();
+
+    let mut event_loop = calloop::EventLoop::<State>::try_new().map_err(|e| e.to_string())?;
+    WaylandSource::new(conn, event_queue)
+        .insert(event_loop.handle())
+        .map_err(|e| e.to_string())?;
+
+    let (activator, activations) = channel::channel();
+    event_loop
+        .handle()
+        .insert_source(activations, |event, _, state: &mut State| {
+            if let channel::Event::Msg(handle) = event {
+                state.activate(&handle);
+            }
+        })
+        .map_err(|e| e.to_string())?;
+
+    let registry_state = RegistryState::new(&globals);
+    let toplevel_manager_state = ToplevelManagerState::try_new(&registry_state, &qh)
+        .ok_or("the compositor doesn't support managing windows")?;
+    let mut state = State {
+        toplevel_info_state: ToplevelInfoState::new(&registry_state, &qh),
+        toplevel_manager_state,
+        seat_state: SeatState::new(&globals, &qh),
+        registry_state,
+        windows: Vec::new(),
+        sender,
+    };
+    _ = state
+        .sender
+        .unbounded_send(WindowEvent::Ready(WindowActivator(activator)));
+
+    loop {
+        event_loop
+            .dispatch(None, &mut state)
+            .map_err(|e| e.to_string())?;
+        if state.sender.is_closed() {
+            return Ok(());
+        }
+    }
+}
+
+struct State {
+    registry_state: RegistryState,
+    seat_state: SeatState,
+    toplevel_info_state: ToplevelInfoState,
+    toplevel_manager_state: ToplevelManagerState,
+    windows: Vec<OpenWindow>,
+    sender: futures::channel::mpsc::UnboundedSender<WindowEvent>,
+}
+
+impl State {
+    fn activate(&mut self, handle: &WindowHandle) {
+        let Some(cosmic_toplevel) = self
+            .toplevel_info_state
+            .info(handle)
+            .and_then(|info| info.cosmic_toplevel.clone())
+        else {
+            return;
+        };
+        if let Some(seat) = self.seat_state.seats().next() {
+            self.toplevel_manager_state
+                .manager
+                .activate(&cosmic_toplevel, &seat);
+        }
+    }
+
+    /// Adds or refreshes the window, then reports the windows.
+    fn update_window(&mut self, handle: &WindowHandle) {
+        let Some(info) = self.toplevel_info_state.info(handle) else {
+            return;
+        };
+        let window = OpenWindow {
+            handle: handle.clone(),
+            title: info.title.clone(),
+            app_id: info.app_id.clone(),
+            icon: Apps::icon_of(&info.app_id),
+        };
+        match self
+            .windows
+            .iter_mut()
+            .find(|other| other.handle == *handle)
+        {
+            Some(other) => *other = window,
+            None => self.windows.push(window),
+        }
+        self.send_windows();
+    }
+
+    fn send_windows(&self) {
+        _ = self
+            .sender
+            .unbounded_send(WindowEvent::Changed(self.windows.clone()));
+    }
+}
+
+impl ProvidesRegistryState for State {
+    fn registry(&mut self) -> &mut RegistryState {
+        &mut self.registry_state
+    }
+
+    sctk::registry_handlers!(SeatState);
+}
+
+impl SeatHandler for State {
+    fn seat_state(&mut self) -> &mut SeatState {
+        &mut self.seat_state
+    }
+
+    fn new_seat(&mut self, _: &Connection, _: &QueueHandle<Self>, _: WlSeat) {}
+
+    fn new_capability(&mut self, _: &Connection, _: &QueueHandle<Self>, _: WlSeat, _: Capability) {}
+
+    fn remove_capability(
+        &mut self,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+        _: WlSeat,
+        _: Capability,
+    ) {
+    }
+
+    fn remove_seat(&mut self, _: &Connection, _: &QueueHandle<Self>, _: WlSeat) {}
+}
+
+impl ToplevelInfoHandler for State {
+    fn toplevel_info_state(&mut self) -> &mut ToplevelInfoState {
+        &mut self.toplevel_info_state
+    }
+
+    fn new_toplevel(&mut self, _: &Connection, _: &QueueHandle<Self>, toplevel: &WindowHandle) {
+        self.update_window(toplevel);
+    }
+
+    fn update_toplevel(&mut self, _: &Connection, _: &QueueHandle<Self>, toplevel: &WindowHandle) {
+        self.update_window(toplevel);
+    }
+
+    fn toplevel_closed(&mut self, _: &Connection, _: &QueueHandle<Self>, toplevel: &WindowHandle) {
+        self.windows.retain(|window| window.handle != *toplevel);
+        self.send_windows();
+    }
+}
+
+impl ToplevelManagerHandler for State {
+    fn toplevel_manager_state(&mut self) -> &mut ToplevelManagerState {
+        &mut self.toplevel_manager_state
+    }
+
+    fn capabilities(
+        &mut self,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+        _: Vec<WEnum<zcosmic_toplevel_manager_v1::ZcosmicToplelevelManagementCapabilitiesV1>>,
+    ) {
+    }
+}
+
+cctk::delegate_toplevel_info!(State);
+cctk::delegate_toplevel_manager!(State);
+sctk::delegate_seat!(State);
+sctk::delegate_registry!(State);
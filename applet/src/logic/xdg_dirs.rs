@@ -0,0 +1,40 @@
+use std::path::PathBuf;
+
+/// Returns the config directories, most important first.
+pub fn config_dirs() -> Vec<PathBuf> {
+    let config_home = std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .filter(|path| path.is_absolute())
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")));
+
+    config_home
+        .into_iter()
+        .chain(env_dirs("XDG_CONFIG_DIRS", "/etc/xdg"))
+        .collect()
+}
+
+/// Returns the data directories, most important first.
+pub fn data_dirs() -> Vec<PathBuf> {
+    let data_home = std::env::var_os("XDG_DATA_HOME")
+        .map(PathBuf::from)
+        .filter(|path| path.is_absolute())
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/share")));
+
+    data_home
+        .into_iter()
+        .chain(env_dirs("XDG_DATA_DIRS", "/usr/local/share:/usr/share"))
+        .collect()
+}
+
+/// Returns the colon separated absolute directories of the variable, or of the fallback when
+/// it's unset or empty.
+fn env_dirs(key: &str, fallback: &str) -> Vec<PathBuf> {
+    std::env::var(key)
+        .ok()
+        .filter(|dirs| !dirs.is_empty())
+        .unwrap_or_else(|| fallback.to_string())
+        .split(':')
+        .map(PathBuf::from)
+        .filter(|path| path.is_absolute())
+        .collect()
+}
@@ -0,0 +1,290 @@
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+use cosmic_classic_menu_core::categories;
+use freedesktop_desktop_entry::DesktopEntry;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+
+use super::xdg_dirs::{config_dirs, data_dirs};
+use crate::model::application_entry::ApplicationEntry;
+
+/// How many menu files may be merged into each other, guarding against merge loops.
+const MAX_MERGE_DEPTH: usize = 8;
+
+/// Submenus of the menu file, `None` until it's parsed.
+static MENU_CACHE: Lazy<RwLock<Option<Vec<MenuCategory>>>> = Lazy::new(|| RwLock::new(None));
+
+/// A top level submenu of the distro's applications menu.
+#[derive(Clone, Debug)]
+pub struct MenuCategory {
+    /// The `<Name>` of the submenu, which identifies it.
+    pub id: String,
+    /// Localized name from its `.directory` file, falling back to the id.
+    pub name: String,
+    /// Icon name from its `.directory` file.
+    pub icon: Option<String>,
+    /// Which apps the submenu includes.
+    pub rule: Rule,
+}
+
+/// Matching rule of a menu, following the `<Include>` and `<Exclude>` elements of the
+/// [menu specification](https://specifications.freedesktop.org/menu-spec/latest/).
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Rule {
+    All,
+    Category(String),
+    /// Desktop file id, like `firefox.desktop`.
+    Filename(String),
+    And(Vec<Rule>),
+    Or(Vec<Rule>),
+    /// Matches the apps matched by none of the rules.
+    Not(Vec<Rule>),
+}
+
+impl Rule {
+    pub fn matches(&self, app: &ApplicationEntry) -> bool {
+        match self {
+            Rule::All => true,
+            Rule::Category(category) => categories::contains(&app.category, category),
+            Rule::Filename(file_name) => {
+                file_name.strip_suffix(".desktop").unwrap_or(file_name) == app.id
+            }
+            Rule::And(rules) => rules.iter().all(|rule| rule.matches(app)),
+            Rule::Or(rules) => rules.iter().any(|rule| rule.matches(app)),
+            Rule::Not(rules) => !rules.iter().any(|rule| rule.matches(app)),
+        }
+    }
+}
+
+/// A submenu as it's declared, possibly across several merged files.
+#[derive(Default)]
+struct Submenu {
+    name: String,
+    directory: Option<String>,
+    include: Vec<Rule>,
+    exclude: Vec<Rule>,
+    deleted: bool,
+    only_unallocated: bool,
+}
+
+impl Submenu {
+    fn rule(&self) -> Rule {
+        Rule::And(vec![
+            Rule::Or(self.include.clone()),
+            Rule::Not(self.exclude.clone()),
+        ])
+    }
+}
+
+/// Loads the top level submenus of the applications menu configured for the desktop, parsed
+/// once until [`invalidate_cache`] is called.
+///
+/// Returns nothing when there's no menu file, so the built-in categories are used instead.
+/// Nested submenus, layouts and legacy directories aren't supported.
+pub fn load(locales: &[String]) -> Vec<MenuCategory> {
+    if let Some(menu) = MENU_CACHE.read().unwrap().as_ref() {
+        return menu.clone();
+    }
+
+    let menu = parse_menu(locales);
+    *MENU_CACHE.write().unwrap() = Some(menu.clone());
+    menu
+}
+
+/// Forgets the parsed menu, so it's parsed again the next time it's loaded.
+pub fn invalidate_cache() {
+    *MENU_CACHE.write().unwrap() = None;
+}
+
+/// Returns the directories menu files are looked up in, the most important first.
+pub fn menus_dirs() -> Vec<PathBuf> {
+    config_dirs()
+        .into_iter()
+        .map(|dir| dir.join("menus"))
+        .collect()
+}
+
+fn parse_menu(locales: &[String]) -> Vec<MenuCategory> {
+    let Some(path) = menu_file() else {
+        return Vec::new();
+    };
+
+    let mut submenus = Vec::new();
+    let mut directory_dirs = Vec::new();
+    parse_file(&path, 0, &mut submenus, &mut directory_dirs);
+
+    // Unallocated menus only get the apps that no other menu includes
+    let allocated: Vec<Rule> = submenus
+        .iter()
+        .filter(|submenu| !submenu.only_unallocated)
+        .map(Submenu::rule)
+        .collect();
+
+    submenus
+        .iter()
+        .filter(|submenu| !submenu.deleted)
+        .map(|submenu| {
+            let rule = if submenu.only_unallocated {
+                Rule::And(vec![submenu.rule(), Rule::Not(allocated.clone())])
+            } else {
+                submenu.rule()
+            };
+            let (name, icon) = submenu
+                .directory
+                .as_deref()
+                .and_then(|directory| read_directory(directory, &directory_dirs, locales))
+                .unwrap_or_else(|| (submenu.name.clone(), None));
+
+            MenuCategory {
+                id: submenu.name.clone(),
+                name,
+                icon,
+                rule,
+            }
+        })
+        .collect()
+}
+
+/// Returns the menu file of the `XDG_MENU_PREFIX` from the most important config directory
+/// that has one, falling back to `applications.menu`. The menus of other desktops are left
+/// alone, they don't fit this one.
+fn menu_file() -> Option<PathBuf> {
+    let menus_dirs = menus_dirs();
+    let find = |file_name: &str| {
+        menus_dirs
+            .iter()
+            .map(|dir| dir.join(file_name))
+            .find(|path| path.is_file())
+    };
+    let prefix = std::env::var("XDG_MENU_PREFIX").unwrap_or_default();
+
+    find(&format!("{}applications.menu", prefix)).or_else(|| find("applications.menu"))
+}
+
+/// Collects the submenus and directory dirs of a menu file and of the files merged into it.
+///
+/// Directory dirs are in increasing priority, like the files declare them.
+fn parse_file(
+    path: &Path,
+    depth: usize,
+    submenus: &mut Vec<Submenu>,
+    directory_dirs: &mut Vec<PathBuf>,
+) {
+    if depth > MAX_MERGE_DEPTH {
+        return;
+    }
+    let Ok(contents) = std::fs::read_to_string(path) else {
+        return;
+    };
+    // Menu files declare the DTD of the specification
+    let options = roxmltree::ParsingOptions {
+        allow_dtd: true,
+        ..Default::default()
+    };
+    let document = match roxmltree::Document::parse_with_options(&contents, options) {
+        Ok(document) => document,
+        Err(e) => {
+            eprintln!("Failed to parse the menu file {}: {}", path.display(), e);
+            return;
+        }
+    };
+    let base_dir = path.parent().unwrap_or(Path::new("/"));
+
+    for node in document
+        .root_element()
+        .children()
+        .filter(|node| node.is_element())
+    {
+        match node.tag_name().name() {
+            "Menu" => {
+                let submenu = parse_submenu(node);
+                match submenus.iter_mut().find(|other| other.name == submenu.name) {
+                    // Menus of the same name are merged, the later one winning
+                    Some(other) => {
+                        other.include.extend(submenu.include);
+                        other.exclude.extend(submenu.exclude);
+                        other.directory = submenu.directory.or(other.directory.take());
+                        other.deleted = submenu.deleted;
+                        other.only_unallocated = submenu.only_unallocated;
+                    }
+                    None => submenus.push(submenu),
+                }
+            }
+            "MergeFile" if node.attribute("type") != Some("parent") => {
+                if let Some(merged) = node.text() {
+                    let merged = base_dir.join(merged.trim());
+                    parse_file(&merged, depth + 1, submenus, directory_dirs);
+                }
+            }
+            "DirectoryDir" => {
+                if let Some(dir) = node.text() {
+                    directory_dirs.push(base_dir.join(dir.trim()));
+                }
+            }
+            "DefaultDirectoryDirs" => {
+                directory_dirs.extend(
+                    data_dirs()
+                        .into_iter()
+                        .rev()
+                        .map(|dir| dir.join("desktop-directories")),
+                );
+            }
+            _ => {}
+        }
+    }
+}
+
+fn parse_submenu(menu: roxmltree::Node) -> Submenu {
+    let mut submenu = Submenu::default();
+    for node in menu.children().filter(|node| node.is_element()) {
+        let text = || node.text().unwrap_or_default().trim().to_string();
+        match node.tag_name().name() {
+            "Name" => submenu.name = text(),
+            "Directory" => submenu.directory = Some(text()),
+            "Include" => submenu.include.extend(parse_rules(node)),
+            "Exclude" => submenu.exclude.extend(parse_rules(node)),
+            "Deleted" => submenu.deleted = true,
+            "NotDeleted" => submenu.deleted = false,
+            "OnlyUnallocated" => submenu.only_unallocated = true,
+            "NotOnlyUnallocated" => submenu.only_unallocated = false,
+            _ => {}
+        }
+    }
+    submenu
+}
+
+fn parse_rules(node: roxmltree::Node) -> Vec<Rule> {
+    node.children()
+        .filter(|child| child.is_element())
+        .filter_map(|child| {
+            let text = || child.text().map(|text| text.trim().to_string());
+            match child.tag_name().name() {
+                "All" => Some(Rule::All),
+                "Category" => text().map(Rule::Category),
+                "Filename" => text().map(Rule::Filename),
+                "And" => Some(Rule::And(parse_rules(child))),
+                "Or" => Some(Rule::Or(parse_rules(child))),
+                "Not" => Some(Rule::Not(parse_rules(child))),
+                _ => None,
+            }
+        })
+        .collect()
+}
+
+/// Reads the localized name and the icon from a `.directory` file.
+fn read_directory(
+    file_name: &str,
+    directory_dirs: &[PathBuf],
+    locales: &[String],
+) -> Option<(String, Option<String>)> {
+    let path = directory_dirs
+        .iter()
+        .rev()
+        .map(|dir| dir.join(file_name))
+        .find(|path| path.is_file())?;
+    let entry = DesktopEntry::from_path(&path, Some(locales)).ok()?;
+    let name = entry.name(locales)?.into_owned();
+
+    Some((name, entry.icon().map(str::to_string)))
+}
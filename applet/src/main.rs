@@ -2,11 +2,18 @@
 
 mod applet;
 mod i18n;
+mod instance;
 mod config;
+mod error;
 mod logic;
 mod power_options;
 mod cosmic_session;
+mod display_manager;
+mod file_manager;
+mod malcontent;
+mod notifications;
 mod session_manager;
+mod switcheroo_control;
 mod applet_button;
 mod applet_menu;
 mod model;
@@ -17,7 +24,22 @@ fn main() -> cosmic::iced::Result {
 
     // Enable localizations to be applied.
     i18n::init(&requested_languages);
-    
-    cosmic::applet::run::<applet::CosmicClassicMenu>(())
+
+    let flags = applet::Flags {
+        windowed: std::env::args().any(|arg| arg == "--windowed"),
+    };
+
+    // Run outside of the panel, for development and testing
+    if flags.windowed {
+        let settings = cosmic::app::Settings::default().size(cosmic::iced::Size::new(720.0, 640.0));
+        return cosmic::app::run::<applet::CosmicClassicMenu>(settings, flags);
+    }
+
+    // Defer to the instance already running for this panel instead of duplicating it
+    if !instance::claim() {
+        return Ok(());
+    }
+
+    cosmic::applet::run::<applet::CosmicClassicMenu>(flags)
 }
 
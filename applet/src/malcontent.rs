@@ -0,0 +1,15 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use zbus::proxy;
+
+/// Parental controls app filter which malcontent stores on the AccountsService user object.
+#[proxy(
+    interface = "com.endlessm.ParentalControls.AppFilter",
+    default_service = "org.freedesktop.Accounts"
+)]
+pub trait AppFilter {
+    /// Whether the list is an allowlist, followed by executable paths, Flatpak refs
+    /// and content types.
+    #[zbus(property)]
+    fn app_filter(&self) -> zbus::Result<(bool, Vec<String>)>;
+}
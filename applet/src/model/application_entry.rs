@@ -1,46 +1,123 @@
+use std::borrow::Cow;
+use std::path::PathBuf;
+
 use cosmic::desktop::DesktopEntryData;
+use cosmic_classic_menu_core::launch;
 use freedesktop_desktop_entry::{DesktopEntry, IconSource};
 
+use crate::config::NameDisplay;
+use crate::fl;
+
 #[derive(Clone, Debug)]
 /// Represents an application entry in the Cosmic Classic Menu.
 pub struct ApplicationEntry {
     pub name: String,
+    /// Kind of the app, e.g. "Web Browser".
+    pub generic_name: Option<String>,
     pub id: String,
     pub icon: IconSource,
     pub comment: Option<String>,
     pub exec: Option<String>,
     pub category: Vec<String>,
     pub is_terminal: bool,
+    pub source: ApplicationSource,
+    pub prefers_dgpu: bool,
+    pub path: Option<PathBuf>,
+    /// Whether the desktop file sets NoDisplay or Hidden, so it's only listed on request.
+    pub is_hidden: bool,
+    /// Program that has to be installed for the app to be listed, from `TryExec`.
+    pub try_exec: Option<String>,
+    /// Whether the program of the app is installed, resolved once when the entry is read.
+    pub is_installed: bool,
+}
+
+impl ApplicationEntry {
+    /// Looks up whether the program of the app is installed, as told by `TryExec`, or else by
+    /// the program of `Exec` when it's an absolute path.
+    pub fn resolve_installed(&self) -> bool {
+        if let Some(try_exec) = &self.try_exec {
+            return launch::is_installed(try_exec);
+        }
+        match self
+            .exec
+            .as_deref()
+            .and_then(|exec| launch::split_exec(exec).into_iter().next())
+        {
+            Some(program) if program.starts_with('/') => launch::is_installed(&program),
+            _ => true,
+        }
+    }
+
+    /// Returns the name the app is listed under, falling back to its name when it has
+    /// no generic name.
+    pub fn display_name(&self, name_display: NameDisplay) -> Cow<'_, str> {
+        match (name_display, self.generic_name.as_deref()) {
+            (NameDisplay::GenericName, Some(generic_name)) => Cow::Borrowed(generic_name),
+            (NameDisplay::GenericNameAndName, Some(generic_name)) => {
+                Cow::Owned(format!("{} — {}", generic_name, self.name))
+            }
+            _ => Cow::Borrowed(&self.name),
+        }
+    }
+
+    /// Letter of the alphabetical index the application is listed under, `#` for non-letters.
+    pub fn index_letter(&self) -> char {
+        match self.name.chars().next() {
+            Some(first) if first.is_alphabetic() => first.to_uppercase().next().unwrap_or(first),
+            _ => '#',
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+/// Represents where an application entry was installed from.
+pub enum ApplicationSource {
+    Flatpak,
+    Native,
+}
+
+impl ApplicationSource {
+    pub fn get_display_name(&self) -> String {
+        match self {
+            ApplicationSource::Flatpak => fl!("source-flatpak"),
+            ApplicationSource::Native => fl!("source-native"),
+        }
+    }
 }
 
 impl Into<ApplicationEntry> for DesktopEntryData {
     fn into(self) -> ApplicationEntry {
         ApplicationEntry {
             comment: get_comment(&self),
+            // Read from the parsed entry by the caller, only when it's shown
+            generic_name: None,
             is_terminal: get_is_terminal(&self),
+            source: get_source(&self),
+            prefers_dgpu: self.prefers_dgpu,
             id: self.id,
             name: self.name,
             icon: self.icon,
             exec: self.exec,
             category: self.categories,
+            path: self.path,
+            is_hidden: false,
+            try_exec: None,
+            is_installed: true,
         }
     }
 }
 
 fn get_comment(app: &DesktopEntryData) -> Option<String> {
+    // Spares parsing the desktop file again while the comments aren't shown
+    if !crate::logic::apps::Apps::loads_comments() {
+        return None;
+    }
     if let Some(path) = &app.path {
-        let locale = std::env::var("LANG")
-                .ok()
-                .and_then(|l| l.split(".").next().map(str::to_string));
-        let desktop_entry = DesktopEntry::from_path(path, Some(locale.as_slice()));
+        let locales = crate::i18n::locale_fallback_chain();
+        let desktop_entry = DesktopEntry::from_path(path, Some(locales.as_slice()));
 
         if let Ok(entry) = desktop_entry {
-            return Some(
-                entry
-                    .comment(locale.as_slice())
-                    .unwrap_or_default()
-                    .into_owned(),
-            );
+            return Some(entry.comment(&locales).unwrap_or_default().into_owned());
         }
     }
 
@@ -49,10 +126,8 @@ fn get_comment(app: &DesktopEntryData) -> Option<String> {
 
 fn get_is_terminal(app: &DesktopEntryData) -> bool {
     if let Some(path) = &app.path {
-        let locale = std::env::var("LANG")
-                .ok()
-                .and_then(|l| l.split(".").next().map(str::to_string));
-        let desktop_entry = DesktopEntry::from_path(path, Some(locale.as_slice()));
+        let locales = crate::i18n::locale_fallback_chain();
+        let desktop_entry = DesktopEntry::from_path(path, Some(locales.as_slice()));
 
         if let Ok(entry) = desktop_entry {
             return entry.terminal();
@@ -60,4 +135,17 @@ fn get_is_terminal(app: &DesktopEntryData) -> bool {
     }
 
     false
-}
\ No newline at end of file
+}
+
+fn get_source(app: &DesktopEntryData) -> ApplicationSource {
+    let is_flatpak = app
+        .path
+        .as_ref()
+        .is_some_and(|path| path.to_string_lossy().contains("/flatpak/exports/"));
+
+    if is_flatpak {
+        ApplicationSource::Flatpak
+    } else {
+        ApplicationSource::Native
+    }
+}
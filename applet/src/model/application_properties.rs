@@ -0,0 +1,97 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::PathBuf;
+
+use freedesktop_desktop_entry::IconSource;
+
+use crate::fl;
+use crate::model::application_entry::ApplicationEntry;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+/// Desktop entry keys that can be edited in the properties view.
+pub enum PropertyField {
+    Name,
+    Exec,
+    Icon,
+    Categories,
+}
+
+impl PropertyField {
+    pub const ALL: [PropertyField; 4] = [
+        PropertyField::Name,
+        PropertyField::Exec,
+        PropertyField::Icon,
+        PropertyField::Categories,
+    ];
+
+    /// Returns the key of the field in the `[Desktop Entry]` group.
+    pub fn key(&self) -> &'static str {
+        match self {
+            PropertyField::Name => "Name",
+            PropertyField::Exec => "Exec",
+            PropertyField::Icon => "Icon",
+            PropertyField::Categories => "Categories",
+        }
+    }
+
+    pub fn get_display_name(&self) -> String {
+        match self {
+            PropertyField::Name => fl!("properties-name"),
+            PropertyField::Exec => fl!("properties-command"),
+            PropertyField::Icon => fl!("properties-icon"),
+            PropertyField::Categories => fl!("properties-categories"),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+/// Properties of an application being edited, keeping track of the changed fields.
+pub struct ApplicationProperties {
+    pub app_name: String,
+    pub path: PathBuf,
+    values: BTreeMap<PropertyField, String>,
+    changed: BTreeSet<PropertyField>,
+}
+
+impl ApplicationProperties {
+    /// Returns the properties of the app, or `None` if it has no desktop file.
+    pub fn from_entry(app: &ApplicationEntry) -> Option<Self> {
+        let icon = match &app.icon {
+            IconSource::Name(name) => name.clone(),
+            IconSource::Path(path) => path.display().to_string(),
+        };
+        let values = BTreeMap::from([
+            (PropertyField::Name, app.name.clone()),
+            (PropertyField::Exec, app.exec.clone().unwrap_or_default()),
+            (PropertyField::Icon, icon),
+            (PropertyField::Categories, app.category.join(";")),
+        ]);
+
+        Some(ApplicationProperties {
+            app_name: app.name.clone(),
+            path: app.path.clone()?,
+            values,
+            changed: BTreeSet::new(),
+        })
+    }
+
+    pub fn get(&self, field: PropertyField) -> &str {
+        self.values
+            .get(&field)
+            .map(String::as_str)
+            .unwrap_or_default()
+    }
+
+    pub fn set(&mut self, field: PropertyField, value: String) {
+        self.values.insert(field, value);
+        self.changed.insert(field);
+    }
+
+    pub fn is_changed(&self) -> bool {
+        !self.changed.is_empty()
+    }
+
+    /// Returns the edited fields with their new values.
+    pub fn changes(&self) -> impl Iterator<Item = (PropertyField, &str)> {
+        self.changed.iter().map(|field| (*field, self.get(*field)))
+    }
+}
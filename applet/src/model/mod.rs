@@ -1 +1,3 @@
-pub mod application_entry;
\ No newline at end of file
+pub mod application_entry;
+pub mod application_properties;
+pub mod new_launcher;
\ No newline at end of file
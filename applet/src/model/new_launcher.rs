@@ -0,0 +1,56 @@
+#[derive(Clone, Debug, Default)]
+/// Launcher being created from the menu, written as a new desktop file.
+pub struct NewLauncher {
+    pub name: String,
+    pub exec: String,
+    pub icon: String,
+    pub terminal: bool,
+}
+
+impl NewLauncher {
+    /// Whether the launcher has everything a desktop entry requires.
+    pub fn is_valid(&self) -> bool {
+        !self.name.trim().is_empty() && !self.exec.trim().is_empty()
+    }
+
+    /// Returns the contents of the desktop file.
+    pub fn to_desktop_entry(&self) -> String {
+        let mut lines = vec![
+            "[Desktop Entry]".to_string(),
+            "Type=Application".to_string(),
+            format!("Name={}", self.name.trim()),
+            format!("Exec={}", self.exec.trim()),
+        ];
+        if !self.icon.trim().is_empty() {
+            lines.push(format!("Icon={}", self.icon.trim()));
+        }
+        lines.push(format!("Terminal={}", self.terminal));
+
+        let mut contents = lines.join("\n");
+        contents.push('\n');
+        contents
+    }
+
+    /// Returns the file name stem derived from the launcher name.
+    pub fn file_stem(&self) -> String {
+        let stem: String = self
+            .name
+            .trim()
+            .chars()
+            .map(|c| {
+                if c.is_alphanumeric() {
+                    c.to_ascii_lowercase()
+                } else {
+                    '-'
+                }
+            })
+            .collect();
+        let stem = stem.trim_matches('-');
+
+        if stem.is_empty() {
+            "launcher".to_string()
+        } else {
+            stem.to_string()
+        }
+    }
+}
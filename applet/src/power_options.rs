@@ -1,3 +1,6 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use zbus::zvariant::OwnedObjectPath;
 use zbus::Connection;
 use rustix::process::getuid;
 use logind_zbus::{
@@ -6,7 +9,44 @@ use logind_zbus::{
     user::UserProxy,
 };
 
-use crate::{cosmic_session::CosmicSessionProxy, session_manager::SessionManagerProxy};
+use crate::{
+    cosmic_session::CosmicSessionProxy, display_manager::DisplayManagerSeatProxy,
+    session_manager::SessionManagerProxy,
+};
+
+/// Names of the errors logind replies with when polkit doesn't allow an action.
+const AUTHORIZATION_ERRORS: [&str; 2] = [
+    "org.freedesktop.DBus.Error.AccessDenied",
+    "org.freedesktop.DBus.Error.InteractiveAuthorizationRequired",
+];
+
+/// Why a power action wasn't performed.
+#[derive(Clone, Debug)]
+pub enum PowerActionError {
+    /// Polkit denied the action, or there was no authentication agent to ask for a password.
+    NotAuthorized,
+    Failed(String),
+}
+
+impl From<zbus::Error> for PowerActionError {
+    fn from(error: zbus::Error) -> Self {
+        let error_name = match &error {
+            zbus::Error::MethodError(name, _, _) => Some(name.as_str()),
+            zbus::Error::FDO(fdo_error) => match fdo_error.as_ref() {
+                zbus::fdo::Error::AccessDenied(_) => Some(AUTHORIZATION_ERRORS[0]),
+                zbus::fdo::Error::InteractiveAuthorizationRequired(_) => {
+                    Some(AUTHORIZATION_ERRORS[1])
+                }
+                _ => None,
+            },
+            _ => None,
+        };
+        match error_name {
+            Some(name) if AUTHORIZATION_ERRORS.contains(&name) => PowerActionError::NotAuthorized,
+            _ => PowerActionError::Failed(error.to_string()),
+        }
+    }
+}
 
 pub async fn restart() -> zbus::Result<()> {
     let connection = Connection::system().await?;
@@ -20,6 +60,204 @@ pub async fn shutdown() -> zbus::Result<()> {
     manager_proxy.power_off(true).await
 }
 
+/// Asks logind to power off at the given time, replacing any shutdown scheduled before.
+pub async fn schedule_shutdown(at: SystemTime) -> zbus::Result<()> {
+    let connection = Connection::system().await?;
+    let manager_proxy = ManagerProxy::new(&connection).await?;
+    let usec = at
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_micros() as u64;
+    manager_proxy
+        .inner()
+        .call::<_, _, ()>("ScheduleShutdown", &("poweroff", usec))
+        .await
+}
+
+pub async fn cancel_scheduled_shutdown() -> zbus::Result<()> {
+    let connection = Connection::system().await?;
+    let manager_proxy = ManagerProxy::new(&connection).await?;
+    manager_proxy
+        .inner()
+        .call::<_, _, bool>("CancelScheduledShutdown", &())
+        .await
+        .map(|_| ())
+}
+
+/// Returns when logind is going to power off, if a shutdown is scheduled.
+pub async fn scheduled_shutdown() -> zbus::Result<Option<SystemTime>> {
+    let connection = Connection::system().await?;
+    let manager_proxy = ManagerProxy::new(&connection).await?;
+    let (kind, usec): (String, u64) = manager_proxy
+        .inner()
+        .get_property("ScheduledShutdown")
+        .await?;
+    Ok((!kind.is_empty() && usec > 0).then(|| UNIX_EPOCH + Duration::from_micros(usec)))
+}
+
+/// Returns whether the system can suspend, which it can't without support by the firmware
+/// or when it's disabled by the administrator.
+pub async fn can_suspend() -> zbus::Result<bool> {
+    let connection = Connection::system().await?;
+    let manager_proxy = ManagerProxy::new(&connection).await?;
+    let answer: String = manager_proxy.inner().call("CanSuspend", &()).await?;
+    // "challenge" means it's allowed after authenticating
+    Ok(matches!(answer.as_str(), "yes" | "challenge"))
+}
+
+/// Returns whether the firmware can be told to open its setup on the next boot.
+pub async fn can_reboot_to_firmware_setup() -> zbus::Result<bool> {
+    let connection = Connection::system().await?;
+    let manager_proxy = ManagerProxy::new(&connection).await?;
+    let answer: String = manager_proxy
+        .inner()
+        .call("CanRebootToFirmwareSetup", &())
+        .await?;
+    Ok(matches!(answer.as_str(), "yes" | "challenge"))
+}
+
+/// An entry of the boot loader menu, like another operating system.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BootEntry {
+    pub id: String,
+    pub title: String,
+}
+
+/// Returns the entries of systemd-boot that can be booted into on the next restart, except
+/// the firmware setup which is offered on its own.
+pub async fn boot_entries() -> zbus::Result<Vec<BootEntry>> {
+    let connection = Connection::system().await?;
+    let manager_proxy = ManagerProxy::new(&connection).await?;
+    let answer: String = manager_proxy
+        .inner()
+        .call("CanRebootToBootLoaderEntry", &())
+        .await?;
+    if !matches!(answer.as_str(), "yes" | "challenge") {
+        return Ok(Vec::new());
+    }
+
+    let ids: Vec<String> = manager_proxy
+        .inner()
+        .get_property("BootLoaderEntries")
+        .await?;
+    let titles = boot_entry_titles().await;
+    Ok(ids
+        .into_iter()
+        .filter(|id| id != "auto-reboot-to-firmware-setup")
+        .map(|id| {
+            let title = titles
+                .iter()
+                .find(|(entry_id, _)| *entry_id == id)
+                .map(|(_, title)| title.clone())
+                .unwrap_or_else(|| fallback_boot_entry_title(&id));
+            BootEntry { id, title }
+        })
+        .collect())
+}
+
+/// Titles of the boot entries as shown by the boot loader, empty when bootctl isn't
+/// available to read them.
+async fn boot_entry_titles() -> Vec<(String, String)> {
+    let Ok(output) = tokio::process::Command::new("bootctl")
+        .args(["list", "--json=short", "--no-pager"])
+        .output()
+        .await
+    else {
+        return Vec::new();
+    };
+    if !output.status.success() {
+        return Vec::new();
+    }
+
+    let entries: Vec<serde_json::Value> =
+        serde_json::from_slice(&output.stdout).unwrap_or_default();
+    entries
+        .iter()
+        .filter_map(|entry| {
+            let id = entry.get("id")?.as_str()?;
+            let title = entry
+                .get("showTitle")
+                .or_else(|| entry.get("title"))?
+                .as_str()?;
+            Some((id.to_string(), title.to_string()))
+        })
+        .collect()
+}
+
+/// Makes a title out of the id of an entry, like "Windows" out of "auto-windows".
+fn fallback_boot_entry_title(id: &str) -> String {
+    let name = id.strip_suffix(".conf").unwrap_or(id);
+    let name = name.strip_prefix("auto-").unwrap_or(name);
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => id.to_string(),
+    }
+}
+
+/// Makes the next restart boot into the given entry of the boot loader, or the default one
+/// again when `id` is empty.
+pub async fn set_boot_loader_entry(id: String) -> zbus::Result<()> {
+    let connection = Connection::system().await?;
+    let manager_proxy = ManagerProxy::new(&connection).await?;
+    manager_proxy
+        .inner()
+        .call::<_, _, ()>("SetRebootToBootLoaderEntry", &(id,))
+        .await
+}
+
+/// Makes the next restart boot into the default entry again, unless the system is already
+/// restarting into the one set before.
+pub async fn reset_boot_loader_entry() -> zbus::Result<()> {
+    let connection = Connection::system().await?;
+    let manager_proxy = ManagerProxy::new(&connection).await?;
+    let preparing_for_shutdown: bool = manager_proxy
+        .inner()
+        .get_property("PreparingForShutdown")
+        .await?;
+    if preparing_for_shutdown {
+        return Ok(());
+    }
+    set_boot_loader_entry(String::new()).await
+}
+
+/// Restarts into the given entry of the boot loader, for this boot only.
+pub async fn restart_to_boot_loader_entry(id: String) -> zbus::Result<()> {
+    let connection = Connection::system().await?;
+    let manager_proxy = ManagerProxy::new(&connection).await?;
+    set_boot_loader_entry(id).await?;
+    let result = manager_proxy.reboot(true).await;
+    // Otherwise the next restart, whenever it happens, would still boot into the entry
+    if result.is_err() {
+        if let Err(e) = set_boot_loader_entry(String::new()).await {
+            eprintln!("Failed to reset the restart into the boot entry: {}", e);
+        }
+    }
+    result
+}
+
+/// Restarts into the setup of the firmware, like the UEFI settings.
+pub async fn restart_to_firmware_setup() -> zbus::Result<()> {
+    let connection = Connection::system().await?;
+    let manager_proxy = ManagerProxy::new(&connection).await?;
+    manager_proxy
+        .inner()
+        .call::<_, _, ()>("SetRebootToFirmwareSetup", &(true,))
+        .await?;
+    let result = manager_proxy.reboot(true).await;
+    // Otherwise the next restart, whenever it happens, would still open the firmware setup
+    if result.is_err() {
+        if let Err(e) = manager_proxy
+            .inner()
+            .call::<_, _, ()>("SetRebootToFirmwareSetup", &(false,))
+            .await
+        {
+            eprintln!("Failed to reset the restart into the firmware setup: {}", e);
+        }
+    }
+    result
+}
+
 pub async fn suspend() -> zbus::Result<()> {
     let connection = Connection::system().await?;
     let manager_proxy = ManagerProxy::new(&connection).await?;
@@ -63,6 +301,72 @@ pub async fn lock() -> zbus::Result<()> {
     }
 }
 
+/// Locks the session, then asks the display manager to show its greeter so another user
+/// can log in or resume their session.
+///
+/// Display managers without the `org.freedesktop.DisplayManager` interface don't set
+/// `XDG_SEAT_PATH`, the session is only locked with them.
+pub async fn lock_and_switch_user() -> zbus::Result<()> {
+    lock().await?;
+
+    let Ok(seat_path) = std::env::var("XDG_SEAT_PATH") else {
+        return Ok(());
+    };
+    let connection = Connection::system().await?;
+    let seat = DisplayManagerSeatProxy::builder(&connection)
+        .path(seat_path)?
+        .build()
+        .await?;
+    seat.switch_to_greeter().await
+}
+
+/// Session of another user logged in on this machine.
+#[derive(Clone, Debug)]
+pub struct UserSession {
+    pub id: String,
+    pub user: String,
+}
+
+/// Returns the graphical sessions of the other logged in users, which can be switched to.
+pub async fn other_sessions() -> zbus::Result<Vec<UserSession>> {
+    let connection = Connection::system().await?;
+    let manager_proxy = ManagerProxy::new(&connection).await?;
+    let our_uid = getuid().as_raw() as u32;
+    let sessions: Vec<(String, u32, String, String, OwnedObjectPath)> =
+        manager_proxy.inner().call("ListSessions", &()).await?;
+
+    let mut other_sessions = vec![];
+    for (id, uid, user, _seat, session_path) in sessions {
+        if uid == our_uid {
+            continue;
+        }
+        let Ok(session) = SessionProxy::builder(&connection)
+            .path(session_path)?
+            .build()
+            .await
+        else {
+            continue;
+        };
+
+        if session.class().await == Ok(SessionClass::User)
+            && session.type_().await? != SessionType::TTY
+        {
+            other_sessions.push(UserSession { id, user });
+        }
+    }
+    Ok(other_sessions)
+}
+
+/// Brings the session to the foreground of its seat, switching the user.
+pub async fn activate_session(id: String) -> zbus::Result<()> {
+    let connection = Connection::system().await?;
+    let manager_proxy = ManagerProxy::new(&connection).await?;
+    manager_proxy
+        .inner()
+        .call::<_, _, ()>("ActivateSession", &(id,))
+        .await
+}
+
 pub async fn log_out() -> zbus::Result<()> {
     // TODO: Fix imports
     let session_type = std::env::var("XDG_CURRENT_DESKTOP").ok();
@@ -0,0 +1,21 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use std::collections::HashMap;
+
+use zbus::proxy;
+use zbus::zvariant::OwnedValue;
+
+#[proxy(
+    interface = "net.hadess.SwitcherooControl",
+    default_service = "net.hadess.SwitcherooControl",
+    default_path = "/net/hadess/SwitcherooControl"
+)]
+pub trait SwitcherooControl {
+    /// Whether the system has a second, non-default GPU.
+    #[zbus(property)]
+    fn has_dual_gpu(&self) -> zbus::Result<bool>;
+
+    /// Available GPUs with their `Name`, `Environment` and `Default` keys.
+    #[zbus(property, name = "GPUs")]
+    fn gpus(&self) -> zbus::Result<Vec<HashMap<String, OwnedValue>>>;
+}
@@ -0,0 +1,79 @@
+use std::cmp::Reverse;
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+
+/// How the categories are ordered in the menu.
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+pub enum CategoryOrder {
+    /// The order of the menu specification, or of the distro's menu file.
+    Specification,
+    /// By their localized names.
+    Alphabetical,
+    /// The categories with the most apps first.
+    AppCount,
+    /// The manual order, followed by the other categories alphabetically.
+    #[default]
+    Manual,
+}
+
+/// Returns whether an app listed in the desktop entry categories belongs to `category`.
+pub fn contains(app_categories: &[String], category: &str) -> bool {
+    app_categories
+        .iter()
+        .any(|app_category| app_category == category)
+}
+
+/// Returns the categories with at least one app listed in them, in their given order.
+pub fn used<'a, T>(
+    categories: impl IntoIterator<Item = T>,
+    app_categories: impl IntoIterator<Item = &'a String>,
+    id: impl Fn(&T) -> &str,
+) -> Vec<T> {
+    let used: HashSet<&str> = app_categories.into_iter().map(String::as_str).collect();
+    categories
+        .into_iter()
+        .filter(|category| {
+            let id = id(category);
+            !id.is_empty() && used.contains(id)
+        })
+        .collect()
+}
+
+/// Sorts categories the way the order describes, keeping the given order for the
+/// specification's order. `app_count` is only called when ordering by the app count.
+pub fn sort_by_order<T>(
+    categories: &mut [T],
+    order: CategoryOrder,
+    manual_order: &[String],
+    id: impl Fn(&T) -> &str,
+    name: impl Fn(&T) -> String,
+    app_count: impl Fn(&T) -> usize,
+) {
+    match order {
+        CategoryOrder::Specification => {}
+        CategoryOrder::Alphabetical => sort(categories, &[], id, name),
+        CategoryOrder::AppCount => categories.sort_by_cached_key(|category| {
+            (Reverse(app_count(category)), name(category).to_lowercase())
+        }),
+        CategoryOrder::Manual => sort(categories, manual_order, id, name),
+    }
+}
+
+/// Sorts categories by the manual order of their ids, or by their names when it's empty.
+///
+/// Categories missing from the manual order follow the ordered ones alphabetically.
+pub fn sort<T>(
+    categories: &mut [T],
+    manual_order: &[String],
+    id: impl Fn(&T) -> &str,
+    name: impl Fn(&T) -> String,
+) {
+    categories.sort_by_cached_key(|category| {
+        let position = manual_order
+            .iter()
+            .position(|ordered_id| ordered_id == id(category))
+            .unwrap_or(usize::MAX);
+        (position, name(category).to_lowercase())
+    });
+}
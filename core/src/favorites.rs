@@ -0,0 +1,45 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct FavoriteFolder {
+    pub name: String,
+    /// Desktop ids of the apps in the folder, in display order.
+    pub apps: Vec<String>,
+}
+
+/// Returns the pinned applications: the user's favorites followed by the system-wide
+/// ones they don't have yet, so vendor entries added later still show up.
+pub fn merge(favorites: &[String], system_favorites: &[String], removed: &[String]) -> Vec<String> {
+    let mut merged = favorites.to_vec();
+    for app_id in system_favorites {
+        if !merged.contains(app_id) && !removed.contains(app_id) {
+            merged.push(app_id.clone());
+        }
+    }
+    merged
+}
+
+/// Moves the app into the named folder, creating it if needed, or out of its folder
+/// when `folder` is `None`. Folders left empty are removed.
+pub fn move_to_folder(folders: &mut Vec<FavoriteFolder>, app_id: &str, folder: Option<String>) {
+    for existing in folders.iter_mut() {
+        existing.apps.retain(|id| id != app_id);
+    }
+    if let Some(name) = folder.filter(|name| !name.is_empty()) {
+        match folders.iter_mut().find(|existing| existing.name == name) {
+            Some(existing) => existing.apps.push(app_id.to_string()),
+            None => folders.push(FavoriteFolder {
+                name,
+                apps: vec![app_id.to_string()],
+            }),
+        }
+    }
+    folders.retain(|existing| !existing.apps.is_empty());
+}
+
+/// Returns whether the app is in one of the folders.
+pub fn is_in_folder(folders: &[FavoriteFolder], app_id: &str) -> bool {
+    folders
+        .iter()
+        .any(|folder| folder.apps.iter().any(|id| id == app_id))
+}
@@ -0,0 +1,309 @@
+use std::path::PathBuf;
+use std::process::{ExitStatus, Stdio};
+use std::time::Duration;
+
+use tokio::process::Command;
+
+/// How long a spawned command has to fail before it's considered successfully started.
+pub const STARTUP_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Returns whether the program is found in one of the `PATH` directories.
+pub fn which(program: &str) -> bool {
+    which_path(program).is_some()
+}
+
+/// Returns the path of the program in the first `PATH` directory containing it.
+pub fn which_path(program: &str) -> Option<PathBuf> {
+    std::env::var_os("PATH").and_then(|paths| {
+        std::env::split_paths(&paths)
+            .map(|dir| dir.join(program))
+            .find(|path| is_executable(path))
+    })
+}
+
+/// Returns whether the program exists, looking names without a slash up in `PATH` like the
+/// `TryExec` key of desktop entries.
+pub fn is_installed(program: &str) -> bool {
+    find_program(program).is_some()
+}
+
+/// Returns the path the program runs from, looking names without a slash up in `PATH`.
+pub fn find_program(program: &str) -> Option<PathBuf> {
+    if program.contains('/') {
+        let path = PathBuf::from(program);
+        is_executable(&path).then_some(path)
+    } else {
+        which_path(program)
+    }
+}
+
+/// Returns whether the path is a file with one of its execute permissions set, as the
+/// desktop entry specification asks of `TryExec`.
+fn is_executable(path: &std::path::Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+
+    std::fs::metadata(path)
+        .is_ok_and(|metadata| metadata.is_file() && metadata.permissions().mode() & 0o111 != 0)
+}
+
+/// Spawns the command and reports whether it failed to start or exited unsuccessfully
+/// within [`STARTUP_TIMEOUT`].
+///
+/// Commands still running after the timeout keep being awaited in the background,
+/// so their process is reaped once they exit instead of lingering as a zombie. They run in
+/// a process group of their own without the applet's standard streams.
+pub async fn supervise(program: &str, args: &[&str]) -> Result<(), String> {
+    try_supervise(program, args)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Like [`supervise`], telling a program that isn't installed apart from other failures.
+pub async fn try_supervise(program: &str, args: &[&str]) -> Result<(), SuperviseError> {
+    let mut command = Command::new(program);
+    command.args(args);
+    supervise_command(command, program).await
+}
+
+/// Why a supervised command didn't start successfully.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SuperviseError {
+    /// The program isn't installed.
+    NotFound(String),
+    /// The command failed to start or exited unsuccessfully within [`STARTUP_TIMEOUT`].
+    Failed(String),
+}
+
+impl std::fmt::Display for SuperviseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SuperviseError::NotFound(message) | SuperviseError::Failed(message) => {
+                f.write_str(message)
+            }
+        }
+    }
+}
+
+/// Spawns the `Exec` line of a desktop entry with the given variables set on top of the
+/// applet's environment, like libcosmic launches apps, reporting failures like [`supervise`].
+pub async fn supervise_exec(exec: String, env: Vec<(String, String)>) -> Result<(), String> {
+    let args = split_exec(&exec);
+    let Some((program, args)) = args.split_first() else {
+        return Err("The command is empty".to_string());
+    };
+
+    let mut command = Command::new(program);
+    command.args(args).envs(env);
+    supervise_command(command, program)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+async fn supervise_command(command: Command, program: &str) -> Result<(), SuperviseError> {
+    match spawn_detached(command, program).await? {
+        Some(status) if !status.success() => Err(SuperviseError::Failed(format!(
+            "'{}' exited with {}",
+            program, status
+        ))),
+        _ => Ok(()),
+    }
+}
+
+/// Spawns the command detached from the applet like [`supervise`], returning its exit status
+/// if it exited within [`STARTUP_TIMEOUT`], or `None` if it's still running.
+pub async fn spawn_detached(
+    mut command: Command,
+    program: &str,
+) -> Result<Option<ExitStatus>, SuperviseError> {
+    // Detached from the applet, so the command outlives it when the panel restarts it and
+    // doesn't write into its log
+    command
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .process_group(0);
+    let mut child = command.spawn().map_err(|e| {
+        let message = format!("Failed to start '{}': {}", program, e);
+        if e.kind() == std::io::ErrorKind::NotFound {
+            SuperviseError::NotFound(message)
+        } else {
+            SuperviseError::Failed(message)
+        }
+    })?;
+
+    match tokio::time::timeout(STARTUP_TIMEOUT, child.wait()).await {
+        Ok(Ok(status)) => Ok(Some(status)),
+        Ok(Err(e)) => Err(SuperviseError::Failed(format!(
+            "Failed to wait for '{}': {}",
+            program, e
+        ))),
+        Err(_) => {
+            let program = program.to_string();
+            tokio::spawn(async move {
+                if let Err(e) = child.wait().await {
+                    eprintln!("Failed to reap '{}': {}", program, e);
+                }
+            });
+            Ok(None)
+        }
+    }
+}
+
+/// Wraps the `Exec` line in `systemd-run`, so the app runs in a transient scope of its own.
+///
+/// The scope is named `app-<app id>-<random>.scope` like the desktop environments name theirs,
+/// with the characters systemd doesn't allow in unit names replaced, so a crashing or
+/// runaway app is contained in its own cgroup instead of the launcher's.
+pub fn scoped_exec(exec: &str, app_id: &str) -> String {
+    let app_id: String = app_id
+        .chars()
+        .map(|c| match c {
+            'a'..='z' | 'A'..='Z' | '0'..='9' | ':' | '_' | '.' => c,
+            _ => '_',
+        })
+        .collect();
+    let random = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|elapsed| elapsed.subsec_nanos())
+        .unwrap_or_default()
+        ^ std::process::id();
+
+    format!(
+        "systemd-run --user --scope --collect --quiet --unit=app-{}-{:x}.scope -- {}",
+        app_id, random, exec
+    )
+}
+
+/// Splits an `Exec` line into arguments, dropping its field codes and turning `%%` into `%`.
+pub fn split_exec(exec: &str) -> Vec<String> {
+    exec_args(exec)
+        .into_iter()
+        // Codes standing alone are left out with their argument, not passed as empty ones
+        .filter(|arg| !is_field_code(arg))
+        .map(|arg| {
+            map_field_codes(&arg, |code| match code {
+                '%' => "%".to_string(),
+                _ => String::new(),
+            })
+        })
+        .collect()
+}
+
+/// Splits an `Exec` line into its unquoted arguments, keeping their field codes.
+pub fn exec_args(exec: &str) -> Vec<String> {
+    let mut args = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+    let mut chars = exec.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => in_quotes = !in_quotes,
+            '\\' if in_quotes => current.extend(chars.next()),
+            c if c.is_whitespace() && !in_quotes => {
+                if !current.is_empty() {
+                    args.push(std::mem::take(&mut current));
+                }
+            }
+            c => current.push(c),
+        }
+    }
+    if !current.is_empty() {
+        args.push(current);
+    }
+    args
+}
+
+/// Joins arguments into an `Exec` line, quoting the ones that need it. Their field codes are
+/// kept, literal text has to be escaped with [`escape_field_codes`] first.
+pub fn join_exec<S: AsRef<str>>(args: &[S]) -> String {
+    args.iter()
+        .map(|arg| quote_exec_arg(arg.as_ref()))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Escapes the percent signs of literal text, so it's not mistaken for field codes.
+pub fn escape_field_codes(text: &str) -> String {
+    text.replace('%', "%%")
+}
+
+/// Returns whether the argument has a field code in it, like `%U` or `--file=%f`.
+pub fn has_field_code(arg: &str) -> bool {
+    let mut found = false;
+    map_field_codes(arg, |code| {
+        found |= code != '%';
+        String::new()
+    });
+    found
+}
+
+/// Puts the files or URLs in place of the file and URL field codes of an `Exec` line, or
+/// after its arguments when it has none, leaving the other field codes in place.
+pub fn exec_with_urls(exec: &str, urls: &[String]) -> String {
+    let urls: Vec<String> = urls.iter().map(|url| escape_field_codes(url)).collect();
+    let mut has_url_code = false;
+    let mut args = Vec::new();
+    for arg in exec_args(exec) {
+        // List codes stand alone and take an argument per URL
+        if arg == "%F" || arg == "%U" {
+            has_url_code = true;
+            args.extend(urls.iter().cloned());
+            continue;
+        }
+        args.push(map_field_codes(&arg, |code| match code {
+            'f' | 'u' | 'F' | 'U' => {
+                has_url_code = true;
+                urls.first().cloned().unwrap_or_default()
+            }
+            code => format!("%{}", code),
+        }));
+    }
+    if !has_url_code {
+        args.extend(urls);
+    }
+    join_exec(&args)
+}
+
+/// Quotes the argument when it has whitespace or characters reserved in an `Exec` line.
+fn quote_exec_arg(arg: &str) -> String {
+    const RESERVED: [char; 16] = [
+        '"', '\'', '\\', '>', '<', '~', '|', '&', ';', '$', '*', '?', '#', '(', ')', '`',
+    ];
+    if !arg.is_empty() && !arg.contains(|c: char| c.is_whitespace() || RESERVED.contains(&c)) {
+        return arg.to_string();
+    }
+
+    let mut quoted = String::from('"');
+    for c in arg.chars() {
+        if matches!(c, '"' | '`' | '$' | '\\') {
+            quoted.push('\\');
+        }
+        quoted.push(c);
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// Returns whether the argument is a single field code, like `%U`, the `%%` literal aside.
+fn is_field_code(arg: &str) -> bool {
+    arg.len() == 2 && arg.starts_with('%') && arg != "%%"
+}
+
+/// Replaces the field codes of an argument in a single pass, so the text they're replaced
+/// with is never scanned for codes again. `map` gets the character after each `%`, the `%`
+/// of the `%%` literal included.
+fn map_field_codes(arg: &str, mut map: impl FnMut(char) -> String) -> String {
+    let mut mapped = String::new();
+    let mut chars = arg.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '%' => match chars.next() {
+                Some(code) => mapped.push_str(&map(code)),
+                None => mapped.push('%'),
+            },
+            c => mapped.push(c),
+        }
+    }
+    mapped
+}
@@ -0,0 +1,23 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Menu logic of the Cosmic Classic Menu which doesn't depend on a toolkit or desktop.
+//!
+//! The applet, its settings and other frontends share these implementations:
+//!
+//! - [`ranking`] orders items by how well their names match a search query,
+//! - [`categories`] filters and orders the categories the applications are listed in,
+//! - [`recents`] records launches and ranks the recently used applications,
+//! - [`favorites`] merges the user's and system-wide favorites and groups them into folders,
+//! - [`launch`] looks up, splits, scopes and supervises the commands starting applications.
+//!
+//! Applications are identified by their desktop ids throughout, so the stores stay
+//! independent of how the desktop entries are indexed.
+//!
+//! Everything here operates on plain data, the tests in `tests/` cover it without a
+//! desktop session.
+
+pub mod categories;
+pub mod favorites;
+pub mod launch;
+pub mod ranking;
+pub mod recents;
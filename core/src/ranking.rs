@@ -0,0 +1,102 @@
+use fuzzy_matcher::{skim::SkimMatcherV2, FuzzyMatcher};
+use serde::{Deserialize, Serialize};
+
+/// How names are matched against the query. All but the fuzzy search ignore case.
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+pub enum SearchMode {
+    /// The characters of the query appear in order anywhere in the name.
+    #[default]
+    Fuzzy,
+    /// The name or one of its words starts with the query.
+    Prefix,
+    /// The query appears anywhere in the name.
+    Substring,
+    /// The name is the query.
+    Exact,
+}
+
+/// Returns the items whose name matches the query fuzzily, best matches first.
+///
+/// Items with equally good matches keep their order.
+pub fn rank<T>(
+    items: impl IntoIterator<Item = T>,
+    query: &str,
+    name: impl Fn(&T) -> String,
+) -> Vec<T> {
+    rank_with(SearchMode::Fuzzy, items, query, name)
+}
+
+/// Like [`rank`], matching the names the way the mode does.
+///
+/// Other than fuzzy matches, earlier matches rank higher.
+pub fn rank_with<T>(
+    mode: SearchMode,
+    items: impl IntoIterator<Item = T>,
+    query: &str,
+    name: impl Fn(&T) -> String,
+) -> Vec<T> {
+    let mut matches: Vec<(i64, T)> = items
+        .into_iter()
+        .filter_map(|item| match_name(mode, &name(&item), query).map(|(score, _)| (score, item)))
+        .collect();
+
+    matches.sort_by(|a, b| b.0.cmp(&a.0));
+    matches.into_iter().map(|(_, item)| item).collect()
+}
+
+/// Returns the indices of the characters of the name matching the query, if it matches.
+pub fn matched_indices(name: &str, query: &str) -> Option<Vec<usize>> {
+    matched_indices_with(SearchMode::Fuzzy, name, query)
+}
+
+/// Like [`matched_indices`], matching the name the way the mode does.
+pub fn matched_indices_with(mode: SearchMode, name: &str, query: &str) -> Option<Vec<usize>> {
+    match_name(mode, name, query).map(|(_, indices)| indices)
+}
+
+/// Returns the score of the match and the indices of the matched characters.
+fn match_name(mode: SearchMode, name: &str, query: &str) -> Option<(i64, Vec<usize>)> {
+    if mode == SearchMode::Fuzzy {
+        return SkimMatcherV2::default().fuzzy_indices(name, query);
+    }
+
+    // Lowercasing char by char keeps the indices in line with the name
+    let lowercase = |text: &str| -> Vec<char> {
+        text.chars()
+            .map(|c| c.to_lowercase().next().unwrap_or(c))
+            .collect()
+    };
+    let name = lowercase(name);
+    let query = lowercase(query);
+    let matches_at = |start: &usize| name[*start..].starts_with(&query);
+
+    let start = match mode {
+        SearchMode::Prefix => (0..name.len())
+            .filter(|&index| index == 0 || !name[index - 1].is_alphanumeric())
+            .find(matches_at),
+        SearchMode::Substring => (0..=name.len().checked_sub(query.len())?).find(matches_at),
+        SearchMode::Exact => (name == query).then_some(0),
+        SearchMode::Fuzzy => unreachable!(),
+    }?;
+
+    Some((-(start as i64), (start..start + query.len()).collect()))
+}
+
+/// Groups the items by their key, keeping their order within each group.
+///
+/// Groups are ordered by their first item, so the best matches stay on top.
+pub fn group_by<T, K: PartialEq>(items: Vec<T>, key: impl Fn(&T) -> K) -> Vec<T> {
+    let mut groups: Vec<(K, Vec<T>)> = Vec::new();
+    for item in items {
+        let item_key = key(&item);
+        match groups.iter_mut().find(|(group, _)| *group == item_key) {
+            Some((_, group_items)) => group_items.push(item),
+            None => groups.push((item_key, vec![item])),
+        }
+    }
+
+    groups
+        .into_iter()
+        .flat_map(|(_, group_items)| group_items)
+        .collect()
+}
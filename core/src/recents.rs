@@ -0,0 +1,175 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+const SECONDS_PER_DAY: f64 = 86_400.0;
+
+/// How the recently used apps are ordered.
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+pub enum RecentsOrder {
+    /// The most frequently and recently used first.
+    #[default]
+    MostUsed,
+    /// The last launched first.
+    LastUsed,
+}
+
+/// Time since an app was last launched, rounded down to the largest whole unit.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Elapsed {
+    /// Less than a minute ago.
+    JustNow,
+    Minutes(u64),
+    Hours(u64),
+    Days(u64),
+}
+
+impl Elapsed {
+    /// Returns the time elapsed from `last_launched` to `now`, `None` for records written
+    /// before timestamps were stored.
+    pub fn since(last_launched: u64, now: u64) -> Option<Self> {
+        if last_launched == 0 {
+            return None;
+        }
+
+        let minutes = now.saturating_sub(last_launched) / 60;
+        Some(match minutes {
+            0 => Elapsed::JustNow,
+            1..=59 => Elapsed::Minutes(minutes),
+            60..=1439 => Elapsed::Hours(minutes / 60),
+            _ => Elapsed::Days(minutes / 1440),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct RecentApplication {
+    pub app_id: String,
+    pub launch_count: u32,
+    /// Unix timestamp (in seconds) of the last launch, `0` if unknown.
+    #[serde(default)]
+    pub last_launched: u64,
+}
+
+impl RecentApplication {
+    /// Returns the launch count decayed exponentially by the time elapsed since the last launch.
+    ///
+    /// A `half_life_days` of `0` disables the decay, as does an unknown launch time
+    /// for records written before timestamps were stored.
+    pub fn decayed_launch_count(&self, now: u64, half_life_days: u32) -> f64 {
+        if half_life_days == 0 || self.last_launched == 0 {
+            return self.launch_count as f64;
+        }
+
+        let elapsed_days = now.saturating_sub(self.last_launched) as f64 / SECONDS_PER_DAY;
+        self.launch_count as f64 * 0.5f64.powf(elapsed_days / half_life_days as f64)
+    }
+}
+
+/// Counts a launch of the app at `now`, adding a record for apps launched the first time.
+pub fn record_launch(
+    recent_applications: &mut Vec<RecentApplication>,
+    app_id: &str,
+    now: u64,
+    half_life_days: u32,
+) {
+    match recent_applications
+        .iter_mut()
+        .find(|recent_app| recent_app.app_id == app_id)
+    {
+        Some(recent_app) => {
+            // Fold the decay into the stored count so it stays relative to `last_launched`
+            let decayed_count = recent_app.decayed_launch_count(now, half_life_days).round() as u32;
+            recent_app.launch_count = decayed_count.saturating_add(1);
+            recent_app.last_launched = now;
+        }
+        None => recent_applications.push(RecentApplication {
+            app_id: app_id.to_string(),
+            launch_count: 1,
+            last_launched: now,
+        }),
+    }
+}
+
+/// Drops the usage record of the app, returning whether it had one.
+pub fn forget(recent_applications: &mut Vec<RecentApplication>, app_id: &str) -> bool {
+    let count = recent_applications.len();
+    recent_applications.retain(|recent_app| recent_app.app_id != app_id);
+    recent_applications.len() != count
+}
+
+/// Returns whether the app matches one of the patterns, app ids with `*` standing for any
+/// text and `?` for any character, like `org.keepassxc.*`.
+pub fn is_excluded(app_id: &str, patterns: &[String]) -> bool {
+    let app_id: Vec<char> = app_id.chars().collect();
+    patterns.iter().any(|pattern| {
+        let pattern: Vec<char> = pattern.trim().chars().collect();
+        !pattern.is_empty() && matches_glob(&pattern, &app_id)
+    })
+}
+
+fn matches_glob(pattern: &[char], text: &[char]) -> bool {
+    match pattern.split_first() {
+        None => text.is_empty(),
+        Some(('*', rest)) => (0..=text.len()).any(|skipped| matches_glob(rest, &text[skipped..])),
+        Some((expected, rest)) => match text.split_first() {
+            Some((actual, text)) => {
+                (*expected == '?' || expected == actual) && matches_glob(rest, text)
+            }
+            None => false,
+        },
+    }
+}
+
+/// Drops the usage records of the apps matching the patterns, returning whether any had one.
+pub fn forget_excluded(
+    recent_applications: &mut Vec<RecentApplication>,
+    patterns: &[String],
+) -> bool {
+    let count = recent_applications.len();
+    recent_applications.retain(|recent_app| !is_excluded(&recent_app.app_id, patterns));
+    recent_applications.len() != count
+}
+
+/// Returns the ids of the recently used apps, the most frequently and recently used first.
+pub fn ranked(
+    recent_applications: &[RecentApplication],
+    now: u64,
+    half_life_days: u32,
+) -> Vec<&str> {
+    let mut ranked: Vec<(f64, &RecentApplication)> = recent_applications
+        .iter()
+        .map(|app| (app.decayed_launch_count(now, half_life_days), app))
+        .collect();
+
+    ranked.sort_by(|a, b| b.0.total_cmp(&a.0));
+    ranked
+        .into_iter()
+        .map(|(_, app)| app.app_id.as_str())
+        .collect()
+}
+
+/// Returns the ids of the recently used apps in the given order.
+pub fn ranked_by(
+    recent_applications: &[RecentApplication],
+    order: RecentsOrder,
+    now: u64,
+    half_life_days: u32,
+) -> Vec<&str> {
+    match order {
+        RecentsOrder::MostUsed => ranked(recent_applications, now, half_life_days),
+        RecentsOrder::LastUsed => {
+            let mut ranked: Vec<&RecentApplication> = recent_applications.iter().collect();
+            ranked.sort_by(|a, b| b.last_launched.cmp(&a.last_launched));
+            ranked.into_iter().map(|app| app.app_id.as_str()).collect()
+        }
+    }
+}
+
+/// Returns the current time as a Unix timestamp in seconds.
+pub fn unix_timestamp() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default()
+}
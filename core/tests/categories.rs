@@ -0,0 +1,78 @@
+use cosmic_classic_menu_core::categories::{self, CategoryOrder};
+
+#[test]
+fn contains_matches_whole_category_names() {
+    let app_categories = vec!["AudioVideo".to_string(), "Player".to_string()];
+
+    assert!(categories::contains(&app_categories, "Player"));
+    assert!(!categories::contains(&app_categories, "Audio"));
+}
+
+#[test]
+fn used_keeps_the_categories_of_the_apps_in_order() {
+    let app_categories = vec!["Utility".to_string(), "Audio".to_string()];
+    let used = categories::used(
+        ["Audio", "Video", "Utility", ""],
+        &app_categories,
+        |category| *category,
+    );
+
+    assert_eq!(used, vec!["Audio", "Utility"]);
+}
+
+#[test]
+fn sort_orders_alphabetically_without_a_manual_order() {
+    let mut sorted = vec!["video", "Audio", "game"];
+    categories::sort(
+        &mut sorted,
+        &[],
+        |category| *category,
+        |category| category.to_string(),
+    );
+
+    assert_eq!(sorted, vec!["Audio", "game", "video"]);
+}
+
+#[test]
+fn sort_puts_manually_ordered_categories_first() {
+    let mut sorted = vec!["audio", "game", "video", "office"];
+    let manual_order = vec!["video".to_string(), "game".to_string()];
+    categories::sort(
+        &mut sorted,
+        &manual_order,
+        |category| *category,
+        |category| category.to_string(),
+    );
+
+    assert_eq!(sorted, vec!["video", "game", "audio", "office"]);
+}
+
+#[test]
+fn sort_by_order_keeps_the_specification_order() {
+    let mut sorted = vec!["video", "audio", "game"];
+    categories::sort_by_order(
+        &mut sorted,
+        CategoryOrder::Specification,
+        &["game".to_string()],
+        |category| *category,
+        |category| category.to_string(),
+        |_| 0,
+    );
+
+    assert_eq!(sorted, vec!["video", "audio", "game"]);
+}
+
+#[test]
+fn sort_by_order_puts_the_fullest_categories_first() {
+    let mut sorted = vec![("audio", 2), ("game", 5), ("video", 2)];
+    categories::sort_by_order(
+        &mut sorted,
+        CategoryOrder::AppCount,
+        &[],
+        |(category, _)| *category,
+        |(category, _)| category.to_string(),
+        |(_, app_count)| *app_count,
+    );
+
+    assert_eq!(sorted, vec![("game", 5), ("audio", 2), ("video", 2)]);
+}
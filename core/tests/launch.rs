@@ -0,0 +1,146 @@
+use cosmic_classic_menu_core::launch::{
+    exec_args, exec_with_urls, find_program, has_field_code, is_installed, join_exec, scoped_exec,
+    split_exec, supervise_exec, try_supervise, SuperviseError,
+};
+
+fn run<F: std::future::Future>(future: F) -> F::Output {
+    tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap()
+        .block_on(future)
+}
+
+fn path_env() -> Vec<(String, String)> {
+    std::env::vars().filter(|(key, _)| key == "PATH").collect()
+}
+
+#[test]
+fn is_installed_checks_paths_and_path_lookups() {
+    let current_exe = std::env::current_exe().unwrap();
+
+    assert!(is_installed(current_exe.to_str().unwrap()));
+    assert!(!is_installed("/nonexistent/cosmic-classic-menu-test"));
+    assert!(!is_installed("cosmic-classic-menu-nonexistent-program"));
+}
+
+#[test]
+fn find_program_resolves_path_lookups() {
+    let current_exe = std::env::current_exe().unwrap();
+    let sh = find_program("sh").unwrap();
+
+    assert!(sh.is_absolute() && sh.ends_with("sh"));
+    assert_eq!(
+        find_program(current_exe.to_str().unwrap()).as_deref(),
+        Some(current_exe.as_path())
+    );
+    assert_eq!(
+        find_program("cosmic-classic-menu-nonexistent-program"),
+        None
+    );
+}
+
+#[test]
+fn is_installed_requires_executable_files() {
+    let path = std::env::temp_dir().join(format!("cosmic-classic-menu-{}", std::process::id()));
+    std::fs::write(&path, "").unwrap();
+    let installed = is_installed(path.to_str().unwrap());
+    std::fs::remove_file(&path).unwrap();
+
+    assert!(!installed);
+}
+
+#[test]
+fn split_exec_drops_field_codes_and_keeps_quoted_arguments() {
+    assert_eq!(
+        split_exec(r#"gimp --new-instance "My \"Image\".png" %U"#),
+        vec!["gimp", "--new-instance", r#"My "Image".png"#]
+    );
+}
+
+#[test]
+fn split_exec_unescapes_percent_signs_and_drops_embedded_field_codes() {
+    assert_eq!(
+        split_exec("printf 100%% --file=%f %% %U"),
+        vec!["printf", "100%", "--file=", "%"]
+    );
+}
+
+#[test]
+fn exec_with_urls_never_substitutes_codes_in_the_urls() {
+    let url = "https://example.com/%F0%9F%98%80 \"quoted\"".to_string();
+
+    assert_eq!(
+        split_exec(&exec_with_urls("firefox --new-tab %u", &[url.clone()])),
+        vec!["firefox".to_string(), "--new-tab".to_string(), url.clone()]
+    );
+    assert_eq!(
+        split_exec(&exec_with_urls("xdg-email", &[url.clone()])),
+        vec!["xdg-email".to_string(), url]
+    );
+}
+
+#[test]
+fn join_exec_keeps_quoted_arguments_whole() {
+    let exec = r#"app --title "Two  spaces" --dir=~/x %U"#;
+
+    assert_eq!(exec_args(&join_exec(&exec_args(exec))), exec_args(exec));
+    assert_eq!(exec_args(exec)[2], "Two  spaces");
+}
+
+#[test]
+fn has_field_code_finds_embedded_codes_but_not_literals() {
+    assert!(has_field_code("%U"));
+    assert!(has_field_code("--file=%f"));
+    assert!(!has_field_code("100%%"));
+    assert!(!has_field_code("@@u"));
+}
+
+#[test]
+fn scoped_exec_names_the_scope_after_the_app() {
+    let args = split_exec(&scoped_exec(
+        "google-chrome --incognito %U",
+        "google-chrome",
+    ));
+
+    assert_eq!(
+        &args[..5],
+        ["systemd-run", "--user", "--scope", "--collect", "--quiet"]
+    );
+    assert!(args[5].starts_with("--unit=app-google_chrome-"));
+    assert!(args[5].ends_with(".scope"));
+    assert_eq!(&args[6..], ["--", "google-chrome", "--incognito"]);
+}
+
+#[test]
+fn supervise_exec_reports_missing_programs() {
+    let result = run(supervise_exec(
+        "cosmic-classic-menu-missing-program".to_string(),
+        path_env(),
+    ));
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn supervise_exec_reports_commands_exiting_unsuccessfully() {
+    assert!(run(supervise_exec("false".to_string(), path_env())).is_err());
+    assert!(run(supervise_exec("true".to_string(), path_env())).is_ok());
+}
+
+#[test]
+fn try_supervise_tells_missing_programs_apart() {
+    assert!(matches!(
+        run(try_supervise("cosmic-classic-menu-missing-program", &[])),
+        Err(SuperviseError::NotFound(_))
+    ));
+    assert!(matches!(
+        run(try_supervise("false", &[])),
+        Err(SuperviseError::Failed(_))
+    ));
+}
+
+#[test]
+fn supervise_exec_rejects_empty_commands() {
+    assert!(run(supervise_exec("%U".to_string(), path_env())).is_err());
+}
@@ -0,0 +1,79 @@
+use cosmic_classic_menu_core::ranking::{
+    group_by, matched_indices, matched_indices_with, rank, rank_with, SearchMode,
+};
+
+#[test]
+fn rank_keeps_only_matching_items() {
+    let ranked = rank(["Files", "Firefox", "Terminal"], "fire", |name| {
+        name.to_string()
+    });
+
+    assert_eq!(ranked, vec!["Firefox"]);
+}
+
+#[test]
+fn rank_puts_better_matches_first() {
+    let ranked = rank(["Text Editor", "Terminal"], "term", |name| name.to_string());
+
+    assert_eq!(ranked.first(), Some(&"Terminal"));
+}
+
+#[test]
+fn rank_keeps_the_order_of_equal_matches() {
+    let ranked = rank([("Editor", 1), ("Editor", 2)], "edit", |(name, _)| {
+        name.to_string()
+    });
+
+    assert_eq!(ranked, vec![("Editor", 1), ("Editor", 2)]);
+}
+
+#[test]
+fn matched_indices_point_at_the_matched_characters() {
+    assert_eq!(matched_indices("Terminal", "term"), Some(vec![0, 1, 2, 3]));
+    assert_eq!(matched_indices("Terminal", "xyz"), None);
+}
+
+#[test]
+fn rank_with_prefix_matches_the_starts_of_words() {
+    let names = ["Text Editor", "Editor", "Credits"];
+    let ranked = rank_with(SearchMode::Prefix, names, "edit", |name| name.to_string());
+
+    assert_eq!(ranked, vec!["Editor", "Text Editor"]);
+}
+
+#[test]
+fn rank_with_substring_puts_earlier_matches_first() {
+    let names = ["Text Editor", "Credits", "Terminal"];
+    let ranked = rank_with(SearchMode::Substring, names, "EDIT", |name| {
+        name.to_string()
+    });
+
+    assert_eq!(ranked, vec!["Credits", "Text Editor"]);
+}
+
+#[test]
+fn rank_with_exact_only_keeps_equal_names() {
+    let names = ["Files", "Files Backup"];
+    let ranked = rank_with(SearchMode::Exact, names, "files", |name| name.to_string());
+
+    assert_eq!(ranked, vec!["Files"]);
+}
+
+#[test]
+fn matched_indices_with_point_at_the_matched_run() {
+    assert_eq!(
+        matched_indices_with(SearchMode::Substring, "Text Editor", "dit"),
+        Some(vec![6, 7, 8])
+    );
+    assert_eq!(
+        matched_indices_with(SearchMode::Prefix, "Text Editor", "dit"),
+        None
+    );
+}
+
+#[test]
+fn group_by_keeps_groups_in_the_order_of_their_first_item() {
+    let grouped = group_by(vec![("b", 1), ("a", 2), ("b", 3)], |(key, _)| *key);
+
+    assert_eq!(grouped, vec![("b", 1), ("b", 3), ("a", 2)]);
+}
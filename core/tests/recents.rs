@@ -0,0 +1,147 @@
+use cosmic_classic_menu_core::recents::{
+    forget, forget_excluded, is_excluded, ranked, ranked_by, record_launch, Elapsed,
+    RecentApplication, RecentsOrder,
+};
+
+const DAY: u64 = 86_400;
+
+fn recent(app_id: &str, launch_count: u32, last_launched: u64) -> RecentApplication {
+    RecentApplication {
+        app_id: app_id.to_string(),
+        launch_count,
+        last_launched,
+    }
+}
+
+#[test]
+fn record_launch_adds_apps_launched_the_first_time() {
+    let mut recent_applications = vec![];
+    record_launch(&mut recent_applications, "org.gnome.Calculator", 100, 30);
+
+    assert_eq!(
+        recent_applications,
+        vec![recent("org.gnome.Calculator", 1, 100)]
+    );
+}
+
+#[test]
+fn record_launch_counts_repeated_launches() {
+    let mut recent_applications = vec![recent("firefox", 3, 100)];
+    record_launch(&mut recent_applications, "firefox", 200, 0);
+
+    assert_eq!(recent_applications, vec![recent("firefox", 4, 200)]);
+}
+
+#[test]
+fn record_launch_folds_the_decay_into_the_count() {
+    let mut recent_applications = vec![recent("firefox", 8, DAY)];
+    record_launch(&mut recent_applications, "firefox", 61 * DAY, 30);
+
+    // Two half-lives leave a quarter of the launches, plus this one
+    assert_eq!(recent_applications, vec![recent("firefox", 3, 61 * DAY)]);
+}
+
+#[test]
+fn forget_drops_only_the_given_app() {
+    let mut recent_applications = vec![recent("firefox", 3, 100), recent("gimp", 1, 200)];
+
+    assert!(forget(&mut recent_applications, "firefox"));
+    assert_eq!(recent_applications, vec![recent("gimp", 1, 200)]);
+    assert!(!forget(&mut recent_applications, "firefox"));
+}
+
+#[test]
+fn is_excluded_matches_ids_and_globs() {
+    let patterns = vec!["org.keepassxc.*".to_string(), "signal-?esktop".to_string()];
+
+    assert!(is_excluded("org.keepassxc.KeePassXC", &patterns));
+    assert!(is_excluded("signal-desktop", &patterns));
+    assert!(!is_excluded("org.keepassxc", &patterns));
+    assert!(!is_excluded("firefox", &patterns));
+}
+
+#[test]
+fn is_excluded_ignores_blank_patterns() {
+    assert!(!is_excluded("firefox", &["  ".to_string()]));
+    assert!(is_excluded("firefox", &[" firefox ".to_string()]));
+}
+
+#[test]
+fn forget_excluded_drops_only_matching_apps() {
+    let mut recent_applications = vec![
+        recent("firefox", 3, 100),
+        recent("org.keepassxc.KeePassXC", 5, 200),
+    ];
+
+    assert!(forget_excluded(
+        &mut recent_applications,
+        &["org.keepassxc.*".to_string()]
+    ));
+    assert_eq!(recent_applications, vec![recent("firefox", 3, 100)]);
+    assert!(!forget_excluded(
+        &mut recent_applications,
+        &["org.keepassxc.*".to_string()]
+    ));
+}
+
+#[test]
+fn ranked_orders_by_launch_count() {
+    let recent_applications = vec![recent("files", 2, 0), recent("firefox", 5, 0)];
+
+    assert_eq!(
+        ranked(&recent_applications, 100 * DAY, 30),
+        vec!["firefox", "files"]
+    );
+}
+
+#[test]
+fn ranked_prefers_recent_launches_with_decay() {
+    let now = 100 * DAY;
+    let recent_applications = vec![
+        recent("gimp", 10, now - 60 * DAY),
+        recent("terminal", 4, now),
+    ];
+
+    assert_eq!(
+        ranked(&recent_applications, now, 30),
+        vec!["terminal", "gimp"]
+    );
+    // Without the decay, only the launch count matters
+    assert_eq!(
+        ranked(&recent_applications, now, 0),
+        vec!["gimp", "terminal"]
+    );
+}
+
+#[test]
+fn ranked_by_last_used_orders_by_launch_time() {
+    let recent_applications = vec![
+        recent("gimp", 10, 100),
+        recent("terminal", 1, 300),
+        recent("firefox", 5, 200),
+    ];
+
+    assert_eq!(
+        ranked_by(&recent_applications, RecentsOrder::LastUsed, 400, 0),
+        vec!["terminal", "firefox", "gimp"]
+    );
+    assert_eq!(
+        ranked_by(&recent_applications, RecentsOrder::MostUsed, 400, 0),
+        vec!["gimp", "firefox", "terminal"]
+    );
+}
+
+#[test]
+fn elapsed_rounds_down_to_the_largest_unit() {
+    let now = 100 * DAY;
+
+    assert_eq!(Elapsed::since(now - 30, now), Some(Elapsed::JustNow));
+    assert_eq!(Elapsed::since(now - 5 * 60, now), Some(Elapsed::Minutes(5)));
+    assert_eq!(Elapsed::since(now - 150 * 60, now), Some(Elapsed::Hours(2)));
+    assert_eq!(
+        Elapsed::since(now - 3 * DAY - 60, now),
+        Some(Elapsed::Days(3))
+    );
+    // Records written before timestamps were stored
+    assert_eq!(Elapsed::since(0, now), None);
+}
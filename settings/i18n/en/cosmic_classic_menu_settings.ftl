@@ -2,21 +2,77 @@ app-title = COSMIC Classic Menu
 about = About
 settings = Settings
 default-settings = Reset to defaults
+diagnostics = Diagnostics
+diagnostics-checking = Checking the desktop files…
+diagnostics-none = No problems found in the desktop files
+diagnostics-skipped = Not shown in the menu
+diagnostics-warnings = Possible problems
+diagnostics-unreadable = Couldn't be read: { $error }
+diagnostics-shadowed = Replaced by { $path }
+diagnostics-not-displayed = Hidden by NoDisplay or Hidden
+diagnostics-not-installed = Hidden, the TryExec program { $program } isn't installed
+diagnostics-missing-exec = Has no Exec line to launch
+diagnostics-program-not-found = The program { $program } isn't installed
+diagnostics-missing-icon = The icon { $icon } wasn't found
 repository = Repository
 support = Support
 
 general = General
 app-menu-position = App menu position
 search-field-position = Search field position
+focus-search-on-open = Focus the search field on open
+focus-search-on-open-description = Turn off to navigate the apps with the keyboard instead of typing a search
+menu-layout = Menu layout
 applet-button-style = Applet button style
 user-widget = User widget appearance
+locked-by-administrator = Locked by your administrator
 button-label = Button label
+button-label-max-length = Longest button label (characters)
+button-label-unlimited = Unlimited
+button-icon-source = Button icon source
+button-icon-custom = Chosen icon
+button-icon-distro-logo = Distribution logo
 button-icon = Button icon
+button-icon-description = Icons named <name>-dark or <name>-light next to the chosen one are used with the matching theme, and symbolic icons follow the theme's colors
 button-label-placeholder = Enter custom button label here...
 button-icon-placeholder = Pick an icon
 select-custom-icon = Select a custom icon
+icon-path-placeholder = Path to a PNG or SVG image
 select = Select
 icon-file = Icon file (*.svg, *.png)
+unknown-icon = Unknown
+launch-count-half-life = Forget launch history after
+show-default-apps = Show default apps
+show-default-apps-description = Web browser, email client, file manager and terminal at the top of the menu
+show-quick-toggles = Show quick toggles
+show-quick-toggles-description = Dark mode switch above the power options
+show-power-options = Show power options
+show-power-labels = Show power option labels
+group-search-results = Group search results by category
+search-mode = Search matching
+search-mode-description = Fuzzy matching also finds names with the typed letters spread out
+search-mode-fuzzy = Fuzzy
+search-mode-prefix = Start of words
+search-mode-substring = Anywhere in the name
+search-mode-exact = Whole name
+search-results-limit = Search results shown
+search-characters = Find emoji and special characters
+search-ssh-hosts = Find SSH hosts
+search-ssh-hosts-description = Hosts from ~/.ssh/config are opened in the terminal
+search-settings-panels = Find settings pages
+search-settings-panels-description = Pages of COSMIC Settings, like Displays or Bluetooth
+search-recent-documents = Find recent files
+search-recent-documents-description = Files recently opened in other apps, listed under Files
+search-bookmarks = Find browser bookmarks
+search-bookmarks-description = Bookmarks of Firefox and Chromium based browsers. Firefox bookmarks require sqlite3.
+clipboard-history = Show recently copied text
+clipboard-history-description = Copied text is only kept in memory and is forgotten when this is turned off. Requires wl-clipboard.
+show-windows = Show open windows
+show-windows-description = Lists the open windows in their own category to switch between them
+activate-running-apps = Switch to running apps
+activate-running-apps-description = Clicking an app with open windows focuses them in turn instead of launching it again
+launch-in-scope = Launch apps in their own scope
+launch-in-scope-description = Runs every app in a systemd scope, so a misbehaving app can't take the panel down with it
 
 top = Top
 bottom = Bottom
@@ -28,4 +84,84 @@ icon-and-label = Icon and Label
 auto = Auto
 username-prefered = Username prefered
 realname-prefered = Realname prefered
-none = None
\ No newline at end of file
+none = None
+layout-classic = Classic
+layout-wide = Wide (multiple columns)
+layout-grid = Grid
+layout-settings = Layout
+output-layouts = Layout per display
+output-layout-default = Same as menu layout
+minimal-popup = Minimal popup
+minimal-popup-automatic = On small screens
+minimal-popup-always = Always
+minimal-popup-never = Never
+category-navigation = Category navigation
+category-navigation-side-pane = Side pane
+category-navigation-drill-down = Open categories in place
+category-navigation-flat-list = One list with category headers
+category-sort = Sort categories
+category-sort-specification = Standard
+category-sort-alphabetical = Alphabetical
+category-sort-app-count = Most apps first
+category-sort-manual = Manual
+category-order = Category order
+context-menu-categories = Category shortcuts in the right-click menu
+context-menu-entries = Right-click menu
+context-menu-add-entry = Add entry
+recents-exclusions = Never shown in Recently Used
+recents-add-exclusion = Exclude apps
+recents-add-exclusion-description = App ids, or patterns like org.keepassxc.*
+recents-exclusion-placeholder = App id or pattern
+application-dirs = Additional application directories
+application-dirs-add = Add directory
+application-dirs-add-description = Also scanned and watched for desktop files, ~/ is your home folder
+application-dirs-placeholder = ~/Apps/launchers
+search-scripts = Search scripts
+search-scripts-add = Add script
+search-scripts-add-description = Receives the search on stdin and prints a JSON array of results with a title, an optional icon and the command to run
+search-scripts-placeholder = ~/.local/bin/search-notes
+context-menu-menu-settings = Menu settings
+context-menu-applet-settings = Classic Menu settings
+context-menu-create-launcher = Create launcher
+context-menu-show-hidden-entries = Show hidden entries
+context-menu-system-settings = System Settings
+context-menu-system-monitor = System Monitor
+context-menu-disks = Disks
+context-menu-separator = Separator
+show-empty-categories = Show empty categories
+show-empty-categories-description = Also lists the categories no installed app belongs to
+use-applications-menu = Use the distribution's categories
+use-applications-menu-description = Takes the categories from the applications menu your distribution configures, if any
+name-display = App names
+name-display-name = Name
+name-display-generic-name = Generic name
+name-display-both = Generic name and name
+touch-mode = Touch mode
+touch-mode-description = Larger apps and scrollbar, hold an app to show its actions
+double-click-launch = Double-click to launch
+double-click-launch-description = A single click only selects the app
+list-density = List density
+list-density-description = Icon size and row height of the apps, scaled further by the menu density
+density-comfortable = Comfortable
+density-cozy = Cozy
+density-compact = Compact
+menu-density = Menu density
+show-comments = Show app descriptions
+show-comments-description = A second line under the app names, single-line rows without it
+hide-uninstalled-apps = Hide uninstalled apps
+hide-uninstalled-apps-description = Leave out apps whose program is missing, like the leftovers of removed software
+menu-density-description = Spacing, row heights and text sizes of the whole menu
+icon-theme = Icon theme
+icon-theme-description = Icons of the apps and categories in the menu, independent of the system theme
+icon-theme-system = System theme
+columns = Columns
+columns-automatic = Fit to width
+icon-size = Icon size
+icon-size-pixels = { $size } px
+grid-paginated = Show apps in pages
+half-life-never = Never
+half-life-week = 1 week
+half-life-month = 1 month
+half-life-three-months = 3 months
+half-life-six-months = 6 months
+search-results-unlimited = All
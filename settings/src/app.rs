@@ -4,16 +4,22 @@ use crate::fl;
 use cosmic::app::context_drawer;
 use cosmic::cosmic_config::CosmicConfigEntry;
 use cosmic::dialog::file_chooser::FileFilter;
+use cosmic::iced::event::{self, wayland};
 use cosmic::iced::{Alignment, Length, Subscription};
 use cosmic::prelude::*;
 use cosmic::widget::{button, icon, menu};
 use cosmic::{iced::Background, widget::text, Element};
 use cosmic_classic_menu::config::{
-    AppletButtonStyle, CosmicClassicMenuConfig, HorizontalPosition, UserWidgetStyle,
-    VerticalPosition,
+    AppletButtonIcon, AppletButtonStyle, CategoryNavigation, CategoryOrder, ContextMenuEntry,
+    CosmicClassicMenuConfig, HorizontalPosition, ListDensity, MenuDensity, MenuLayout,
+    MinimalPopup, NameDisplay, SearchMode, SystemPolicy, UserWidgetStyle, VerticalPosition,
 };
+use cosmic_classic_menu::logic::apps::{ApplicationCategory, Apps};
+use cosmic_classic_menu::logic::diagnostics::{self, Diagnostic, Problem};
+use cosmic_classic_menu::logic::icon_themes;
+use cosmic_classic_menu::logic::integrations::Integrations;
 use futures_util::SinkExt;
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
 use std::fs;
 use std::path::PathBuf;
 
@@ -30,6 +36,24 @@ pub struct AppModel {
     key_binds: HashMap<menu::KeyBind, MenuAction>,
     // Configuration data that persists between application runs.
     config: CosmicClassicMenuConfig,
+    /// Settings enforced by the administrator, shown locked instead of editable.
+    policy: SystemPolicy,
+    /// Names of the connected outputs.
+    outputs: BTreeSet<String>,
+    /// Desktop services found at startup.
+    integrations: Integrations,
+    /// Categories of the installed apps in their current order, without the pinned ones.
+    categories: Vec<ApplicationCategory>,
+    /// Names of the installed icon themes.
+    icon_themes: Vec<String>,
+    /// Pattern typed to be excluded from the recently used apps.
+    new_recents_exclusion: String,
+    /// Directory typed to be scanned for desktop files.
+    new_application_dir: String,
+    /// Path typed to be queried as a search provider.
+    new_search_script: String,
+    /// Problems of the desktop files, `None` while they're checked.
+    diagnostics: Option<Vec<Diagnostic>>,
 }
 
 /// Messages emitted by the application and its widgets.
@@ -41,14 +65,91 @@ pub enum Message {
     AppPositionChanged(HorizontalPosition),
     SearchFieldPositionChanged(VerticalPosition),
     AppletButtonStyleChanged(usize),
+    ButtonIconSourceChanged(usize),
     UserWidgetChanged(usize),
     ButtonLabelChanged(String),
+    ButtonLabelMaxLengthChanged(usize),
     ToggleContextPage(ContextPage),
     OpenIconPicker,
+    ShowDiagnostics,
+    UpdateDiagnostics(Vec<Diagnostic>),
     ButtonIconChanged(PathBuf),
     CustomIconSelected,
+    LaunchCountHalfLifeChanged(usize),
+    MenuLayoutChanged(usize),
+    MinimalPopupChanged(usize),
+    CategoryNavigationChanged(usize),
+    NameDisplayChanged(usize),
+    MenuDensityChanged(usize),
+    IconThemeChanged(usize),
+    ShowCommentsChanged(bool),
+    HideUninstalledAppsChanged(bool),
+    ShowEmptyCategoriesChanged(bool),
+    UseApplicationsMenuChanged(bool),
+    SearchModeChanged(usize),
+    ListDensityChanged(usize),
+    WideColumnsChanged(usize),
+    WideDensityChanged(usize),
+    GridColumnsChanged(usize),
+    GridIconSizeChanged(usize),
+    GridPaginatedChanged(bool),
+    ShowDefaultAppsChanged(bool),
+    ShowQuickTogglesChanged(bool),
+    ShowPowerOptionsChanged(bool),
+    ShowPowerLabelsChanged(bool),
+    GroupSearchResultsChanged(bool),
+    SearchResultsLimitChanged(usize),
+    SearchCharactersChanged(bool),
+    SearchSshHostsChanged(bool),
+    SearchBookmarksChanged(bool),
+    SearchSettingsPanelsChanged(bool),
+    SearchRecentDocumentsChanged(bool),
+    ClipboardHistoryChanged(bool),
+    ShowWindowsChanged(bool),
+    ActivateRunningAppsChanged(bool),
+    LaunchInScopeChanged(bool),
+    TouchModeChanged(bool),
+    DoubleClickLaunchChanged(bool),
+    FocusSearchOnOpenChanged(bool),
+    OutputAdded(String),
+    UpdateIntegrations(Integrations),
+    UpdateCategories(Vec<ApplicationCategory>),
+    CategorySortChanged(usize),
+    MoveCategory(usize, usize),
+    ContextMenuCategoryToggled(String, bool),
+    AddContextMenuEntry(ContextMenuEntry),
+    MoveContextMenuEntry(usize, usize),
+    RemoveContextMenuEntry(usize),
+    RecentsExclusionInput(String),
+    AddRecentsExclusion,
+    RemoveRecentsExclusion(usize),
+    ApplicationDirInput(String),
+    AddApplicationDir,
+    RemoveApplicationDir(usize),
+    SearchScriptInput(String),
+    AddSearchScript,
+    RemoveSearchScript(usize),
+    OutputLayoutChanged(String, usize),
 }
 
+/// Selectable half-lives (in days) for the launch count decay, `0` disables the decay.
+const LAUNCH_COUNT_HALF_LIFE_OPTIONS: [u32; 5] = [0, 7, 30, 90, 180];
+
+/// Selectable maximum lengths (in characters) of the button label, `0` doesn't limit it.
+const BUTTON_LABEL_MAX_LENGTH_OPTIONS: [u16; 4] = [10, 20, 40, 0];
+
+/// Selectable search result limits, `0` shows all results.
+const SEARCH_RESULTS_LIMIT_OPTIONS: [u32; 4] = [10, 20, 50, 0];
+
+/// Selectable column counts of the wide layout.
+const WIDE_COLUMNS_OPTIONS: [u8; 2] = [2, 3];
+
+/// Selectable column counts of the grid layout, `0` fits the columns to the popup width.
+const GRID_COLUMNS_OPTIONS: [u8; 5] = [0, 3, 4, 5, 6];
+
+/// Selectable icon sizes (in logical pixels) of the grid layout.
+const GRID_ICON_SIZE_OPTIONS: [u16; 4] = [24, 32, 48, 64];
+
 /// Create a COSMIC application from the app model
 impl cosmic::Application for AppModel {
     /// The async executor that will be used to run your application's commands.
@@ -102,10 +203,28 @@ impl cosmic::Application for AppModel {
             context_page: ContextPage::default(),
             key_binds: HashMap::new(),
             // Optional configuration file for an application.
-            config: CosmicClassicMenuConfig::config(),
+            config: CosmicClassicMenuConfig::user_config(),
+            policy: SystemPolicy::policy(),
+            outputs: BTreeSet::new(),
+            integrations: Integrations::default(),
+            categories: Vec::new(),
+            icon_themes: icon_themes::installed(),
+            new_recents_exclusion: String::new(),
+            new_application_dir: String::new(),
+            new_search_script: String::new(),
+            diagnostics: None,
         };
 
-        (app, Task::none())
+        let probe_integrations_task =
+            Task::perform(cosmic_classic_menu::logic::integrations::probe(), |res| {
+                cosmic::Action::App(Message::UpdateIntegrations(res))
+            });
+        let load_categories_task = AppModel::load_categories(&app.config);
+
+        (
+            app,
+            Task::batch(vec![probe_integrations_task, load_categories_task]),
+        )
     }
 
     /// Elements to pack at the start of the header bar.
@@ -120,6 +239,7 @@ impl cosmic::Application for AppModel {
                         None,
                         MenuAction::SetDefaultSettings,
                     ),
+                    menu::Item::Button(fl!("diagnostics"), None, MenuAction::Diagnostics),
                     menu::Item::Button(fl!("about"), None, MenuAction::About),
                 ],
             ),
@@ -165,72 +285,526 @@ impl cosmic::Application for AppModel {
                 Message::SearchFieldPositionChanged
             )
         ];
-        let applet_button_style = cosmic::iced::widget::row![
+        let applet_button_styles = vec![
+            fl!("icon-only"),
+            fl!("label-only"),
+            fl!("icon-and-label"),
+            fl!("auto"),
+        ];
+        let applet_button_style: Element<Message> = match self.policy.applet_button_style {
+            Some(style) => AppModel::locked_control(applet_button_styles[style as usize].clone()),
+            None => cosmic::iced::widget::row![
+                cosmic::widget::Space::new(Length::Fill, 5),
+                cosmic::widget::dropdown(
+                    applet_button_styles,
+                    Some(self.config.applet_button_style as usize),
+                    Message::AppletButtonStyleChanged
+                )
+            ]
+            .into(),
+        };
+        let user_widgets = vec![
+            fl!("username-prefered"),
+            fl!("realname-prefered"),
+            fl!("none"),
+        ];
+        let user_widget: Element<Message> = match self.policy.user_widget {
+            Some(style) => AppModel::locked_control(user_widgets[style as usize].clone()),
+            None => cosmic::iced::widget::row![
+                cosmic::widget::Space::new(Length::Fill, 5),
+                cosmic::widget::dropdown(
+                    user_widgets,
+                    Some(self.config.user_widget as usize),
+                    Message::UserWidgetChanged
+                )
+            ]
+            .into(),
+        };
+        let button_label = cosmic::iced::widget::row![
+            cosmic::widget::Space::new(Length::Fill, 5),
+            cosmic::widget::text_input(fl!("button-label-placeholder"), &self.config.button_label)
+                .on_input(Message::ButtonLabelChanged)
+        ];
+        let button_label_max_length = cosmic::iced::widget::row![
             cosmic::widget::Space::new(Length::Fill, 5),
             cosmic::widget::dropdown(
                 vec![
-                    fl!("icon-only"),
-                    fl!("label-only"),
-                    fl!("icon-and-label"),
-                    fl!("auto")
+                    BUTTON_LABEL_MAX_LENGTH_OPTIONS[0].to_string(),
+                    BUTTON_LABEL_MAX_LENGTH_OPTIONS[1].to_string(),
+                    BUTTON_LABEL_MAX_LENGTH_OPTIONS[2].to_string(),
+                    fl!("button-label-unlimited")
                 ],
-                Some(self.config.applet_button_style as usize),
-                Message::AppletButtonStyleChanged
+                BUTTON_LABEL_MAX_LENGTH_OPTIONS
+                    .iter()
+                    .position(|length| *length == self.config.button_label_max_length),
+                Message::ButtonLabelMaxLengthChanged
+            )
+        ];
+        let button_icon_source = cosmic::iced::widget::row![
+            cosmic::widget::Space::new(Length::Fill, 5),
+            cosmic::widget::dropdown(
+                vec![fl!("button-icon-custom"), fl!("button-icon-distro-logo")],
+                Some(self.config.button_icon_source as usize),
+                Message::ButtonIconSourceChanged
             )
         ];
-        let user_widget = cosmic::iced::widget::row![
+        let button_icon = cosmic::iced::widget::row![
+            cosmic::widget::Space::new(Length::Fill, 5),
+            cosmic::widget::button::text(fl!("button-icon-placeholder"))
+                .on_press(Message::OpenIconPicker) // 4. Open picker on click
+        ];
+        let menu_layouts = vec![
+            fl!("layout-classic"),
+            fl!("layout-wide"),
+            fl!("layout-grid"),
+        ];
+        let menu_layout: Element<Message> = match self.policy.menu_layout {
+            Some(layout) => AppModel::locked_control(menu_layouts[layout as usize].clone()),
+            None => cosmic::iced::widget::row![
+                cosmic::widget::Space::new(Length::Fill, 5),
+                cosmic::widget::dropdown(
+                    menu_layouts,
+                    Some(self.config.menu_layout as usize),
+                    Message::MenuLayoutChanged
+                )
+            ]
+            .into(),
+        };
+        let minimal_popup = cosmic::iced::widget::row![
             cosmic::widget::Space::new(Length::Fill, 5),
             cosmic::widget::dropdown(
                 vec![
-                    fl!("username-prefered"),
-                    fl!("realname-prefered"),
-                    fl!("none")
+                    fl!("minimal-popup-automatic"),
+                    fl!("minimal-popup-always"),
+                    fl!("minimal-popup-never")
                 ],
-                Some(self.config.user_widget as usize),
-                Message::UserWidgetChanged
+                Some(self.config.minimal_popup as usize),
+                Message::MinimalPopupChanged
             )
         ];
-        let button_label = cosmic::iced::widget::row![
+        let category_navigation = cosmic::iced::widget::row![
             cosmic::widget::Space::new(Length::Fill, 5),
-            cosmic::widget::text_input(fl!("button-label-placeholder"), &self.config.button_label)
-                .on_input(Message::ButtonLabelChanged)
+            cosmic::widget::dropdown(
+                vec![
+                    fl!("category-navigation-side-pane"),
+                    fl!("category-navigation-drill-down"),
+                    fl!("category-navigation-flat-list")
+                ],
+                Some(self.config.category_navigation as usize),
+                Message::CategoryNavigationChanged
+            )
         ];
-        let button_icon = cosmic::iced::widget::row![
+        let name_display = cosmic::iced::widget::row![
             cosmic::widget::Space::new(Length::Fill, 5),
-            cosmic::widget::button::text(fl!("button-icon-placeholder"))
-                .on_press(Message::OpenIconPicker) // 4. Open picker on click
+            cosmic::widget::dropdown(
+                vec![
+                    fl!("name-display-name"),
+                    fl!("name-display-generic-name"),
+                    fl!("name-display-both")
+                ],
+                Some(self.config.name_display as usize),
+                Message::NameDisplayChanged
+            )
+        ];
+        let search_characters = cosmic::iced::widget::row![
+            cosmic::widget::Space::new(Length::Fill, 5),
+            cosmic::widget::toggler(self.config.search_characters)
+                .on_toggle(Message::SearchCharactersChanged)
+        ];
+        let search_ssh_hosts = cosmic::iced::widget::row![
+            cosmic::widget::Space::new(Length::Fill, 5),
+            cosmic::widget::toggler(self.config.search_ssh_hosts)
+                .on_toggle(Message::SearchSshHostsChanged)
+        ];
+        let search_settings_panels = cosmic::iced::widget::row![
+            cosmic::widget::Space::new(Length::Fill, 5),
+            cosmic::widget::toggler(self.config.search_settings_panels)
+                .on_toggle(Message::SearchSettingsPanelsChanged)
+        ];
+        let search_recent_documents = cosmic::iced::widget::row![
+            cosmic::widget::Space::new(Length::Fill, 5),
+            cosmic::widget::toggler(self.config.search_recent_documents)
+                .on_toggle(Message::SearchRecentDocumentsChanged)
+        ];
+        let search_bookmarks = cosmic::iced::widget::row![
+            cosmic::widget::Space::new(Length::Fill, 5),
+            cosmic::widget::toggler(self.config.search_bookmarks)
+                .on_toggle(Message::SearchBookmarksChanged)
+        ];
+        let focus_search_on_open = cosmic::iced::widget::row![
+            cosmic::widget::Space::new(Length::Fill, 5),
+            cosmic::widget::toggler(self.config.focus_search_on_open)
+                .on_toggle(Message::FocusSearchOnOpenChanged)
+        ];
+        let touch_mode = cosmic::iced::widget::row![
+            cosmic::widget::Space::new(Length::Fill, 5),
+            cosmic::widget::toggler(self.config.touch_mode).on_toggle(Message::TouchModeChanged)
+        ];
+        let double_click_launch = cosmic::iced::widget::row![
+            cosmic::widget::Space::new(Length::Fill, 5),
+            cosmic::widget::toggler(self.config.double_click_launch)
+                .on_toggle(Message::DoubleClickLaunchChanged)
+        ];
+        let clipboard_history = cosmic::iced::widget::row![
+            cosmic::widget::Space::new(Length::Fill, 5),
+            cosmic::widget::toggler(self.config.clipboard_history)
+                .on_toggle(Message::ClipboardHistoryChanged)
+        ];
+        let show_windows = cosmic::iced::widget::row![
+            cosmic::widget::Space::new(Length::Fill, 5),
+            cosmic::widget::toggler(self.config.show_windows)
+                .on_toggle(Message::ShowWindowsChanged)
+        ];
+        let activate_running_apps = cosmic::iced::widget::row![
+            cosmic::widget::Space::new(Length::Fill, 5),
+            cosmic::widget::toggler(self.config.activate_running_apps)
+                .on_toggle(Message::ActivateRunningAppsChanged)
+        ];
+        let launch_in_scope = cosmic::iced::widget::row![
+            cosmic::widget::Space::new(Length::Fill, 5),
+            cosmic::widget::toggler(self.config.launch_in_scope)
+                .on_toggle(Message::LaunchInScopeChanged)
+        ];
+        let category_sort = cosmic::iced::widget::row![
+            cosmic::widget::Space::new(Length::Fill, 5),
+            cosmic::widget::dropdown(
+                vec![
+                    fl!("category-sort-specification"),
+                    fl!("category-sort-alphabetical"),
+                    fl!("category-sort-app-count"),
+                    fl!("category-sort-manual")
+                ],
+                Some(self.config.category_sort as usize),
+                Message::CategorySortChanged
+            )
+        ];
+        let show_empty_categories = cosmic::iced::widget::row![
+            cosmic::widget::Space::new(Length::Fill, 5),
+            cosmic::widget::toggler(self.config.show_empty_categories)
+                .on_toggle(Message::ShowEmptyCategoriesChanged)
+        ];
+        let use_applications_menu = cosmic::iced::widget::row![
+            cosmic::widget::Space::new(Length::Fill, 5),
+            cosmic::widget::toggler(self.config.use_applications_menu)
+                .on_toggle(Message::UseApplicationsMenuChanged)
+        ];
+        let search_mode = cosmic::iced::widget::row![
+            cosmic::widget::Space::new(Length::Fill, 5),
+            cosmic::widget::dropdown(
+                vec![
+                    fl!("search-mode-fuzzy"),
+                    fl!("search-mode-prefix"),
+                    fl!("search-mode-substring"),
+                    fl!("search-mode-exact")
+                ],
+                Some(self.config.search_mode as usize),
+                Message::SearchModeChanged
+            )
+        ];
+        let search_results_limit = cosmic::iced::widget::row![
+            cosmic::widget::Space::new(Length::Fill, 5),
+            cosmic::widget::dropdown(
+                vec![
+                    SEARCH_RESULTS_LIMIT_OPTIONS[0].to_string(),
+                    SEARCH_RESULTS_LIMIT_OPTIONS[1].to_string(),
+                    SEARCH_RESULTS_LIMIT_OPTIONS[2].to_string(),
+                    fl!("search-results-unlimited")
+                ],
+                SEARCH_RESULTS_LIMIT_OPTIONS
+                    .iter()
+                    .position(|limit| *limit == self.config.search_results_limit),
+                Message::SearchResultsLimitChanged
+            )
+        ];
+        let hide_uninstalled_apps = cosmic::iced::widget::row![
+            cosmic::widget::Space::new(Length::Fill, 5),
+            cosmic::widget::toggler(self.config.hide_uninstalled_apps)
+                .on_toggle(Message::HideUninstalledAppsChanged)
+        ];
+        let show_comments = cosmic::iced::widget::row![
+            cosmic::widget::Space::new(Length::Fill, 5),
+            cosmic::widget::toggler(self.config.show_comments)
+                .on_toggle(Message::ShowCommentsChanged)
+        ];
+        let menu_density = cosmic::iced::widget::row![
+            cosmic::widget::Space::new(Length::Fill, 5),
+            cosmic::widget::dropdown(
+                vec![
+                    fl!("density-comfortable"),
+                    fl!("density-cozy"),
+                    fl!("density-compact")
+                ],
+                Some(self.config.density as usize),
+                Message::MenuDensityChanged
+            )
+        ];
+        // The first option follows the system theme
+        let icon_theme = cosmic::iced::widget::row![
+            cosmic::widget::Space::new(Length::Fill, 5),
+            cosmic::widget::dropdown(
+                std::iter::once(fl!("icon-theme-system"))
+                    .chain(self.icon_themes.iter().cloned())
+                    .collect::<Vec<_>>(),
+                Some(
+                    self.icon_themes
+                        .iter()
+                        .position(|theme| *theme == self.config.icon_theme)
+                        .map_or(0, |index| index + 1)
+                ),
+                Message::IconThemeChanged
+            )
+        ];
+        let launch_count_half_life = cosmic::iced::widget::row![
+            cosmic::widget::Space::new(Length::Fill, 5),
+            cosmic::widget::dropdown(
+                vec![
+                    fl!("half-life-never"),
+                    fl!("half-life-week"),
+                    fl!("half-life-month"),
+                    fl!("half-life-three-months"),
+                    fl!("half-life-six-months")
+                ],
+                LAUNCH_COUNT_HALF_LIFE_OPTIONS
+                    .iter()
+                    .position(|days| *days == self.config.launch_count_half_life_days),
+                Message::LaunchCountHalfLifeChanged
+            )
         ];
 
-        let settings_container =
-            cosmic::widget::settings::view_column(vec![cosmic::widget::settings::section()
-                .title(fl!("general"))
-                .add(cosmic::widget::settings::item(
-                    fl!("app-menu-position"),
-                    app_menu_position,
-                ))
-                .add(cosmic::widget::settings::item(
-                    fl!("search-field-position"),
-                    search_field_position,
-                ))
-                .add(cosmic::widget::settings::item(
-                    fl!("applet-button-style"),
-                    applet_button_style,
-                ))
-                .add(cosmic::widget::settings::item(
-                    fl!("user-widget"),
-                    user_widget,
-                ))
-                .add(cosmic::widget::settings::item(
-                    fl!("button-label"),
-                    button_label,
-                ))
-                .add(cosmic::widget::settings::item(
-                    fl!("button-icon"),
-                    button_icon,
-                ))
-                .into()]);
+        let show_default_apps = cosmic::iced::widget::row![
+            cosmic::widget::Space::new(Length::Fill, 5),
+            cosmic::widget::toggler(self.config.show_default_apps)
+                .on_toggle(Message::ShowDefaultAppsChanged)
+        ];
+
+        let show_quick_toggles = cosmic::iced::widget::row![
+            cosmic::widget::Space::new(Length::Fill, 5),
+            cosmic::widget::toggler(self.config.show_quick_toggles)
+                .on_toggle(Message::ShowQuickTogglesChanged)
+        ];
 
-        settings_container.padding([5, 10]).into()
+        // A toggler without a message is disabled
+        let show_power_options = cosmic::iced::widget::row![
+            cosmic::widget::Space::new(Length::Fill, 5),
+            cosmic::widget::toggler(
+                self.config.show_power_options && !self.policy.hide_power_options
+            )
+            .on_toggle_maybe(
+                (!self.policy.hide_power_options).then_some(Message::ShowPowerOptionsChanged)
+            )
+        ];
+
+        let show_power_labels = cosmic::iced::widget::row![
+            cosmic::widget::Space::new(Length::Fill, 5),
+            cosmic::widget::toggler(self.config.show_power_labels)
+                .on_toggle(Message::ShowPowerLabelsChanged)
+        ];
+
+        let group_search_results = cosmic::iced::widget::row![
+            cosmic::widget::Space::new(Length::Fill, 5),
+            cosmic::widget::toggler(self.config.group_search_results)
+                .on_toggle(Message::GroupSearchResultsChanged)
+        ];
+
+        let general_section = cosmic::widget::settings::section()
+            .title(fl!("general"))
+            .add(cosmic::widget::settings::item(
+                fl!("app-menu-position"),
+                app_menu_position,
+            ))
+            .add(cosmic::widget::settings::item(
+                fl!("search-field-position"),
+                search_field_position,
+            ))
+            .add(
+                cosmic::widget::settings::item::builder(fl!("focus-search-on-open"))
+                    .description(fl!("focus-search-on-open-description"))
+                    .control(focus_search_on_open),
+            )
+            .add(AppModel::policy_item(
+                fl!("menu-layout"),
+                self.policy.menu_layout.is_some(),
+                menu_layout,
+            ))
+            .add(cosmic::widget::settings::item(
+                fl!("minimal-popup"),
+                minimal_popup,
+            ))
+            .add(cosmic::widget::settings::item(
+                fl!("category-navigation"),
+                category_navigation,
+            ))
+            .add(cosmic::widget::settings::item(
+                fl!("category-sort"),
+                category_sort,
+            ))
+            .add(
+                cosmic::widget::settings::item::builder(fl!("show-empty-categories"))
+                    .description(fl!("show-empty-categories-description"))
+                    .control(show_empty_categories),
+            )
+            .add(
+                cosmic::widget::settings::item::builder(fl!("use-applications-menu"))
+                    .description(fl!("use-applications-menu-description"))
+                    .control(use_applications_menu),
+            )
+            .add(cosmic::widget::settings::item(
+                fl!("name-display"),
+                name_display,
+            ))
+            .add(
+                cosmic::widget::settings::item::builder(fl!("show-comments"))
+                    .description(fl!("show-comments-description"))
+                    .control(show_comments),
+            )
+            .add(
+                cosmic::widget::settings::item::builder(fl!("hide-uninstalled-apps"))
+                    .description(fl!("hide-uninstalled-apps-description"))
+                    .control(hide_uninstalled_apps),
+            )
+            .add(
+                cosmic::widget::settings::item::builder(fl!("menu-density"))
+                    .description(fl!("menu-density-description"))
+                    .control(menu_density),
+            )
+            .add(
+                cosmic::widget::settings::item::builder(fl!("icon-theme"))
+                    .description(fl!("icon-theme-description"))
+                    .control(icon_theme),
+            )
+            .add(
+                cosmic::widget::settings::item::builder(fl!("touch-mode"))
+                    .description(fl!("touch-mode-description"))
+                    .control(touch_mode),
+            )
+            .add(
+                cosmic::widget::settings::item::builder(fl!("double-click-launch"))
+                    .description(fl!("double-click-launch-description"))
+                    .control(double_click_launch),
+            )
+            .add(AppModel::policy_item(
+                fl!("applet-button-style"),
+                self.policy.applet_button_style.is_some(),
+                applet_button_style,
+            ))
+            .add(AppModel::policy_item(
+                fl!("user-widget"),
+                self.policy.user_widget.is_some(),
+                user_widget,
+            ))
+            .add(cosmic::widget::settings::item(
+                fl!("button-label"),
+                button_label,
+            ))
+            .add(cosmic::widget::settings::item(
+                fl!("button-label-max-length"),
+                button_label_max_length,
+            ))
+            .add(cosmic::widget::settings::item(
+                fl!("button-icon-source"),
+                button_icon_source,
+            ))
+            .add(
+                cosmic::widget::settings::item::builder(fl!("button-icon"))
+                    .description(fl!("button-icon-description"))
+                    .control(button_icon),
+            )
+            .add(
+                cosmic::widget::settings::item::builder(fl!("show-default-apps"))
+                    .description(fl!("show-default-apps-description"))
+                    .control(show_default_apps),
+            )
+            .add(
+                cosmic::widget::settings::item::builder(fl!("show-quick-toggles"))
+                    .description(fl!("show-quick-toggles-description"))
+                    .control(show_quick_toggles),
+            )
+            .add(AppModel::policy_item(
+                fl!("show-power-options"),
+                self.policy.hide_power_options,
+                show_power_options,
+            ))
+            .add(cosmic::widget::settings::item(
+                fl!("show-power-labels"),
+                show_power_labels,
+            ))
+            .add(cosmic::widget::settings::item(
+                fl!("group-search-results"),
+                group_search_results,
+            ))
+            .add(
+                cosmic::widget::settings::item::builder(fl!("search-mode"))
+                    .description(fl!("search-mode-description"))
+                    .control(search_mode),
+            )
+            .add(cosmic::widget::settings::item(
+                fl!("search-results-limit"),
+                search_results_limit,
+            ))
+            .add(cosmic::widget::settings::item(
+                fl!("search-characters"),
+                search_characters,
+            ))
+            .add(
+                cosmic::widget::settings::item::builder(fl!("search-ssh-hosts"))
+                    .description(fl!("search-ssh-hosts-description"))
+                    .control(search_ssh_hosts),
+            )
+            .add(
+                cosmic::widget::settings::item::builder(fl!("search-settings-panels"))
+                    .description(fl!("search-settings-panels-description"))
+                    .control(search_settings_panels),
+            )
+            .add(
+                cosmic::widget::settings::item::builder(fl!("search-recent-documents"))
+                    .description(fl!("search-recent-documents-description"))
+                    .control(search_recent_documents),
+            )
+            .add(
+                cosmic::widget::settings::item::builder(fl!("search-bookmarks"))
+                    .description(fl!("search-bookmarks-description"))
+                    .control(search_bookmarks),
+            )
+            .add(
+                cosmic::widget::settings::item::builder(fl!("clipboard-history"))
+                    .description(fl!("clipboard-history-description"))
+                    .control(clipboard_history),
+            )
+            .add(
+                cosmic::widget::settings::item::builder(fl!("show-windows"))
+                    .description(fl!("show-windows-description"))
+                    .control(show_windows),
+            )
+            .add(
+                cosmic::widget::settings::item::builder(fl!("activate-running-apps"))
+                    .description(fl!("activate-running-apps-description"))
+                    .control(activate_running_apps),
+            )
+            .add(
+                cosmic::widget::settings::item::builder(fl!("launch-in-scope"))
+                    .description(fl!("launch-in-scope-description"))
+                    .control(launch_in_scope),
+            )
+            .add(cosmic::widget::settings::item(
+                fl!("launch-count-half-life"),
+                launch_count_half_life,
+            ));
+
+        let mut sections = vec![general_section.into(), self.layout_section()];
+        if self.config.category_sort == CategoryOrder::Manual {
+            sections.push(self.category_order_section());
+        }
+        sections.push(self.context_menu_categories_section());
+        sections.push(self.context_menu_entries_section());
+        sections.push(self.recents_exclusions_section());
+        sections.push(self.application_dirs_section());
+        sections.push(self.search_scripts_section());
+        if self.policy.menu_layout.is_none() {
+            sections.push(self.output_layouts_section());
+        }
+        let settings_container = cosmic::widget::settings::view_column(sections);
+
+        cosmic::widget::scrollable(settings_container.padding([5, 10])).into()
     }
 
     /// Display a context drawer if the context page is requested.
@@ -251,6 +825,11 @@ impl cosmic::Application for AppModel {
                 Message::ToggleContextPage(ContextPage::IconPicker),
             )
             .title(fl!("button-icon")),
+            ContextPage::Diagnostics => context_drawer::context_drawer(
+                self.diagnostics_page(),
+                Message::ToggleContextPage(ContextPage::Diagnostics),
+            )
+            .title(fl!("diagnostics")),
         })
     }
 
@@ -282,6 +861,17 @@ impl cosmic::Application for AppModel {
 
                     Message::UpdateConfig(update.config)
                 }),
+            // Collect the outputs layouts can be bound to.
+            event::listen_with(|event, _status, _id| match event {
+                cosmic::iced::Event::PlatformSpecific(event::PlatformSpecific::Wayland(
+                    wayland::Event::Output(
+                        wayland::OutputEvent::Created(Some(info))
+                        | wayland::OutputEvent::InfoUpdate(info),
+                        _,
+                    ),
+                )) => Some(Message::OutputAdded(info.name?)),
+                _ => None,
+            }),
         ])
     }
 
@@ -349,6 +939,20 @@ impl cosmic::Application for AppModel {
 
                 Task::none()
             }
+            Message::ButtonIconSourceChanged(button_icon_source) => {
+                println!("Button icon source changed to: {:?}", button_icon_source);
+                self.config.button_icon_source = match button_icon_source {
+                    0 => AppletButtonIcon::Custom,
+                    1 => AppletButtonIcon::DistroLogo,
+                    _ => AppletButtonIcon::Custom,
+                };
+
+                self.config
+                    .write_entry(CosmicClassicMenuConfig::config_handler().as_ref().unwrap())
+                    .expect("Failed to write button icon source config");
+
+                Task::none()
+            }
             Message::UserWidgetChanged(user_widget_style) => {
                 println!("User widget style changed to: {:?}", user_widget_style);
                 self.config.user_widget = match user_widget_style {
@@ -380,6 +984,20 @@ impl cosmic::Application for AppModel {
 
                 Task::none()
             }
+            Message::ButtonLabelMaxLengthChanged(index) => {
+                let max_length = BUTTON_LABEL_MAX_LENGTH_OPTIONS
+                    .get(index)
+                    .copied()
+                    .unwrap_or_default();
+                println!("Button label max length changed to: {:?}", max_length);
+                self.config.button_label_max_length = max_length;
+
+                self.config
+                    .write_entry(CosmicClassicMenuConfig::config_handler().as_ref().unwrap())
+                    .expect("Failed to write button label max length config");
+
+                Task::none()
+            }
             Message::ButtonIconChanged(new_icon) => {
                 println!(
                     "Button icon changed to: {:?}",
@@ -393,78 +1011,1167 @@ impl cosmic::Application for AppModel {
 
                 Task::none()
             }
-            Message::ToggleContextPage(context_page) => {
-                if self.context_page == context_page {
-                    // Close the context drawer if the toggled context page is the same.
-                    self.core.window.show_context = !self.core.window.show_context;
-                } else {
-                    // Open the context drawer to display the requested context page.
-                    self.context_page = context_page;
-                    self.core.window.show_context = true;
-                }
+            Message::MenuLayoutChanged(menu_layout) => {
+                println!("Menu layout changed to: {:?}", menu_layout);
+                self.config.menu_layout = match menu_layout {
+                    0 => MenuLayout::Classic,
+                    1 => MenuLayout::Wide,
+                    2 => MenuLayout::Grid,
+                    _ => MenuLayout::Classic,
+                };
+
+                self.config
+                    .write_entry(CosmicClassicMenuConfig::config_handler().as_ref().unwrap())
+                    .expect("Failed to write menu layout config");
 
                 Task::none()
             }
-            Message::OpenIconPicker => {
-                self.context_page = ContextPage::IconPicker;
-                self.core.window.show_context = true;
+            Message::MinimalPopupChanged(minimal_popup) => {
+                println!("Minimal popup changed to: {:?}", minimal_popup);
+                self.config.minimal_popup = match minimal_popup {
+                    0 => MinimalPopup::Automatic,
+                    1 => MinimalPopup::Always,
+                    2 => MinimalPopup::Never,
+                    _ => MinimalPopup::Automatic,
+                };
+
+                self.config
+                    .write_entry(CosmicClassicMenuConfig::config_handler().as_ref().unwrap())
+                    .expect("Failed to write minimal popup config");
 
                 Task::none()
             }
-            Message::CustomIconSelected => Task::perform(AppModel::pick_custom_icon(), |res| {
-                if let Some(icon_pathbuf) = res {
-                    // Icon exists and was selected
-                    cosmic::action::app(Message::ButtonIconChanged(icon_pathbuf))
-                } else {
-                    // Icon selection was cancelled or failed, revert to default
-                    cosmic::action::none()
-                }
-            }),
-        }
-    }
-}
+            Message::CategoryNavigationChanged(category_navigation) => {
+                println!("Category navigation changed to: {:?}", category_navigation);
+                self.config.category_navigation = match category_navigation {
+                    0 => CategoryNavigation::SidePane,
+                    1 => CategoryNavigation::DrillDown,
+                    2 => CategoryNavigation::FlatList,
+                    _ => CategoryNavigation::SidePane,
+                };
 
-impl AppModel {
-    /// Helper to find available system icons in standard locations.
-    fn system_icon_names() -> Vec<String> {
-        let mut icons: Vec<String> = Vec::new();
-        let search_dirs = [
-            format!(
-                "/usr/share/cosmic/{}/applet-buttons",
-                cosmic_classic_menu::applet::APP_ID
-            ),
-            // Add more icon theme paths if needed
-        ];
-        for dir in &search_dirs {
-            if let Ok(entries) = fs::read_dir(dir) {
-                for entry in entries.flatten() {
-                    let path = entry.path();
-                    if let Some(ext) = path.extension() {
-                        if ext == "svg" || ext == "png" {
-                            if let Ok(abs_path) = path.canonicalize() {
-                                icons.push(abs_path.to_string_lossy().into_owned());
-                            }
-                        }
-                    }
-                }
+                self.config
+                    .write_entry(CosmicClassicMenuConfig::config_handler().as_ref().unwrap())
+                    .expect("Failed to write category navigation config");
+
+                Task::none()
             }
-        }
-        icons.sort();
-        icons.dedup();
-        icons
-    }
+            Message::NameDisplayChanged(name_display) => {
+                println!("Name display changed to: {:?}", name_display);
+                self.config.name_display = match name_display {
+                    0 => NameDisplay::Name,
+                    1 => NameDisplay::GenericName,
+                    2 => NameDisplay::GenericNameAndName,
+                    _ => NameDisplay::Name,
+                };
 
-    async fn pick_custom_icon() -> Option<PathBuf> {
-        if let Ok(result) = cosmic::dialog::file_chooser::open::Dialog::new()
-            .title(fl!("select-custom-icon"))
-            .accept_label(fl!("select"))
-            .current_filter(FileFilter::new("icon-file").glob("*.svg").glob("*.png"))
-            .open_file()
-            .await
-        {
-            let icon_pathbuf: PathBuf = PathBuf::from(result.0.uris()[0].path());
-            if icon_pathbuf.exists() {
-                return Some(icon_pathbuf);
+                self.config
+                    .write_entry(CosmicClassicMenuConfig::config_handler().as_ref().unwrap())
+                    .expect("Failed to write name display config");
+
+                Task::none()
+            }
+            Message::ShowCommentsChanged(show_comments) => {
+                println!("Show comments changed to: {:?}", show_comments);
+                self.config.show_comments = show_comments;
+
+                self.config
+                    .write_entry(CosmicClassicMenuConfig::config_handler().as_ref().unwrap())
+                    .expect("Failed to write show comments config");
+
+                Task::none()
+            }
+            Message::HideUninstalledAppsChanged(hide_uninstalled_apps) => {
+                println!(
+                    "Hide uninstalled apps changed to: {:?}",
+                    hide_uninstalled_apps
+                );
+                self.config.hide_uninstalled_apps = hide_uninstalled_apps;
+
+                self.config
+                    .write_entry(CosmicClassicMenuConfig::config_handler().as_ref().unwrap())
+                    .expect("Failed to write hide uninstalled apps config");
+
+                Task::none()
+            }
+            Message::MenuDensityChanged(density) => {
+                println!("Menu density changed to: {:?}", density);
+                self.config.density = match density {
+                    0 => MenuDensity::Comfortable,
+                    1 => MenuDensity::Cozy,
+                    2 => MenuDensity::Compact,
+                    _ => MenuDensity::Comfortable,
+                };
+
+                self.config
+                    .write_entry(CosmicClassicMenuConfig::config_handler().as_ref().unwrap())
+                    .expect("Failed to write menu density config");
+
+                Task::none()
+            }
+            Message::IconThemeChanged(index) => {
+                println!("Icon theme changed to: {:?}", index);
+                self.config.icon_theme = index
+                    .checked_sub(1)
+                    .and_then(|index| self.icon_themes.get(index).cloned())
+                    .unwrap_or_default();
+
+                self.config
+                    .write_entry(CosmicClassicMenuConfig::config_handler().as_ref().unwrap())
+                    .expect("Failed to write icon theme config");
+
+                Task::none()
+            }
+            Message::ListDensityChanged(density) => {
+                println!("List density changed to: {:?}", density);
+                self.config.list_layout.density = Self::density_from_index(density);
+
+                self.config
+                    .write_entry(CosmicClassicMenuConfig::config_handler().as_ref().unwrap())
+                    .expect("Failed to write list layout config");
+
+                Task::none()
+            }
+            Message::WideColumnsChanged(index) => {
+                let columns = WIDE_COLUMNS_OPTIONS
+                    .get(index)
+                    .copied()
+                    .unwrap_or(WIDE_COLUMNS_OPTIONS[0]);
+                println!("Wide layout columns changed to: {:?}", columns);
+                self.config.wide_layout.columns = columns;
+
+                self.config
+                    .write_entry(CosmicClassicMenuConfig::config_handler().as_ref().unwrap())
+                    .expect("Failed to write wide layout config");
+
+                Task::none()
+            }
+            Message::WideDensityChanged(density) => {
+                println!("Wide layout density changed to: {:?}", density);
+                self.config.wide_layout.density = Self::density_from_index(density);
+
+                self.config
+                    .write_entry(CosmicClassicMenuConfig::config_handler().as_ref().unwrap())
+                    .expect("Failed to write wide layout config");
+
+                Task::none()
+            }
+            Message::GridColumnsChanged(index) => {
+                let columns = GRID_COLUMNS_OPTIONS
+                    .get(index)
+                    .copied()
+                    .unwrap_or(GRID_COLUMNS_OPTIONS[2]);
+                println!("Grid layout columns changed to: {:?}", columns);
+                self.config.grid_layout.columns = columns;
+
+                self.config
+                    .write_entry(CosmicClassicMenuConfig::config_handler().as_ref().unwrap())
+                    .expect("Failed to write grid layout config");
+
+                Task::none()
+            }
+            Message::GridIconSizeChanged(index) => {
+                let icon_size = GRID_ICON_SIZE_OPTIONS
+                    .get(index)
+                    .copied()
+                    .unwrap_or(GRID_ICON_SIZE_OPTIONS[1]);
+                println!("Grid layout icon size changed to: {:?}", icon_size);
+                self.config.grid_layout.icon_size = icon_size;
+
+                self.config
+                    .write_entry(CosmicClassicMenuConfig::config_handler().as_ref().unwrap())
+                    .expect("Failed to write grid layout config");
+
+                Task::none()
+            }
+            Message::GridPaginatedChanged(paginated) => {
+                println!("Grid layout pagination changed to: {:?}", paginated);
+                self.config.grid_layout.paginated = paginated;
+
+                self.config
+                    .write_entry(CosmicClassicMenuConfig::config_handler().as_ref().unwrap())
+                    .expect("Failed to write grid layout config");
+
+                Task::none()
+            }
+            Message::ShowDefaultAppsChanged(show_default_apps) => {
+                println!("Show default apps changed to: {:?}", show_default_apps);
+                self.config.show_default_apps = show_default_apps;
+
+                self.config
+                    .write_entry(CosmicClassicMenuConfig::config_handler().as_ref().unwrap())
+                    .expect("Failed to write default apps config");
+
+                Task::none()
+            }
+            Message::ShowQuickTogglesChanged(show_quick_toggles) => {
+                println!("Show quick toggles changed to: {:?}", show_quick_toggles);
+                self.config.show_quick_toggles = show_quick_toggles;
+
+                self.config
+                    .write_entry(CosmicClassicMenuConfig::config_handler().as_ref().unwrap())
+                    .expect("Failed to write quick toggles config");
+
+                Task::none()
+            }
+            Message::ShowPowerOptionsChanged(show_power_options) => {
+                println!("Show power options changed to: {:?}", show_power_options);
+                self.config.show_power_options = show_power_options;
+
+                self.config
+                    .write_entry(CosmicClassicMenuConfig::config_handler().as_ref().unwrap())
+                    .expect("Failed to write power options config");
+
+                Task::none()
+            }
+            Message::ShowPowerLabelsChanged(show_power_labels) => {
+                println!("Show power labels changed to: {:?}", show_power_labels);
+                self.config.show_power_labels = show_power_labels;
+
+                self.config
+                    .write_entry(CosmicClassicMenuConfig::config_handler().as_ref().unwrap())
+                    .expect("Failed to write power labels config");
+
+                Task::none()
+            }
+            Message::GroupSearchResultsChanged(group_search_results) => {
+                println!(
+                    "Group search results changed to: {:?}",
+                    group_search_results
+                );
+                self.config.group_search_results = group_search_results;
+
+                self.config
+                    .write_entry(CosmicClassicMenuConfig::config_handler().as_ref().unwrap())
+                    .expect("Failed to write search config");
+
+                Task::none()
+            }
+            Message::OutputAdded(output) => {
+                self.outputs.insert(output);
+                Task::none()
+            }
+            Message::UpdateIntegrations(integrations) => {
+                self.integrations = integrations;
+                Task::none()
+            }
+            Message::UpdateCategories(categories) => {
+                self.categories = categories;
+                Task::none()
+            }
+            Message::CategorySortChanged(category_sort) => {
+                println!("Category sort changed to: {:?}", category_sort);
+                self.config.category_sort = match category_sort {
+                    0 => CategoryOrder::Specification,
+                    1 => CategoryOrder::Alphabetical,
+                    2 => CategoryOrder::AppCount,
+                    3 => CategoryOrder::Manual,
+                    _ => CategoryOrder::Manual,
+                };
+
+                self.config
+                    .write_entry(CosmicClassicMenuConfig::config_handler().as_ref().unwrap())
+                    .expect("Failed to write category sort config");
+
+                AppModel::load_categories(&self.config)
+            }
+            Message::MoveCategory(from, to) => {
+                if from < self.categories.len() && to < self.categories.len() {
+                    let category = self.categories.remove(from);
+                    self.categories.insert(to, category);
+                    // The whole list is stored, so new categories go after it alphabetically
+                    self.config.category_order = self
+                        .categories
+                        .iter()
+                        .map(|category| category.id().to_string())
+                        .collect();
+
+                    self.config
+                        .write_entry(CosmicClassicMenuConfig::config_handler().as_ref().unwrap())
+                        .expect("Failed to write category order config");
+                }
+
+                Task::none()
+            }
+            Message::ContextMenuCategoryToggled(id, shown) => {
+                println!("Category {} shown in the context menu: {}", id, shown);
+                self.config
+                    .context_menu_categories
+                    .retain(|category| *category != id);
+                if shown {
+                    self.config.context_menu_categories.push(id);
+                }
+
+                self.config
+                    .write_entry(CosmicClassicMenuConfig::config_handler().as_ref().unwrap())
+                    .expect("Failed to write context menu categories config");
+
+                Task::none()
+            }
+            Message::AddContextMenuEntry(entry) => {
+                println!("Context menu entry added: {:?}", entry);
+                self.config.context_menu_entries.push(entry);
+                self.write_context_menu_entries()
+            }
+            Message::MoveContextMenuEntry(from, to) => {
+                let entries = &mut self.config.context_menu_entries;
+                if from < entries.len() && to < entries.len() {
+                    let entry = entries.remove(from);
+                    entries.insert(to, entry);
+                }
+                self.write_context_menu_entries()
+            }
+            Message::RemoveContextMenuEntry(index) => {
+                if index < self.config.context_menu_entries.len() {
+                    self.config.context_menu_entries.remove(index);
+                }
+                self.write_context_menu_entries()
+            }
+            Message::RecentsExclusionInput(pattern) => {
+                self.new_recents_exclusion = pattern;
+                Task::none()
+            }
+            Message::AddRecentsExclusion => {
+                let pattern = std::mem::take(&mut self.new_recents_exclusion)
+                    .trim()
+                    .to_string();
+                if pattern.is_empty() || self.config.recents_exclusions.contains(&pattern) {
+                    return Task::none();
+                }
+                println!("Recents exclusion added: {}", pattern);
+                self.config.recents_exclusions.push(pattern);
+                self.write_recents_exclusions()
+            }
+            Message::RemoveRecentsExclusion(index) => {
+                if index < self.config.recents_exclusions.len() {
+                    self.config.recents_exclusions.remove(index);
+                }
+                self.write_recents_exclusions()
+            }
+            Message::ApplicationDirInput(dir) => {
+                self.new_application_dir = dir;
+                Task::none()
+            }
+            Message::AddApplicationDir => {
+                let dir = std::mem::take(&mut self.new_application_dir)
+                    .trim()
+                    .to_string();
+                if dir.is_empty() || self.config.extra_application_dirs.contains(&dir) {
+                    return Task::none();
+                }
+                println!("Application directory added: {}", dir);
+                self.config.extra_application_dirs.push(dir);
+                self.write_application_dirs()
+            }
+            Message::RemoveApplicationDir(index) => {
+                if index < self.config.extra_application_dirs.len() {
+                    self.config.extra_application_dirs.remove(index);
+                }
+                self.write_application_dirs()
+            }
+            Message::SearchScriptInput(script) => {
+                self.new_search_script = script;
+                Task::none()
+            }
+            Message::AddSearchScript => {
+                let script = std::mem::take(&mut self.new_search_script)
+                    .trim()
+                    .to_string();
+                if script.is_empty() || self.config.search_scripts.contains(&script) {
+                    return Task::none();
+                }
+                println!("Search script added: {}", script);
+                self.config.search_scripts.push(script);
+                self.write_search_scripts()
+            }
+            Message::RemoveSearchScript(index) => {
+                if index < self.config.search_scripts.len() {
+                    self.config.search_scripts.remove(index);
+                }
+                self.write_search_scripts()
+            }
+            Message::OutputLayoutChanged(output, index) => {
+                println!("Layout of output {} changed to: {:?}", output, index);
+                // The first option follows the general menu layout
+                let layout = match index {
+                    1 => Some(MenuLayout::Classic),
+                    2 => Some(MenuLayout::Wide),
+                    3 => Some(MenuLayout::Grid),
+                    _ => None,
+                };
+                match layout {
+                    Some(layout) => self.config.output_layouts.insert(output, layout),
+                    None => self.config.output_layouts.remove(&output),
+                };
+
+                self.config
+                    .write_entry(CosmicClassicMenuConfig::config_handler().as_ref().unwrap())
+                    .expect("Failed to write output layouts config");
+
+                Task::none()
+            }
+            Message::SearchCharactersChanged(search_characters) => {
+                println!("Search characters changed to: {:?}", search_characters);
+                self.config.search_characters = search_characters;
+
+                self.config
+                    .write_entry(CosmicClassicMenuConfig::config_handler().as_ref().unwrap())
+                    .expect("Failed to write search characters config");
+
+                Task::none()
+            }
+            Message::SearchSshHostsChanged(search_ssh_hosts) => {
+                println!("Search SSH hosts changed to: {:?}", search_ssh_hosts);
+                self.config.search_ssh_hosts = search_ssh_hosts;
+
+                self.config
+                    .write_entry(CosmicClassicMenuConfig::config_handler().as_ref().unwrap())
+                    .expect("Failed to write search SSH hosts config");
+
+                Task::none()
+            }
+            Message::SearchBookmarksChanged(search_bookmarks) => {
+                println!("Search bookmarks changed to: {:?}", search_bookmarks);
+                self.config.search_bookmarks = search_bookmarks;
+
+                self.config
+                    .write_entry(CosmicClassicMenuConfig::config_handler().as_ref().unwrap())
+                    .expect("Failed to write search bookmarks config");
+
+                Task::none()
+            }
+            Message::SearchSettingsPanelsChanged(search_settings_panels) => {
+                println!(
+                    "Search settings panels changed to: {:?}",
+                    search_settings_panels
+                );
+                self.config.search_settings_panels = search_settings_panels;
+
+                self.config
+                    .write_entry(CosmicClassicMenuConfig::config_handler().as_ref().unwrap())
+                    .expect("Failed to write search settings panels config");
+
+                Task::none()
+            }
+            Message::SearchRecentDocumentsChanged(search_recent_documents) => {
+                println!(
+                    "Search recent documents changed to: {:?}",
+                    search_recent_documents
+                );
+                self.config.search_recent_documents = search_recent_documents;
+
+                self.config
+                    .write_entry(CosmicClassicMenuConfig::config_handler().as_ref().unwrap())
+                    .expect("Failed to write search recent documents config");
+
+                Task::none()
+            }
+            Message::FocusSearchOnOpenChanged(focus_search_on_open) => {
+                println!(
+                    "Focus search on open changed to: {:?}",
+                    focus_search_on_open
+                );
+                self.config.focus_search_on_open = focus_search_on_open;
+
+                self.config
+                    .write_entry(CosmicClassicMenuConfig::config_handler().as_ref().unwrap())
+                    .expect("Failed to write focus search on open config");
+
+                Task::none()
+            }
+            Message::TouchModeChanged(touch_mode) => {
+                println!("Touch mode changed to: {:?}", touch_mode);
+                self.config.touch_mode = touch_mode;
+
+                self.config
+                    .write_entry(CosmicClassicMenuConfig::config_handler().as_ref().unwrap())
+                    .expect("Failed to write touch mode config");
+
+                Task::none()
+            }
+            Message::DoubleClickLaunchChanged(double_click_launch) => {
+                println!("Double-click launch changed to: {:?}", double_click_launch);
+                self.config.double_click_launch = double_click_launch;
+
+                self.config
+                    .write_entry(CosmicClassicMenuConfig::config_handler().as_ref().unwrap())
+                    .expect("Failed to write double-click launch config");
+
+                Task::none()
+            }
+            Message::ClipboardHistoryChanged(clipboard_history) => {
+                println!("Clipboard history changed to: {:?}", clipboard_history);
+                self.config.clipboard_history = clipboard_history;
+
+                self.config
+                    .write_entry(CosmicClassicMenuConfig::config_handler().as_ref().unwrap())
+                    .expect("Failed to write clipboard history config");
+
+                Task::none()
+            }
+            Message::ShowWindowsChanged(show_windows) => {
+                println!("Show windows changed to: {:?}", show_windows);
+                self.config.show_windows = show_windows;
+
+                self.config
+                    .write_entry(CosmicClassicMenuConfig::config_handler().as_ref().unwrap())
+                    .expect("Failed to write show windows config");
+
+                Task::none()
+            }
+            Message::ActivateRunningAppsChanged(activate_running_apps) => {
+                println!(
+                    "Activate running apps changed to: {:?}",
+                    activate_running_apps
+                );
+                self.config.activate_running_apps = activate_running_apps;
+
+                self.config
+                    .write_entry(CosmicClassicMenuConfig::config_handler().as_ref().unwrap())
+                    .expect("Failed to write activate running apps config");
+
+                Task::none()
+            }
+            Message::LaunchInScopeChanged(launch_in_scope) => {
+                println!("Launch in scope changed to: {:?}", launch_in_scope);
+                self.config.launch_in_scope = launch_in_scope;
+
+                self.config
+                    .write_entry(CosmicClassicMenuConfig::config_handler().as_ref().unwrap())
+                    .expect("Failed to write launch in scope config");
+
+                Task::none()
+            }
+            Message::ShowEmptyCategoriesChanged(show_empty_categories) => {
+                println!(
+                    "Show empty categories changed to: {:?}",
+                    show_empty_categories
+                );
+                self.config.show_empty_categories = show_empty_categories;
+
+                self.config
+                    .write_entry(CosmicClassicMenuConfig::config_handler().as_ref().unwrap())
+                    .expect("Failed to write show empty categories config");
+
+                Task::none()
+            }
+            Message::UseApplicationsMenuChanged(use_applications_menu) => {
+                println!(
+                    "Use applications menu changed to: {:?}",
+                    use_applications_menu
+                );
+                self.config.use_applications_menu = use_applications_menu;
+
+                self.config
+                    .write_entry(CosmicClassicMenuConfig::config_handler().as_ref().unwrap())
+                    .expect("Failed to write use applications menu config");
+
+                // The categories to order come from the other source now
+                Self::load_categories(&self.config)
+            }
+            Message::SearchModeChanged(search_mode) => {
+                println!("Search mode changed to: {:?}", search_mode);
+                self.config.search_mode = match search_mode {
+                    0 => SearchMode::Fuzzy,
+                    1 => SearchMode::Prefix,
+                    2 => SearchMode::Substring,
+                    3 => SearchMode::Exact,
+                    _ => SearchMode::Fuzzy,
+                };
+
+                self.config
+                    .write_entry(CosmicClassicMenuConfig::config_handler().as_ref().unwrap())
+                    .expect("Failed to write search mode config");
+
+                Task::none()
+            }
+            Message::SearchResultsLimitChanged(index) => {
+                let limit = SEARCH_RESULTS_LIMIT_OPTIONS
+                    .get(index)
+                    .copied()
+                    .unwrap_or_default();
+                println!("Search results limit changed to: {:?}", limit);
+                self.config.search_results_limit = limit;
+
+                self.config
+                    .write_entry(CosmicClassicMenuConfig::config_handler().as_ref().unwrap())
+                    .expect("Failed to write search results limit config");
+
+                Task::none()
+            }
+            Message::LaunchCountHalfLifeChanged(index) => {
+                let half_life_days = LAUNCH_COUNT_HALF_LIFE_OPTIONS
+                    .get(index)
+                    .copied()
+                    .unwrap_or_default();
+                println!("Launch count half-life changed to: {:?}", half_life_days);
+                self.config.launch_count_half_life_days = half_life_days;
+
+                self.config
+                    .write_entry(CosmicClassicMenuConfig::config_handler().as_ref().unwrap())
+                    .expect("Failed to write launch count half-life config");
+
+                Task::none()
+            }
+            Message::ToggleContextPage(context_page) => {
+                if self.context_page == context_page {
+                    // Close the context drawer if the toggled context page is the same.
+                    self.core.window.show_context = !self.core.window.show_context;
+                } else {
+                    // Open the context drawer to display the requested context page.
+                    self.context_page = context_page;
+                    self.core.window.show_context = true;
+                }
+
+                Task::none()
+            }
+            Message::ShowDiagnostics => {
+                self.context_page = ContextPage::Diagnostics;
+                self.core.window.show_context = true;
+                // Checked again each time, the files may have been fixed in the meantime
+                self.diagnostics = None;
+
+                Task::perform(diagnostics::scan(), |diagnostics| {
+                    cosmic::Action::App(Message::UpdateDiagnostics(diagnostics))
+                })
+            }
+            Message::UpdateDiagnostics(diagnostics) => {
+                self.diagnostics = Some(diagnostics);
+                Task::none()
+            }
+            Message::OpenIconPicker => {
+                self.context_page = ContextPage::IconPicker;
+                self.core.window.show_context = true;
+
+                Task::none()
+            }
+            Message::CustomIconSelected => Task::perform(AppModel::pick_custom_icon(), |res| {
+                if let Some(icon_pathbuf) = res {
+                    // Icon exists and was selected
+                    cosmic::action::app(Message::ButtonIconChanged(icon_pathbuf))
+                } else {
+                    // Icon selection was cancelled or failed, revert to default
+                    cosmic::action::none()
+                }
+            }),
+        }
+    }
+}
+
+impl AppModel {
+    /// Settings specific to the currently selected menu layout.
+    fn layout_section(&'_ self) -> Element<'_, Message> {
+        let density_dropdown = |density: ListDensity, on_select: fn(usize) -> Message| {
+            cosmic::iced::widget::row![
+                cosmic::widget::Space::new(Length::Fill, 5),
+                cosmic::widget::dropdown(
+                    vec![fl!("density-comfortable"), fl!("density-compact")],
+                    Some(density as usize),
+                    on_select
+                )
+            ]
+        };
+        let columns_dropdown = |options: &[u8], columns: u8, on_select: fn(usize) -> Message| {
+            cosmic::iced::widget::row![
+                cosmic::widget::Space::new(Length::Fill, 5),
+                cosmic::widget::dropdown(
+                    options.iter().map(u8::to_string).collect::<Vec<_>>(),
+                    options.iter().position(|option| *option == columns),
+                    on_select
+                )
+            ]
+        };
+
+        let section = cosmic::widget::settings::section().title(fl!("layout-settings"));
+        let menu_layout = self.policy.menu_layout.unwrap_or(self.config.menu_layout);
+        let section = match menu_layout {
+            MenuLayout::Classic => section.add(
+                cosmic::widget::settings::item::builder(fl!("list-density"))
+                    .description(fl!("list-density-description"))
+                    .control(density_dropdown(
+                        self.config.list_layout.density,
+                        Message::ListDensityChanged,
+                    )),
+            ),
+            MenuLayout::Wide => section
+                .add(cosmic::widget::settings::item(
+                    fl!("columns"),
+                    columns_dropdown(
+                        &WIDE_COLUMNS_OPTIONS,
+                        self.config.wide_layout.columns,
+                        Message::WideColumnsChanged,
+                    ),
+                ))
+                .add(
+                    cosmic::widget::settings::item::builder(fl!("list-density"))
+                        .description(fl!("list-density-description"))
+                        .control(density_dropdown(
+                            self.config.wide_layout.density,
+                            Message::WideDensityChanged,
+                        )),
+                ),
+            MenuLayout::Grid => section
+                .add(cosmic::widget::settings::item(
+                    fl!("columns"),
+                    cosmic::iced::widget::row![
+                        cosmic::widget::Space::new(Length::Fill, 5),
+                        cosmic::widget::dropdown(
+                            GRID_COLUMNS_OPTIONS
+                                .iter()
+                                .map(|columns| match columns {
+                                    0 => fl!("columns-automatic"),
+                                    columns => columns.to_string(),
+                                })
+                                .collect::<Vec<_>>(),
+                            GRID_COLUMNS_OPTIONS
+                                .iter()
+                                .position(|columns| *columns == self.config.grid_layout.columns),
+                            Message::GridColumnsChanged
+                        )
+                    ],
+                ))
+                .add(cosmic::widget::settings::item(
+                    fl!("icon-size"),
+                    cosmic::iced::widget::row![
+                        cosmic::widget::Space::new(Length::Fill, 5),
+                        cosmic::widget::dropdown(
+                            GRID_ICON_SIZE_OPTIONS
+                                .iter()
+                                .map(|size| fl!("icon-size-pixels", size = *size))
+                                .collect::<Vec<_>>(),
+                            GRID_ICON_SIZE_OPTIONS
+                                .iter()
+                                .position(|size| *size == self.config.grid_layout.icon_size),
+                            Message::GridIconSizeChanged
+                        )
+                    ],
+                ))
+                .add(cosmic::widget::settings::item(
+                    fl!("grid-paginated"),
+                    cosmic::iced::widget::row![
+                        cosmic::widget::Space::new(Length::Fill, 5),
+                        cosmic::widget::toggler(self.config.grid_layout.paginated)
+                            .on_toggle(Message::GridPaginatedChanged)
+                    ],
+                )),
+        };
+
+        section.into()
+    }
+
+    /// Layouts bound to the connected outputs and to the ones configured earlier.
+    fn output_layouts_section(&'_ self) -> Element<'_, Message> {
+        let outputs: BTreeSet<&String> = self
+            .outputs
+            .iter()
+            .chain(self.config.output_layouts.keys())
+            .collect();
+
+        outputs
+            .into_iter()
+            .fold(
+                cosmic::widget::settings::section().title(fl!("output-layouts")),
+                |section, output| {
+                    let selected = match self.config.output_layouts.get(output) {
+                        None => 0,
+                        Some(MenuLayout::Classic) => 1,
+                        Some(MenuLayout::Wide) => 2,
+                        Some(MenuLayout::Grid) => 3,
+                    };
+                    let output_name = output.clone();
+                    let on_select =
+                        move |index| Message::OutputLayoutChanged(output_name.clone(), index);
+
+                    section.add(cosmic::widget::settings::item(
+                        output.clone(),
+                        cosmic::iced::widget::row![
+                            cosmic::widget::Space::new(Length::Fill, 5),
+                            cosmic::widget::dropdown(
+                                vec![
+                                    fl!("output-layout-default"),
+                                    fl!("layout-classic"),
+                                    fl!("layout-wide"),
+                                    fl!("layout-grid")
+                                ],
+                                Some(selected),
+                                on_select
+                            )
+                        ],
+                    ))
+                },
+            )
+            .into()
+    }
+
+    /// Lists the categories with buttons moving them up and down the manual order.
+    fn category_order_section(&'_ self) -> Element<'_, Message> {
+        let last = self.categories.len().saturating_sub(1);
+
+        self.categories
+            .iter()
+            .enumerate()
+            .fold(
+                cosmic::widget::settings::section().title(fl!("category-order")),
+                |section, (index, category)| {
+                    let move_up = cosmic::widget::button::icon(icon::from_name("go-up-symbolic"))
+                        .on_press_maybe(
+                            (index > 0).then(|| Message::MoveCategory(index, index - 1)),
+                        );
+                    let move_down =
+                        cosmic::widget::button::icon(icon::from_name("go-down-symbolic"))
+                            .on_press_maybe(
+                                (index < last).then(|| Message::MoveCategory(index, index + 1)),
+                            );
+
+                    section.add(cosmic::widget::settings::item(
+                        category.get_display_name(),
+                        cosmic::iced::widget::row![
+                            cosmic::widget::Space::new(Length::Fill, 5),
+                            move_up,
+                            move_down
+                        ],
+                    ))
+                },
+            )
+            .into()
+    }
+
+    /// Lists the categories with togglers adding them to the applet's right-click menu.
+    fn context_menu_categories_section(&'_ self) -> Element<'_, Message> {
+        self.categories
+            .iter()
+            .fold(
+                cosmic::widget::settings::section().title(fl!("context-menu-categories")),
+                |section, category| {
+                    let id = category.id().to_string();
+                    let shown = self.config.context_menu_categories.contains(&id);
+
+                    section.add(cosmic::widget::settings::item(
+                        category.get_display_name(),
+                        cosmic::iced::widget::row![
+                            cosmic::widget::Space::new(Length::Fill, 5),
+                            cosmic::widget::toggler(shown).on_toggle(move |shown| {
+                                Message::ContextMenuCategoryToggled(id.clone(), shown)
+                            })
+                        ],
+                    ))
+                },
+            )
+            .into()
+    }
+
+    /// Lists the entries of the applet's right-click menu with buttons reordering and removing
+    /// them, followed by a dropdown adding the missing ones.
+    fn context_menu_entries_section(&'_ self) -> Element<'_, Message> {
+        let entries = &self.config.context_menu_entries;
+        let last = entries.len().saturating_sub(1);
+
+        // Only separators can be added more than once
+        let addable: Vec<ContextMenuEntry> = ContextMenuEntry::ALL
+            .into_iter()
+            .filter(|entry| *entry == ContextMenuEntry::Separator || !entries.contains(entry))
+            .collect();
+        let addable_labels: Vec<String> = addable
+            .iter()
+            .map(|entry| AppModel::context_menu_entry_label(*entry))
+            .collect();
+
+        entries
+            .iter()
+            .enumerate()
+            .fold(
+                cosmic::widget::settings::section().title(fl!("context-menu-entries")),
+                |section, (index, entry)| {
+                    let move_up = cosmic::widget::button::icon(icon::from_name("go-up-symbolic"))
+                        .on_press_maybe(
+                            (index > 0).then(|| Message::MoveContextMenuEntry(index, index - 1)),
+                        );
+                    let move_down =
+                        cosmic::widget::button::icon(icon::from_name("go-down-symbolic"))
+                            .on_press_maybe(
+                                (index < last)
+                                    .then(|| Message::MoveContextMenuEntry(index, index + 1)),
+                            );
+                    let remove =
+                        cosmic::widget::button::icon(icon::from_name("list-remove-symbolic"))
+                            .on_press(Message::RemoveContextMenuEntry(index));
+
+                    section.add(cosmic::widget::settings::item(
+                        AppModel::context_menu_entry_label(*entry),
+                        cosmic::iced::widget::row![
+                            cosmic::widget::Space::new(Length::Fill, 5),
+                            move_up,
+                            move_down,
+                            remove
+                        ],
+                    ))
+                },
+            )
+            .add(cosmic::widget::settings::item(
+                fl!("context-menu-add-entry"),
+                cosmic::iced::widget::row![
+                    cosmic::widget::Space::new(Length::Fill, 5),
+                    cosmic::widget::dropdown(addable_labels, None, move |index| {
+                        Message::AddContextMenuEntry(addable[index])
+                    })
+                ],
+            ))
+            .into()
+    }
+
+    fn context_menu_entry_label(entry: ContextMenuEntry) -> String {
+        match entry {
+            ContextMenuEntry::MenuSettings => fl!("context-menu-menu-settings"),
+            ContextMenuEntry::AppletSettings => fl!("context-menu-applet-settings"),
+            ContextMenuEntry::CreateLauncher => fl!("context-menu-create-launcher"),
+            ContextMenuEntry::ShowHiddenEntries => fl!("context-menu-show-hidden-entries"),
+            ContextMenuEntry::SystemSettings => fl!("context-menu-system-settings"),
+            ContextMenuEntry::SystemMonitor => fl!("context-menu-system-monitor"),
+            ContextMenuEntry::DiskManagement => fl!("context-menu-disks"),
+            ContextMenuEntry::Separator => fl!("context-menu-separator"),
+        }
+    }
+
+    fn write_context_menu_entries(&self) -> Task<cosmic::Action<Message>> {
+        self.config
+            .write_entry(CosmicClassicMenuConfig::config_handler().as_ref().unwrap())
+            .expect("Failed to write context menu entries config");
+
+        Task::none()
+    }
+
+    fn recents_exclusions_section(&'_ self) -> Element<'_, Message> {
+        let add = cosmic::widget::button::icon(icon::from_name("list-add-symbolic"))
+            .on_press_maybe(
+                (!self.new_recents_exclusion.trim().is_empty())
+                    .then_some(Message::AddRecentsExclusion),
+            );
+
+        self.config
+            .recents_exclusions
+            .iter()
+            .enumerate()
+            .fold(
+                cosmic::widget::settings::section().title(fl!("recents-exclusions")),
+                |section, (index, pattern)| {
+                    section.add(cosmic::widget::settings::item(
+                        pattern.clone(),
+                        cosmic::iced::widget::row![
+                            cosmic::widget::Space::new(Length::Fill, 5),
+                            cosmic::widget::button::icon(icon::from_name("list-remove-symbolic"))
+                                .on_press(Message::RemoveRecentsExclusion(index))
+                        ],
+                    ))
+                },
+            )
+            .add(
+                cosmic::widget::settings::item::builder(fl!("recents-add-exclusion"))
+                    .description(fl!("recents-add-exclusion-description"))
+                    .control(cosmic::iced::widget::row![
+                        cosmic::widget::text_input(
+                            fl!("recents-exclusion-placeholder"),
+                            &self.new_recents_exclusion
+                        )
+                        .on_input(Message::RecentsExclusionInput),
+                        add
+                    ]),
+            )
+            .into()
+    }
+
+    fn write_recents_exclusions(&self) -> Task<cosmic::Action<Message>> {
+        self.config
+            .write_entry(CosmicClassicMenuConfig::config_handler().as_ref().unwrap())
+            .expect("Failed to write recents exclusions config");
+
+        Task::none()
+    }
+
+    fn application_dirs_section(&'_ self) -> Element<'_, Message> {
+        let add = cosmic::widget::button::icon(icon::from_name("list-add-symbolic"))
+            .on_press_maybe(
+                (!self.new_application_dir.trim().is_empty()).then_some(Message::AddApplicationDir),
+            );
+
+        self.config
+            .extra_application_dirs
+            .iter()
+            .enumerate()
+            .fold(
+                cosmic::widget::settings::section().title(fl!("application-dirs")),
+                |section, (index, dir)| {
+                    section.add(cosmic::widget::settings::item(
+                        dir.clone(),
+                        cosmic::iced::widget::row![
+                            cosmic::widget::Space::new(Length::Fill, 5),
+                            cosmic::widget::button::icon(icon::from_name("list-remove-symbolic"))
+                                .on_press(Message::RemoveApplicationDir(index))
+                        ],
+                    ))
+                },
+            )
+            .add(
+                cosmic::widget::settings::item::builder(fl!("application-dirs-add"))
+                    .description(fl!("application-dirs-add-description"))
+                    .control(cosmic::iced::widget::row![
+                        cosmic::widget::text_input(
+                            fl!("application-dirs-placeholder"),
+                            &self.new_application_dir
+                        )
+                        .on_input(Message::ApplicationDirInput),
+                        add
+                    ]),
+            )
+            .into()
+    }
+
+    fn write_application_dirs(&self) -> Task<cosmic::Action<Message>> {
+        self.config
+            .write_entry(CosmicClassicMenuConfig::config_handler().as_ref().unwrap())
+            .expect("Failed to write application directories config");
+        Apps::set_extra_application_dirs(self.config.extra_application_dirs.clone());
+
+        Task::none()
+    }
+
+    fn search_scripts_section(&'_ self) -> Element<'_, Message> {
+        let add = cosmic::widget::button::icon(icon::from_name("list-add-symbolic"))
+            .on_press_maybe(
+                (!self.new_search_script.trim().is_empty()).then_some(Message::AddSearchScript),
+            );
+
+        self.config
+            .search_scripts
+            .iter()
+            .enumerate()
+            .fold(
+                cosmic::widget::settings::section().title(fl!("search-scripts")),
+                |section, (index, script)| {
+                    section.add(cosmic::widget::settings::item(
+                        script.clone(),
+                        cosmic::iced::widget::row![
+                            cosmic::widget::Space::new(Length::Fill, 5),
+                            cosmic::widget::button::icon(icon::from_name("list-remove-symbolic"))
+                                .on_press(Message::RemoveSearchScript(index))
+                        ],
+                    ))
+                },
+            )
+            .add(
+                cosmic::widget::settings::item::builder(fl!("search-scripts-add"))
+                    .description(fl!("search-scripts-add-description"))
+                    .control(cosmic::iced::widget::row![
+                        cosmic::widget::text_input(
+                            fl!("search-scripts-placeholder"),
+                            &self.new_search_script
+                        )
+                        .on_input(Message::SearchScriptInput),
+                        add
+                    ]),
+            )
+            .into()
+    }
+
+    /// Returns the settings item, noting that the administrator enforces it when locked.
+    fn policy_item<'a>(
+        title: String,
+        locked: bool,
+        control: impl Into<Element<'a, Message>> + 'a,
+    ) -> Element<'a, Message> {
+        if locked {
+            cosmic::widget::settings::item::builder(title)
+                .description(fl!("locked-by-administrator"))
+                .control(control)
+                .into()
+        } else {
+            cosmic::widget::settings::item(title, control).into()
+        }
+    }
+
+    /// Shows the value enforced by the administrator in place of the control changing it.
+    fn locked_control<'a>(value: String) -> Element<'a, Message> {
+        cosmic::iced::widget::row![
+            cosmic::widget::Space::new(Length::Fill, 5),
+            icon::from_name("system-lock-screen-symbolic").size(16),
+            text::body(value)
+        ]
+        .spacing(8)
+        .align_y(Alignment::Center)
+        .into()
+    }
+
+    fn write_search_scripts(&self) -> Task<cosmic::Action<Message>> {
+        self.config
+            .write_entry(CosmicClassicMenuConfig::config_handler().as_ref().unwrap())
+            .expect("Failed to write search scripts config");
+
+        Task::none()
+    }
+
+    /// Loads the categories in the order the applet lists them.
+    fn load_categories(config: &CosmicClassicMenuConfig) -> Task<cosmic::Action<Message>> {
+        Task::perform(Apps::load_app_categories(config.clone()), |categories| {
+            // All applications, recently used and the other pinned categories can't be moved
+            let categories = categories
+                .into_iter()
+                .filter(|category| !category.mime_name.is_empty())
+                .collect();
+            cosmic::Action::App(Message::UpdateCategories(categories))
+        })
+    }
+
+    fn density_from_index(index: usize) -> ListDensity {
+        match index {
+            0 => ListDensity::Comfortable,
+            1 => ListDensity::Compact,
+            _ => ListDensity::Comfortable,
+        }
+    }
+
+    /// Helper to find available system icons in standard locations.
+    fn system_icon_names() -> Vec<String> {
+        let mut icons: Vec<String> = Vec::new();
+        let search_dirs = [
+            format!(
+                "/usr/share/cosmic/{}/applet-buttons",
+                cosmic_classic_menu::applet::APP_ID
+            ),
+            // Add more icon theme paths if needed
+        ];
+        for dir in &search_dirs {
+            if let Ok(entries) = fs::read_dir(dir) {
+                for entry in entries.flatten() {
+                    let path = entry.path();
+                    if let Some(ext) = path.extension() {
+                        if ext == "svg" || ext == "png" {
+                            if let Ok(abs_path) = path.canonicalize() {
+                                icons.push(abs_path.to_string_lossy().into_owned());
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        icons.sort();
+        icons.dedup();
+        icons
+    }
+
+    async fn pick_custom_icon() -> Option<PathBuf> {
+        if let Ok(result) = cosmic::dialog::file_chooser::open::Dialog::new()
+            .title(fl!("select-custom-icon"))
+            .accept_label(fl!("select"))
+            .current_filter(
+                FileFilter::new(fl!("icon-file"))
+                    .glob("*.svg")
+                    .glob("*.png"),
+            )
+            .open_file()
+            .await
+        {
+            let icon_pathbuf: PathBuf = PathBuf::from(result.0.uris()[0].path());
+            if icon_pathbuf.exists() {
+                return Some(icon_pathbuf);
             } else {
                 return None;
             }
@@ -473,6 +2180,61 @@ impl AppModel {
         None
     }
 
+    /// Lists the desktop files left out of the menu and the ones that won't work right.
+    fn diagnostics_page(&'_ self) -> Element<'_, Message> {
+        let Some(diagnostics) = &self.diagnostics else {
+            return text::body(fl!("diagnostics-checking")).into();
+        };
+        if diagnostics.is_empty() {
+            return text::body(fl!("diagnostics-none")).into();
+        }
+
+        let (skipped, warnings): (Vec<&Diagnostic>, Vec<&Diagnostic>) = diagnostics
+            .iter()
+            .partition(|diagnostic| diagnostic.problem.is_skipped());
+        let section = |title: String, diagnostics: Vec<&Diagnostic>| {
+            diagnostics.into_iter().fold(
+                cosmic::widget::settings::section().title(title),
+                |section, diagnostic| {
+                    section.add(
+                        cosmic::widget::settings::item::builder(
+                            diagnostic.path.display().to_string(),
+                        )
+                        .description(AppModel::problem_description(&diagnostic.problem))
+                        .control(cosmic::widget::Space::new(0, 0)),
+                    )
+                },
+            )
+        };
+
+        let mut sections: Vec<Element<Message>> = Vec::new();
+        if !skipped.is_empty() {
+            sections.push(section(fl!("diagnostics-skipped"), skipped).into());
+        }
+        if !warnings.is_empty() {
+            sections.push(section(fl!("diagnostics-warnings"), warnings).into());
+        }
+        cosmic::widget::settings::view_column(sections).into()
+    }
+
+    fn problem_description(problem: &Problem) -> String {
+        match problem {
+            Problem::Unreadable(error) => fl!("diagnostics-unreadable", error = error.clone()),
+            Problem::Shadowed(path) => {
+                fl!("diagnostics-shadowed", path = path.display().to_string())
+            }
+            Problem::NotDisplayed => fl!("diagnostics-not-displayed"),
+            Problem::NotInstalled(program) => {
+                fl!("diagnostics-not-installed", program = program.clone())
+            }
+            Problem::MissingExec => fl!("diagnostics-missing-exec"),
+            Problem::ProgramNotFound(program) => {
+                fl!("diagnostics-program-not-found", program = program.clone())
+            }
+            Problem::MissingIcon(icon) => fl!("diagnostics-missing-icon", icon = icon.clone()),
+        }
+    }
+
     pub fn icon_picker(&'_ self) -> Element<'_, Message> {
         let mut icons = Self::system_icon_names();
         let icons_per_row = 3;
@@ -482,7 +2244,7 @@ impl AppModel {
         let mut grid = cosmic::iced_widget::Column::new().spacing(theme.space_xs());
 
         // handle custom icon selection
-        let currently_selected_icon: PathBuf = PathBuf::from(&self.config.button_icon);
+        let currently_selected_icon = self.config.button_icon_path();
         if currently_selected_icon.exists()
             && !icons.contains(&currently_selected_icon.to_string_lossy().into_owned())
         {
@@ -497,8 +2259,8 @@ impl AppModel {
                 let icon_name = icon_pathbuf
                     .file_stem()
                     .and_then(|stem| stem.to_str())
-                    .unwrap_or("unknown")
-                    .to_string();
+                    .map(str::to_string)
+                    .unwrap_or_else(|| fl!("unknown-icon"));
                 row = row.push(Self::button(
                     &icon_name,
                     icon_pathbuf,
@@ -509,10 +2271,20 @@ impl AppModel {
             grid = grid.push(row);
         }
 
+        // Any image can be entered by its path, without needing the file chooser
+        let icon_path_input =
+            cosmic::widget::text_input(fl!("icon-path-placeholder"), &self.config.button_icon)
+                .on_input(|path| Message::ButtonIconChanged(PathBuf::from(path)));
+
+        // Custom icons are picked with the file chooser of the desktop portal
         let custom_icon_button = cosmic::widget::button::standard(fl!("select-custom-icon"))
-            .on_press(Message::CustomIconSelected);
+            .on_press_maybe(
+                self.integrations
+                    .portals
+                    .then_some(Message::CustomIconSelected),
+            );
 
-        cosmic::iced_widget::column![custom_icon_button, grid]
+        cosmic::iced_widget::column![icon_path_input, custom_icon_button, grid]
             .width(Length::Fill)
             .align_x(Alignment::Center)
             .spacing(theme.space_xs())
@@ -601,6 +2373,7 @@ impl AppModel {
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum MenuAction {
     About,
+    Diagnostics,
     SetDefaultSettings,
 }
 
@@ -610,6 +2383,7 @@ impl menu::action::MenuAction for MenuAction {
     fn message(&self) -> Self::Message {
         match self {
             MenuAction::About => Message::ToggleContextPage(ContextPage::About),
+            MenuAction::Diagnostics => Message::ShowDiagnostics,
             MenuAction::SetDefaultSettings => {
                 Message::UpdateConfig(CosmicClassicMenuConfig::default())
             }
@@ -623,4 +2397,5 @@ pub enum ContextPage {
     #[default]
     About,
     IconPicker, // 1. Add new variant
+    Diagnostics,
 }